@@ -2,7 +2,8 @@
 // Licensed under MPL-2.0
 
 use crate::doc_formatter::{DocumentFormatter, TextFormat};
-use crate::text_annotations::{InlineAnnotation, Overlay, TextAnnotations};
+use crate::text_annotations::{InlineAnnotation, LineAnnotation, Overlay, TextAnnotations};
+use crate::Position;
 
 impl TextFormat {
     fn new_test(softwrap: bool) -> Self {
@@ -214,3 +215,36 @@ fn annotation_and_overlay() {
         "fooo  bar "
     );
 }
+
+struct LinesAboveFirstLine(usize);
+
+impl LineAnnotation for LinesAboveFirstLine {
+    fn insert_virtual_lines(&mut self, _: usize, _: Position, _: usize) -> Position {
+        Position::new(0, 0)
+    }
+
+    fn virtual_lines_above_first_line(&mut self) -> usize {
+        self.0
+    }
+}
+
+#[test]
+fn virtual_lines_above_first_line() {
+    let mut annotations = TextAnnotations::default();
+    annotations.add_line_annotation(Box::new(LinesAboveFirstLine(2)));
+    let text_fmt = TextFormat::new_test(false);
+    let first_row = |char_idx| {
+        DocumentFormatter::new_at_prev_checkpoint(
+            "a\nb\n".into(),
+            &text_fmt,
+            &annotations,
+            char_idx,
+        )
+        .next()
+        .unwrap()
+        .visual_pos
+        .row
+    };
+    assert_eq!(first_row(0), 2);
+    assert_eq!(first_row(2), 0);
+}
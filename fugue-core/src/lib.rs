@@ -24,6 +24,7 @@
 pub mod match_brackets;
 pub mod movement;
 pub mod object;
+pub mod operator;
 mod position;
 pub mod search;
 pub mod selection;
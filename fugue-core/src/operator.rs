@@ -0,0 +1,186 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+//! The text an operator acts on when it is followed by a motion, following the vi rules for
+//! characterwise and linewise motions.
+
+use fugue_stdx::rope::RopeSliceExt;
+use ropey::RopeSlice;
+
+use crate::chars::char_is_whitespace;
+use crate::graphemes::next_grapheme_boundary;
+use crate::line_ending::line_end_char_index;
+use crate::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MotionKind {
+    /// From the cursor up to the target, without the character at the end, like `dh` or `db`.
+    Exclusive,
+    /// From the cursor up to and including the character at the end, like `de` or `df,`.
+    Inclusive,
+    /// The whole lines from the line of the cursor to the line of the target, like `dj`.
+    Linewise,
+    /// Up to the start of the text the motion selected, like a search match. Exclusive.
+    Match,
+    /// The selection the motion made, like a text object.
+    Selection,
+}
+
+/// Returns the range an operator acts on when a motion of `kind` moved `origin` to `motion`,
+/// along with whether the range covers whole lines.
+pub fn motion_range(
+    text: RopeSlice,
+    origin: Range,
+    motion: Range,
+    kind: MotionKind,
+) -> (Range, bool) {
+    let cursor = origin.cursor(text);
+    match kind {
+        MotionKind::Selection => (motion, false),
+        MotionKind::Linewise => {
+            let target = text.char_to_line(motion.cursor(text));
+            let line = text.char_to_line(cursor);
+            (line_range(text, line.min(target), line.max(target)), true)
+        }
+        MotionKind::Inclusive => {
+            let target = motion.cursor(text);
+            let (start, end) = (cursor.min(target), cursor.max(target));
+            (Range::new(start, next_grapheme_boundary(text, end)), false)
+        }
+        MotionKind::Exclusive | MotionKind::Match => {
+            let target = match kind {
+                MotionKind::Match => motion.from(),
+                _ => motion.cursor(text),
+            };
+            exclusive_range(text, cursor.min(target), cursor.max(target))
+        }
+    }
+}
+
+/// Applies the exceptions vi makes for exclusive motions ending at the start of a line: the end
+/// moves back to the end of the previous line, and if the start is in the indentation of its
+/// line the motion becomes linewise.
+fn exclusive_range(text: RopeSlice, start: usize, end: usize) -> (Range, bool) {
+    let end_line = text.char_to_line(end);
+    let start_line = text.char_to_line(start);
+    if end_line == start_line || text.line_to_char(end_line) != end {
+        return (Range::new(start, end), false);
+    }
+    let line_start = text.line_to_char(start_line);
+    let indent = text
+        .line(start_line)
+        .chars()
+        .take_while(|&ch| char_is_whitespace(ch))
+        .count();
+    if start <= line_start + indent {
+        return (line_range(text, start_line, end_line - 1), true);
+    }
+    (
+        Range::new(start, line_end_char_index(&text, end_line - 1)),
+        false,
+    )
+}
+
+/// Returns the range of the lines `first` to `last`, with their line endings.
+pub fn line_range(text: RopeSlice, first: usize, last: usize) -> Range {
+    let last = last.min(text.len_lines() - 1);
+    Range::new(text.line_to_char(first), text.line_to_char(last + 1))
+}
+
+/// Shrinks a range of whole lines to their content, from the first non-whitespace character
+/// up to the last line ending, like `cc` keeping the indentation of the line it changes.
+pub fn line_content(text: RopeSlice, range: Range) -> Range {
+    let first = text.char_to_line(range.from());
+    let last = text.char_to_line(range.to().saturating_sub(1).max(range.from()));
+    let line_start = text.line_to_char(first);
+    let indent = text
+        .line(first)
+        .first_non_whitespace_char()
+        .unwrap_or_else(|| line_end_char_index(&text, first) - line_start);
+    Range::new(
+        line_start + indent,
+        line_end_char_index(&text, last).max(line_start + indent),
+    )
+}
+
+/// Drops the whitespace at the end of `range` if it starts in a word, making `cw` change the
+/// rest of the word like `ce` the way vi does.
+pub fn trim_trailing_whitespace(text: RopeSlice, range: Range) -> Range {
+    if range.is_empty() || char_is_whitespace(text.char(range.from())) {
+        return range;
+    }
+    let mut end = range.to();
+    while char_is_whitespace(text.char(end - 1)) {
+        end -= 1;
+    }
+    Range::new(range.from(), end)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use ropey::Rope;
+
+    #[test]
+    fn test_motion_range() {
+        let doc = Rope::from("one two\n  three four\n\nfive\n");
+        let text = doc.slice(..);
+        let point = |pos: usize| Range::point(pos);
+        // (cursor, motion, kind, range, linewise)
+        let tests = [
+            // dl deletes the character under the cursor, dh the one before it
+            (4, point(5), MotionKind::Exclusive, (4, 5), false),
+            (4, point(3), MotionKind::Exclusive, (3, 4), false),
+            // df deletes up to and including the character found
+            (0, point(5), MotionKind::Inclusive, (0, 6), false),
+            // dj deletes both lines
+            (4, point(12), MotionKind::Linewise, (0, 21), true),
+            (12, point(4), MotionKind::Linewise, (0, 21), true),
+            // a search match is deleted up to its start
+            (0, Range::new(10, 15), MotionKind::Match, (0, 10), false),
+            // ending at the start of a line leaves the line ending alone
+            (4, point(8), MotionKind::Exclusive, (4, 7), false),
+            // unless the motion started in the indentation, which makes it linewise
+            (8, point(21), MotionKind::Exclusive, (8, 21), true),
+            (0, point(22), MotionKind::Exclusive, (0, 22), true),
+            (3, Range::new(5, 9), MotionKind::Selection, (5, 9), false),
+        ];
+        for (cursor, motion, kind, (from, to), linewise) in tests {
+            assert_eq!(
+                motion_range(text, point(cursor), motion, kind),
+                (Range::new(from, to), linewise),
+                "{kind:?} from {cursor} to {motion:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_line_content() {
+        let doc = Rope::from("one\n  two\n  three\n\n");
+        let text = doc.slice(..);
+        assert_eq!(
+            line_content(text, line_range(text, 1, 2)),
+            Range::new(6, 17)
+        );
+        assert_eq!(line_content(text, line_range(text, 0, 0)), Range::new(0, 3));
+        assert_eq!(
+            line_content(text, line_range(text, 3, 3)),
+            Range::new(18, 18)
+        );
+    }
+
+    #[test]
+    fn test_trim_trailing_whitespace() {
+        let doc = Rope::from("word  next");
+        let text = doc.slice(..);
+        assert_eq!(
+            trim_trailing_whitespace(text, Range::new(0, 6)),
+            Range::new(0, 4)
+        );
+        assert_eq!(
+            trim_trailing_whitespace(text, Range::new(4, 6)),
+            Range::new(4, 6)
+        );
+    }
+}
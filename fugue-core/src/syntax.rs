@@ -2,6 +2,7 @@
 // Licensed under MPL-2.0
 
 pub mod config;
+pub mod structural;
 
 use std::{
     borrow::Cow,
@@ -46,6 +47,7 @@ pub struct LanguageData {
     indent_query: OnceCell<Option<IndentQuery>>,
     textobject_query: OnceCell<Option<TextObjectQuery>>,
     tag_query: OnceCell<Option<TagQuery>>,
+    locals_query: OnceCell<Option<LocalsQuery>>,
     rainbow_query: OnceCell<Option<RainbowQuery>>,
 }
 
@@ -57,6 +59,7 @@ fn new(config: LanguageConfiguration) -> Self {
             indent_query: OnceCell::new(),
             textobject_query: OnceCell::new(),
             tag_query: OnceCell::new(),
+            locals_query: OnceCell::new(),
             rainbow_query: OnceCell::new(),
         }
     }
@@ -204,6 +207,36 @@ fn tag_query(&self, loader: &Loader) -> Option<&TagQuery> {
             .as_ref()
     }
 
+    /// Compiles the locals.scm query for a language.
+    /// This function should only be used by this module or the xtask crate.
+    pub fn compile_locals_query(
+        grammar: Grammar,
+        config: &LanguageConfiguration,
+    ) -> Result<Option<LocalsQuery>> {
+        let name = &config.language_id;
+        let text = read_query(name, "locals.scm");
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let query = Query::new(grammar, &text, |_, _| Ok(()))
+            .with_context(|| format!("Failed to compile locals.scm query for '{name}'"))?;
+        Ok(Some(LocalsQuery { query }))
+    }
+
+    fn locals_query(&self, loader: &Loader) -> Option<&LocalsQuery> {
+        self.locals_query
+            .get_or_init(|| {
+                let grammar = self.syntax_config(loader)?.grammar;
+                Self::compile_locals_query(grammar, &self.config)
+                    .map_err(|err| {
+                        log::error!("{err}");
+                    })
+                    .ok()
+                    .flatten()
+            })
+            .as_ref()
+    }
+
     /// Compiles the rainbows.scm query for a language.
     /// This function should only be used by this module or the xtask crate.
     pub fn compile_rainbow_query(
@@ -423,6 +456,10 @@ pub fn tag_query(&self, lang: Language) -> Option<&TagQuery> {
         self.language(lang).tag_query(self)
     }
 
+    pub fn locals_query(&self, lang: Language) -> Option<&LocalsQuery> {
+        self.language(lang).locals_query(self)
+    }
+
     fn rainbow_query(&self, lang: Language) -> Option<&RainbowQuery> {
         self.language(lang).rainbow_query(self)
     }
@@ -629,6 +666,19 @@ pub fn tags<'a>(
         )
     }
 
+    pub fn locals<'a>(
+        &'a self,
+        source: RopeSlice<'a>,
+        loader: &'a Loader,
+        range: impl RangeBounds<u32>,
+    ) -> QueryIter<'a, 'a, impl FnMut(Language) -> Option<&'a Query> + 'a, ()> {
+        self.query_iter(
+            source,
+            |lang| loader.locals_query(lang).map(|q| &q.query),
+            range,
+        )
+    }
+
     pub fn rainbow_highlights(
         &self,
         source: RopeSlice,
@@ -1076,6 +1126,12 @@ pub struct TagQuery {
     pub query: Query,
 }
 
+/// The query of the scopes, definitions and references of local variables.
+#[derive(Debug)]
+pub struct LocalsQuery {
+    pub query: Query,
+}
+
 pub fn pretty_print_tree<W: fmt::Write>(fmt: &mut W, node: Node) -> fmt::Result {
     if node.child_count() == 0 {
         if node_is_visible(&node) {
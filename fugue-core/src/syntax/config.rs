@@ -277,6 +277,7 @@ pub enum LanguageServerFeature {
     RenameSymbol,
     InlayHints,
     DocumentColors,
+    InlineValues,
 }
 
 impl Display for LanguageServerFeature {
@@ -302,6 +303,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             RenameSymbol => "rename-symbol",
             InlayHints => "inlay-hints",
             DocumentColors => "document-colors",
+            InlineValues => "inline-values",
         };
         write!(f, "{feature}",)
     }
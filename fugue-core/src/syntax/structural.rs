@@ -0,0 +1,335 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+//! Structural search matches syntax trees against patterns. A pattern is either a tree-sitter
+//! query or code with metavariables, like `foo($A, $B)`. Code patterns are parsed with the
+//! grammar of the searched language and turned into a query in which every metavariable matches
+//! any node. Replacement templates refer to the captured nodes with the same metavariables.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use ropey::RopeSlice;
+use tree_house::tree_sitter::{Grammar, InactiveQueryCursor, Node, Parser, Query, RopeInput, Tree};
+
+use super::TREE_SITTER_MATCH_LIMIT;
+
+/// Names the capture spanning a whole match in tree-sitter query patterns.
+pub const MATCH_CAPTURE: &str = "match";
+
+/// Prefixes the identifiers that stand in for metavariables while a pattern is parsed.
+const PLACEHOLDER_PREFIX: &str = "fugue_metavar_";
+
+const PARSE_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Debug)]
+pub struct StructuralPattern {
+    query: Query,
+    /// Whether the pattern was written as code, so that its metavariables are captured.
+    is_code: bool,
+}
+
+/// A match of a pattern, with byte ranges into the searched text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructuralMatch {
+    pub range: Range<usize>,
+    /// The nodes captured by metavariables, or by the captures of a query.
+    pub captures: HashMap<String, Range<usize>>,
+}
+
+impl StructuralPattern {
+    /// Compiles `pattern` for `grammar`. Patterns starting with `(` or `[` are tree-sitter
+    /// queries; their `@match` capture, or else all their captures, span a match.
+    pub fn new(grammar: Grammar, pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            bail!("empty pattern");
+        }
+        let is_code = !pattern.starts_with(['(', '[']);
+        let source = if is_code {
+            code_to_query(grammar, pattern)?
+        } else {
+            pattern.to_owned()
+        };
+        log::debug!("structural search query: {source}");
+        let query = Query::new(grammar, &source, |_, _| Ok(()))
+            .context("failed to compile the structural search query")?;
+        Ok(Self { query, is_code })
+    }
+
+    /// Returns the matches of the pattern below `node`, outermost first. Matches nested in an
+    /// earlier match are left out.
+    pub fn matches(&self, node: &Node, text: RopeSlice) -> Vec<StructuralMatch> {
+        let match_capture = self.query.get_capture(MATCH_CAPTURE);
+        let mut cursor = InactiveQueryCursor::new(0..u32::MAX, TREE_SITTER_MATCH_LIMIT)
+            .execute_query(&self.query, node, RopeInput::new(text));
+        let mut matches: Vec<StructuralMatch> = Vec::new();
+        while let Some(mat) = cursor.next_match() {
+            let mut range: Option<Range<usize>> = None;
+            let mut captures = HashMap::new();
+            for matched in mat.matched_nodes() {
+                let node_range =
+                    matched.node.start_byte() as usize..matched.node.end_byte() as usize;
+                let name = self.query.capture_name(matched.capture);
+                if Some(matched.capture) == match_capture {
+                    range = Some(node_range.clone());
+                } else if match_capture.is_none() {
+                    range = Some(match range {
+                        Some(range) => {
+                            range.start.min(node_range.start)..range.end.max(node_range.end)
+                        }
+                        None => node_range.clone(),
+                    });
+                }
+                // captures of literals and repeated metavariables are internal
+                if !self.is_code || !name.starts_with('_') {
+                    captures.insert(name.to_owned(), node_range);
+                }
+            }
+            if let Some(range) = range {
+                matches.push(StructuralMatch { range, captures });
+            }
+        }
+        // tree-sitter reports matches by their first captured node, so outer matches can come
+        // after the matches nested in them. Of the matches starting at the same byte the
+        // longest, outer one comes first
+        matches.sort_by_key(|mat| (mat.range.start, std::cmp::Reverse(mat.range.end)));
+        let mut end = 0;
+        matches.retain(|mat| {
+            let keep = mat.range.start >= end;
+            if keep {
+                end = mat.range.end;
+            }
+            keep
+        });
+        matches
+    }
+}
+
+/// Parses `text` with `grammar`, without injected languages.
+pub fn parse(grammar: Grammar, text: RopeSlice) -> Result<Tree> {
+    let mut parser = Parser::new();
+    parser.set_grammar(grammar)?;
+    parser.set_timeout(PARSE_TIMEOUT);
+    parser.parse(text, None).context("parsing timed out")
+}
+
+/// Expands `template` for `mat`, replacing metavariables like `$A` with the text they captured.
+/// `$$` inserts a dollar sign.
+pub fn expand_template(template: &str, text: RopeSlice, mat: &StructuralMatch) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        let len = metavariable_len(rest);
+        match mat.captures.get(&rest[..len]).filter(|_| len > 0) {
+            Some(range) => {
+                expanded.extend(text.byte_slice(range.clone()).chunks());
+                rest = &rest[len..];
+            }
+            None => expanded.push('$'),
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// The length of the metavariable name at the start of `text`: an uppercase letter or
+/// underscore followed by uppercase letters, digits and underscores. Metavariables starting with
+/// an underscore aren't captured, `$_` matches any node.
+fn metavariable_len(text: &str) -> usize {
+    let mut chars = text.char_indices();
+    match chars.next() {
+        Some((_, c)) if c.is_ascii_uppercase() || c == '_' => (),
+        _ => return 0,
+    }
+    chars
+        .find(|(_, c)| !(c.is_ascii_uppercase() || c.is_ascii_digit() || *c == '_'))
+        .map_or(text.len(), |(idx, _)| idx)
+}
+
+/// Replaces the metavariables in `pattern` with placeholder identifiers the grammar can parse.
+fn substitute_metavariables(pattern: &str) -> String {
+    let mut substituted = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(start) = rest.find('$') {
+        substituted.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let len = metavariable_len(rest);
+        if len == 0 {
+            substituted.push('$');
+            continue;
+        }
+        substituted.push_str(PLACEHOLDER_PREFIX);
+        substituted.push_str(&rest[..len]);
+        rest = &rest[len..];
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+/// Turns a code pattern into a tree-sitter query matching the same syntax tree, with the
+/// metavariables matching any node.
+fn code_to_query(grammar: Grammar, pattern: &str) -> Result<String> {
+    let source = substitute_metavariables(pattern);
+    let text = RopeSlice::from(source.as_str());
+    let tree = parse(grammar, text)?;
+
+    // the parsed pattern is wrapped in nodes like the source file or an expression statement
+    let mut node = tree.root_node();
+    while node.named_child_count() == 1 {
+        let child = node.named_child(0).unwrap();
+        if child.byte_range() != node.byte_range() {
+            break;
+        }
+        node = child;
+    }
+
+    let mut query = QueryBuilder {
+        text: &source,
+        query: String::from("("),
+        predicates: String::new(),
+        metavariables: HashMap::new(),
+        literals: 0,
+    };
+    query.node(&node)?;
+    write!(query.query, " @{MATCH_CAPTURE}{})", query.predicates).unwrap();
+    Ok(query.query)
+}
+
+struct QueryBuilder<'a> {
+    text: &'a str,
+    query: String,
+    /// The predicates of the query, added after the pattern.
+    predicates: String,
+    /// How often each metavariable was used so far.
+    metavariables: HashMap<String, usize>,
+    /// The number of captured literals so far, used to name their captures.
+    literals: usize,
+}
+
+impl QueryBuilder<'_> {
+    fn node(&mut self, node: &Node) -> Result<()> {
+        if node.kind() == "ERROR" || node.is_missing() {
+            let text = &self.text[node.start_byte() as usize..node.end_byte() as usize];
+            bail!("the pattern doesn't parse at '{text}', try writing it as a tree-sitter query");
+        }
+        let text = &self.text[node.start_byte() as usize..node.end_byte() as usize];
+
+        if let Some(name) = text.strip_prefix(PLACEHOLDER_PREFIX) {
+            if name == "_" {
+                self.query.push_str("(_)");
+                return Ok(());
+            }
+            let uses = self.metavariables.entry(name.to_owned()).or_default();
+            *uses += 1;
+            if *uses == 1 {
+                write!(self.query, "(_) @{name}").unwrap();
+            } else {
+                // repeated metavariables match the same text
+                let capture = format!("_{name}_{uses}");
+                write!(self.query, "(_) @{capture}").unwrap();
+                write!(self.predicates, " (#eq? @{capture} @{name})").unwrap();
+            }
+            return Ok(());
+        }
+
+        if !node.is_named() {
+            write!(self.query, "\"{}\"", escape(node.kind())).unwrap();
+            return Ok(());
+        }
+
+        let children: Vec<_> = node.children().filter(|child| !child.is_extra()).collect();
+        if children.is_empty() {
+            // leaves like identifiers and literals match by their text
+            self.literals += 1;
+            let capture = format!("_literal{}", self.literals);
+            write!(self.query, "({}) @{capture}", node.kind()).unwrap();
+            write!(self.predicates, " (#eq? @{capture} \"{}\")", escape(text)).unwrap();
+            return Ok(());
+        }
+
+        write!(self.query, "({}", node.kind()).unwrap();
+        // anchors keep named children adjacent, so that `foo($A)` doesn't match `foo(a, b)`
+        let mut previous_named = true;
+        for child in &children {
+            if previous_named && child.is_named() {
+                self.query.push_str(" .");
+            }
+            self.query.push(' ');
+            self.node(child)?;
+            previous_named = child.is_named();
+        }
+        if previous_named {
+            self.query.push_str(" .");
+        }
+        self.query.push(')');
+        Ok(())
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use once_cell::sync::Lazy;
+
+    use super::*;
+    use crate::syntax::Loader;
+    use crate::Rope;
+    use tree_house::LanguageLoader;
+
+    static LOADER: Lazy<Loader> = Lazy::new(crate::config::default_lang_loader);
+
+    #[test]
+    fn outer_matches() {
+        let language = LOADER.language_for_name("rust").unwrap();
+        let grammar = LOADER.get_config(language).unwrap().grammar;
+        let source = Rope::from_str("fn f() { a + b + c; g(g(x)); }");
+        let tree = parse(grammar, source.slice(..)).unwrap();
+        let ranges = |pattern| {
+            StructuralPattern::new(grammar, pattern)
+                .unwrap()
+                .matches(&tree.root_node(), source.slice(..))
+                .into_iter()
+                .map(|mat| mat.range)
+                .collect::<Vec<_>>()
+        };
+
+        // the nested `a + b` starts with the outer expression
+        assert_eq!(ranges("(binary_expression) @match"), [9..18]);
+        assert_eq!(ranges("g($A)"), [20..27]);
+    }
+
+    #[test]
+    fn substitute() {
+        assert_eq!(
+            substitute_metavariables("foo($A, $B1, $_, $x, $)"),
+            "foo(fugue_metavar_A, fugue_metavar_B1, fugue_metavar__, $x, $)"
+        );
+    }
+
+    #[test]
+    fn expand() {
+        let text = RopeSlice::from("foo(a, bar)");
+        let mat = StructuralMatch {
+            range: 0..11,
+            captures: [("A".to_owned(), 4..5), ("B".to_owned(), 7..10)].into(),
+        };
+        assert_eq!(expand_template("foo($B, $A)", text, &mat), "foo(bar, a)");
+        assert_eq!(expand_template("$$A $C $", text, &mat), "$A $C $");
+    }
+}
@@ -174,6 +174,12 @@ fn insert_virtual_lines(
         line_end_visual_pos: Position,
         doc_line: usize,
     ) -> Position;
+
+    /// The number of virtual lines to reserve above the first line of the document. These
+    /// can't be inserted by `insert_virtual_lines` which is only called at the end of a line.
+    fn virtual_lines_above_first_line(&mut self) -> usize {
+        0
+    }
 }
 
 #[derive(Debug)]
@@ -408,6 +414,13 @@ pub(crate) fn process_virtual_text_anchors(&self, grapheme: &FormattedGrapheme)
         }
     }
 
+    pub(crate) fn virtual_lines_above_first_line(&self) -> usize {
+        self.line_annotations
+            .iter()
+            .map(|(_, layer)| unsafe { layer.get().virtual_lines_above_first_line() })
+            .sum()
+    }
+
     pub(crate) fn virtual_lines_at(
         &self,
         char_idx: usize,
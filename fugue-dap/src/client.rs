@@ -13,6 +13,7 @@
 use serde_json::Value;
 
 use anyhow::anyhow;
+use base64::prelude::{Engine as _, BASE64_STANDARD};
 use std::{
     collections::HashMap,
     future::Future,
@@ -40,6 +41,8 @@ pub struct Client {
     /// The socket address of the debugger, if using TCP transport.
     pub socket: Option<SocketAddr>,
     pub caps: Option<DebuggerCapabilities>,
+    /// Threads reported by the debuggee the last time it stopped.
+    pub threads: Vec<Thread>,
     // thread_id -> frames
     pub stack_frames: HashMap<ThreadId, Vec<StackFrame>>,
     pub thread_states: ThreadStates,
@@ -49,6 +52,10 @@ pub struct Client {
     pub quirks: DebuggerQuirks,
     /// The config which was used to start this debugger.
     pub config: Option<DebugAdapterConfig>,
+    /// Name of the session shown to the user, usually the name of the launch configuration.
+    pub name: String,
+    /// The session that requested this one through `startDebugging`.
+    pub parent: Option<DebugAdapterId>,
 }
 
 impl Client {
@@ -87,6 +94,7 @@ pub fn streams(
             server_tx,
             request_counter: AtomicU64::new(0),
             caps: None,
+            threads: Vec::new(),
             connection_type: None,
             starting_request_args: None,
             socket: None,
@@ -96,6 +104,8 @@ pub fn streams(
             active_frame: None,
             quirks: DebuggerQuirks::default(),
             config: None,
+            name: String::new(),
+            parent: None,
         };
 
         tokio::spawn(Self::recv(id, server_rx, client_tx));
@@ -356,9 +366,10 @@ pub async fn initialize(&mut self, adapter_id: String) -> Result<()> {
             supports_variable_type: Some(true),
             supports_variable_paging: Some(false),
             supports_run_in_terminal_request: Some(true),
-            supports_memory_references: Some(false),
+            supports_memory_references: Some(true),
             supports_progress_reporting: Some(false),
             supports_invalidated_event: Some(false),
+            supports_memory_event: Some(true),
         };
 
         let response = self.request::<requests::Initialize>(args).await?;
@@ -466,6 +477,19 @@ pub async fn scopes(&self, frame_id: usize) -> Result<Vec<Scope>> {
         Ok(response.scopes)
     }
 
+    pub fn source(
+        &self,
+        source: Source,
+    ) -> impl Future<Output = Result<requests::SourceResponse>> + 'static {
+        let args = requests::SourceArguments {
+            source_reference: source.source_reference.unwrap_or_default(),
+            source: Some(source),
+        };
+
+        let request = self.call::<requests::Source>(args);
+        async move { Ok(serde_json::from_value(request.await?)?) }
+    }
+
     pub async fn variables(&self, variables_reference: usize) -> Result<Vec<Variable>> {
         let args = requests::VariablesArguments {
             variables_reference,
@@ -479,29 +503,41 @@ pub async fn variables(&self, variables_reference: usize) -> Result<Vec<Variable
         Ok(response.variables)
     }
 
-    pub fn step_in(&self, thread_id: ThreadId) -> impl Future<Output = Result<Value>> {
+    pub fn step_in(
+        &self,
+        thread_id: ThreadId,
+        granularity: Option<String>,
+    ) -> impl Future<Output = Result<Value>> {
         let args = requests::StepInArguments {
             thread_id,
             target_id: None,
-            granularity: None,
+            granularity,
         };
 
         self.call::<requests::StepIn>(args)
     }
 
-    pub fn step_out(&self, thread_id: ThreadId) -> impl Future<Output = Result<Value>> {
+    pub fn step_out(
+        &self,
+        thread_id: ThreadId,
+        granularity: Option<String>,
+    ) -> impl Future<Output = Result<Value>> {
         let args = requests::StepOutArguments {
             thread_id,
-            granularity: None,
+            granularity,
         };
 
         self.call::<requests::StepOut>(args)
     }
 
-    pub fn next(&self, thread_id: ThreadId) -> impl Future<Output = Result<Value>> {
+    pub fn next(
+        &self,
+        thread_id: ThreadId,
+        granularity: Option<String>,
+    ) -> impl Future<Output = Result<Value>> {
         let args = requests::NextArguments {
             thread_id,
-            granularity: None,
+            granularity,
         };
 
         self.call::<requests::Next>(args)
@@ -528,6 +564,55 @@ pub async fn eval(
         self.request::<requests::Evaluate>(args).await
     }
 
+    pub async fn read_memory(
+        &self,
+        memory_reference: String,
+        offset: isize,
+        count: usize,
+    ) -> Result<requests::ReadMemoryResponse> {
+        let args = requests::ReadMemoryArguments {
+            memory_reference,
+            offset: Some(offset),
+            count,
+        };
+
+        self.request::<requests::ReadMemory>(args).await
+    }
+
+    pub async fn write_memory(
+        &self,
+        memory_reference: String,
+        offset: isize,
+        data: &[u8],
+    ) -> Result<requests::WriteMemoryResponse> {
+        let args = requests::WriteMemoryArguments {
+            memory_reference,
+            offset: Some(offset),
+            allow_partial: Some(false),
+            data: BASE64_STANDARD.encode(data),
+        };
+
+        self.request::<requests::WriteMemory>(args).await
+    }
+
+    pub async fn disassemble(
+        &self,
+        memory_reference: String,
+        instruction_offset: isize,
+        instruction_count: usize,
+    ) -> Result<Vec<DisassembledInstruction>> {
+        let args = requests::DisassembleArguments {
+            memory_reference,
+            offset: None,
+            instruction_offset: Some(instruction_offset),
+            instruction_count,
+            resolve_symbols: Some(true),
+        };
+
+        let response = self.request::<requests::Disassemble>(args).await?;
+        Ok(response.instructions)
+    }
+
     pub fn set_exception_breakpoints(
         &self,
         filters: Vec<String>,
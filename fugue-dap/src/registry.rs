@@ -1,7 +1,8 @@
 // Copyright (c) 2020 Blaž Hrastnik
 // Licensed under MPL-2.0
 
-use crate::{Client, Payload, Result, StackFrame};
+use crate::{Client, Error, Payload, Result, StackFrame};
+use anyhow::anyhow;
 use fugue_core::syntax::config::DebugAdapterConfig;
 use futures_executor::block_on;
 use futures_util::stream::SelectAll;
@@ -14,10 +15,8 @@
 /// plus showing the heirarcihical nature betweeen them
 pub struct Registry {
     inner: SlotMap<DebugAdapterId, Client>,
-    /// The active debugger client
-    ///
-    /// TODO: You can have multiple active debuggers, so the concept of a single active debugger
-    /// may need to be changed
+    /// The focused debugger client. Several sessions can run at once, but commands and views
+    /// act on the focused one.
     current_client_id: Option<DebugAdapterId>,
     /// A stream of incoming messages from all debuggers
     pub incoming: SelectAll<UnboundedReceiverStream<(DebugAdapterId, Payload)>>,
@@ -54,6 +53,7 @@ pub fn start_client(
             self.incoming.push(UnboundedReceiverStream::new(receiver));
 
             client.config = Some(config.clone());
+            client.name = config.name.clone();
             block_on(client.initialize(config.name.clone()))?;
             client.quirks = config.quirks.clone();
 
@@ -61,8 +61,68 @@ pub fn start_client(
         })
     }
 
+    /// Starts a child session requested by `parent` through `startDebugging`. Adapters using
+    /// TCP are connected to again, while adapters using stdio are spawned once more.
+    pub fn start_child_client(
+        &mut self,
+        parent: DebugAdapterId,
+        name: Option<String>,
+    ) -> Result<DebugAdapterId> {
+        let parent_client = self
+            .get_client(parent)
+            .ok_or_else(|| Error::Other(anyhow!("Parent debugger not found")))?;
+        let config = parent_client
+            .config
+            .clone()
+            .ok_or_else(|| Error::Other(anyhow!("No configuration found for the debugger")))?;
+        let socket = parent_client.socket;
+        let name = name.unwrap_or_else(|| format!("{} (child)", parent_client.name));
+
+        let id = self.start_client(socket, &config)?;
+        let client = &mut self.inner[id];
+        client.parent = Some(parent);
+        client.name = name;
+        Ok(id)
+    }
+
+    /// Removes a session. If it was focused, its parent or another running session is focused
+    /// instead.
     pub fn remove_client(&mut self, id: DebugAdapterId) {
-        self.inner.remove(id);
+        let Some(client) = self.inner.remove(id) else {
+            return;
+        };
+        for child in self.inner.values_mut() {
+            if child.parent == Some(id) {
+                child.parent = client.parent;
+            }
+        }
+        if self.current_client_id == Some(id) {
+            self.current_client_id = client
+                .parent
+                .filter(|parent| self.inner.contains_key(*parent))
+                .or_else(|| self.inner.keys().next());
+        }
+    }
+
+    /// All running sessions.
+    pub fn clients(&self) -> impl Iterator<Item = &Client> {
+        self.inner.values()
+    }
+
+    pub fn clients_mut(&mut self) -> impl Iterator<Item = &mut Client> {
+        self.inner.values_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn active_client_id(&self) -> Option<DebugAdapterId> {
+        self.current_client_id
     }
 
     pub fn get_client(&self, id: DebugAdapterId) -> Option<&Client> {
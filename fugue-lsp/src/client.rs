@@ -392,6 +392,10 @@ pub fn supports_feature(&self, feature: LanguageServerFeature) -> bool {
                         | ColorProviderCapability::Options(_)
                 )
             ),
+            LanguageServerFeature::InlineValues => matches!(
+                capabilities.inline_value_provider,
+                Some(OneOf::Left(true) | OneOf::Right(_))
+            ),
         }
     }
 
@@ -705,6 +709,9 @@ pub(crate) async fn initialize(&self, enable_snippets: bool) -> Result<lsp::Init
                         dynamic_registration: Some(false),
                         resolve_support: None,
                     }),
+                    inline_value: Some(lsp::InlineValueClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
                     ..Default::default()
                 }),
                 window: Some(lsp::WindowClientCapabilities {
@@ -1140,6 +1147,29 @@ pub fn text_document_range_inlay_hints(
         Some(self.call::<lsp::request::InlayHintRequest>(params))
     }
 
+    pub fn text_document_inline_value(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        range: lsp::Range,
+        context: lsp::InlineValueContext,
+    ) -> Option<impl Future<Output = Result<Option<Vec<lsp::InlineValue>>>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        match capabilities.inline_value_provider {
+            Some(lsp::OneOf::Left(true) | lsp::OneOf::Right(_)) => (),
+            _ => return None,
+        }
+
+        let params = lsp::InlineValueParams {
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            text_document,
+            range,
+            context,
+        };
+
+        Some(self.call::<lsp::request::InlineValueRequest>(params))
+    }
+
     pub fn text_document_document_color(
         &self,
         text_document: lsp::TextDocumentIdentifier,
@@ -12,29 +12,34 @@
 use fugue_view::{
     align_view,
     document::{DocumentOpenError, DocumentSavedEventResult},
-    editor::{ConfigEvent, EditorEvent},
+    editor::{Action, ConfigEvent, EditorEvent},
+    file_watcher::FileWatcher,
     graphics::Rect,
+    pinned_files::PinnedFiles,
+    recent_files::RecentFiles,
     tree::Layout,
-    Align, Editor,
+    workspace_index::WorkspaceIndex,
+    Align, Document, DocumentId, Editor, ViewId,
 };
 use futures_util::Stream;
 use serde_json::json;
-use tui::backend::Backend;
+use tui::{backend::Backend, widgets::Row};
 
 use crate::{
     args::Args,
+    commands,
     compositor::{Compositor, Event},
     config::Config,
     handlers,
     job::Jobs,
     keymap::Keymaps,
-    ui::{self, overlay::overlaid},
+    ui::{self, file_tree::FileTree, overlay::overlaid, Popup, PromptEvent},
 };
 
 use log::{debug, error, info, warn};
 use std::{
     io::{stdin, IsTerminal},
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
@@ -96,8 +101,6 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
         #[cfg(feature = "integration")]
         setup_integration_logging();
 
-        use fugue_view::editor::Action;
-
         #[cfg(not(feature = "integration"))]
         let backend = TerminaBackend::new((&config.editor).into())
             .context("failed to create terminal backend")?;
@@ -119,6 +122,18 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
             handlers,
         );
 
+        if !cfg!(feature = "integration") {
+            let workspace = fugue_stdx::path::canonicalize(fugue_loader::find_workspace().0);
+            editor.recent_files = RecentFiles::load(
+                fugue_loader::cache_dir().join("recent_files.json"),
+                workspace.clone(),
+            );
+            editor.pinned_files = PinnedFiles::load(
+                fugue_loader::cache_dir().join("pinned_files.json"),
+                workspace,
+            );
+        }
+
         let keys = Box::new(Map::new(Arc::clone(&config), |config: &Config| {
             &config.keys
         }));
@@ -218,6 +233,14 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
                 .unwrap_or_else(|_| editor.new_file(Action::VerticalSplit));
         }
 
+        if editor.config().watch_files && !cfg!(feature = "integration") {
+            editor.file_watcher = FileWatcher::new(fugue_loader::find_workspace().0);
+        }
+
+        if editor.config().workspace_index && !cfg!(feature = "integration") {
+            editor.workspace_index = Some(spawn_workspace_index(&editor));
+        }
+
         let signals = Signals::new([
             signal::SIGTSTP,
             signal::SIGCONT,
@@ -555,6 +578,13 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 
         self.editor
             .set_doc_path(doc_save_event.doc_id, &doc_save_event.path);
+        if let Some(index) = &self.editor.workspace_index {
+            index.update([doc_save_event.path.clone()]);
+        }
+        self.editor
+            .file_statuses
+            .invalidate(self.editor.diff_providers.clone());
+        commands::refresh_blame(&mut self.editor, doc_save_event.doc_id);
         // TODO: fix being overwritten by lsp
         self.editor.set_status(format!(
             "'{}' written, {lines}L {size}",
@@ -589,6 +619,10 @@ pub async fn handle_editor_event(&mut self, event: EditorEvent) -> bool {
             EditorEvent::Redraw => {
                 self.render().await;
             }
+            EditorEvent::FilesChanged(paths) => {
+                self.handle_file_changes(paths);
+                self.render().await;
+            }
             EditorEvent::IdleTimer => {
                 self.editor.clear_idle_timer();
                 self.handle_idle_timeout().await;
@@ -1029,6 +1063,68 @@ macro_rules! language_server {
         }
     }
 
+    /// Reloads the buffers of files changed by other programs and notifies the language servers
+    /// watching them. Buffers with unsaved changes ask what to do instead of being reloaded.
+    fn handle_file_changes(&mut self, paths: Vec<PathBuf>) {
+        if let Some(index) = &self.editor.workspace_index {
+            index.update(paths.iter().cloned());
+        }
+        let mut changed = Vec::new();
+        for path in paths {
+            if let Some(doc) = self.editor.document_by_path(&path) {
+                match doc.read_file() {
+                    // our own writes are reported as well
+                    Ok(text) if text == *doc.text() => continue,
+                    Ok(_) => {
+                        let doc_id = doc.id();
+                        if doc.is_modified() {
+                            changed.push((doc_id, doc.display_name().into_owned()));
+                        } else if let Err(err) = reload_document(&mut self.editor, doc_id) {
+                            self.editor.set_error(err.to_string());
+                        }
+                    }
+                    Err(err) => debug!("failed to read {}: {err}", path.display()),
+                }
+            }
+            self.editor
+                .language_servers
+                .file_event_handler
+                .file_changed(path);
+        }
+        if !changed.is_empty() {
+            // a single menu asks about every changed buffer, including those of earlier batches
+            // that weren't answered yet
+            match self
+                .compositor
+                .find_id::<Popup<ui::Menu<ExternalChange>>>(EXTERNAL_CHANGE_ID)
+            {
+                Some(popup) => {
+                    let (_, options) = popup.contents_mut().update_options();
+                    for option in options {
+                        for doc in &changed {
+                            if !option.docs.contains(doc) {
+                                option.docs.push(doc.clone());
+                            }
+                        }
+                    }
+                }
+                None => self
+                    .compositor
+                    .push(Box::new(external_change_menu(changed))),
+            }
+        }
+        self.editor
+            .file_statuses
+            .invalidate(self.editor.diff_providers.clone());
+        if let Some(tree) = self
+            .compositor
+            .find::<ui::EditorView>()
+            .and_then(|editor_view| editor_view.dock.find_id::<FileTree>(ui::file_tree::ID))
+        {
+            tree.refresh(&self.editor);
+        }
+    }
+
     fn handle_show_document(
         &mut self,
         params: lsp::ShowDocumentParams,
@@ -1177,6 +1273,29 @@ pub async fn close(&mut self) -> Vec<anyhow::Error> {
             errs.push(err);
         }
 
+        if let Err(err) = self.editor.recent_files.save() {
+            log::error!("Error saving the recent files: {}", err);
+            errs.push(err);
+        }
+
+        self.editor.remember_pinned_cursors();
+        if let Err(err) = self.editor.pinned_files.save() {
+            log::error!("Error saving the pinned files: {}", err);
+            errs.push(err);
+        }
+
+        if let Some(index) = self.editor.workspace_index.clone() {
+            // serializing a large index takes a while, keep it off the runtime
+            let result = tokio::task::spawn_blocking(move || index.save())
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result);
+            if let Err(err) = result {
+                log::error!("Error saving the workspace index: {}", err);
+                errs.push(err);
+            }
+        }
+
         if self.editor.close_language_servers(None).await.is_err() {
             log::error!("Timed out waiting for language servers to shutdown");
             errs.push(anyhow::format_err!(
@@ -1187,3 +1306,153 @@ pub async fn close(&mut self) -> Vec<anyhow::Error> {
         errs
     }
 }
+
+const EXTERNAL_CHANGE_ID: &str = "external-change";
+
+/// What to do with a buffer with unsaved changes whose file was changed by another program.
+#[derive(Debug, Clone, Copy)]
+enum ExternalChangeAction {
+    Reload,
+    Keep,
+    Diff,
+}
+
+/// An answer applied to all the buffers listed in it.
+struct ExternalChange {
+    action: ExternalChangeAction,
+    docs: Vec<(DocumentId, String)>,
+}
+
+impl ui::menu::Item for ExternalChange {
+    type Data = ();
+
+    fn format(&self, _data: &Self::Data) -> Row<'_> {
+        let names = self
+            .docs
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self.action {
+            ExternalChangeAction::Reload => {
+                format!("{names} changed on disk: reload, discarding changes")
+            }
+            ExternalChangeAction::Keep => format!("{names} changed on disk: keep unsaved changes"),
+            ExternalChangeAction::Diff => format!("{names} changed on disk: diff against the file"),
+        }
+        .into()
+    }
+}
+
+fn external_change_menu(docs: Vec<(DocumentId, String)>) -> Popup<ui::Menu<ExternalChange>> {
+    let items = [
+        ExternalChangeAction::Reload,
+        ExternalChangeAction::Keep,
+        ExternalChangeAction::Diff,
+    ]
+    .into_iter()
+    .map(|action| ExternalChange {
+        action,
+        docs: docs.clone(),
+    })
+    .collect();
+    let mut menu = ui::Menu::new(items, (), move |editor, change, event| {
+        if event != PromptEvent::Validate {
+            return;
+        }
+        let Some(change) = change else {
+            return;
+        };
+        for &(doc_id, _) in &change.docs {
+            let result = match change.action {
+                ExternalChangeAction::Reload => reload_document(editor, doc_id),
+                ExternalChangeAction::Diff => diff_with_file(editor, doc_id),
+                ExternalChangeAction::Keep => Ok(()),
+            };
+            if let Err(err) = result {
+                editor.set_error(err.to_string());
+            }
+        }
+    });
+    menu.move_down(); // pre-select the first item
+    Popup::new(EXTERNAL_CHANGE_ID, menu)
+        .with_scrollbar(false)
+        .auto_close(true)
+}
+
+/// Reloads a document from its file in every view showing it, like `:reload` does.
+fn reload_document(editor: &mut Editor, doc_id: DocumentId) -> anyhow::Result<()> {
+    let scrolloff = editor.config().scrolloff;
+    let focus = editor.tree.focus;
+    let doc = editor.document_mut(doc_id).context("document was closed")?;
+    let mut view_ids: Vec<ViewId> = doc.selections().keys().copied().collect();
+    if view_ids.is_empty() {
+        doc.ensure_view_init(focus);
+        view_ids.push(focus);
+    }
+
+    let doc = doc_mut!(editor, &doc_id);
+    let view = view_mut!(editor, view_ids[0]);
+    view.sync_changes(doc);
+    doc.reload(view, &editor.diff_providers, &editor.diff_base)?;
+
+    for view_id in view_ids {
+        let view = view_mut!(editor, view_id);
+        if view.doc == doc_id {
+            view.ensure_cursor_in_view(doc, scrolloff);
+        }
+    }
+    // the file may have changed because `HEAD` moved, e.g. by a checkout
+    commands::refresh_blame(editor, doc_id);
+    Ok(())
+}
+
+/// Opens a diff view of the file of a document on disk against its unsaved changes.
+fn diff_with_file(editor: &mut Editor, doc_id: DocumentId) -> anyhow::Result<()> {
+    let doc = editor.document(doc_id).context("document was closed")?;
+    let text = doc.read_file()?;
+    let language = doc.language_name().map(str::to_owned);
+
+    let mut file_doc = Document::from(text, None, editor.config.clone(), editor.syn_loader.clone());
+    file_doc.readonly = true;
+    if let Some(language) = language {
+        let loader = editor.syn_loader.load();
+        if let Err(err) = file_doc.set_language_by_language_id(&language, &loader) {
+            debug!("{err}");
+        }
+    }
+    let file_id = editor.new_file_from_document(Action::Load, file_doc);
+    commands::open_diff_view(editor, file_id, doc_id);
+    Ok(())
+}
+
+/// Starts indexing the files of the workspace that the file picker would list.
+fn spawn_workspace_index(editor: &Editor) -> WorkspaceIndex {
+    let root = fugue_stdx::path::canonicalize(fugue_loader::find_workspace().0);
+    let absolute_root = root.clone();
+    let config = &editor.config().file_picker;
+    let dedup_symlinks = config.deduplicate_links;
+
+    let mut walker = ignore::WalkBuilder::new(&root);
+    walker
+        .hidden(config.hidden)
+        .parents(config.parents)
+        .ignore(config.ignore)
+        .follow_links(config.follow_symlinks)
+        .git_ignore(config.git_ignore)
+        .git_global(config.git_global)
+        .git_exclude(config.git_exclude)
+        .max_depth(config.max_depth)
+        .filter_entry(move |entry| {
+            crate::filter_picker_entry(entry, &absolute_root, dedup_symlinks)
+        })
+        .add_custom_ignore_filename(fugue_loader::config_dir().join("ignore"))
+        .add_custom_ignore_filename(".fugue/ignore");
+
+    WorkspaceIndex::spawn(
+        root,
+        walker,
+        editor.syn_loader.clone(),
+        Some(&fugue_loader::cache_dir().join("workspace_index")),
+    )
+}
@@ -3,8 +3,11 @@
 
 pub(crate) mod dap;
 pub(crate) mod lsp;
+pub(crate) mod operator;
+pub(crate) mod quickfix;
 pub(crate) mod syntax;
 pub(crate) mod typed;
+pub(crate) mod vcs;
 
 pub use dap::*;
 use fugue_event::status;
@@ -12,15 +15,18 @@
     path::{self, find_paths},
     rope::{self, RopeSliceExt},
 };
-use fugue_vcs::{FileChange, Hunk};
+use fugue_vcs::{DiffBase, FileChange, Hunk, LogCommit, Resolution};
 use futures_util::FutureExt;
 pub use lsp::*;
+pub use operator::*;
+pub use quickfix::*;
 pub use syntax::*;
 use tui::{
     text::{Span, Spans},
     widgets::Cell,
 };
 pub use typed::*;
+pub use vcs::*;
 
 use fugue_core::{
     char_idx_at_visual_offset,
@@ -36,7 +42,9 @@
     line_ending::{get_line_ending_of_str, line_end_char_index},
     match_brackets,
     movement::{self, move_vertically_visual, Direction},
-    object, pos_at_coords,
+    object,
+    operator::MotionKind,
+    pos_at_coords,
     regex::{self, Regex},
     search::{self, CharMatcher},
     selection, surround,
@@ -69,7 +77,10 @@
     compositor::{self, Component, Compositor},
     filter_picker_entry,
     job::Callback,
-    ui::{self, overlay::overlaid, Picker, PickerColumn, Popup, Prompt, PromptEvent},
+    ui::{
+        self, dock::DockSide, overlay::overlaid, FileStatusMarkers, Picker, PickerColumn, Popup,
+        Prompt, PromptEvent,
+    },
 };
 
 use crate::job::{self, Jobs};
@@ -93,6 +104,7 @@
 use serde::de::{self, Deserialize, Deserializer};
 use url::Url;
 
+use grep_matcher::Matcher;
 use grep_regex::RegexMatcherBuilder;
 use grep_searcher::{sinks, BinaryDetection, SearcherBuilder};
 use ignore::{DirEntry, WalkBuilder, WalkState};
@@ -111,6 +123,8 @@ pub struct Context<'a> {
 
     pub callback: Vec<crate::compositor::Callback>,
     pub on_next_key_callback: Option<(OnKeyCallback, OnKeyCallbackKind)>,
+    /// An operator started by the command, waiting for the motion it applies to.
+    pub pending_operator: Option<PendingOperator>,
     pub jobs: &'a mut Jobs,
 }
 
@@ -213,6 +227,8 @@ fn make_job_callback<T, F>(
 /// * Typable: commands executable from command mode, prefixed with a `:`,
 ///   for example `:write!`.
 /// * Macro: a sequence of keys to execute, for example `@miw`.
+/// * Operator: a command applied to the text of the motion that follows, for
+///   example `operator :reflow`.
 #[derive(Clone)]
 pub enum MappableCommand {
     Typable {
@@ -224,21 +240,31 @@ pub enum MappableCommand {
         name: &'static str,
         fun: fn(cx: &mut Context),
         doc: &'static str,
+        /// How operators treat the text the command moves over, `None` for commands that
+        /// aren't motions.
+        motion: Option<MotionKind>,
     },
     Macro {
         name: String,
         keys: Vec<KeyEvent>,
     },
+    Operator {
+        name: String,
+        command: Box<MappableCommand>,
+    },
 }
 
 macro_rules! static_commands {
-    ( $($name:ident, $doc:literal,)* ) => {
+    (@motion) => { None };
+    (@motion $motion:ident) => { Some(MotionKind::$motion) };
+    ( $($name:ident, $doc:literal $(=> $motion:ident)?,)* ) => {
         $(
             #[allow(non_upper_case_globals)]
             pub const $name: Self = Self::Static {
                 name: stringify!($name),
                 fun: $name,
-                doc: $doc
+                doc: $doc,
+                motion: static_commands!(@motion $($motion)?),
             };
         )*
 
@@ -285,6 +311,7 @@ pub fn execute(&self, cx: &mut Context) {
                     cx.editor.macro_replaying.pop();
                 }));
             }
+            Self::Operator { command, .. } => start_operator(cx, (**command).clone(), false),
         }
     }
 
@@ -293,6 +320,7 @@ pub fn name(&self) -> &str {
             Self::Typable { name, .. } => name,
             Self::Static { name, .. } => name,
             Self::Macro { name, .. } => name,
+            Self::Operator { name, .. } => name,
         }
     }
 
@@ -301,18 +329,28 @@ pub fn doc(&self) -> &str {
             Self::Typable { doc, .. } => doc,
             Self::Static { doc, .. } => doc,
             Self::Macro { name, .. } => name,
+            Self::Operator { name, .. } => name,
+        }
+    }
+
+    /// Returns how an operator treats the text the command moves over, or `None` for commands
+    /// that aren't motions.
+    pub fn motion_kind(&self) -> Option<MotionKind> {
+        match &self {
+            Self::Static { motion, .. } => *motion,
+            _ => None,
         }
     }
 
     #[rustfmt::skip]
     static_commands!(
         no_op, "Do nothing",
-        move_char_left, "Move left",
-        move_char_right, "Move right",
-        move_line_up, "Move up",
-        move_line_down, "Move down",
-        move_visual_line_up, "Move up",
-        move_visual_line_down, "Move down",
+        move_char_left, "Move left" => Exclusive,
+        move_char_right, "Move right" => Exclusive,
+        move_line_up, "Move up" => Linewise,
+        move_line_down, "Move down" => Linewise,
+        move_visual_line_up, "Move up" => Linewise,
+        move_visual_line_down, "Move down" => Linewise,
         extend_char_left, "Extend left",
         extend_char_right, "Extend right",
         extend_line_up, "Extend up",
@@ -321,20 +359,21 @@ pub fn doc(&self) -> &str {
         extend_visual_line_down, "Extend down",
         copy_selection_on_next_line, "Copy selection on next line",
         copy_selection_on_prev_line, "Copy selection on previous line",
-        move_next_word_start, "Move to start of next word",
-        move_prev_word_start, "Move to start of previous word",
-        move_next_word_end, "Move to end of next word",
-        move_prev_word_end, "Move to end of previous word",
-        move_next_long_word_start, "Move to start of next long word",
-        move_prev_long_word_start, "Move to start of previous long word",
-        move_next_long_word_end, "Move to end of next long word",
-        move_prev_long_word_end, "Move to end of previous long word",
-        move_next_sub_word_start, "Move to start of next sub word",
-        move_prev_sub_word_start, "Move to start of previous sub word",
-        move_next_sub_word_end, "Move to end of next sub word",
-        move_prev_sub_word_end, "Move to end of previous sub word",
-        move_parent_node_end, "Move to end of the parent node",
-        move_parent_node_start, "Move to beginning of the parent node",
+        // the cursor of forward word motions is on the last character they move over
+        move_next_word_start, "Move to start of next word" => Inclusive,
+        move_prev_word_start, "Move to start of previous word" => Exclusive,
+        move_next_word_end, "Move to end of next word" => Inclusive,
+        move_prev_word_end, "Move to end of previous word" => Inclusive,
+        move_next_long_word_start, "Move to start of next long word" => Inclusive,
+        move_prev_long_word_start, "Move to start of previous long word" => Exclusive,
+        move_next_long_word_end, "Move to end of next long word" => Inclusive,
+        move_prev_long_word_end, "Move to end of previous long word" => Inclusive,
+        move_next_sub_word_start, "Move to start of next sub word" => Inclusive,
+        move_prev_sub_word_start, "Move to start of previous sub word" => Exclusive,
+        move_next_sub_word_end, "Move to end of next sub word" => Inclusive,
+        move_prev_sub_word_end, "Move to end of previous sub word" => Inclusive,
+        move_parent_node_end, "Move to end of the parent node" => Inclusive,
+        move_parent_node_start, "Move to beginning of the parent node" => Exclusive,
         extend_next_word_start, "Extend to start of next word",
         extend_prev_word_start, "Extend to start of previous word",
         extend_next_word_end, "Extend to end of next word",
@@ -349,12 +388,12 @@ pub fn doc(&self) -> &str {
         extend_prev_sub_word_end, "Extend to end of prev sub word",
         extend_parent_node_end, "Extend to end of the parent node",
         extend_parent_node_start, "Extend to beginning of the parent node",
-        find_till_char, "Move till next occurrence of char",
-        find_next_char, "Move to next occurrence of char",
+        find_till_char, "Move till next occurrence of char" => Inclusive,
+        find_next_char, "Move to next occurrence of char" => Inclusive,
         extend_till_char, "Extend till next occurrence of char",
         extend_next_char, "Extend to next occurrence of char",
-        till_prev_char, "Move till previous occurrence of char",
-        find_prev_char, "Move to previous occurrence of char",
+        till_prev_char, "Move till previous occurrence of char" => Exclusive,
+        find_prev_char, "Move to previous occurrence of char" => Exclusive,
         extend_till_prev_char, "Extend till previous occurrence of char",
         extend_prev_char, "Extend to previous occurrence of char",
         repeat_last_motion, "Repeat last motion",
@@ -362,15 +401,15 @@ pub fn doc(&self) -> &str {
         switch_case, "Switch (toggle) case",
         switch_to_uppercase, "Switch to uppercase",
         switch_to_lowercase, "Switch to lowercase",
-        page_up, "Move page up",
-        page_down, "Move page down",
+        page_up, "Move page up" => Linewise,
+        page_down, "Move page down" => Linewise,
         half_page_up, "Move half page up",
         half_page_down, "Move half page down",
-        page_cursor_up, "Move page and cursor up",
-        page_cursor_down, "Move page and cursor down",
-        page_cursor_half_up, "Move page and cursor half up",
-        page_cursor_half_down, "Move page and cursor half down",
-        select_all, "Select whole document",
+        page_cursor_up, "Move page and cursor up" => Linewise,
+        page_cursor_down, "Move page and cursor down" => Linewise,
+        page_cursor_half_up, "Move page and cursor half up" => Linewise,
+        page_cursor_half_down, "Move page and cursor half down" => Linewise,
+        select_all, "Select whole document" => Selection,
         select_regex, "Select all regex matches inside selections",
         split_selection, "Split selections on regex matches",
         split_selection_on_newline, "Split selection on newlines",
@@ -378,20 +417,22 @@ pub fn doc(&self) -> &str {
         merge_consecutive_selections, "Merge consecutive selections",
         search, "Search for regex pattern",
         rsearch, "Reverse search for regex pattern",
-        search_next, "Select next search match",
-        search_prev, "Select previous search match",
+        search_next, "Select next search match" => Match,
+        search_prev, "Select previous search match" => Match,
         extend_search_next, "Add next search match to selection",
         extend_search_prev, "Add previous search match to selection",
         search_selection, "Use current selection as search pattern",
         search_selection_detect_word_boundaries, "Use current selection as the search pattern, automatically wrapping with `\\b` on word boundaries",
         make_search_word_bounded, "Modify current search to make it word bounded",
         global_search, "Global search in workspace folder",
+        line_picker, "Open fuzzy picker of the lines of the current buffer",
+        buffer_line_picker, "Open fuzzy picker of the lines of all open buffers",
         extend_line, "Select current line, if already selected, extend to another line based on the anchor",
-        extend_line_below, "Select current line, if already selected, extend to next line",
-        extend_line_above, "Select current line, if already selected, extend to previous line",
+        extend_line_below, "Select current line, if already selected, extend to next line" => Selection,
+        extend_line_above, "Select current line, if already selected, extend to previous line" => Selection,
         select_line_above, "Select current line, if already selected, extend or shrink line above based on the anchor",
         select_line_below, "Select current line, if already selected, extend or shrink line below based on the anchor",
-        extend_to_line_bounds, "Extend selection to line bounds",
+        extend_to_line_bounds, "Extend selection to line bounds" => Selection,
         shrink_to_line_bounds, "Shrink selection to line bounds",
         delete_selection, "Delete selection",
         delete_selection_noyank, "Delete selection without yanking",
@@ -445,6 +486,17 @@ pub fn doc(&self) -> &str {
         yank_to_line, "Yank to line",
         yank_to_file_start, "Yank to file start",
         yank_to_last_line, "Yank to last line",
+        delete_operator, "Delete the text of a motion or text object",
+        change_operator, "Change the text of a motion or text object",
+        yank_operator, "Yank the text of a motion or text object",
+        indent_operator, "Indent the lines of a motion or text object",
+        unindent_operator, "Unindent the lines of a motion or text object",
+        switch_case_operator, "Switch (toggle) case of the text of a motion or text object",
+        lowercase_operator, "Switch the text of a motion or text object to lowercase",
+        uppercase_operator, "Switch the text of a motion or text object to uppercase",
+        comment_operator, "Comment/uncomment the lines of a motion or text object",
+        format_operator, "Format the text of a motion or text object",
+        surround_operator, "Surround the text of a motion or text object",
         collapse_selection, "Collapse selection into single cursor",
         flip_selections, "Flip selection cursor and anchor",
         ensure_selections_forward, "Ensure all selections face forward",
@@ -457,13 +509,25 @@ pub fn doc(&self) -> &str {
         file_explorer, "Open file explorer in workspace root",
         file_explorer_in_current_buffer_directory, "Open file explorer at current buffer's directory",
         file_explorer_in_current_directory, "Open file explorer at current working directory",
+        file_tree, "Open, focus or close file tree sidebar",
+        file_tree_reveal, "Reveal current file in file tree sidebar",
         code_action, "Perform code action",
         buffer_picker, "Open buffer picker",
+        recent_files_picker, "Open picker of recently opened files",
+        pinned_files_picker, "Open picker of pinned files",
+        pin_buffer, "Pin current buffer to a new slot",
+        unpin_buffer, "Unpin current buffer",
+        goto_pinned_file_1, "Goto file pinned to slot 1",
+        goto_pinned_file_2, "Goto file pinned to slot 2",
+        goto_pinned_file_3, "Goto file pinned to slot 3",
+        goto_pinned_file_4, "Goto file pinned to slot 4",
         jumplist_picker, "Open jumplist picker",
         symbol_picker, "Open symbol picker",
         syntax_symbol_picker, "Open symbol picker from syntax information",
         lsp_or_syntax_symbol_picker, "Open symbol picker from LSP or syntax information",
         changed_file_picker, "Open changed file picker",
+        commit_picker, "Open commit picker",
+        file_history_picker, "Open commit picker for the current file",
         select_references_to_symbol_under_cursor, "Select symbol references",
         workspace_symbol_picker, "Open workspace symbol picker",
         syntax_workspace_symbol_picker, "Open workspace symbol picker from syntax information",
@@ -484,39 +548,45 @@ pub fn doc(&self) -> &str {
         add_newline_below, "Add newline below",
         goto_type_definition, "Goto type definition",
         goto_implementation, "Goto implementation",
-        goto_file_start, "Goto line number <n> else file start",
-        goto_file_end, "Goto file end",
+        goto_file_start, "Goto line number <n> else file start" => Linewise,
+        goto_file_end, "Goto file end" => Inclusive,
         extend_to_file_start, "Extend to line number<n> else file start",
         extend_to_file_end, "Extend to file end",
         goto_file, "Goto files/URLs in selections",
         goto_file_hsplit, "Goto files in selections (hsplit)",
         goto_file_vsplit, "Goto files in selections (vsplit)",
         goto_reference, "Goto references",
-        goto_window_top, "Goto window top",
-        goto_window_center, "Goto window center",
-        goto_window_bottom, "Goto window bottom",
+        goto_window_top, "Goto window top" => Linewise,
+        goto_window_center, "Goto window center" => Linewise,
+        goto_window_bottom, "Goto window bottom" => Linewise,
         goto_last_accessed_file, "Goto last accessed file",
         goto_last_modified_file, "Goto last modified file",
-        goto_last_modification, "Goto last modification",
-        goto_line, "Goto line",
-        goto_last_line, "Goto last line",
+        goto_last_modification, "Goto last modification" => Exclusive,
+        goto_line, "Goto line" => Linewise,
+        goto_last_line, "Goto last line" => Linewise,
         extend_to_last_line, "Extend to last line",
-        goto_first_diag, "Goto first diagnostic",
-        goto_last_diag, "Goto last diagnostic",
-        goto_next_diag, "Goto next diagnostic",
-        goto_prev_diag, "Goto previous diagnostic",
-        goto_next_change, "Goto next change",
-        goto_prev_change, "Goto previous change",
-        goto_first_change, "Goto first change",
-        goto_last_change, "Goto last change",
-        goto_line_start, "Goto line start",
-        goto_line_end, "Goto line end",
-        goto_column, "Goto column",
+        goto_first_diag, "Goto first diagnostic" => Match,
+        goto_last_diag, "Goto last diagnostic" => Match,
+        goto_next_diag, "Goto next diagnostic" => Match,
+        goto_prev_diag, "Goto previous diagnostic" => Match,
+        goto_next_change, "Goto next change" => Match,
+        goto_prev_change, "Goto previous change" => Match,
+        goto_first_change, "Goto first change" => Match,
+        goto_last_change, "Goto last change" => Match,
+        goto_next_conflict, "Goto next merge conflict" => Match,
+        goto_prev_conflict, "Goto previous merge conflict" => Match,
+        resolve_conflict_ours, "Resolve merge conflict with our side",
+        resolve_conflict_theirs, "Resolve merge conflict with their side",
+        resolve_conflict_base, "Resolve merge conflict with the common ancestor",
+        resolve_conflict_both, "Resolve merge conflict with both sides",
+        goto_line_start, "Goto line start" => Exclusive,
+        goto_line_end, "Goto line end" => Inclusive,
+        goto_column, "Goto column" => Exclusive,
         extend_to_column, "Extend to column",
         goto_next_buffer, "Goto next buffer",
         goto_previous_buffer, "Goto previous buffer",
-        goto_line_end_newline, "Goto newline at line end",
-        goto_first_nonwhitespace, "Goto first non-blank in line",
+        goto_line_end_newline, "Goto newline at line end" => Exclusive,
+        goto_first_nonwhitespace, "Goto first non-blank in line" => Exclusive,
         trim_selections, "Trim whitespace from selections",
         extend_to_line_start, "Extend to line start",
         extend_to_first_nonwhitespace, "Extend to first non-blank in line",
@@ -576,12 +646,12 @@ pub fn doc(&self) -> &str {
         rotate_selection_contents_forward, "Rotate selection contents forward",
         rotate_selection_contents_backward, "Rotate selections contents backward",
         reverse_selection_contents, "Reverse selections contents",
-        expand_selection, "Expand selection to parent syntax node",
+        expand_selection, "Expand selection to parent syntax node" => Selection,
         shrink_selection, "Shrink selection to previously expanded syntax node",
-        select_next_sibling, "Select next sibling in the syntax tree",
-        select_prev_sibling, "Select previous sibling the in syntax tree",
-        select_all_siblings, "Select all siblings of the current node",
-        select_all_children, "Select all children of the current node",
+        select_next_sibling, "Select next sibling in the syntax tree" => Selection,
+        select_prev_sibling, "Select previous sibling the in syntax tree" => Selection,
+        select_all_siblings, "Select all siblings of the current node" => Selection,
+        select_all_children, "Select all children of the current node" => Selection,
         jump_forward, "Jump forward on jumplist",
         jump_backward, "Jump backward on jumplist",
         save_selection, "Save current selection to jumplist",
@@ -611,28 +681,30 @@ pub fn doc(&self) -> &str {
         align_view_bottom, "Align view bottom",
         scroll_up, "Scroll view up",
         scroll_down, "Scroll view down",
-        match_brackets, "Goto matching bracket",
+        match_brackets, "Goto matching bracket" => Inclusive,
         surround_add, "Surround add",
         surround_replace, "Surround replace",
         surround_delete, "Surround delete",
-        select_textobject_around, "Select around object",
-        select_textobject_inner, "Select inside object",
-        goto_next_function, "Goto next function",
-        goto_prev_function, "Goto previous function",
-        goto_next_class, "Goto next type definition",
-        goto_prev_class, "Goto previous type definition",
-        goto_next_parameter, "Goto next parameter",
-        goto_prev_parameter, "Goto previous parameter",
-        goto_next_comment, "Goto next comment",
-        goto_prev_comment, "Goto previous comment",
-        goto_next_test, "Goto next test",
-        goto_prev_test, "Goto previous test",
-        goto_next_xml_element, "Goto next (X)HTML element",
-        goto_prev_xml_element, "Goto previous (X)HTML element",
-        goto_next_entry, "Goto next pairing",
-        goto_prev_entry, "Goto previous pairing",
-        goto_next_paragraph, "Goto next paragraph",
-        goto_prev_paragraph, "Goto previous paragraph",
+        select_textobject_around, "Select around object" => Selection,
+        select_textobject_inner, "Select inside object" => Selection,
+        goto_next_function, "Goto next function" => Match,
+        goto_prev_function, "Goto previous function" => Match,
+        goto_next_class, "Goto next type definition" => Match,
+        goto_prev_class, "Goto previous type definition" => Match,
+        goto_next_parameter, "Goto next parameter" => Match,
+        goto_prev_parameter, "Goto previous parameter" => Match,
+        goto_next_comment, "Goto next comment" => Match,
+        goto_prev_comment, "Goto previous comment" => Match,
+        goto_next_test, "Goto next test" => Match,
+        goto_prev_test, "Goto previous test" => Match,
+        goto_next_xml_element, "Goto next (X)HTML element" => Match,
+        goto_prev_xml_element, "Goto previous (X)HTML element" => Match,
+        goto_next_entry, "Goto next pairing" => Match,
+        goto_prev_entry, "Goto previous pairing" => Match,
+        goto_next_paragraph, "Goto next paragraph" => Exclusive,
+        goto_prev_paragraph, "Goto previous paragraph" => Exclusive,
+        goto_next_quickfix, "Goto next quickfix entry",
+        goto_prev_quickfix, "Goto previous quickfix entry",
         dap_launch, "Launch debug target",
         dap_restart, "Restart debugging session",
         dap_toggle_breakpoint, "Toggle breakpoint",
@@ -645,8 +717,11 @@ pub fn doc(&self) -> &str {
         dap_terminate, "End debug session",
         dap_edit_condition, "Edit breakpoint condition on current line",
         dap_edit_log, "Edit breakpoint log message on current line",
+        dap_switch_session, "Switch focused debug session",
         dap_switch_thread, "Switch current thread",
         dap_switch_stack_frame, "Switch stack frame",
+        dap_stack_trace, "Toggle or focus call stack view",
+        dap_disassemble, "Disassemble around the instruction pointer",
         dap_enable_exceptions, "Enable exception breakpoints",
         dap_disable_exceptions, "Disable exception breakpoints",
         shell_pipe, "Pipe selections through shell command",
@@ -661,7 +736,7 @@ pub fn doc(&self) -> &str {
         record_macro, "Record macro",
         replay_macro, "Replay macro",
         command_palette, "Open command palette",
-        goto_word, "Jump to a two-character label",
+        goto_word, "Jump to a two-character label" => Match,
         extend_to_word, "Extend to a two-character label",
         goto_next_tabstop, "Goto next snippet placeholder",
         goto_prev_tabstop, "Goto next snippet placeholder",
@@ -686,6 +761,11 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                 .field(name)
                 .field(keys)
                 .finish(),
+            MappableCommand::Operator { name, command } => f
+                .debug_tuple("MappableCommand")
+                .field(name)
+                .field(command)
+                .finish(),
         }
     }
 }
@@ -723,6 +803,16 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
                 name: s.to_string(),
                 keys,
             })
+        } else if let Some(command) = s.strip_prefix("operator ") {
+            let command: MappableCommand = command.trim().parse()?;
+            ensure!(
+                matches!(command, Self::Static { .. } | Self::Typable { .. }),
+                "Operators must be static or typable commands"
+            );
+            Ok(Self::Operator {
+                name: s.to_string(),
+                command: Box::new(command),
+            })
         } else {
             MappableCommand::STATIC_COMMAND_LIST
                 .iter()
@@ -766,6 +856,14 @@ fn eq(&self, other: &Self) -> bool {
                     name: second_name, ..
                 },
             ) => first_name == second_name,
+            (
+                MappableCommand::Operator {
+                    name: first_name, ..
+                },
+                MappableCommand::Operator {
+                    name: second_name, ..
+                },
+            ) => first_name == second_name,
             _ => false,
         }
     }
@@ -2508,13 +2606,16 @@ struct FileResult {
         path: PathBuf,
         /// 0 indexed lines
         line_num: usize,
+        /// The character offset of the match in its line.
+        column: usize,
     }
 
     impl FileResult {
-        fn new(path: &Path, line_num: usize) -> Self {
+        fn new(path: &Path, line_num: usize, column: usize) -> Self {
             Self {
                 path: path.to_path_buf(),
                 line_num,
+                column,
             }
         }
     }
@@ -2639,9 +2740,14 @@ struct GlobalSearchConfig {
                         };
 
                         let mut stop = false;
-                        let sink = sinks::UTF8(|line_num, _line_content| {
+                        let sink = sinks::UTF8(|line_num, line_content| {
+                            let column = matcher
+                                .find(line_content.as_bytes())
+                                .ok()
+                                .flatten()
+                                .map_or(0, |m| line_content[..m.start()].chars().count());
                             stop = injector
-                                .push(FileResult::new(entry.path(), line_num as usize - 1))
+                                .push(FileResult::new(entry.path(), line_num as usize - 1, column))
                                 .is_err();
 
                             Ok(!stop)
@@ -2726,12 +2832,115 @@ struct GlobalSearchConfig {
     .with_preview(|_editor, FileResult { path, line_num, .. }| {
         Some((path.as_path().into(), Some((*line_num, *line_num))))
     })
+    .with_quickfix_name("search")
+    .with_quickfix_column(|_editor, item| item.column)
     .with_history_register(Some(reg))
     .with_dynamic_query(get_files, Some(275));
 
     cx.push_layer(Box::new(overlaid(picker)));
 }
 
+fn line_picker(cx: &mut Context) {
+    line_picker_impl(cx, false)
+}
+
+fn buffer_line_picker(cx: &mut Context) {
+    line_picker_impl(cx, true)
+}
+
+/// Fuzzy matches the lines of the current buffer, or of all open buffers, including the edits
+/// that aren't saved yet.
+fn line_picker_impl(cx: &mut Context, all_buffers: bool) {
+    struct LineResult {
+        id: DocumentId,
+        path: Option<PathBuf>,
+        /// 0 indexed lines
+        line_num: usize,
+        contents: String,
+    }
+
+    let current = view!(cx.editor).doc;
+    let documents: Vec<&Document> = if all_buffers {
+        let mut documents: Vec<_> = cx.editor.documents().collect();
+        // the current buffer first, then the most recently used ones
+        documents.sort_by_key(|doc| (doc.id() != current, std::cmp::Reverse(doc.focused_at)));
+        documents
+    } else {
+        vec![doc!(cx.editor)]
+    };
+
+    let mut items = Vec::new();
+    for doc in documents {
+        for (line_num, line) in doc.text().lines().enumerate() {
+            let contents = line.to_string();
+            let contents = contents.trim_end_matches(['\n', '\r']);
+            if contents.trim().is_empty() {
+                continue;
+            }
+            items.push(LineResult {
+                id: doc.id(),
+                path: doc.path().cloned(),
+                line_num,
+                contents: contents.to_owned(),
+            });
+        }
+    }
+
+    let line_column = if all_buffers {
+        PickerColumn::new("path", |item: &LineResult, _| {
+            let path = item
+                .path
+                .as_deref()
+                .map(fugue_stdx::path::get_relative_path);
+            let path = path
+                .as_deref()
+                .and_then(Path::to_str)
+                .unwrap_or(SCRATCH_BUFFER_NAME);
+            format!("{path}:{}", item.line_num + 1).into()
+        })
+    } else {
+        PickerColumn::new("line", |item: &LineResult, _| {
+            (item.line_num + 1).to_string().into()
+        })
+        .without_filtering()
+    };
+    let columns = [
+        line_column,
+        PickerColumn::new("contents", |item: &LineResult, _| {
+            item.contents.as_str().into()
+        }),
+    ];
+
+    let picker = Picker::new(columns, 1, items, (), |cx, item, action| {
+        if !cx.editor.documents.contains_key(&item.id) {
+            cx.editor.set_error("The buffer of the line was closed");
+            return;
+        }
+        cx.editor.switch(item.id, action);
+        let (view, doc) = current!(cx.editor);
+        let text = doc.text();
+        if item.line_num >= text.len_lines() {
+            cx.editor.set_error(
+                "The line you jumped to does not exist anymore because the buffer has changed.",
+            );
+            return;
+        }
+        let start = text.line_to_char(item.line_num);
+        let end = text.line_to_char((item.line_num + 1).min(text.len_lines()));
+        doc.set_selection(view.id, Selection::single(start, end));
+        if action.align_view(view, doc.id()) {
+            align_view(doc, view, Align::Center);
+        }
+    })
+    .with_preview(|_editor, item| Some((item.id.into(), Some((item.line_num, item.line_num)))))
+    .with_preview_match(|editor, item| {
+        let text = editor.document(item.id)?.text();
+        (item.line_num < text.len_lines()).then(|| text.line_to_char(item.line_num))
+    })
+    .with_quickfix_name("lines");
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
 enum Extend {
     Above,
     Below,
@@ -3023,32 +3232,6 @@ fn change_selection_noyank(cx: &mut Context) {
     delete_selection_impl(cx, Operation::Change, YankAction::NoYank);
 }
 
-// ============================================================================
-// OPERATOR HELPERS
-// ============================================================================
-
-/// Delete the current selection
-fn delete_selection_op(cx: &mut Context) {
-    delete_selection_impl(cx, Operation::Delete, YankAction::Yank);
-    cx.editor.mode = Mode::Normal;
-}
-
-/// Change the current selection (delete and enter insert mode)
-fn change_selection_op(cx: &mut Context) {
-    delete_selection_impl(cx, Operation::Change, YankAction::Yank);
-    // Mode is set by delete_selection_impl
-}
-
-/// Yank the current selection
-fn yank_selection_op(cx: &mut Context) {
-    yank(cx);
-    cx.editor.mode = Mode::Normal;
-}
-
-// ============================================================================
-// TEXT OBJECT HANDLING WITH OPERATORS
-// ============================================================================
-
 fn collapse_selection(cx: &mut Context) {
     let (view, doc) = current!(cx.editor);
     let text = doc.text().slice(..);
@@ -3235,6 +3418,59 @@ fn file_explorer_in_current_directory(cx: &mut Context) {
     }
 }
 
+fn file_tree(cx: &mut Context) {
+    let root = find_workspace().0;
+    if !root.exists() {
+        cx.editor.set_error("Workspace directory does not exist");
+        return;
+    }
+    let path = doc!(cx.editor).path().cloned();
+
+    cx.callback.push(Box::new(move |compositor, cx| {
+        let Some(editor_view) = compositor.find::<ui::EditorView>() else {
+            return;
+        };
+        // an open tree is focused first and only closed once it has the focus
+        if editor_view.dock.has_focus(ui::file_tree::ID) {
+            editor_view.dock.close(ui::file_tree::ID);
+            return;
+        }
+        if editor_view.dock.focus(ui::file_tree::ID) {
+            return;
+        }
+        let mut tree = ui::file_tree::FileTree::new(root, cx.editor);
+        if let Some(path) = path {
+            tree.reveal(&path, cx.editor);
+        }
+        editor_view.dock.open(DockSide::Left, 32, tree);
+    }));
+}
+
+fn file_tree_reveal(cx: &mut Context) {
+    let Some(path) = doc!(cx.editor).path().cloned() else {
+        cx.editor.set_error("Current buffer has no path");
+        return;
+    };
+
+    cx.callback.push(Box::new(move |compositor, cx| {
+        let Some(editor_view) = compositor.find::<ui::EditorView>() else {
+            return;
+        };
+        if !editor_view.dock.focus(ui::file_tree::ID) {
+            let tree = ui::file_tree::FileTree::new(find_workspace().0, cx.editor);
+            editor_view.dock.open(DockSide::Left, 32, tree);
+        }
+        if let Some(tree) = editor_view
+            .dock
+            .find_id::<ui::file_tree::FileTree>(ui::file_tree::ID)
+        {
+            if !tree.reveal(&path, cx.editor) {
+                cx.editor.set_error("Current file is not in the file tree");
+            }
+        }
+    }));
+}
+
 fn buffer_picker(cx: &mut Context) {
     let current = view!(cx.editor).doc;
 
@@ -3276,6 +3512,16 @@ struct BufferMeta {
             }
             flags.into()
         }),
+        PickerColumn::new(
+            "status",
+            |meta: &BufferMeta, markers: &FileStatusMarkers| {
+                meta.path
+                    .as_deref()
+                    .map(|path| markers.marker(path))
+                    .unwrap_or_default()
+            },
+        )
+        .without_filtering(),
         PickerColumn::new("path", |meta: &BufferMeta, _| {
             let path = meta
                 .path
@@ -3302,9 +3548,29 @@ struct BufferMeta {
         0
     };
 
-    let picker = Picker::new(columns, 2, items, (), |cx, meta, action| {
+    let markers = FileStatusMarkers::new(cx.editor, fugue_stdx::env::current_working_dir());
+    let picker = Picker::new(columns, 3, items, markers, |cx, meta, action| {
         cx.editor.switch(meta.id, action);
     })
+    .with_batch_action(crate::alt!('x'), |cx, metas| {
+        // modified buffers are kept open
+        let modified: Vec<_> = metas
+            .into_iter()
+            .filter_map(|meta| match cx.editor.close_document(meta.id, false) {
+                Err(fugue_view::editor::CloseError::BufferModified(name)) => Some(name),
+                _ => None,
+            })
+            .collect();
+        if !modified.is_empty() {
+            cx.editor.set_error(format!(
+                "{} unsaved buffer{} remaining: {:?}",
+                modified.len(),
+                if modified.len() == 1 { "" } else { "s" },
+                modified,
+            ));
+        }
+        None
+    })
     .with_initial_cursor(initial_cursor)
     .with_preview(|editor, meta| {
         let doc = &editor.documents.get(&meta.id)?;
@@ -3317,6 +3583,236 @@ struct BufferMeta {
     cx.push_layer(Box::new(overlaid(picker)));
 }
 
+fn recent_files_picker(cx: &mut Context) {
+    let items: Vec<_> = cx
+        .editor
+        .recent_files
+        .ranked()
+        .iter()
+        .filter(|file| file.path.is_file())
+        .map(|file| file.path.clone())
+        .collect();
+    if items.is_empty() {
+        cx.editor.set_status("No recently opened files");
+        return;
+    }
+
+    let columns = [
+        PickerColumn::new("status", |path: &PathBuf, markers: &FileStatusMarkers| {
+            markers.marker(path)
+        })
+        .without_filtering(),
+        PickerColumn::new("path", |path: &PathBuf, _| {
+            fugue_stdx::path::get_relative_path(path)
+                .to_string_lossy()
+                .into_owned()
+                .into()
+        }),
+    ];
+    let markers = FileStatusMarkers::new(cx.editor, fugue_stdx::env::current_working_dir());
+    let picker = Picker::new(columns, 1, items, markers, |cx, path: &PathBuf, action| {
+        if let Err(err) = cx.editor.open(path, action) {
+            cx.editor
+                .set_error(format!("unable to open \"{}\": {err}", path.display()));
+        }
+    })
+    .with_preview(|_editor, path| Some((path.as_path().into(), None)))
+    .with_quickfix_name("recent");
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
+fn pin_buffer(cx: &mut Context) {
+    pin_current_buffer(cx.editor, None);
+}
+
+fn unpin_buffer(cx: &mut Context) {
+    let slot = doc!(cx.editor)
+        .path()
+        .and_then(|path| cx.editor.pinned_files.slot(path));
+    match slot {
+        Some(slot) => unpin_slot(cx.editor, slot),
+        None => cx.editor.set_error("Current buffer is not pinned"),
+    }
+}
+
+fn goto_pinned_file_1(cx: &mut Context) {
+    goto_pinned_file(cx.editor, 0, Action::Replace);
+}
+
+fn goto_pinned_file_2(cx: &mut Context) {
+    goto_pinned_file(cx.editor, 1, Action::Replace);
+}
+
+fn goto_pinned_file_3(cx: &mut Context) {
+    goto_pinned_file(cx.editor, 2, Action::Replace);
+}
+
+fn goto_pinned_file_4(cx: &mut Context) {
+    goto_pinned_file(cx.editor, 3, Action::Replace);
+}
+
+/// Pins the current buffer to `slot`, or to a new slot at the end. Slots are 0 indexed.
+fn pin_current_buffer(editor: &mut Editor, slot: Option<usize>) {
+    let (view, doc) = current_ref!(editor);
+    let Some(path) = doc.path().cloned() else {
+        editor.set_error("Cannot pin a buffer without a path");
+        return;
+    };
+    let text = doc.text().slice(..);
+    let coords = fugue_core::coords_at_pos(text, doc.selection(view.id).primary().cursor(text));
+    let slot = editor.pinned_files.pin(&path, slot, coords.row, coords.col);
+    editor.set_status(format!(
+        "Pinned {} to slot {}",
+        path::get_relative_path(&path).display(),
+        slot + 1
+    ));
+}
+
+fn unpin_slot(editor: &mut Editor, slot: usize) {
+    match editor.pinned_files.unpin(slot) {
+        Some(file) => editor.set_status(format!(
+            "Unpinned {} from slot {}",
+            path::get_relative_path(&file.path).display(),
+            slot + 1
+        )),
+        None => editor.set_error(format!("No file pinned to slot {}", slot + 1)),
+    }
+}
+
+/// Opens the file pinned to `slot`. Files that aren't shown in the view yet are opened at the
+/// cursor position they were left at.
+fn goto_pinned_file(editor: &mut Editor, slot: usize, action: Action) {
+    editor.remember_pinned_cursors();
+    let Some(file) = editor.pinned_files.get(slot).cloned() else {
+        editor.set_error(format!("No file pinned to slot {}", slot + 1));
+        return;
+    };
+
+    let (view, doc) = current!(editor);
+    let replace = matches!(action, Action::Replace);
+    if replace && doc.path() == Some(&file.path) {
+        return;
+    }
+    doc.append_changes_to_history(view);
+    let jump = (doc.id(), doc.selection(view.id).clone());
+    let view_id = view.id;
+    let shown = editor
+        .document_by_path(&file.path)
+        .is_some_and(|doc| doc.selections().contains_key(&view_id));
+
+    let doc_id = match editor.open(&file.path, action) {
+        Ok(id) => id,
+        Err(err) => {
+            editor.set_error(format!("unable to open \"{}\": {err}", file.path.display()));
+            return;
+        }
+    };
+    // the jump is only recorded once the file could be opened
+    view_mut!(editor, view_id).jumps.push(jump);
+    let (view, doc) = current!(editor);
+    if doc.id() != doc_id || (replace && shown) {
+        return;
+    }
+    let text = doc.text().slice(..);
+    let pos = pos_at_coords(text, Position::new(file.line, file.column), true);
+    doc.set_selection(view.id, Selection::point(pos));
+    align_view(doc, view, Align::Center);
+}
+
+fn pinned_files_picker(cx: &mut Context) {
+    if cx.editor.pinned_files.files().is_empty() {
+        cx.editor.set_status("No pinned files");
+        return;
+    }
+    let picker = pinned_files_picker_at(cx.editor, 0);
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
+/// A picker of the pinned files, starting at `slot`, in which they can be unpinned and
+/// reordered.
+fn pinned_files_picker_at(
+    editor: &mut Editor,
+    slot: usize,
+) -> Picker<PinnedItem, FileStatusMarkers> {
+    editor.remember_pinned_cursors();
+    let items: Vec<_> = editor
+        .pinned_files
+        .files()
+        .iter()
+        .enumerate()
+        .map(|(slot, file)| PinnedItem {
+            slot,
+            path: file.path.clone(),
+            line: file.line,
+        })
+        .collect();
+
+    let columns = [
+        PickerColumn::new("slot", |item: &PinnedItem, _| {
+            (item.slot + 1).to_string().into()
+        })
+        .without_filtering(),
+        PickerColumn::new(
+            "status",
+            |item: &PinnedItem, markers: &FileStatusMarkers| markers.marker(&item.path),
+        )
+        .without_filtering(),
+        PickerColumn::new("path", |item: &PinnedItem, _| {
+            path::get_relative_path(&item.path)
+                .to_string_lossy()
+                .into_owned()
+                .into()
+        }),
+    ];
+
+    // the picker is reopened to show the changed slots
+    let reopen = |slot: usize| -> compositor::Callback {
+        Box::new(move |compositor, cx| {
+            if !cx.editor.pinned_files.files().is_empty() {
+                let picker = pinned_files_picker_at(cx.editor, slot);
+                compositor.push(Box::new(overlaid(picker)));
+            }
+        })
+    };
+    let move_by = move |cx: &mut compositor::Context, items: Vec<&PinnedItem>, up: bool| {
+        let item = items.first()?;
+        let to = if up {
+            item.slot.checked_sub(1)?
+        } else {
+            item.slot + 1
+        };
+        cx.editor
+            .pinned_files
+            .move_slot(item.slot, to)
+            .then(|| reopen(to))
+    };
+
+    let markers = FileStatusMarkers::new(editor, fugue_stdx::env::current_working_dir());
+    Picker::new(columns, 2, items, markers, |cx, item, action| {
+        goto_pinned_file(cx.editor, item.slot, action)
+    })
+    .with_batch_action(crate::alt!('x'), move |cx, items| {
+        let mut slots: Vec<_> = items.iter().map(|item| item.slot).collect();
+        // later slots first, so that the earlier ones don't shift
+        slots.sort_unstable_by(|a, b| b.cmp(a));
+        for slot in &slots {
+            cx.editor.pinned_files.unpin(*slot);
+        }
+        Some(reopen(slots.last().copied().unwrap_or_default()))
+    })
+    .with_batch_action(crate::alt!('k'), move |cx, items| move_by(cx, items, true))
+    .with_batch_action(crate::alt!('j'), move |cx, items| move_by(cx, items, false))
+    .with_initial_cursor(slot as u32)
+    .with_preview(|_editor, item| Some((item.path.as_path().into(), Some((item.line, item.line)))))
+    .with_quickfix_name("pinned")
+}
+
+struct PinnedItem {
+    slot: usize,
+    path: PathBuf,
+    line: usize,
+}
+
 fn jumplist_picker(cx: &mut Context) {
     struct JumpMeta {
         id: DocumentId,
@@ -3486,13 +3982,15 @@ pub struct FileChangeData {
             }
         },
     )
-    .with_preview(|_editor, meta| Some((meta.path().into(), None)));
+    .with_preview(|_editor, meta| Some((meta.path().into(), None)))
+    .with_quickfix_name("changes");
     let injector = picker.injector();
 
+    let diff_base = cx.editor.diff_base.clone();
     cx.editor
         .diff_providers
         .clone()
-        .for_each_changed_file(cwd, move |change| match change {
+        .for_each_changed_file(cwd, diff_base, move |change| match change {
             Ok(change) => injector.push(change).is_ok(),
             Err(err) => {
                 status::report_blocking(err);
@@ -3502,6 +4000,132 @@ pub struct FileChangeData {
     cx.push_layer(Box::new(overlaid(picker)));
 }
 
+/// The maximum number of commits listed by the commit pickers.
+const MAX_LOG_COMMITS: usize = 10_000;
+
+fn commit_picker(cx: &mut Context) {
+    commit_picker_impl(cx, false)
+}
+
+fn file_history_picker(cx: &mut Context) {
+    commit_picker_impl(cx, true)
+}
+
+fn commit_picker_impl(cx: &mut Context, file_history: bool) {
+    let cwd = fugue_stdx::env::current_working_dir();
+    if !cwd.exists() {
+        cx.editor
+            .set_error("Current working directory does not exist");
+        return;
+    }
+    let doc = doc!(cx.editor);
+    let doc_id = doc.id();
+    let file = doc.path().cloned();
+    if file_history && file.is_none() {
+        cx.editor.set_error("Current buffer has no path");
+        return;
+    }
+
+    let columns = [
+        PickerColumn::new("commit", |commit: &LogCommit, _| {
+            commit.short_id().to_string().into()
+        }),
+        PickerColumn::new("author", |commit: &LogCommit, _| {
+            commit.author.as_str().into()
+        }),
+        PickerColumn::new("date", |commit: &LogCommit, _| {
+            commit.relative_time().into()
+        }),
+        PickerColumn::new("summary", |commit: &LogCommit, _| {
+            commit.summary.as_str().into()
+        }),
+    ];
+
+    let open_file = file.clone();
+    let open_cwd = cwd.clone();
+    let preview_cwd = cwd.clone();
+    let picker = Picker::new(
+        columns,
+        3, // summary
+        [],
+        (),
+        move |cx, commit: &LogCommit, action| {
+            // the repository wide history shows the whole commit as most commits don't touch the
+            // current buffer
+            let result = match open_file.as_deref() {
+                Some(file) if file_history => commit_file_document(
+                    cx.editor,
+                    file,
+                    &commit.id,
+                    commit.path.as_deref(),
+                    action,
+                )
+                .map(|_| ()),
+                _ => show_commit_diff(cx.editor, &open_cwd, &commit.id, None, action),
+            };
+            if let Err(err) = result {
+                cx.editor.set_error(err.to_string());
+            }
+        },
+    )
+    .with_text_preview(
+        |commit| &commit.id,
+        move |editor, commit| {
+            let diff = editor
+                .diff_providers
+                .commit_diff(&preview_cwd, &commit.id, commit.path.as_deref())
+                .ok()?;
+            Some((diff, Some("diff")))
+        },
+    )
+    .with_key_action(crate::alt!('b'), move |cx, commit| {
+        let base = DiffBase::Rev(commit.id.clone());
+        if file_history {
+            if let Some(doc) = cx.editor.documents.get_mut(&doc_id) {
+                doc.set_diff_base_override(Some(base.clone()));
+                doc.refresh_version_control(&cx.editor.diff_providers, &cx.editor.diff_base);
+            }
+        } else {
+            cx.editor.diff_base = base.clone();
+            refresh_documents(cx.editor);
+        }
+        cx.editor.set_status(format!("Diffing against {base}"));
+    });
+    let picker = match file.clone() {
+        Some(diff_file) if file_history => {
+            picker.with_key_action(crate::alt!('d'), move |cx, commit| {
+                let result = commit_file_document(
+                    cx.editor,
+                    &diff_file,
+                    &commit.id,
+                    commit.path.as_deref(),
+                    Action::Load,
+                );
+                match result {
+                    Ok(base) => open_diff_view(cx.editor, base, doc_id),
+                    Err(err) => cx.editor.set_error(err.to_string()),
+                }
+            })
+        }
+        _ => picker,
+    };
+    let injector = picker.injector();
+
+    let registry = cx.editor.diff_providers.clone();
+    let file = if file_history { file } else { None };
+    tokio::spawn(async move {
+        let result = registry
+            .log(cwd, file, MAX_LOG_COMMITS, move |commit| {
+                injector.push(commit).is_ok()
+            })
+            .await;
+        if let Err(err) = result {
+            status::report(err).await;
+        }
+    });
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
 pub fn command_palette(cx: &mut Context) {
     let register = cx.register;
     let count = cx.count;
@@ -3526,8 +4150,8 @@ pub fn command_palette(cx: &mut Context) {
                 ui::PickerColumn::new("name", |item, _| match item {
                     MappableCommand::Typable { name, .. } => format!(":{name}").into(),
                     MappableCommand::Static { name, .. } => (*name).into(),
-                    MappableCommand::Macro { .. } => {
-                        unreachable!("macros aren't included in the command palette")
+                    MappableCommand::Macro { .. } | MappableCommand::Operator { .. } => {
+                        unreachable!("macros and operators aren't included in the command palette")
                     }
                 }),
                 ui::PickerColumn::new(
@@ -3560,6 +4184,7 @@ pub fn command_palette(cx: &mut Context) {
                     editor: cx.editor,
                     callback: Vec::new(),
                     on_next_key_callback: None,
+                    pending_operator: None,
                     jobs: cx.jobs,
                 };
                 let focus = view!(ctx.editor).id;
@@ -4217,6 +4842,86 @@ fn hunk_range(hunk: Hunk, text: RopeSlice) -> Range {
     Range::new(anchor, head)
 }
 
+fn goto_next_conflict(cx: &mut Context) {
+    goto_next_conflict_impl(cx, Direction::Forward)
+}
+
+fn goto_prev_conflict(cx: &mut Context) {
+    goto_next_conflict_impl(cx, Direction::Backward)
+}
+
+fn goto_next_conflict_impl(cx: &mut Context, direction: Direction) {
+    let count = cx.count() - 1;
+    let motion = move |editor: &mut Editor| {
+        let (view, doc) = current!(editor);
+        let doc_text = doc.text().slice(..);
+        let conflicts = doc.conflicts();
+        if conflicts.is_empty() {
+            editor.set_status("There are no conflicts in the current buffer");
+            return;
+        }
+
+        let selection = doc.selection(view.id).clone().transform(|range| {
+            let cursor_line = range.cursor_line(doc_text);
+            let idx = match direction {
+                Direction::Forward => {
+                    let idx = conflicts.partition_point(|conflict| conflict.start <= cursor_line);
+                    (idx < conflicts.len()).then(|| (idx + count).min(conflicts.len() - 1))
+                }
+                Direction::Backward => conflicts
+                    .partition_point(|conflict| conflict.end < cursor_line)
+                    .checked_sub(1)
+                    .map(|idx| idx.saturating_sub(count)),
+            };
+            let Some(conflict) = idx.map(|idx| &conflicts[idx]) else {
+                return range;
+            };
+            let lines = conflict.lines();
+            let new_range = Range::new(
+                doc_text.line_to_char(lines.start),
+                doc_text.line_to_char(lines.end),
+            );
+            if editor.mode == Mode::Select {
+                let head = if new_range.head < range.anchor {
+                    new_range.anchor
+                } else {
+                    new_range.head
+                };
+
+                Range::new(range.anchor, head)
+            } else {
+                new_range.with_direction(direction)
+            }
+        });
+
+        push_jump(view, doc);
+        doc.set_selection(view.id, selection)
+    };
+    cx.editor.apply_motion(motion);
+}
+
+fn resolve_conflict_ours(cx: &mut Context) {
+    resolve_conflict_impl(cx, Resolution::Ours)
+}
+
+fn resolve_conflict_theirs(cx: &mut Context) {
+    resolve_conflict_impl(cx, Resolution::Theirs)
+}
+
+fn resolve_conflict_base(cx: &mut Context) {
+    resolve_conflict_impl(cx, Resolution::Base)
+}
+
+fn resolve_conflict_both(cx: &mut Context) {
+    resolve_conflict_impl(cx, Resolution::Both)
+}
+
+fn resolve_conflict_impl(cx: &mut Context, resolution: Resolution) {
+    if let Err(err) = resolve_conflicts(cx.editor, resolution) {
+        cx.editor.set_error(err.to_string());
+    }
+}
+
 pub mod insert {
     use crate::{events::PostInsertChar, key};
 
@@ -7075,450 +7780,3 @@ fn lsp_or_syntax_workspace_symbol_picker(cx: &mut Context) {
         syntax_workspace_symbol_picker(cx);
     }
 }
-
-// ============================================================================
-// OPERATOR + MOTION/TEXT OBJECT COMMANDS (Helix-style nested menus)
-// ============================================================================
-
-// Helper macro to create operator+motion commands
-// This extends the selection from current position to the motion destination,
-// then applies the operator to that selection
-macro_rules! operator_motion {
-    ($name:ident, $operator:expr, $motion:expr, $doc:expr) => {
-        #[doc = $doc]
-        fn $name(cx: &mut Context) {
-            // Store original selection to extend from
-            let (view, doc) = current!(cx.editor);
-            let original_selection = doc.selection(view.id).clone();
-
-            // Apply the motion (this moves the cursor)
-            $motion(cx);
-
-            // Now extend selection from original position to new cursor position
-            let (view, doc) = current!(cx.editor);
-            let text = doc.text().slice(..);
-            let new_selection = doc.selection(view.id).clone();
-
-            // Pair up the original and new ranges and extend each one
-            let extended_selection = original_selection
-                .ranges()
-                .iter()
-                .zip(new_selection.ranges().iter())
-                .map(|(orig_range, new_range)| {
-                    let end_pos = new_range.cursor(text);
-                    orig_range.put_cursor(text, end_pos, true)
-                })
-                .collect();
-
-            doc.set_selection(view.id, extended_selection);
-
-            // Now apply the operator to the extended selection
-            $operator(cx);
-        }
-    };
-}
-
-// Helper to create operator+textobject commands
-fn operator_with_textobject(
-    cx: &mut Context,
-    operator_fn: fn(&mut Context),
-    title: &'static str,
-    objtype: textobject::TextObject,
-) {
-    let count = cx.count.map(|n| n.get()).unwrap_or(1);
-
-    let help_text = [
-        ("w", "Word"),
-        ("W", "WORD"),
-        ("p", "Paragraph"),
-        ("t", "Type definition (tree-sitter)"),
-        ("f", "Function (tree-sitter)"),
-        ("a", "Argument/parameter (tree-sitter)"),
-        ("c", "Comment (tree-sitter)"),
-        ("T", "Test (tree-sitter)"),
-        ("e", "Data structure entry (tree-sitter)"),
-        ("m", "Closest surrounding pair (tree-sitter)"),
-        ("x", "(X)HTML element (tree-sitter)"),
-        (" ", "... or any character acting as a pair"),
-    ];
-
-    cx.editor.autoinfo = Some(Info::new(title, &help_text));
-
-    cx.on_next_key(move |cx, event| {
-        cx.editor.autoinfo = None;
-        if let Some(ch) = event.char() {
-            let (view, doc) = current!(cx.editor);
-            let loader = cx.editor.syn_loader.load();
-            let text = doc.text().slice(..);
-
-            let textobject_treesitter = |obj_name: &str, range: Range| -> Range {
-                let Some(syntax) = doc.syntax() else {
-                    return range;
-                };
-                textobject::textobject_treesitter(
-                    text, range, objtype, obj_name, syntax, &loader, count,
-                )
-            };
-
-            // Transform selection based on text object
-            let selection = doc.selection(view.id).clone().transform(|range| match ch {
-                'w' => textobject::textobject_word(text, range, objtype, count, false),
-                'W' => textobject::textobject_word(text, range, objtype, count, true),
-                't' => textobject_treesitter("class", range),
-                'f' => textobject_treesitter("function", range),
-                'a' => textobject_treesitter("parameter", range),
-                'c' => textobject_treesitter("comment", range),
-                'T' => textobject_treesitter("test", range),
-                'e' => textobject_treesitter("entry", range),
-                'x' => textobject_treesitter("xml-element", range),
-                'p' => textobject::textobject_paragraph(text, range, objtype, count),
-                'm' => textobject::textobject_pair_surround_closest(
-                    doc.syntax(),
-                    text,
-                    range,
-                    objtype,
-                    count,
-                ),
-                ch if !ch.is_ascii_alphanumeric() => textobject::textobject_pair_surround(
-                    doc.syntax(),
-                    text,
-                    range,
-                    objtype,
-                    ch,
-                    count,
-                ),
-                _ => return range,
-            });
-
-            doc.set_selection(view.id, selection);
-
-            operator_fn(cx);
-        }
-    });
-}
-
-// DELETE text object commands
-fn delete_textobject_inner(cx: &mut Context) {
-    operator_with_textobject(
-        cx,
-        delete_selection_op,
-        "Delete inside",
-        textobject::TextObject::Inside,
-    );
-}
-
-fn delete_textobject_around(cx: &mut Context) {
-    operator_with_textobject(
-        cx,
-        delete_selection_op,
-        "Delete around",
-        textobject::TextObject::Around,
-    );
-}
-
-// CHANGE text object commands
-fn change_textobject_inner(cx: &mut Context) {
-    operator_with_textobject(
-        cx,
-        change_selection_op,
-        "Change inside",
-        textobject::TextObject::Inside,
-    );
-}
-
-fn change_textobject_around(cx: &mut Context) {
-    operator_with_textobject(
-        cx,
-        change_selection_op,
-        "Change around",
-        textobject::TextObject::Around,
-    );
-}
-
-// YANK text object commands
-fn yank_textobject_inner(cx: &mut Context) {
-    operator_with_textobject(
-        cx,
-        yank_selection_op,
-        "Yank inside",
-        textobject::TextObject::Inside,
-    );
-}
-
-fn yank_textobject_around(cx: &mut Context) {
-    operator_with_textobject(
-        cx,
-        yank_selection_op,
-        "Yank around",
-        textobject::TextObject::Around,
-    );
-}
-
-// DELETE motion commands
-operator_motion!(
-    delete_next_word_start,
-    delete_selection_op,
-    move_next_word_start,
-    "Delete to start of next word"
-);
-operator_motion!(
-    delete_prev_word_start,
-    delete_selection_op,
-    move_prev_word_start,
-    "Delete to start of previous word"
-);
-operator_motion!(
-    delete_next_word_end,
-    delete_selection_op,
-    move_next_word_end,
-    "Delete to end of next word"
-);
-operator_motion!(
-    delete_next_long_word_start,
-    delete_selection_op,
-    move_next_long_word_start,
-    "Delete to start of next WORD"
-);
-operator_motion!(
-    delete_prev_long_word_start,
-    delete_selection_op,
-    move_prev_long_word_start,
-    "Delete to start of previous WORD"
-);
-operator_motion!(
-    delete_next_long_word_end,
-    delete_selection_op,
-    move_next_long_word_end,
-    "Delete to end of next WORD"
-);
-operator_motion!(
-    delete_to_line_end,
-    delete_selection_op,
-    goto_line_end,
-    "Delete to line end"
-);
-operator_motion!(
-    delete_to_line_start,
-    delete_selection_op,
-    goto_line_start,
-    "Delete to line start"
-);
-operator_motion!(
-    delete_to_first_nonwhitespace,
-    delete_selection_op,
-    goto_first_nonwhitespace,
-    "Delete to first non-whitespace"
-);
-operator_motion!(
-    delete_to_line,
-    delete_selection_op,
-    goto_line,
-    "Delete to line"
-);
-operator_motion!(
-    delete_to_file_start,
-    delete_selection_op,
-    goto_file_start,
-    "Delete to file start"
-);
-operator_motion!(
-    delete_to_last_line,
-    delete_selection_op,
-    goto_last_line,
-    "Delete to last line"
-);
-
-// CHANGE motion commands
-operator_motion!(
-    change_next_word_start,
-    change_selection_op,
-    move_next_word_start,
-    "Change to start of next word"
-);
-operator_motion!(
-    change_prev_word_start,
-    change_selection_op,
-    move_prev_word_start,
-    "Change to start of previous word"
-);
-operator_motion!(
-    change_next_word_end,
-    change_selection_op,
-    move_next_word_end,
-    "Change to end of next word"
-);
-operator_motion!(
-    change_next_long_word_start,
-    change_selection_op,
-    move_next_long_word_start,
-    "Change to start of next WORD"
-);
-operator_motion!(
-    change_prev_long_word_start,
-    change_selection_op,
-    move_prev_long_word_start,
-    "Change to start of previous WORD"
-);
-operator_motion!(
-    change_next_long_word_end,
-    change_selection_op,
-    move_next_long_word_end,
-    "Change to end of next WORD"
-);
-operator_motion!(
-    change_to_line_end,
-    change_selection_op,
-    goto_line_end,
-    "Change to line end"
-);
-operator_motion!(
-    change_to_line_start,
-    change_selection_op,
-    goto_line_start,
-    "Change to line start"
-);
-operator_motion!(
-    change_to_first_nonwhitespace,
-    change_selection_op,
-    goto_first_nonwhitespace,
-    "Change to first non-whitespace"
-);
-operator_motion!(
-    change_to_line,
-    change_selection_op,
-    goto_line,
-    "Change to line"
-);
-operator_motion!(
-    change_to_file_start,
-    change_selection_op,
-    goto_file_start,
-    "Change to file start"
-);
-operator_motion!(
-    change_to_last_line,
-    change_selection_op,
-    goto_last_line,
-    "Change to last line"
-);
-
-// YANK motion commands
-operator_motion!(
-    yank_next_word_start,
-    yank_selection_op,
-    move_next_word_start,
-    "Yank to start of next word"
-);
-operator_motion!(
-    yank_prev_word_start,
-    yank_selection_op,
-    move_prev_word_start,
-    "Yank to start of previous word"
-);
-operator_motion!(
-    yank_next_word_end,
-    yank_selection_op,
-    move_next_word_end,
-    "Yank to end of next word"
-);
-operator_motion!(
-    yank_next_long_word_start,
-    yank_selection_op,
-    move_next_long_word_start,
-    "Yank to start of next WORD"
-);
-operator_motion!(
-    yank_prev_long_word_start,
-    yank_selection_op,
-    move_prev_long_word_start,
-    "Yank to start of previous WORD"
-);
-operator_motion!(
-    yank_next_long_word_end,
-    yank_selection_op,
-    move_next_long_word_end,
-    "Yank to end of next WORD"
-);
-operator_motion!(
-    yank_to_line_end,
-    yank_selection_op,
-    goto_line_end,
-    "Yank to line end"
-);
-operator_motion!(
-    yank_to_line_start,
-    yank_selection_op,
-    goto_line_start,
-    "Yank to line start"
-);
-operator_motion!(
-    yank_to_first_nonwhitespace,
-    yank_selection_op,
-    goto_first_nonwhitespace,
-    "Yank to first non-whitespace"
-);
-operator_motion!(yank_to_line, yank_selection_op, goto_line, "Yank to line");
-operator_motion!(
-    yank_to_file_start,
-    yank_selection_op,
-    goto_file_start,
-    "Yank to file start"
-);
-operator_motion!(
-    yank_to_last_line,
-    yank_selection_op,
-    goto_last_line,
-    "Yank to last line"
-);
-
-// dd, cc, yy - operate on current line
-fn delete_current_line(cx: &mut Context) {
-    let (view, doc) = current!(cx.editor);
-    let text = doc.text().slice(..);
-
-    // Transform selection to select whole lines
-    let selection = doc.selection(view.id).clone().transform(|range| {
-        let from_line = text.char_to_line(range.from());
-        let to_line = text.char_to_line(range.to());
-        let from = text.line_to_char(from_line);
-        let to = text.line_to_char((to_line + 1).min(text.len_lines()));
-        Range::new(from, to)
-    });
-
-    doc.set_selection(view.id, selection);
-    delete_selection(cx);
-}
-
-fn change_current_line(cx: &mut Context) {
-    let (view, doc) = current!(cx.editor);
-    let text = doc.text().slice(..);
-
-    // Transform selection to select whole lines
-    let selection = doc.selection(view.id).clone().transform(|range| {
-        let from_line = text.char_to_line(range.from());
-        let to_line = text.char_to_line(range.to());
-        let from = text.line_to_char(from_line);
-        let to = text.line_to_char((to_line + 1).min(text.len_lines()));
-        Range::new(from, to)
-    });
-
-    doc.set_selection(view.id, selection);
-    change_selection(cx);
-}
-
-fn yank_current_line(cx: &mut Context) {
-    let (view, doc) = current!(cx.editor);
-    let text = doc.text().slice(..);
-
-    // Transform selection to select whole lines
-    let selection = doc.selection(view.id).clone().transform(|range| {
-        let from_line = text.char_to_line(range.from());
-        let to_line = text.char_to_line(range.to());
-        let from = text.line_to_char(from_line);
-        let to = text.line_to_char((to_line + 1).min(text.len_lines()));
-        Range::new(from, to)
-    });
-
-    doc.set_selection(view.id, selection);
-    yank(cx);
-}
@@ -4,13 +4,16 @@
 use super::{Context, Editor};
 use crate::{
     compositor::{self, Compositor},
-    job::{Callback, Jobs},
-    ui::{self, overlay::overlaid, Picker, Popup, Prompt, PromptEvent, Text},
+    job::{self, Callback, Jobs},
+    ui::{self, dock::DockSide, overlay::overlaid, Picker, Popup, Prompt, PromptEvent, Text},
 };
 use dap::{StackFrame, Thread, ThreadStates};
-use fugue_core::syntax::config::{DebugArgumentValue, DebugConfigCompletion, DebugTemplate};
-use fugue_dap::{self as dap, requests::TerminateArguments};
+use fugue_core::syntax::config::{
+    DebugAdapterConfig, DebugArgumentValue, DebugConfigCompletion, DebugTemplate,
+};
+use fugue_dap::{self as dap, registry::DebugAdapterId, requests::TerminateArguments};
 use fugue_lsp::block_on;
+use fugue_view::debug_config::{self, ProjectDebugConfig};
 use fugue_view::editor::Breakpoint;
 
 use serde_json::{to_value, Value};
@@ -20,9 +23,28 @@
 use std::future::Future;
 use std::path::PathBuf;
 
-use anyhow::{anyhow, bail};
+use anyhow::{anyhow, bail, ensure};
+
+use fugue_view::handlers::dap::{
+    add_debug_source, clear_inline_values, disassemble, focus_session, refresh_inline_values,
+    select_thread_id, show_stack_frame, source_request, stepping_granularity, sync_breakpoints,
+};
 
-use fugue_view::handlers::dap::{breakpoints_changed, jump_to_stack_frame, select_thread_id};
+/// Jumps to `frame`, fetching its source in a job if it only exists inside the debugger.
+pub(crate) fn jump_to_frame(editor: &mut Editor, jobs: &mut Jobs, frame: StackFrame) {
+    let Some((debugger, request)) = source_request(editor, &frame) else {
+        show_stack_frame(editor, &frame);
+        return;
+    };
+    jobs.callback(async move {
+        let response = request.await?;
+        let callback = move |editor: &mut Editor| {
+            add_debug_source(editor, debugger, &frame, response.content);
+            show_stack_frame(editor, &frame);
+        };
+        Ok(Callback::Editor(Box::new(callback)))
+    });
+}
 
 fn thread_picker(
     cx: &mut Context,
@@ -141,6 +163,10 @@ pub fn dap_start_impl(
     }
     .ok_or_else(|| anyhow!("No debug config with given name"))?;
 
+    if let Some(debugger) = cx.editor.debug_adapters.get_client_mut(id) {
+        debugger.name = template.name.clone();
+    }
+
     let mut args: HashMap<&str, Value> = HashMap::new();
 
     for (k, t) in &template.args {
@@ -231,45 +257,76 @@ pub fn dap_start_impl(
     Ok(())
 }
 
-pub fn dap_launch(cx: &mut Context) {
-    // TODO: Now that we support multiple Clients, we could run multiple debuggers at once but for now keep this as is
-    if cx.editor.debug_adapters.get_active_client().is_some() {
-        cx.editor.set_error("Debugger is already running");
-        return;
+/// An entry of the `dap_launch` picker.
+enum LaunchTemplate {
+    /// A template of the focused language's debugger from `languages.toml`.
+    Language(DebugTemplate),
+    /// A configuration of the workspace.
+    Project(ProjectDebugConfig),
+}
+
+impl LaunchTemplate {
+    fn name(&self) -> &str {
+        match self {
+            Self::Language(template) => &template.name,
+            Self::Project(config) => &config.name,
+        }
     }
+}
 
-    let doc = doc!(cx.editor);
+pub fn dap_launch(cx: &mut Context) {
+    let project = match debug_config::load(&fugue_loader::find_workspace().0) {
+        Ok(configs) => configs,
+        Err(err) => {
+            cx.editor.set_error(format!("{err:#}"));
+            Vec::new()
+        }
+    };
 
-    let config = match doc
+    let doc = doc!(cx.editor);
+    let templates = doc
         .language_config()
         .and_then(|config| config.debugger.as_ref())
-    {
-        Some(c) => c,
-        None => {
-            cx.editor
-                .set_error("No debug adapter available for language");
-            return;
-        }
-    };
+        .map(|config| config.templates.clone())
+        .unwrap_or_default();
+
+    if project.is_empty() && templates.is_empty() {
+        cx.editor
+            .set_error("No debug adapter available for language");
+        return;
+    }
 
-    let templates = config.templates.clone();
+    let items: Vec<_> = project
+        .into_iter()
+        .map(LaunchTemplate::Project)
+        .chain(templates.into_iter().map(LaunchTemplate::Language))
+        .collect();
 
-    let columns = [ui::PickerColumn::new(
-        "template",
-        |item: &DebugTemplate, _| item.name.as_str().into(),
-    )];
+    let columns = [
+        ui::PickerColumn::new("template", |item: &LaunchTemplate, _| item.name().into()),
+        ui::PickerColumn::new("source", |item: &LaunchTemplate, _| match item {
+            LaunchTemplate::Language(_) => "languages.toml".into(),
+            LaunchTemplate::Project(config) => config.source.to_string().into(),
+        }),
+    ];
 
     cx.push_layer(Box::new(overlaid(Picker::new(
         columns,
         0,
-        templates,
+        items,
         (),
-        |cx, template, _action| {
-            if template.completion.is_empty() {
+        |cx, item, _action| match item {
+            LaunchTemplate::Project(config) => {
+                if let Err(err) = dap_start_project(cx, config) {
+                    cx.editor.set_error(err.to_string());
+                }
+            }
+            LaunchTemplate::Language(template) if template.completion.is_empty() => {
                 if let Err(err) = dap_start_impl(cx, Some(&template.name), None, None) {
                     cx.editor.set_error(err.to_string());
                 }
-            } else {
+            }
+            LaunchTemplate::Language(template) => {
                 let completions = template.completion.clone();
                 let name = template.name.clone();
                 let callback = Box::pin(async move {
@@ -286,6 +343,116 @@ pub fn dap_launch(cx: &mut Context) {
     ))));
 }
 
+/// Starts a debug session from a workspace configuration, running its pre-launch task first.
+fn dap_start_project(
+    cx: &mut compositor::Context,
+    config: &ProjectDebugConfig,
+) -> anyhow::Result<()> {
+    let adapter = project_debug_adapter(cx.editor, config.adapter.as_deref())?;
+    let workspace = fugue_loader::find_workspace().0;
+    let args = config.expanded_args(cx.editor, &workspace)?;
+    let request = config.request.clone();
+    if !matches!(request.as_str(), "launch" | "attach") {
+        bail!("Unsupported request '{}'", request);
+    }
+
+    let Some(task) = config.expanded_pre_launch_task(cx.editor)? else {
+        return dap_start_request(cx.editor, &adapter, config.name.clone(), request, args);
+    };
+
+    let shell = cx.editor.config().shell.clone();
+    ensure!(!shell.is_empty(), "No shell set");
+    cx.editor
+        .set_status(format!("Running pre-launch task '{task}'"));
+    let name = config.name.clone();
+    let callback = async move {
+        let output = tokio::process::Command::new(&shell[0])
+            .args(&shell[1..])
+            .arg(&task)
+            .current_dir(&workspace)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .await?;
+        let call: Callback = Callback::Editor(Box::new(move |editor| {
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let reason = stderr.lines().rev().find(|line| !line.trim().is_empty());
+                editor.set_error(match reason {
+                    Some(reason) => format!("Pre-launch task '{task}' failed: {reason}"),
+                    None => format!("Pre-launch task '{task}' failed: {}", output.status),
+                });
+                return;
+            }
+            editor.clear_status();
+            if let Err(err) = dap_start_request(editor, &adapter, name, request, args) {
+                editor.set_error(err.to_string());
+            }
+        }));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+    Ok(())
+}
+
+/// Finds the debug adapter named by a project configuration, falling back to the debugger of
+/// the focused document's language.
+fn project_debug_adapter(
+    editor: &Editor,
+    name: Option<&str>,
+) -> anyhow::Result<DebugAdapterConfig> {
+    let loader = editor.syn_loader.load();
+    name.and_then(|name| {
+        loader.language_configs().find_map(|config| {
+            config
+                .debugger
+                .as_ref()
+                .filter(|debugger| debugger.name == name)
+                .cloned()
+        })
+    })
+    .or_else(|| {
+        doc!(editor)
+            .language_config()
+            .and_then(|config| config.debugger.clone())
+    })
+    .ok_or_else(|| match name {
+        Some(name) => anyhow!("No debug adapter named '{}'", name),
+        None => anyhow!("No debug adapter available for language"),
+    })
+}
+
+fn dap_start_request(
+    editor: &mut Editor,
+    adapter: &DebugAdapterConfig,
+    name: String,
+    request: String,
+    args: Value,
+) -> anyhow::Result<()> {
+    let id = editor
+        .debug_adapters
+        .start_client(None, adapter)
+        .map_err(|e| anyhow!("Failed to start debug client: {}", e))?;
+    let debugger = editor
+        .debug_adapters
+        .get_client_mut(id)
+        .ok_or_else(|| anyhow!("Failed to get child debugger."))?;
+    debugger.name = name;
+
+    let call = match request.as_str() {
+        "launch" => futures_util::future::Either::Left(debugger.launch(args)),
+        _ => futures_util::future::Either::Right(debugger.attach(args)),
+    };
+    tokio::spawn(async move {
+        if let Err(err) = call.await {
+            job::dispatch(move |editor, _| {
+                editor.set_error(format!("Failed to {request} the debuggee: {err}"))
+            })
+            .await;
+        }
+    });
+    Ok(())
+}
+
 pub fn dap_restart(cx: &mut Context) {
     let debugger = match cx.editor.debug_adapters.get_active_client() {
         Some(debugger) => debugger,
@@ -420,9 +587,7 @@ pub fn dap_toggle_breakpoint_impl(cx: &mut Context, path: PathBuf, line: usize)
         });
     }
 
-    let debugger = debugger!(cx.editor);
-
-    if let Err(e) = breakpoints_changed(debugger, path, breakpoints) {
+    if let Err(e) = sync_breakpoints(cx.editor, path) {
         cx.editor
             .set_error(format!("Failed to set breakpoints: {}", e));
     }
@@ -439,6 +604,7 @@ pub fn dap_continue(cx: &mut Context) {
             request,
             |editor, _compositor, _response: dap::requests::ContinueResponse| {
                 debugger!(editor).resume_application();
+                clear_inline_values(editor);
             },
         );
     } else {
@@ -459,13 +625,15 @@ pub fn dap_pause(cx: &mut Context) {
 }
 
 pub fn dap_step_in(cx: &mut Context) {
+    let granularity = stepping_granularity(cx.editor);
     let debugger = debugger!(cx.editor);
 
     if let Some(thread_id) = debugger.thread_id {
-        let request = debugger.step_in(thread_id);
+        let request = debugger.step_in(thread_id, granularity);
 
         dap_callback(cx.jobs, request, |editor, _compositor, _response: ()| {
             debugger!(editor).resume_application();
+            clear_inline_values(editor);
         });
     } else {
         cx.editor
@@ -474,12 +642,14 @@ pub fn dap_step_in(cx: &mut Context) {
 }
 
 pub fn dap_step_out(cx: &mut Context) {
+    let granularity = stepping_granularity(cx.editor);
     let debugger = debugger!(cx.editor);
 
     if let Some(thread_id) = debugger.thread_id {
-        let request = debugger.step_out(thread_id);
+        let request = debugger.step_out(thread_id, granularity);
         dap_callback(cx.jobs, request, |editor, _compositor, _response: ()| {
             debugger!(editor).resume_application();
+            clear_inline_values(editor);
         });
     } else {
         cx.editor
@@ -488,12 +658,14 @@ pub fn dap_step_out(cx: &mut Context) {
 }
 
 pub fn dap_next(cx: &mut Context) {
+    let granularity = stepping_granularity(cx.editor);
     let debugger = debugger!(cx.editor);
 
     if let Some(thread_id) = debugger.thread_id {
-        let request = debugger.next(thread_id);
+        let request = debugger.next(thread_id, granularity);
         dap_callback(cx.jobs, request, |editor, _compositor, _response: ()| {
             debugger!(editor).resume_application();
+            clear_inline_values(editor);
         });
     } else {
         cx.editor
@@ -599,12 +771,18 @@ pub fn dap_terminate(cx: &mut Context) {
         });
 
         let request = debugger.terminate(terminate_arguments);
+        // the session is removed once the debugger reports it terminated
         dap_callback(cx.jobs, request, |editor, _compositor, _response: ()| {
             // editor.set_error(format!("Failed to disconnect: {}", e));
-            editor.debug_adapters.unset_active_client();
+            clear_inline_values(editor);
         });
     } else {
-        cx.editor.debug_adapters.unset_active_client();
+        let id = debugger.id();
+        cx.editor.debug_adapters.remove_client(id);
+        clear_inline_values(cx.editor);
+        if let Some(next) = cx.editor.debug_adapters.active_client_id() {
+            block_on(focus_session(cx.editor, next));
+        }
     }
 }
 
@@ -665,9 +843,7 @@ pub fn dap_edit_condition(cx: &mut Context) {
                             input => Some(input.to_owned()),
                         };
 
-                        let debugger = debugger!(cx.editor);
-
-                        if let Err(e) = breakpoints_changed(debugger, path.clone(), breakpoints) {
+                        if let Err(e) = sync_breakpoints(cx.editor, path.clone()) {
                             cx.editor
                                 .set_error(format!("Failed to set breakpoints: {}", e));
                         }
@@ -707,8 +883,7 @@ pub fn dap_edit_log(cx: &mut Context) {
                             input => Some(input.to_owned()),
                         };
 
-                        let debugger = debugger!(cx.editor);
-                        if let Err(e) = breakpoints_changed(debugger, path.clone(), breakpoints) {
+                        if let Err(e) = sync_breakpoints(cx.editor, path.clone()) {
                             cx.editor
                                 .set_error(format!("Failed to set breakpoints: {}", e));
                         }
@@ -725,11 +900,91 @@ pub fn dap_edit_log(cx: &mut Context) {
     }
 }
 
+/// An entry of the debug session picker.
+struct Session {
+    id: DebugAdapterId,
+    name: String,
+    adapter: String,
+    parent: Option<String>,
+    state: &'static str,
+}
+
+pub fn dap_switch_session(cx: &mut Context) {
+    let active = cx.editor.debug_adapters.active_client_id();
+    let mut sessions: Vec<_> = cx
+        .editor
+        .debug_adapters
+        .clients()
+        .map(|debugger| Session {
+            id: debugger.id(),
+            name: debugger.name.clone(),
+            adapter: debugger
+                .config
+                .as_ref()
+                .map(|config| config.name.clone())
+                .unwrap_or_default(),
+            parent: debugger
+                .parent
+                .and_then(|parent| cx.editor.debug_adapters.get_client(parent))
+                .map(|parent| parent.name.clone()),
+            state: if debugger.stack_frames.is_empty() {
+                "running"
+            } else {
+                "stopped"
+            },
+        })
+        .collect();
+    if sessions.is_empty() {
+        cx.editor.set_error("Debugger is not running");
+        return;
+    }
+    // list the focused session first
+    sessions.sort_by_key(|session| Some(session.id) != active);
+
+    let columns = [
+        ui::PickerColumn::new("name", |item: &Session, active: &Option<DebugAdapterId>| {
+            if Some(item.id) == *active {
+                format!("{} (focused)", item.name).into()
+            } else {
+                item.name.as_str().into()
+            }
+        }),
+        ui::PickerColumn::new("adapter", |item: &Session, _| item.adapter.as_str().into()),
+        ui::PickerColumn::new("state", |item: &Session, _| item.state.into()),
+        ui::PickerColumn::new("parent", |item: &Session, _| {
+            item.parent.as_deref().unwrap_or_default().into()
+        }),
+    ];
+    let picker = Picker::new(columns, 0, sessions, active, |cx, session, _action| {
+        block_on(focus_session(cx.editor, session.id));
+    });
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
 pub fn dap_switch_thread(cx: &mut Context) {
     thread_picker(cx, |editor, thread| {
         block_on(select_thread_id(editor, thread.id, true));
+        block_on(refresh_inline_values(editor));
     })
 }
+pub fn dap_disassemble(cx: &mut Context) {
+    if let Err(e) = block_on(disassemble(cx.editor)) {
+        cx.editor.set_error(format!("Failed to disassemble: {}", e));
+    }
+}
+
+pub fn dap_stack_trace(cx: &mut Context) {
+    cx.callback.push(Box::new(|compositor, _cx| {
+        if let Some(editor_view) = compositor.find::<ui::EditorView>() {
+            editor_view.dock.toggle(
+                DockSide::Right,
+                40,
+                ui::dap::stack_trace::StackTrace::default(),
+            );
+        }
+    }));
+}
+
 pub fn dap_switch_stack_frame(cx: &mut Context) {
     let debugger = debugger!(cx.editor);
 
@@ -757,9 +1012,10 @@ pub fn dap_switch_stack_frame(cx: &mut Context) {
         let frame = debugger.stack_frames[&thread_id]
             .get(pos.unwrap_or(0))
             .cloned();
-        if let Some(frame) = &frame {
-            jump_to_stack_frame(cx.editor, frame);
+        if let Some(frame) = frame {
+            jump_to_frame(cx.editor, cx.jobs, frame);
         }
+        block_on(refresh_inline_values(cx.editor));
     })
     .with_preview(move |_editor, frame| {
         frame
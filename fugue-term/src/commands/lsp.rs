@@ -7,7 +7,7 @@
         self, CodeAction, CodeActionOrCommand, CodeActionTriggerKind, DiagnosticSeverity,
         NumberOrString,
     },
-    util::{diagnostic_to_lsp_diagnostic, lsp_range_to_range, range_to_lsp_range},
+    util::{diagnostic_to_lsp_diagnostic, lsp_pos_to_pos, lsp_range_to_range, range_to_lsp_range},
     Client, LanguageServerId, OffsetEncoding,
 };
 use futures_util::{stream::FuturesOrdered, FutureExt};
@@ -112,6 +112,22 @@ fn location_to_file_location(location: &Location) -> Option<FileLocation<'_>> {
     Some((path.into(), line))
 }
 
+/// The character offset of the start of `location` in its line. Positions in files that aren't
+/// open are taken as they are, which is exact for ASCII lines.
+fn location_column(editor: &Editor, location: &Location) -> usize {
+    let start = location.range.start;
+    location
+        .uri
+        .as_path()
+        .and_then(|path| editor.document_by_path(path))
+        .and_then(|doc| {
+            let text = doc.text();
+            let pos = lsp_pos_to_pos(text, start, location.offset_encoding)?;
+            Some(pos - text.line_to_char(text.char_to_line(pos)))
+        })
+        .unwrap_or(start.character as usize)
+}
+
 fn jump_to_location(editor: &mut Editor, location: &Location, action: Action) {
     let (view, doc) = current!(editor);
     push_jump(view, doc);
@@ -309,6 +325,8 @@ fn diag_picker(
         },
     )
     .with_preview(move |_editor, diag| location_to_file_location(&diag.location))
+    .with_quickfix_name("diagnostics")
+    .with_quickfix_column(|editor, diag| location_column(editor, &diag.location))
     .truncate_start(false)
 }
 
@@ -437,6 +455,8 @@ fn nested_to_flat(
                 },
             )
             .with_preview(move |_editor, item| location_to_file_location(&item.location))
+            .with_quickfix_name("symbols")
+            .with_quickfix_column(|editor, item| location_column(editor, &item.location))
             .truncate_start(false);
 
             compositor.push(Box::new(overlaid(picker)))
@@ -563,6 +583,8 @@ pub fn workspace_symbol_picker(cx: &mut Context) {
         },
     )
     .with_preview(|_editor, item| location_to_file_location(&item.location))
+    .with_quickfix_name("symbols")
+    .with_quickfix_column(|editor, item| location_column(editor, &item.location))
     .with_dynamic_query(get_symbols, None)
     .truncate_start(false);
 
@@ -881,7 +903,9 @@ fn goto_impl(editor: &mut Editor, compositor: &mut Compositor, locations: Vec<Lo
             let picker = Picker::new(columns, 0, locations, cwdir, |cx, location, action| {
                 jump_to_location(cx.editor, location, action)
             })
-            .with_preview(|_editor, location| location_to_file_location(location));
+            .with_preview(|_editor, location| location_to_file_location(location))
+            .with_quickfix_name("locations")
+            .with_quickfix_column(location_column);
             compositor.push(Box::new(overlaid(picker)));
         }
     }
@@ -962,6 +986,14 @@ pub fn goto_declaration(cx: &mut Context) {
 }
 
 pub fn goto_definition(cx: &mut Context) {
+    if doc!(cx.editor)
+        .language_servers_with_feature(LanguageServerFeature::GotoDefinition)
+        .next()
+        .is_none()
+    {
+        super::syntax_goto_definition(cx);
+        return;
+    }
     goto_single_impl(
         cx,
         LanguageServerFeature::GotoDefinition,
@@ -0,0 +1,361 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+//! Operators like `d` or `gu` wait for a motion or text object and then act on the text it moves
+//! over. Any command acting on the selections can be used as an operator, keymaps bind custom
+//! ones as `operator <command>`.
+
+use std::num::NonZeroUsize;
+
+use fugue_core::{
+    operator::{self, MotionKind},
+    textobject::TextObject,
+    Range, Selection,
+};
+use fugue_view::{document::Mode, input::KeyEvent, keyboard::KeyCode, DocumentId, ViewId};
+
+use super::{select_textobject, Context, MappableCommand};
+
+#[derive(Debug, Clone)]
+pub struct PendingOperator {
+    /// The command applied to the selections the motion makes.
+    pub command: MappableCommand,
+    /// Whether linewise motions cover the content of the lines rather than the whole lines,
+    /// like `cc` keeping the line and its indentation.
+    pub line_content: bool,
+    /// The count given to the operator, multiplied with the count of the motion.
+    pub count: Option<NonZeroUsize>,
+    pub register: Option<char>,
+}
+
+impl PendingOperator {
+    /// Multiplies the count of the operator with the count of the motion.
+    fn count(&self, motion_count: Option<NonZeroUsize>) -> Option<NonZeroUsize> {
+        match (self.count, motion_count) {
+            (Some(count), Some(motion_count)) => count.checked_mul(motion_count),
+            (count, None) | (None, count) => count,
+        }
+    }
+}
+
+/// Starts `command` as an operator. Selections are already made in select mode, so the command
+/// applies to them right away.
+pub fn start_operator(cx: &mut Context, command: MappableCommand, line_content: bool) {
+    if cx.editor.mode == Mode::Select {
+        command.execute(cx);
+        return;
+    }
+    cx.pending_operator = Some(PendingOperator {
+        command,
+        line_content,
+        count: cx.count,
+        register: cx.register,
+    });
+}
+
+pub fn delete_operator(cx: &mut Context) {
+    start_operator(cx, MappableCommand::delete_selection, false);
+}
+
+pub fn change_operator(cx: &mut Context) {
+    start_operator(cx, MappableCommand::change_selection, true);
+}
+
+pub fn yank_operator(cx: &mut Context) {
+    start_operator(cx, MappableCommand::yank, false);
+}
+
+pub fn indent_operator(cx: &mut Context) {
+    start_operator(cx, MappableCommand::indent, false);
+}
+
+pub fn unindent_operator(cx: &mut Context) {
+    start_operator(cx, MappableCommand::unindent, false);
+}
+
+pub fn switch_case_operator(cx: &mut Context) {
+    start_operator(cx, MappableCommand::switch_case, false);
+}
+
+pub fn lowercase_operator(cx: &mut Context) {
+    start_operator(cx, MappableCommand::switch_to_lowercase, false);
+}
+
+pub fn uppercase_operator(cx: &mut Context) {
+    start_operator(cx, MappableCommand::switch_to_uppercase, false);
+}
+
+pub fn comment_operator(cx: &mut Context) {
+    start_operator(cx, MappableCommand::toggle_comments, false);
+}
+
+pub fn format_operator(cx: &mut Context) {
+    start_operator(cx, MappableCommand::format_selections, false);
+}
+
+pub fn surround_operator(cx: &mut Context) {
+    start_operator(cx, MappableCommand::surround_add, true);
+}
+
+/// Defines commands applying an operator to a fixed motion, the way `dd`, `diw` or `dw` do.
+macro_rules! operator_commands {
+    (
+        $command:ident, $line_content:literal,
+        $lines:ident, $inner:ident, $around:ident,
+        $($name:ident => $motion:ident,)*
+    ) => {
+        pub fn $lines(cx: &mut Context) {
+            let operator = fixed_operator(cx, MappableCommand::$command, $line_content);
+            apply_operator_lines(cx, operator);
+        }
+
+        pub fn $inner(cx: &mut Context) {
+            let operator = fixed_operator(cx, MappableCommand::$command, $line_content);
+            apply_operator_textobject(cx, operator, TextObject::Inside);
+        }
+
+        pub fn $around(cx: &mut Context) {
+            let operator = fixed_operator(cx, MappableCommand::$command, $line_content);
+            apply_operator_textobject(cx, operator, TextObject::Around);
+        }
+
+        $(
+            pub fn $name(cx: &mut Context) {
+                let operator = fixed_operator(cx, MappableCommand::$command, $line_content);
+                apply_operator_motion(cx, operator, &[MappableCommand::$motion]);
+            }
+        )*
+    };
+}
+
+/// The operator of a command with a fixed motion. The count is left to the motion.
+fn fixed_operator(cx: &Context, command: MappableCommand, line_content: bool) -> PendingOperator {
+    PendingOperator {
+        command,
+        line_content,
+        count: None,
+        register: cx.register,
+    }
+}
+
+operator_commands!(
+    delete_selection, false,
+    delete_current_line, delete_textobject_inner, delete_textobject_around,
+    delete_next_word_start => move_next_word_start,
+    delete_prev_word_start => move_prev_word_start,
+    delete_next_word_end => move_next_word_end,
+    delete_next_long_word_start => move_next_long_word_start,
+    delete_prev_long_word_start => move_prev_long_word_start,
+    delete_next_long_word_end => move_next_long_word_end,
+    delete_to_line_end => goto_line_end,
+    delete_to_line_start => goto_line_start,
+    delete_to_first_nonwhitespace => goto_first_nonwhitespace,
+    delete_to_line => goto_line,
+    delete_to_file_start => goto_file_start,
+    delete_to_last_line => goto_last_line,
+);
+
+operator_commands!(
+    change_selection, true,
+    change_current_line, change_textobject_inner, change_textobject_around,
+    change_next_word_start => move_next_word_start,
+    change_prev_word_start => move_prev_word_start,
+    change_next_word_end => move_next_word_end,
+    change_next_long_word_start => move_next_long_word_start,
+    change_prev_long_word_start => move_prev_long_word_start,
+    change_next_long_word_end => move_next_long_word_end,
+    change_to_line_end => goto_line_end,
+    change_to_line_start => goto_line_start,
+    change_to_first_nonwhitespace => goto_first_nonwhitespace,
+    change_to_line => goto_line,
+    change_to_file_start => goto_file_start,
+    change_to_last_line => goto_last_line,
+);
+
+operator_commands!(
+    yank, false,
+    yank_current_line, yank_textobject_inner, yank_textobject_around,
+    yank_next_word_start => move_next_word_start,
+    yank_prev_word_start => move_prev_word_start,
+    yank_next_word_end => move_next_word_end,
+    yank_next_long_word_start => move_next_long_word_start,
+    yank_prev_long_word_start => move_prev_long_word_start,
+    yank_next_long_word_end => move_next_long_word_end,
+    yank_to_line_end => goto_line_end,
+    yank_to_line_start => goto_line_start,
+    yank_to_first_nonwhitespace => goto_first_nonwhitespace,
+    yank_to_line => goto_line,
+    yank_to_file_start => goto_file_start,
+    yank_to_last_line => goto_last_line,
+);
+
+/// Where a motion started, to tell what it moved over.
+struct Origin {
+    view: ViewId,
+    doc: DocumentId,
+    selection: Selection,
+}
+
+impl Origin {
+    fn new(cx: &mut Context) -> Self {
+        let (view, doc) = current!(cx.editor);
+        Self {
+            view: view.id,
+            doc: doc.id(),
+            selection: doc.selection(view.id).clone(),
+        }
+    }
+}
+
+/// Applies `operator` to the text the `motion` commands move over. Operators are cancelled by
+/// commands that aren't motions.
+pub fn apply_operator_motion(
+    cx: &mut Context,
+    operator: PendingOperator,
+    motion: &[MappableCommand],
+) {
+    if let Some(command) = motion
+        .iter()
+        .find(|command| command.motion_kind().is_none())
+    {
+        cx.editor
+            .set_error(format!("'{}' is not a motion", command.name()));
+        return;
+    }
+    let Some(kind) = motion.last().and_then(MappableCommand::motion_kind) else {
+        return;
+    };
+    // vi makes `cw` change to the end of the word, leaving the whitespace after it
+    let trim_whitespace = operator.line_content
+        && matches!(
+            motion.last().unwrap().name(),
+            "move_next_word_start" | "move_next_long_word_start" | "move_next_sub_word_start"
+        );
+
+    let origin = Origin::new(cx);
+    cx.count = operator.count(cx.count);
+    for command in motion {
+        command.execute(cx);
+    }
+    // motions like `f` wait for another key
+    after_next_key(cx, move |cx, event| {
+        if event.is_some_and(|event| matches!(event.code, KeyCode::Esc | KeyCode::Null)) {
+            return;
+        }
+        finish_motion(cx, operator, origin, kind, trim_whitespace);
+    });
+}
+
+/// Applies `operator` to the text object chosen by the next key.
+pub fn apply_operator_textobject(cx: &mut Context, operator: PendingOperator, objtype: TextObject) {
+    let origin = Origin::new(cx);
+    cx.count = operator.count(cx.count);
+    select_textobject(cx, objtype);
+    after_next_key(cx, move |cx, event| {
+        if event.and_then(|event| event.char()).is_some() {
+            finish_motion(cx, operator, origin, MotionKind::Selection, false);
+        }
+    });
+}
+
+/// Applies `operator` to `count` whole lines starting with the current line, like `dd`.
+pub fn apply_operator_lines(cx: &mut Context, operator: PendingOperator) {
+    let count = operator.count(cx.count).map_or(1, NonZeroUsize::get);
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let ranges = doc.selection(view.id).clone().transform(|range| {
+        let line = range.cursor_line(text);
+        operator::line_range(text, line, line + count - 1)
+    });
+    apply(cx, operator, ranges, true);
+}
+
+fn finish_motion(
+    cx: &mut Context,
+    operator: PendingOperator,
+    origin: Origin,
+    kind: MotionKind,
+    trim_whitespace: bool,
+) {
+    let (view, doc) = current!(cx.editor);
+    if view.id != origin.view || doc.id() != origin.doc {
+        return;
+    }
+    let text = doc.text().slice(..);
+    let motion = doc.selection(view.id).clone();
+    // motions that fail to move, like `j` on the last line, cancel the operator
+    if motion == origin.selection
+        || motion.len() != origin.selection.len() && kind != MotionKind::Selection
+    {
+        doc.set_selection(view.id, origin.selection);
+        return;
+    }
+    if kind == MotionKind::Selection {
+        apply(cx, operator, motion, false);
+        return;
+    }
+
+    let mut linewise = true;
+    let ranges = origin
+        .selection
+        .ranges()
+        .iter()
+        .zip(motion.ranges())
+        .map(|(&from, &to)| {
+            let (range, lines) = operator::motion_range(text, from, to, kind);
+            linewise &= lines;
+            if trim_whitespace {
+                operator::trim_trailing_whitespace(text, range)
+            } else {
+                range
+            }
+        })
+        .collect();
+    let selection = Selection::new(ranges, origin.selection.primary_index());
+    apply(cx, operator, selection, linewise);
+}
+
+/// Runs the operator on `selection` and leaves a cursor at the start of each range.
+fn apply(cx: &mut Context, operator: PendingOperator, selection: Selection, linewise: bool) {
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let selection = if linewise && operator.line_content {
+        selection.transform(|range| operator::line_content(text, range))
+    } else {
+        selection
+    };
+    doc.set_selection(view.id, selection);
+
+    cx.count = None;
+    cx.register = operator.register;
+    operator.command.execute(cx);
+    // operators like surround wait for another key
+    after_next_key(cx, |cx, _| {
+        if cx.editor.mode != Mode::Normal {
+            return;
+        }
+        let (view, doc) = current!(cx.editor);
+        let selection = doc
+            .selection(view.id)
+            .clone()
+            .transform(|range| Range::point(range.from()));
+        doc.set_selection(view.id, selection);
+    });
+}
+
+/// Runs `f` once the on next key callback set by the last command ran, with the key it got,
+/// or right away without a key.
+fn after_next_key(cx: &mut Context, f: impl FnOnce(&mut Context, Option<KeyEvent>) + 'static) {
+    match cx.on_next_key_callback.take() {
+        Some((callback, kind)) => {
+            cx.on_next_key_callback = Some((
+                Box::new(move |cx, event| {
+                    callback(cx, event);
+                    f(cx, Some(event));
+                }),
+                kind,
+            ))
+        }
+        None => f(cx, None),
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use anyhow::Context as _;
+use fugue_core::Selection;
+use fugue_view::{align_view, editor::Action, Align, Editor};
+
+use super::{push_jump, Context};
+
+/// Opens the entry `idx` of the current quickfix list and makes it the current entry.
+pub fn goto_quickfix_entry(editor: &mut Editor, idx: usize) -> anyhow::Result<()> {
+    let list = editor
+        .quickfix
+        .current_mut()
+        .context("No quickfix list, export one from a picker with C-q")?;
+    let len = list.entries.len();
+    let entry = list
+        .entries
+        .get(idx)
+        .with_context(|| format!("No quickfix entry {}", idx + 1))?;
+    list.index = idx;
+    let path = entry.path.clone();
+
+    let (view, doc) = current!(editor);
+    push_jump(view, doc);
+    editor.open(&path, Action::Replace)?;
+    // positions are mapped once the document is open
+    editor.sync_quickfix();
+
+    let entry = &editor.quickfix.current().unwrap().entries[idx];
+    let text = entry.text.clone();
+    let (view, doc) = current!(editor);
+    let pos = entry.char_idx(doc.text().slice(..));
+    doc.set_selection(view.id, Selection::point(pos));
+    align_view(doc, view, Align::Center);
+    editor.set_status(format!("({}/{len}) {text}", idx + 1));
+    Ok(())
+}
+
+/// Moves `count` entries through the current quickfix list, stopping at its ends.
+pub fn goto_quickfix_offset(editor: &mut Editor, count: isize) -> anyhow::Result<()> {
+    let list = editor
+        .quickfix
+        .current()
+        .context("No quickfix list, export one from a picker with C-q")?;
+    let idx = list
+        .index
+        .saturating_add_signed(count)
+        .min(list.entries.len().saturating_sub(1));
+    goto_quickfix_entry(editor, idx)
+}
+
+pub fn goto_next_quickfix(cx: &mut Context) {
+    if let Err(err) = goto_quickfix_offset(cx.editor, cx.count() as isize) {
+        cx.editor.set_error(err.to_string());
+    }
+}
+
+pub fn goto_prev_quickfix(cx: &mut Context) {
+    if let Err(err) = goto_quickfix_offset(cx.editor, -(cx.count() as isize)) {
+        cx.editor.set_error(err.to_string());
+    }
+}
@@ -8,9 +8,14 @@
     sync::Arc,
 };
 
+use anyhow::{ensure, Context as _};
 use dashmap::DashMap;
 use fugue_core::{
-    syntax::{Loader, QueryIterEvent},
+    syntax::{
+        structural::{self, StructuralPattern},
+        LanguageLoader as _, Loader, QueryIterEvent,
+    },
+    textobject::{self, TextObject},
     Rope, RopeSlice, Selection, Syntax, Uri,
 };
 use fugue_stdx::{
@@ -20,6 +25,8 @@
 use fugue_view::{
     align_view,
     document::{from_reader, SCRATCH_BUFFER_NAME},
+    editor::Action,
+    workspace_index::{IndexedTag, WorkspaceIndex},
     Align, Document, DocumentId, Editor,
 };
 use futures_util::FutureExt;
@@ -28,8 +35,10 @@
 use ignore::{DirEntry, WalkBuilder, WalkState};
 
 use crate::{
-    filter_picker_entry,
+    compositor::{self, Component},
+    filter_picker_entry, job,
     ui::{
+        self,
         overlay::overlaid,
         picker::{Injector, PathOrId},
         Picker, PickerColumn,
@@ -106,6 +115,20 @@ struct Tag {
     doc: UriOrDocumentId,
 }
 
+impl Tag {
+    fn from_indexed(path: PathBuf, tag: IndexedTag) -> Option<Self> {
+        Some(Self {
+            kind: TagKind::from_name(&tag.kind)?,
+            name: tag.name,
+            start: tag.start,
+            end: tag.end,
+            start_line: tag.start_line,
+            end_line: tag.end_line,
+            doc: UriOrDocumentId::Uri(Uri::from(path)),
+        })
+    }
+}
+
 fn tags_iter<'a>(
     syntax: &'a Syntax,
     loader: &'a Loader,
@@ -191,6 +214,18 @@ pub fn syntax_symbol_picker(cx: &mut Context) {
 }
 
 pub fn syntax_workspace_symbol_picker(cx: &mut Context) {
+    if let Some(index) = cx
+        .editor
+        .workspace_index
+        .as_ref()
+        .filter(|index| index.is_ready())
+    {
+        let root = index.root().to_path_buf();
+        let tags = indexed_tags(cx.editor, index, None);
+        cx.push_layer(Box::new(overlaid(tag_picker(tags, root))));
+        return;
+    }
+
     #[derive(Debug)]
     struct SearchState {
         searcher_builder: SearcherBuilder,
@@ -399,43 +434,139 @@ struct SearchState {
         1, // name
         [],
         state,
-        move |cx, tag, action| {
-            let doc_id = match &tag.doc {
-                UriOrDocumentId::Id(id) => *id,
-                UriOrDocumentId::Uri(uri) => match cx.editor.open(uri.as_path().expect(""), action) {
-                    Ok(id) => id,
-                    Err(e) => {
-                        cx.editor
-                            .set_error(format!("Failed to open file '{uri:?}': {e}"));
-                        return;
-                    }
-                }
-            };
-            let doc = doc_mut!(cx.editor, &doc_id);
-            let view = view_mut!(cx.editor);
-            let len_chars = doc.text().len_chars();
-            if tag.start >= len_chars || tag.end > len_chars {
-                cx.editor.set_error("The location you jumped to does not exist anymore because the file has changed.");
-                return;
-            }
-            doc.set_selection(view.id, Selection::single(tag.start, tag.end));
-            if action.align_view(view, doc.id()) {
-                align_view(doc, view, Align::Center)
-            }
-        },
+        move |cx, tag, action| jump_to_tag(cx.editor, tag, action),
     )
     .with_dynamic_query(get_tags, Some(275))
     .with_preview(move |_editor, tag| {
-        Some((
-            tag.doc.path_or_id()?,
-            Some((tag.start_line, tag.end_line)),
-        ))
+        Some((tag.doc.path_or_id()?, Some((tag.start_line, tag.end_line))))
     })
     .with_history_register(Some(reg))
     .truncate_start(false);
     cx.push_layer(Box::new(overlaid(picker)));
 }
 
+/// Jumps to the definitions of the word under the cursor found by the workspace index, for
+/// buffers without a language server.
+pub fn syntax_goto_definition(cx: &mut Context) {
+    let Some(index) = cx.editor.workspace_index.clone() else {
+        cx.editor.set_error("No definition found.");
+        return;
+    };
+    let (view, doc) = current_ref!(cx.editor);
+    let text = doc.text().slice(..);
+    let range = doc.selection(view.id).primary();
+    let name: String = textobject::textobject_word(text, range, TextObject::Inside, 1, false)
+        .fragment(text)
+        .into();
+    if name.trim().is_empty() {
+        cx.editor.set_error("No definition found.");
+        return;
+    }
+
+    let mut tags = indexed_tags(cx.editor, &index, Some(&name));
+    match tags.len() {
+        0 => cx.editor.set_error("No definition found."),
+        1 => {
+            let (view, doc) = current!(cx.editor);
+            super::push_jump(view, doc);
+            jump_to_tag(cx.editor, &tags.pop().unwrap(), Action::Replace);
+        }
+        _ => {
+            let root = index.root().to_path_buf();
+            cx.push_layer(Box::new(overlaid(tag_picker(tags, root))));
+        }
+    }
+}
+
+/// Collects the definitions in the workspace, or only those of `name`, from the workspace
+/// index. Open buffers are searched themselves, so that their unsaved changes are reflected.
+fn indexed_tags(editor: &Editor, index: &WorkspaceIndex, name: Option<&str>) -> Vec<Tag> {
+    let loader = editor.syn_loader.load();
+    let mut tags = Vec::new();
+    let mut open = HashSet::new();
+    for doc in editor.documents() {
+        if let Some(path) = doc.path() {
+            open.insert(path.clone());
+        }
+        let Some(syntax) = doc.syntax() else { continue };
+        let uri_or_id = doc
+            .uri()
+            .map(UriOrDocumentId::Uri)
+            .unwrap_or_else(|| UriOrDocumentId::Id(doc.id()));
+        tags.extend(
+            tags_iter(syntax, &loader, doc.text().slice(..), uri_or_id, None)
+                .filter(|tag| name.is_none_or(|name| tag.name == name)),
+        );
+    }
+    let indexed = match name {
+        Some(name) => index.definitions(name),
+        None => index.tags(),
+    };
+    tags.extend(
+        indexed
+            .into_iter()
+            .filter(|(path, _)| !open.contains(path))
+            .filter_map(|(path, tag)| Tag::from_indexed(path, tag)),
+    );
+    tags
+}
+
+fn tag_picker(tags: Vec<Tag>, root: PathBuf) -> Picker<Tag, PathBuf> {
+    let columns = vec![
+        PickerColumn::new("kind", |tag: &Tag, _| tag.kind.as_str().into()),
+        PickerColumn::new("name", |tag: &Tag, _| tag.name.as_str().into()),
+        PickerColumn::new("path", |tag: &Tag, root: &PathBuf| match &tag.doc {
+            UriOrDocumentId::Uri(uri) => match uri.as_path() {
+                Some(path) => path
+                    .strip_prefix(root)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .into(),
+                None => uri.to_string().into(),
+            },
+            UriOrDocumentId::Id(_) => SCRATCH_BUFFER_NAME.into(),
+        }),
+    ];
+    Picker::new(
+        columns,
+        1, // name
+        tags,
+        root,
+        |cx, tag, action| jump_to_tag(cx.editor, tag, action),
+    )
+    .with_preview(|_editor, tag| {
+        Some((tag.doc.path_or_id()?, Some((tag.start_line, tag.end_line))))
+    })
+    .with_quickfix_name("symbols")
+    .truncate_start(false)
+}
+
+fn jump_to_tag(editor: &mut Editor, tag: &Tag, action: Action) {
+    let doc_id = match &tag.doc {
+        UriOrDocumentId::Id(id) => *id,
+        UriOrDocumentId::Uri(uri) => match editor.open(uri.as_path().expect(""), action) {
+            Ok(id) => id,
+            Err(e) => {
+                editor.set_error(format!("Failed to open file '{uri:?}': {e}"));
+                return;
+            }
+        },
+    };
+    let doc = doc_mut!(editor, &doc_id);
+    let view = view_mut!(editor);
+    let len_chars = doc.text().len_chars();
+    if tag.start >= len_chars || tag.end > len_chars {
+        editor.set_error(
+            "The location you jumped to does not exist anymore because the file has changed.",
+        );
+        return;
+    }
+    doc.set_selection(view.id, Selection::single(tag.start, tag.end));
+    if action.align_view(view, doc.id()) {
+        align_view(doc, view, Align::Center)
+    }
+}
+
 /// Create a Rope and language config for a given existing path without creating a full Document.
 fn syntax_for_path(path: &Path, loader: &Loader) -> Option<(Rope, Syntax)> {
     let mut file = std::fs::File::open(path).ok()?;
@@ -448,3 +579,186 @@ fn syntax_for_path(path: &Path, loader: &Loader) -> Option<(Rope, Syntax)> {
         .ok()
         .map(|syntax| (rope, syntax))
 }
+
+/// A match of a structural search, with the character range of the match.
+struct StructuralResult {
+    path: PathBuf,
+    range: std::ops::Range<usize>,
+    line: usize,
+    /// The first line of the match.
+    text: String,
+}
+
+/// Searches the current buffer, or all files of its language in the working directory, for
+/// `pattern`. Matches are listed in a picker, or with a `template` previewed as replacements.
+pub fn structural_search(
+    cx: &mut compositor::Context,
+    pattern: &str,
+    template: Option<&str>,
+    workspace: bool,
+) -> anyhow::Result<()> {
+    let loader = cx.editor.syn_loader.load_full();
+    let doc = doc!(cx.editor);
+    let language = doc
+        .language_name()
+        .and_then(|name| loader.language_for_name(name))
+        .context("the current buffer has no language")?;
+    let grammar = loader
+        .get_config(language)
+        .context("the grammar of the current buffer isn't available")?
+        .grammar;
+    let title = match template {
+        Some(template) => format!("Replace '{pattern}' with '{template}'"),
+        None => String::new(),
+    };
+    let replace = template.is_some();
+    let pattern = StructuralPattern::new(grammar, pattern)?;
+    let template = template.map(str::to_owned);
+    let find = move |text: &Rope| -> Vec<(std::ops::Range<usize>, String)> {
+        let text = text.slice(..);
+        let Ok(tree) = structural::parse(grammar, text) else {
+            return Vec::new();
+        };
+        pattern
+            .matches(&tree.root_node(), text)
+            .into_iter()
+            .map(|mat| {
+                let replacement = match &template {
+                    Some(template) => structural::expand_template(template, text, &mat),
+                    None => String::new(),
+                };
+                (mat.range, replacement)
+            })
+            .collect()
+    };
+
+    if !workspace {
+        let path = doc
+            .path()
+            .cloned()
+            .context("the current buffer has no path")?;
+        let matches = find(doc.text());
+        let files = ui::search_replace::FileMatches::new(path, doc.text().clone(), matches);
+        if files.is_empty() {
+            cx.editor.set_status("No matches found");
+            return Ok(());
+        }
+        cx.jobs.callback(async move {
+            let call: job::Callback =
+                job::Callback::EditorCompositor(Box::new(move |_, compositor| {
+                    compositor.push(structural_results(vec![files], replace, title))
+                }));
+            Ok(call)
+        });
+        return Ok(());
+    }
+
+    let root = fugue_stdx::env::current_working_dir();
+    ensure!(root.exists(), "Current working directory does not exist");
+    let config = cx.editor.config().file_picker.clone();
+    let documents: Vec<_> = cx
+        .editor
+        .documents()
+        .filter_map(|doc| Some((doc.path()?.clone(), doc.text().clone())))
+        .collect();
+    cx.editor.set_status("Searching...");
+    cx.jobs.callback(async move {
+        let files = tokio::task::spawn_blocking(move || {
+            ui::search_replace::search_workspace_with(
+                &root,
+                &config,
+                &documents,
+                |path| loader.language_for_filename(path) == Some(language),
+                |_, text| find(text),
+            )
+        })
+        .await?;
+        let call: job::Callback =
+            job::Callback::EditorCompositor(Box::new(move |editor, compositor| {
+                if files.is_empty() {
+                    editor.set_status("No matches found");
+                    return;
+                }
+                editor.clear_status();
+                compositor.push(structural_results(files, replace, title));
+            }));
+        Ok(call)
+    });
+    Ok(())
+}
+
+/// Shows the matches of a structural search in a picker, or previews their replacements.
+fn structural_results(
+    files: Vec<ui::search_replace::FileMatches>,
+    replace: bool,
+    title: String,
+) -> Box<dyn Component> {
+    if replace {
+        return Box::new(overlaid(ui::search_replace::SearchReplace::new(
+            files, title,
+        )));
+    }
+
+    let results: Vec<_> = files
+        .iter()
+        .flat_map(|file| {
+            file.locations().map(|(range, line)| {
+                let text = file.text().slice(range.clone());
+                StructuralResult {
+                    path: file.path().to_path_buf(),
+                    line,
+                    text: text.lines().next().map(String::from).unwrap_or_default(),
+                    range,
+                }
+            })
+        })
+        .collect();
+    let columns = [
+        PickerColumn::new("path", |result: &StructuralResult, _| {
+            format!(
+                "{}:{}",
+                path::get_relative_path(&result.path).display(),
+                result.line + 1
+            )
+            .into()
+        }),
+        PickerColumn::new("match", |result: &StructuralResult, _| {
+            result.text.trim().to_owned().into()
+        }),
+    ];
+    let picker = Picker::new(
+        columns,
+        1,
+        results,
+        (),
+        |cx, result: &StructuralResult, action| {
+            let doc_id = match cx.editor.open(&result.path, action) {
+                Ok(id) => id,
+                Err(err) => {
+                    cx.editor.set_error(format!(
+                        "Failed to open file '{}': {err}",
+                        result.path.display()
+                    ));
+                    return;
+                }
+            };
+            let doc = doc_mut!(cx.editor, &doc_id);
+            let view = view_mut!(cx.editor);
+            let len = doc.text().len_chars();
+            let selection =
+                Selection::single(result.range.start.min(len), result.range.end.min(len));
+            doc.set_selection(view.id, selection);
+            if action.align_view(view, doc.id()) {
+                align_view(doc, view, Align::Center);
+            }
+        },
+    )
+    .with_preview(|_editor, result| {
+        Some((
+            result.path.as_path().into(),
+            Some((result.line, result.line)),
+        ))
+    })
+    .with_quickfix_name("structural");
+    Box::new(overlaid(picker))
+}
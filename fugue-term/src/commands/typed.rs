@@ -14,6 +14,7 @@
 use fugue_core::indent::MAX_INDENT;
 use fugue_core::line_ending;
 use fugue_stdx::path::home_dir;
+use fugue_vcs::DiffBase;
 use fugue_view::document::{read_to_string, DEFAULT_LANGUAGE_NAME};
 use fugue_view::editor::{CloseError, ConfigEvent};
 use fugue_view::expansion;
@@ -1397,9 +1398,10 @@ fn reload(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyh
 
     let scrolloff = cx.editor.config().scrolloff;
     let (view, doc) = current!(cx.editor);
-    doc.reload(view, &cx.editor.diff_providers).map(|_| {
-        view.ensure_cursor_in_view(doc, scrolloff);
-    })?;
+    doc.reload(view, &cx.editor.diff_providers, &cx.editor.diff_base)
+        .map(|_| {
+            view.ensure_cursor_in_view(doc, scrolloff);
+        })?;
     if let Some(path) = doc.path() {
         cx.editor
             .language_servers
@@ -1441,7 +1443,7 @@ fn reload_all(cx: &mut compositor::Context, _args: Args, event: PromptEvent) ->
         // Ensure that the view is synced with the document's history.
         view.sync_changes(doc);
 
-        if let Err(error) = doc.reload(view, &cx.editor.diff_providers) {
+        if let Err(error) = doc.reload(view, &cx.editor.diff_providers, &cx.editor.diff_base) {
             cx.editor.set_error(format!("{}", error));
             continue;
         }
@@ -1930,6 +1932,75 @@ fn debug_eval(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> a
     Ok(())
 }
 
+fn blame(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let doc = doc!(cx.editor);
+    let doc_id = doc.id();
+    if cx.editor.blame_views.contains_key(&doc_id) {
+        return show_blamed_commit(cx.editor, doc_id);
+    }
+    if doc.blame().is_some() {
+        show_blame_view(cx.editor, doc_id);
+        return Ok(());
+    }
+
+    cx.editor.set_status("Computing blame...");
+    request_blame(cx.editor, doc_id, |editor, result| match result {
+        Ok(doc_id) => {
+            editor.clear_status();
+            show_blame_view(editor, doc_id);
+        }
+        Err(err) => editor.set_error(format!("Failed to blame: {err:#}")),
+    });
+    Ok(())
+}
+
+fn debug_memory(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let length = match args.get(1) {
+        Some(length) => length
+            .parse()
+            .map_err(|_| anyhow!("invalid number of bytes: '{length}'"))?,
+        None => fugue_view::handlers::dap::MEMORY_VIEW_SIZE,
+    };
+    fugue_lsp::block_on(fugue_view::handlers::dap::show_memory(
+        cx.editor, &args[0], length,
+    ))
+}
+
+fn debug_write_memory(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let hex: String = args.iter().skip(1).map(|arg| arg.as_ref()).collect();
+    if hex.len() % 2 != 0 {
+        bail!("expected a whole number of hex bytes");
+    }
+    let data = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2).unwrap_or_default(), 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| anyhow!("invalid hex bytes: '{hex}'"))?;
+    fugue_lsp::block_on(fugue_view::handlers::dap::write_memory(
+        cx.editor, &args[0], &data,
+    ))
+}
+
 fn debug_start(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
@@ -2523,6 +2594,435 @@ fn reset_diff_change(
     Ok(())
 }
 
+fn stage_hunk(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let changes = stage_selected_hunks(cx.editor)?;
+    cx.editor.set_status(format!(
+        "Staged {changes} change{}",
+        if changes == 1 { "" } else { "s" }
+    ));
+    Ok(())
+}
+
+fn unstage_hunk(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let changes = unstage_selected_hunks(cx.editor)?;
+    cx.editor.set_status(format!(
+        "Unstaged {changes} change{}",
+        if changes == 1 { "" } else { "s" }
+    ));
+    Ok(())
+}
+
+fn stage_file(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    vcs::stage_file(cx.editor)?;
+    cx.editor.set_status("Staged file");
+    Ok(())
+}
+
+fn unstage_file(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    vcs::unstage_file(cx.editor)?;
+    cx.editor.set_status("Unstaged file");
+    Ok(())
+}
+
+fn resolve(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let resolution: Resolution = args[0].parse()?;
+    let conflicts = resolve_conflicts(cx.editor, resolution)?;
+    cx.editor.set_status(format!(
+        "Resolved {conflicts} conflict{} with {resolution}",
+        if conflicts == 1 { "" } else { "s" }
+    ));
+    Ok(())
+}
+
+fn commit(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let id = match args.first() {
+        Some(message) => commit_staged_changes(cx.editor, message)?,
+        None => {
+            let doc = doc!(cx.editor);
+            if !doc.is_commit_message() {
+                open_commit_message(cx.editor)?;
+                return Ok(());
+            }
+            if doc.path().is_some() {
+                bail!("git commits this message once it is written, use :wq instead");
+            }
+            let (doc_id, message) = (doc.id(), doc.text().to_string());
+            let id = commit_staged_changes(cx.editor, &message)?;
+            // the message is committed, so its unsaved changes can be discarded
+            let _ = cx.editor.close_document(doc_id, true);
+            id
+        }
+    };
+    cx.editor.set_status(format!("Committed {id}"));
+    Ok(())
+}
+
+fn diff_base(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let buffer = args.has_flag("buffer");
+    let base = args.first().map(str::parse::<DiffBase>).transpose()?;
+    match (base, buffer) {
+        (None, false) => {
+            let doc = doc!(cx.editor);
+            cx.editor
+                .set_status(format!("Diffing against {}", doc.active_diff_base()));
+            return Ok(());
+        }
+        (base, true) => {
+            let doc = doc_mut!(cx.editor);
+            doc.set_diff_base_override(base);
+            doc.refresh_version_control(&cx.editor.diff_providers, &cx.editor.diff_base);
+        }
+        (Some(base), false) => {
+            cx.editor.diff_base = base;
+            vcs::refresh_documents(cx.editor);
+        }
+    }
+
+    let doc = doc!(cx.editor);
+    if doc.path().is_some() && doc.diff_handle().is_none() {
+        bail!(
+            "{} has no version of this file to diff against",
+            doc.active_diff_base()
+        );
+    }
+    cx.editor
+        .set_status(format!("Diffing against {}", doc.active_diff_base()));
+    Ok(())
+}
+
+fn diff(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let doc_id = doc!(cx.editor).id();
+    if let Some(file) = args.first() {
+        let path = fugue_stdx::path::expand_tilde(Path::new(file)).into_owned();
+        let base_id = cx.editor.open(&path, Action::Load)?;
+        let doc = doc!(cx.editor, &doc_id);
+        if args.has_flag("unified") {
+            let before = doc!(cx.editor, &base_id).text().clone();
+            let after = doc.text().clone();
+            let name = doc.display_name().into_owned();
+            show_unified_diff(cx.editor, &before, file, &after, &name);
+            return Ok(());
+        }
+        open_diff_view(cx.editor, base_id, doc_id);
+        return Ok(());
+    }
+
+    let doc = doc!(cx.editor);
+    if args.has_flag("unified") {
+        let handle = doc
+            .diff_handle()
+            .context("Diff is not available in the current buffer")?;
+        let diff_base = handle.load().diff_base().clone();
+        let text = doc.text().clone();
+        let name = doc.display_name().into_owned();
+        show_unified_diff(
+            cx.editor,
+            &diff_base,
+            &format!("a/{name}"),
+            &text,
+            &format!("b/{name}"),
+        );
+        return Ok(());
+    }
+
+    let base_id = vcs::diff_base_document(cx.editor, doc_id)?;
+    open_diff_view(cx.editor, base_id, doc_id);
+    Ok(())
+}
+
+fn diff_files(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let before = fugue_stdx::path::expand_tilde(Path::new(&args[0])).into_owned();
+    let after = fugue_stdx::path::expand_tilde(Path::new(&args[1])).into_owned();
+    let base_id = cx.editor.open(&before, Action::Load)?;
+    let doc_id = cx.editor.open(&after, Action::Load)?;
+    if args.has_flag("unified") {
+        let before_text = doc!(cx.editor, &base_id).text().clone();
+        let after_text = doc!(cx.editor, &doc_id).text().clone();
+        show_unified_diff(cx.editor, &before_text, &args[0], &after_text, &args[1]);
+        return Ok(());
+    }
+    open_diff_view(cx.editor, base_id, doc_id);
+    Ok(())
+}
+
+fn quickfix_next(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    goto_quickfix_offset(cx.editor, 1)
+}
+
+fn quickfix_prev(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    goto_quickfix_offset(cx.editor, -1)
+}
+
+fn quickfix_first(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    goto_quickfix_entry(cx.editor, 0)
+}
+
+fn quickfix_last(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let len = cx
+        .editor
+        .quickfix
+        .current()
+        .map_or(0, |list| list.entries.len());
+    goto_quickfix_entry(cx.editor, len.saturating_sub(1))
+}
+
+fn quickfix_open(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    if let Some(name) = args.first() {
+        ensure!(
+            cx.editor.quickfix.select(name),
+            "No quickfix list named '{name}'"
+        );
+    }
+    cx.jobs.callback(async {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(|_, compositor| {
+            ui::quickfix::open(compositor, true)
+        }));
+        Ok(call)
+    });
+    Ok(())
+}
+
+fn quickfix_close(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    cx.jobs.callback(async {
+        let call: job::Callback =
+            Callback::EditorCompositor(Box::new(|_, compositor| ui::quickfix::close(compositor)));
+        Ok(call)
+    });
+    Ok(())
+}
+
+/// Parses a 1 indexed slot of the pinned files into a 0 indexed one.
+fn parse_pinned_slot(arg: &str) -> anyhow::Result<usize> {
+    arg.parse::<usize>()
+        .ok()
+        .and_then(|slot| slot.checked_sub(1))
+        .with_context(|| format!("invalid slot '{arg}'"))
+}
+
+fn pin(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let slot = args.first().map(parse_pinned_slot).transpose()?;
+    pin_current_buffer(cx.editor, slot);
+    Ok(())
+}
+
+fn unpin(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let slot = match args.first() {
+        Some(arg) => parse_pinned_slot(arg)?,
+        None => doc!(cx.editor)
+            .path()
+            .and_then(|path| cx.editor.pinned_files.slot(path))
+            .context("current buffer is not pinned")?,
+    };
+    unpin_slot(cx.editor, slot);
+    Ok(())
+}
+
+fn goto_pinned(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    goto_pinned_file(cx.editor, parse_pinned_slot(&args[0])?, Action::Replace);
+    Ok(())
+}
+
+fn pin_move(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let from = parse_pinned_slot(&args[0])?;
+    let to = parse_pinned_slot(&args[1])?;
+    ensure!(
+        cx.editor.pinned_files.move_slot(from, to),
+        "only {} files are pinned",
+        cx.editor.pinned_files.files().len()
+    );
+    Ok(())
+}
+
+fn replace_all(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let literal = args.has_flag(REPLACE_ALL_LITERAL_FLAG.name);
+    let (pattern, replacement) = (&args[0], &args[1]);
+    let escaped;
+    let pattern = if literal {
+        escaped = fugue_core::regex::escape(pattern);
+        &escaped
+    } else {
+        pattern
+    };
+    let smart_case = cx.editor.config().search.smart_case;
+    let regex = fugue_core::regex::RegexBuilder::new(pattern)
+        .case_insensitive(smart_case && pattern.chars().all(|c| !c.is_uppercase()))
+        .multi_line(true)
+        .build()?;
+    let replacement = ui::search_replace::Replacement {
+        regex,
+        replacement: replacement.to_string(),
+        literal,
+    };
+
+    let root = fugue_stdx::env::current_working_dir();
+    ensure!(root.exists(), "Current working directory does not exist");
+    let config = cx.editor.config().file_picker.clone();
+    let documents: Vec<_> = cx
+        .editor
+        .documents()
+        .filter_map(|doc| Some((doc.path()?.clone(), doc.text().clone())))
+        .collect();
+    let title = format!("Replace '{}' with '{}'", &args[0], &args[1]);
+
+    cx.editor.set_status("Searching...");
+    cx.jobs.callback(async move {
+        let files = tokio::task::spawn_blocking(move || {
+            ui::search_replace::search_workspace(&root, &config, &documents, &replacement)
+        })
+        .await?;
+        let call: job::Callback =
+            Callback::EditorCompositor(Box::new(move |editor, compositor| {
+                if files.is_empty() {
+                    editor.set_status("No matches found");
+                    return;
+                }
+                editor.clear_status();
+                let view = ui::search_replace::SearchReplace::new(files, title);
+                compositor.push(Box::new(overlaid(view)));
+            }));
+        Ok(call)
+    });
+    Ok(())
+}
+
+fn structural_search(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let workspace = args.has_flag(STRUCTURAL_WORKSPACE_FLAG.name);
+    super::structural_search(cx, &args[0], None, workspace)
+}
+
+fn structural_replace(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let workspace = args.has_flag(STRUCTURAL_WORKSPACE_FLAG.name);
+    super::structural_search(cx, &args[0], Some(&args[1]), workspace)
+}
+
 fn clear_register(
     cx: &mut compositor::Context,
     args: Args,
@@ -2725,6 +3225,27 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
     ..Flag::DEFAULT
 };
 
+const DIFF_UNIFIED_FLAG: Flag = Flag {
+    name: "unified",
+    alias: Some('u'),
+    doc: "write a unified diff to a scratch buffer",
+    ..Flag::DEFAULT
+};
+
+const REPLACE_ALL_LITERAL_FLAG: Flag = Flag {
+    name: "literal",
+    alias: Some('l'),
+    doc: "match the pattern literally and don't expand capture groups in the replacement",
+    ..Flag::DEFAULT
+};
+
+const STRUCTURAL_WORKSPACE_FLAG: Flag = Flag {
+    name: "workspace",
+    alias: Some('w'),
+    doc: "search all files of the buffer's language in the working directory",
+    ..Flag::DEFAULT
+};
+
 pub const TYPABLE_COMMAND_LIST: &[TypableCommand] = &[
     TypableCommand {
         name: "exit",
@@ -3397,6 +3918,39 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "blame",
+        aliases: &[],
+        doc: "Show the commit that last changed each line next to the current buffer. In a blame view, show the changes of the commit on the cursor line.",
+        fun: blame,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "debug-memory",
+        aliases: &[],
+        doc: "Show a hex dump of the debuggee's memory at an address or the value of an expression, optionally with the number of bytes to show.",
+        fun: debug_memory,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (1, Some(2)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "debug-write-memory",
+        aliases: &[],
+        doc: "Write hex bytes to the debuggee's memory at an address or the value of an expression.",
+        fun: debug_write_memory,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (2, None),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "debug-eval",
         aliases: &[],
@@ -3663,6 +4217,260 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "stage-hunk",
+        aliases: &[],
+        doc: "Stage the diff changes intersecting with the selections.",
+        fun: stage_hunk,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "unstage-hunk",
+        aliases: &[],
+        doc: "Unstage the staged changes intersecting with the selections.",
+        fun: unstage_hunk,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "stage-file",
+        aliases: &[],
+        doc: "Stage the contents of the current buffer.",
+        fun: stage_file,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "unstage-file",
+        aliases: &[],
+        doc: "Reset the staged contents of the current buffer to HEAD.",
+        fun: unstage_file,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "resolve",
+        aliases: &[],
+        doc: "Resolve the merge conflicts under the selections with ours, theirs, base or both sides.",
+        fun: resolve,
+        completer: CommandCompleter::positional(&[completers::resolution]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "commit",
+        aliases: &[],
+        doc: "Commit the staged changes with the given message. Without a message a buffer with a commit message template is opened, run `:commit` again in it to commit.",
+        fun: commit,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            raw_after: Some(0),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "diff-base",
+        aliases: &[],
+        doc: "Set the revision the diff gutter compares against: `index` (the default), a branch, tag or commit, or `...<rev>` for the merge base of HEAD and a revision. Shows the current base without arguments. With `--buffer` only the current buffer is changed, and no revision restores the editor wide base.",
+        fun: diff_base,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            flags: &[Flag {
+                name: "buffer",
+                alias: Some('b'),
+                doc: "only change the diff base of the current buffer",
+                ..Flag::DEFAULT
+            }],
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "diff",
+        aliases: &[],
+        doc: "Show the diff base of the current buffer, or the given file, side by side with the buffer. With `--unified` the changes are written to a new scratch buffer as a unified diff instead.",
+        fun: diff,
+        completer: CommandCompleter::positional(&[completers::filename]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            flags: &[DIFF_UNIFIED_FLAG],
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "diff-files",
+        aliases: &[],
+        doc: "Show the differences between two files side by side, or as a unified diff with `--unified`.",
+        fun: diff_files,
+        completer: CommandCompleter::all(completers::filename),
+        signature: Signature {
+            positionals: (2, Some(2)),
+            flags: &[DIFF_UNIFIED_FLAG],
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "cnext",
+        aliases: &["cn"],
+        doc: "Go to the next entry of the current quickfix list.",
+        fun: quickfix_next,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "cprev",
+        aliases: &["cp", "cN"],
+        doc: "Go to the previous entry of the current quickfix list.",
+        fun: quickfix_prev,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "cfirst",
+        aliases: &["cr"],
+        doc: "Go to the first entry of the current quickfix list.",
+        fun: quickfix_first,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "clast",
+        aliases: &["cla"],
+        doc: "Go to the last entry of the current quickfix list.",
+        fun: quickfix_last,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "copen",
+        aliases: &["cope"],
+        doc: "Open the quickfix panel. A list name makes that list the current one. Pickers export their matched items to a list with `C-q`.",
+        fun: quickfix_open,
+        completer: CommandCompleter::positional(&[completers::quickfix_list]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "cclose",
+        aliases: &["ccl"],
+        doc: "Close the quickfix panel.",
+        fun: quickfix_close,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "pin",
+        aliases: &[],
+        doc: "Pin the current buffer to a slot, or to a new slot at the end. Pinned buffers are moved.",
+        fun: pin,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "unpin",
+        aliases: &[],
+        doc: "Unpin the file in a slot, or the current buffer.",
+        fun: unpin,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "goto-pinned",
+        aliases: &["gp"],
+        doc: "Goto the file pinned to a slot.",
+        fun: goto_pinned,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "pin-move",
+        aliases: &[],
+        doc: "Move the pinned file in a slot to another slot.",
+        fun: pin_move,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (2, Some(2)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "replace-all",
+        aliases: &[],
+        doc: "Replace the matches of a regex in all files of the working directory, previewing the changes first. Capture groups can be used in the replacement like `$1` or `${name}`.",
+        fun: replace_all,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (2, Some(2)),
+            flags: &[REPLACE_ALL_LITERAL_FLAG],
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "structural-search",
+        aliases: &["ss"],
+        doc: "Search the syntax tree of the buffer for a pattern, written as code with metavariables like `foo($A, $B)` or as a tree-sitter query.",
+        fun: structural_search,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            flags: &[STRUCTURAL_WORKSPACE_FLAG],
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "structural-replace",
+        aliases: &["sr"],
+        doc: "Replace the matches of a structural search pattern with a template using its metavariables, like `:sr 'foo($A, $B)' 'foo($B, $A)'`, previewing the changes first.",
+        fun: structural_replace,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (2, Some(2)),
+            flags: &[STRUCTURAL_WORKSPACE_FLAG],
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "clear-register",
         aliases: &[],
@@ -0,0 +1,708 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, ensure, Context as _};
+use fugue_core::{encoding, Rope, Selection, Tendril, Transaction};
+use fugue_vcs::{unified_diff, Conflict, Diff, DiffBase, Hunk, Resolution};
+use fugue_view::diff_view::DiffView;
+use fugue_view::editor::{Action, BlameView};
+use fugue_view::{align_view, Align, Document, DocumentId, Editor};
+
+use crate::job;
+
+/// Computes the blame of a document in a background task and stores it on the document. `then`
+/// runs with the document once the blame is available.
+pub fn request_blame(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    then: impl FnOnce(&mut Editor, anyhow::Result<DocumentId>) + Send + 'static,
+) {
+    let Some(path) = editor.document(doc_id).and_then(|doc| doc.path()).cloned() else {
+        then(editor, Err(anyhow!("cannot blame a buffer without a path")));
+        return;
+    };
+    let registry = editor.diff_providers.clone();
+    tokio::spawn(async move {
+        let result = registry.blame(path).await;
+        job::dispatch(move |editor, _| {
+            let Some(doc) = editor.document_mut(doc_id) else {
+                return;
+            };
+            let result = result.map(|blame| {
+                doc.set_blame(Some(Arc::new(blame)));
+                doc_id
+            });
+            then(editor, result);
+        })
+        .await;
+    });
+}
+
+/// The text of a blame view: one line per line of `doc` describing the commit that last
+/// changed it.
+fn blame_text(doc: &Document) -> String {
+    let text = doc.text();
+    let mut lines = text.len_lines();
+    // the empty line after a trailing newline has no blame
+    if text.len_chars() == 0 || text.char(text.len_chars() - 1) == '\n' {
+        lines -= 1;
+    }
+    let blames: Vec<_> = (0..lines).map(|line| doc.line_blame(line)).collect();
+    let author_width = blames
+        .iter()
+        .flatten()
+        .map(|commit| commit.author.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(20);
+
+    let mut out = String::new();
+    for blame in blames {
+        match blame {
+            Some(commit) => {
+                let author: String = commit.author.chars().take(author_width).collect();
+                out.push_str(&format!(
+                    "{} {author:<author_width$} {:>14} {}\n",
+                    commit.short_id(),
+                    commit.relative_time(),
+                    commit.summary
+                ));
+            }
+            None => out.push_str("00000000 Not committed yet\n"),
+        }
+    }
+    out
+}
+
+/// Opens a view next to the focused one showing which commit last changed every line of the
+/// focused document.
+pub fn show_blame_view(editor: &mut Editor, doc_id: DocumentId) {
+    let Some(doc) = editor.document(doc_id) else {
+        return;
+    };
+    let mut blame_doc = Document::from(
+        Rope::from(blame_text(doc)),
+        None,
+        editor.config.clone(),
+        editor.syn_loader.clone(),
+    );
+    blame_doc.readonly = true;
+    let line = editor
+        .tree
+        .views()
+        .find(|(view, _)| view.doc == doc_id)
+        .map(|(view, _)| {
+            let text = doc.text().slice(..);
+            doc.selection(view.id).primary().cursor_line(text)
+        })
+        .unwrap_or(0);
+
+    let version = doc.version();
+    let blame_id = editor.new_file_from_document(Action::VerticalSplit, blame_doc);
+    editor.blame_views.insert(
+        blame_id,
+        BlameView {
+            doc: doc_id,
+            version,
+        },
+    );
+
+    let (view, doc) = current!(editor);
+    let pos = doc
+        .text()
+        .line_to_char(line.min(doc.text().len_lines() - 1));
+    doc.set_selection(view.id, Selection::point(pos));
+    align_view(doc, view, Align::Center);
+}
+
+/// Regenerates the text of the blame views of `doc_id`, e.g. after it was edited or its blame
+/// was computed again.
+fn update_blame_views(editor: &mut Editor, doc_id: DocumentId) {
+    let Some(doc) = editor.document(doc_id) else {
+        return;
+    };
+    if doc.blame().is_none() {
+        return;
+    }
+    let (text, version) = (blame_text(doc), doc.version());
+    for (blame_id, blame_view) in editor.blame_views.iter_mut() {
+        if blame_view.doc != doc_id {
+            continue;
+        }
+        blame_view.version = version;
+        let Some(view) = editor
+            .tree
+            .views_mut()
+            .find(|(view, _)| view.doc == *blame_id)
+        else {
+            continue;
+        };
+        if let Some(blame_doc) = editor.documents.get_mut(blame_id) {
+            blame_doc.replace_generated_text(text.clone(), view.0);
+        }
+    }
+}
+
+/// Drops the blame of `doc_id`, which no longer applies once the file was written or `HEAD`
+/// moved, and computes it again if it is shown inline or in a blame view.
+pub fn refresh_blame(editor: &mut Editor, doc_id: DocumentId) {
+    let Some(doc) = editor.document_mut(doc_id) else {
+        return;
+    };
+    if doc.blame().is_none() {
+        return;
+    }
+    doc.set_blame(None);
+    let shown =
+        editor.config().inline_blame || editor.blame_views.values().any(|view| view.doc == doc_id);
+    if !shown {
+        return;
+    }
+    request_blame(editor, doc_id, |editor, result| match result {
+        Ok(doc_id) => update_blame_views(editor, doc_id),
+        Err(err) => log::debug!("failed to blame document: {err:#}"),
+    });
+}
+
+/// Keeps blame views in line with the document they belong to: their text follows edits and
+/// they scroll along unless they are focused.
+pub fn sync_blame_views(editor: &mut Editor) {
+    let edited: Vec<_> = editor
+        .blame_views
+        .values()
+        .filter(|view| {
+            editor
+                .document(view.doc)
+                .is_some_and(|doc| doc.version() != view.version)
+        })
+        .map(|view| view.doc)
+        .collect();
+    for doc_id in edited {
+        update_blame_views(editor, doc_id);
+    }
+
+    let focus = editor.tree.focus;
+    let mut offsets = Vec::new();
+    for (&blame_id, blame_view) in &editor.blame_views {
+        let doc_id = blame_view.doc;
+        let (Some(doc), Some(blame_doc)) = (editor.document(doc_id), editor.document(blame_id))
+        else {
+            continue;
+        };
+        let Some(source_view) = editor.tree.views().find(|(view, _)| view.doc == doc_id) else {
+            continue;
+        };
+        let source = doc.view_offset(source_view.0.id);
+        let line = doc
+            .text()
+            .char_to_line(source.anchor.min(doc.text().len_chars()));
+        for (view, _) in editor.tree.views() {
+            if view.doc != blame_id || view.id == focus {
+                continue;
+            }
+            let mut offset = blame_doc.view_offset(view.id);
+            offset.anchor = blame_doc
+                .text()
+                .line_to_char(line.min(blame_doc.text().len_lines() - 1));
+            offset.vertical_offset = source.vertical_offset;
+            offsets.push((blame_id, view.id, offset));
+        }
+    }
+    for (blame_id, view_id, offset) in offsets {
+        if let Some(doc) = editor.document_mut(blame_id) {
+            doc.set_view_offset(view_id, offset);
+        }
+    }
+}
+
+/// Shows `base` and `doc` side by side in the focused view and a new view to its right, with
+/// their lines aligned and their differences highlighted.
+pub fn open_diff_view(editor: &mut Editor, base: DocumentId, doc: DocumentId) {
+    editor.switch(doc, Action::Replace);
+    let left = view!(editor).id;
+    editor.switch(doc, Action::VerticalSplit);
+    let right = view!(editor).id;
+    editor.focus(left);
+    editor.switch(base, Action::Replace);
+    editor.focus(right);
+
+    editor
+        .diff_views
+        .retain(|view| !view.contains(base) && !view.contains(doc));
+    let diff_view = DiffView::new(&editor.documents[&base], &editor.documents[&doc]);
+    editor.diff_views.push(diff_view);
+    sync_diff_views(editor);
+}
+
+/// Opens a read-only document with the diff base of `doc_id`. The document opened by an earlier
+/// call for `doc_id` is reused.
+pub fn diff_base_document(editor: &mut Editor, doc_id: DocumentId) -> anyhow::Result<DocumentId> {
+    let doc = editor.document(doc_id).context("document was closed")?;
+    let handle = doc
+        .diff_handle()
+        .context("Diff is not available in the current buffer")?;
+    let diff_base = handle.load().diff_base().clone();
+    let language = doc.language_name().map(str::to_owned);
+
+    if let Some(&base_id) = editor.diff_bases.get(&doc_id) {
+        if let Some(base_doc) = editor.documents.get_mut(&base_id) {
+            if base_doc.text() != &diff_base {
+                let view = view_mut!(editor);
+                base_doc.replace_generated_text(diff_base.to_string(), view);
+            }
+            return Ok(base_id);
+        }
+    }
+
+    let mut base_doc = Document::from(
+        diff_base,
+        None,
+        editor.config.clone(),
+        editor.syn_loader.clone(),
+    );
+    base_doc.readonly = true;
+    if let Some(language) = language {
+        let loader = editor.syn_loader.load();
+        if let Err(err) = base_doc.set_language_by_language_id(&language, &loader) {
+            log::debug!("{err}");
+        }
+    }
+    let base_id = editor.new_file_from_document(Action::Load, base_doc);
+    editor.diff_bases.insert(doc_id, base_id);
+    Ok(base_id)
+}
+
+/// Opens a read-only document with `file` as it was in `commit`. `path` is the path of the file
+/// relative to the repository root in that commit, if it differs from the current one.
+pub fn commit_file_document(
+    editor: &mut Editor,
+    file: &Path,
+    commit: &str,
+    path: Option<&str>,
+    action: Action,
+) -> anyhow::Result<DocumentId> {
+    let content = editor.diff_providers.file_at_commit(file, commit, path)?;
+    let mut doc = Document::from(
+        Rope::from(String::from_utf8_lossy(&content).as_ref()),
+        None,
+        editor.config.clone(),
+        editor.syn_loader.clone(),
+    );
+    doc.readonly = true;
+    let loader = editor.syn_loader.load();
+    let language = loader
+        .language_for_filename(path.map_or(file, Path::new))
+        .map(|language| loader.language(language).config().clone());
+    doc.set_language(language, &loader);
+    drop(loader);
+    Ok(editor.new_file_from_document(action, doc))
+}
+
+/// Opens a scratch buffer with a commit message template for the changes staged in the
+/// repository of the working directory, listing them followed by their diff.
+pub fn open_commit_message(editor: &mut Editor) -> anyhow::Result<DocumentId> {
+    let cwd = fugue_stdx::env::current_working_dir();
+    let template = editor.diff_providers.commit_template(&cwd)?;
+    let mut doc = Document::from(
+        Rope::from(template),
+        None,
+        editor.config.clone(),
+        editor.syn_loader.clone(),
+    );
+    let loader = editor.syn_loader.load();
+    if let Err(err) = doc.set_language_by_language_id("git-commit", &loader) {
+        log::debug!("{err}");
+    }
+    drop(loader);
+    Ok(editor.new_file_from_document(Action::Replace, doc))
+}
+
+/// Commits the changes staged in the repository of the working directory with `message`, after
+/// stripping its comments and the diff below the scissors line. Returns the short id of the
+/// commit.
+pub fn commit_staged_changes(editor: &mut Editor, message: &str) -> anyhow::Result<String> {
+    let cwd = fugue_stdx::env::current_working_dir();
+    let message = fugue_vcs::cleanup_message(message);
+    let id = editor.diff_providers.commit(&cwd, &message)?;
+    refresh_documents(editor);
+    Ok(id)
+}
+
+/// Feeds edits of diff views to their differ and keeps both sides of a diff view scrolled to
+/// the same lines. Diff views are closed once either side is no longer visible.
+pub fn sync_diff_views(editor: &mut Editor) {
+    let tree = &editor.tree;
+    let visible = |doc: DocumentId| tree.views().any(|(view, _)| view.doc == doc);
+    let mut closed = Vec::new();
+    editor.diff_views.retain(|view| {
+        let open = visible(view.base) && visible(view.doc);
+        if !open {
+            closed.extend([view.base, view.doc]);
+        }
+        open
+    });
+    for doc_id in closed {
+        if let Some(doc) = editor.documents.get_mut(&doc_id) {
+            doc.set_diff_side(None);
+        }
+    }
+
+    let focus = editor.tree.focus;
+    let focused_doc = editor.tree.get(focus).doc;
+    let mut offsets = Vec::new();
+    for diff_view in &mut editor.diff_views {
+        let base = &editor.documents[&diff_view.base];
+        let doc = &editor.documents[&diff_view.doc];
+        if let Some((base_side, doc_side)) = diff_view.update(base, doc) {
+            if let Some(base) = editor.documents.get_mut(&diff_view.base) {
+                base.set_diff_side(Some(base_side));
+            }
+            if let Some(doc) = editor.documents.get_mut(&diff_view.doc) {
+                doc.set_diff_side(Some(doc_side));
+            }
+        }
+
+        let (source, target, from_base) = if focused_doc == diff_view.base {
+            (diff_view.base, diff_view.doc, true)
+        } else if focused_doc == diff_view.doc {
+            (diff_view.doc, diff_view.base, false)
+        } else {
+            continue;
+        };
+        let source_doc = &editor.documents[&source];
+        let source_offset = source_doc.view_offset(focus);
+        let line = source_doc
+            .text()
+            .char_to_line(source_offset.anchor.min(source_doc.text().len_chars()));
+        let target_line = diff_view.map_line(line, from_base);
+        let target_doc = &editor.documents[&target];
+        for (view, _) in editor.tree.views() {
+            if view.doc != target {
+                continue;
+            }
+            let text = target_doc.text();
+            let mut offset = target_doc.view_offset(view.id);
+            offset.anchor = text.line_to_char(target_line.min(text.len_lines() - 1));
+            offset.vertical_offset = source_offset.vertical_offset;
+            offset.horizontal_offset = source_offset.horizontal_offset;
+            offsets.push((target, view.id, offset));
+        }
+    }
+    for (doc_id, view_id, offset) in offsets {
+        if let Some(doc) = editor.documents.get_mut(&doc_id) {
+            doc.set_view_offset(view_id, offset);
+        }
+    }
+}
+
+/// Renders the changes between two documents as a unified diff in a new scratch buffer.
+pub fn show_unified_diff(
+    editor: &mut Editor,
+    before: &Rope,
+    before_name: &str,
+    after: &Rope,
+    after_name: &str,
+) {
+    let mut diff = format!("--- {before_name}\n+++ {after_name}\n");
+    diff.push_str(&unified_diff(&before.to_string(), &after.to_string()));
+    let mut diff_doc = Document::from(
+        Rope::from(diff),
+        None,
+        editor.config.clone(),
+        editor.syn_loader.clone(),
+    );
+    let loader = editor.syn_loader.load();
+    if let Err(err) = diff_doc.set_language_by_language_id("diff", &loader) {
+        log::debug!("{err}");
+    }
+    drop(loader);
+    editor.new_file_from_document(Action::Replace, diff_doc);
+}
+
+/// Replaces the focused blame view with the changes of the commit on the cursor line.
+pub fn show_blamed_commit(editor: &mut Editor, blame_id: DocumentId) -> anyhow::Result<()> {
+    let doc_id = editor.blame_views[&blame_id].doc;
+    let (view, blame_doc) = current_ref!(editor);
+    let line = blame_doc
+        .selection(view.id)
+        .primary()
+        .cursor_line(blame_doc.text().slice(..));
+
+    let doc = editor
+        .document(doc_id)
+        .ok_or_else(|| anyhow!("the blamed document was closed"))?;
+    let commit = doc
+        .line_blame(line)
+        .ok_or_else(|| anyhow!("line {} is not committed yet", line + 1))?;
+    let cwd = doc
+        .path()
+        .and_then(|path| path.parent())
+        .context("document has no path")?
+        .to_path_buf();
+    let (id, path) = (commit.id.clone(), commit.path.clone());
+    show_commit_diff(editor, &cwd, &id, Some(&path), Action::Replace)
+}
+
+/// Opens a read-only document with the changes of `commit` in the repository containing `cwd`,
+/// only those to `path` if given.
+pub fn show_commit_diff(
+    editor: &mut Editor,
+    cwd: &Path,
+    commit: &str,
+    path: Option<&str>,
+    action: Action,
+) -> anyhow::Result<()> {
+    let diff = editor.diff_providers.commit_diff(cwd, commit, path)?;
+    let mut diff_doc = Document::from(
+        Rope::from(diff),
+        None,
+        editor.config.clone(),
+        editor.syn_loader.clone(),
+    );
+    diff_doc.readonly = true;
+    let loader = editor.syn_loader.load();
+    if let Err(err) = diff_doc.set_language_by_language_id("diff", &loader) {
+        log::debug!("{err}");
+    }
+    drop(loader);
+    editor.new_file_from_document(action, diff_doc);
+    Ok(())
+}
+
+/// The path of the focused document if its changes can be written to the index.
+fn stageable_path(doc: &Document) -> anyhow::Result<PathBuf> {
+    let path = doc.path().context("buffer has no path")?.clone();
+    ensure!(
+        *doc.active_diff_base() == DiffBase::Index,
+        "staging requires the index as diff base, not {}",
+        doc.active_diff_base()
+    );
+    ensure!(
+        doc.encoding() == encoding::UTF_8,
+        "staging is only supported for UTF-8 buffers"
+    );
+    Ok(path)
+}
+
+/// Reloads the diff base of every document, e.g. after the editor wide diff base changed.
+pub fn refresh_documents(editor: &mut Editor) {
+    let registry = editor.diff_providers.clone();
+    let diff_base = editor.diff_base.clone();
+    for doc in editor.documents_mut() {
+        doc.refresh_version_control(&registry, &diff_base);
+    }
+    editor.file_statuses.invalidate(registry);
+    let docs: Vec<_> = editor.documents().map(|doc| doc.id()).collect();
+    for doc_id in docs {
+        refresh_blame(editor, doc_id);
+    }
+}
+
+/// Recomputes the VCS status of the files of the repository containing `cwd` in the background.
+pub fn refresh_file_statuses(editor: &Editor, cwd: PathBuf) {
+    editor
+        .file_statuses
+        .refresh(editor.diff_providers.clone(), cwd);
+}
+
+/// Reloads the diff base of the focused document after the index changed.
+fn refresh_focused_document(editor: &mut Editor) {
+    let registry = editor.diff_providers.clone();
+    let diff_base = editor.diff_base.clone();
+    let doc = doc_mut!(editor);
+    doc.refresh_version_control(&registry, &diff_base);
+    editor.file_statuses.invalidate(registry);
+}
+
+/// Writes the changes of the focused document that intersect with its selections to the index.
+/// Returns the number of staged hunks.
+pub fn stage_selected_hunks(editor: &mut Editor) -> anyhow::Result<usize> {
+    let (view, doc) = current_ref!(editor);
+    let path = stageable_path(doc)?;
+    let handle = doc
+        .diff_handle()
+        .context("Diff is not available in the current buffer")?;
+
+    let diff = handle.load();
+    let doc_text = doc.text().slice(..);
+    let diff_base = diff.diff_base();
+    let mut changes = 0;
+
+    let transaction = Transaction::change(
+        diff_base,
+        diff.hunks_intersecting_line_ranges(doc.selection(view.id).line_ranges(doc_text))
+            .map(|hunk| {
+                changes += 1;
+                let start = doc_text.line_to_char(hunk.after.start as usize);
+                let end = doc_text.line_to_char(hunk.after.end as usize);
+                let text: Tendril = doc_text.slice(start..end).chunks().collect();
+                (
+                    diff_base.line_to_char(hunk.before.start as usize),
+                    diff_base.line_to_char(hunk.before.end as usize),
+                    (!text.is_empty()).then_some(text),
+                )
+            }),
+    );
+    if changes == 0 {
+        bail!("There are no unstaged changes under any selection");
+    }
+    let mut staged = diff_base.clone();
+    transaction.apply(&mut staged);
+    drop(diff);
+
+    editor
+        .diff_providers
+        .stage(&path, staged.to_string().as_bytes())?;
+    refresh_focused_document(editor);
+    Ok(changes)
+}
+
+/// Resets the staged changes of the focused document that intersect with its selections to
+/// `HEAD`. Returns the number of unstaged hunks.
+pub fn unstage_selected_hunks(editor: &mut Editor) -> anyhow::Result<usize> {
+    let (view, doc) = current_ref!(editor);
+    let path = stageable_path(doc)?;
+    let handle = doc
+        .diff_handle()
+        .context("Diff is not available in the current buffer")?;
+
+    let diff = handle.load();
+    let doc_text = doc.text().slice(..);
+    let index_text = diff.diff_base();
+    let line_ranges: Vec<_> = doc
+        .selection(view.id)
+        .line_ranges(doc_text)
+        .map(|(start, end)| {
+            (
+                index_line(&diff, start as u32, false),
+                index_line(&diff, end as u32, true),
+            )
+        })
+        .collect();
+    let hunks: Vec<&Hunk> = doc
+        .staged_hunks()
+        .iter()
+        .filter(|hunk| {
+            line_ranges
+                .iter()
+                .any(|&(start, end)| hunk.after.end >= start && hunk.after.start <= end)
+        })
+        .collect();
+    if hunks.is_empty() {
+        bail!("There are no staged changes under any selection");
+    }
+
+    let head = match editor.diff_providers.get_head_base(&path) {
+        Some(head) => Rope::from(String::from_utf8(head).context("file at HEAD is not UTF-8")?),
+        // the file is newly added so the only staged change is the whole file
+        None => {
+            drop(diff);
+            editor.diff_providers.unstage(&path)?;
+            refresh_focused_document(editor);
+            return Ok(1);
+        }
+    };
+    let transaction = Transaction::change(
+        index_text,
+        hunks.iter().map(|hunk| {
+            let start = head.line_to_char(hunk.before.start as usize);
+            let end = head.line_to_char(hunk.before.end as usize);
+            let text: Tendril = head.slice(start..end).chunks().collect();
+            (
+                index_text.line_to_char(hunk.after.start as usize),
+                index_text.line_to_char(hunk.after.end as usize),
+                (!text.is_empty()).then_some(text),
+            )
+        }),
+    );
+    let changes = hunks.len();
+    let mut staged = index_text.clone();
+    transaction.apply(&mut staged);
+    drop(diff);
+
+    editor
+        .diff_providers
+        .stage(&path, staged.to_string().as_bytes())?;
+    refresh_focused_document(editor);
+    Ok(changes)
+}
+
+/// Maps a line of the document to the corresponding line of the index. Lines changed in the
+/// document map to the start of the change or, if `end` is set, to its last line.
+fn index_line(diff: &Diff, line: u32, end: bool) -> u32 {
+    let mut offset: i64 = 0;
+    for i in 0..diff.len() {
+        let hunk = diff.nth_hunk(i);
+        if hunk.after.start > line {
+            break;
+        }
+        if hunk.after.contains(&line) {
+            return if end {
+                hunk.before.end.saturating_sub(1).max(hunk.before.start)
+            } else {
+                hunk.before.start
+            };
+        }
+        offset = hunk.before.end as i64 - hunk.after.end as i64;
+    }
+    (line as i64 + offset) as u32
+}
+
+/// Writes the whole content of the focused document to the index.
+pub fn stage_file(editor: &mut Editor) -> anyhow::Result<()> {
+    let doc = doc!(editor);
+    let path = stageable_path(doc)?;
+    let content = doc.text().to_string();
+    editor.diff_providers.stage(&path, content.as_bytes())?;
+    refresh_focused_document(editor);
+    Ok(())
+}
+
+/// Resets the staged content of the focused document to `HEAD`.
+pub fn unstage_file(editor: &mut Editor) -> anyhow::Result<()> {
+    let path = stageable_path(doc!(editor))?;
+    editor.diff_providers.unstage(&path)?;
+    refresh_focused_document(editor);
+    Ok(())
+}
+
+/// Replaces the merge conflicts under the selections of the focused document with the sides
+/// chosen by `resolution`. Returns the number of resolved conflicts.
+pub fn resolve_conflicts(editor: &mut Editor, resolution: Resolution) -> anyhow::Result<usize> {
+    let (view, doc) = current!(editor);
+    let text = doc.text().slice(..);
+    let mut conflicts: Vec<&Conflict> = Vec::new();
+    for (start, end) in doc.selection(view.id).line_ranges(text) {
+        conflicts.extend(
+            doc.conflicts()
+                .iter()
+                .filter(|conflict| conflict.start <= end && start <= conflict.end),
+        );
+    }
+    conflicts.dedup_by_key(|conflict| conflict.start);
+    if conflicts.is_empty() {
+        bail!("There are no conflicts under any selection");
+    }
+
+    let mut changes = Vec::with_capacity(conflicts.len());
+    for conflict in &conflicts {
+        let mut resolved = Tendril::new();
+        for lines in conflict.resolve(resolution)? {
+            let slice = text.slice(text.line_to_char(lines.start)..text.line_to_char(lines.end));
+            resolved.extend(slice.chunks());
+        }
+        let lines = conflict.lines();
+        changes.push((
+            text.line_to_char(lines.start),
+            text.line_to_char(lines.end),
+            (!resolved.is_empty()).then_some(resolved),
+        ));
+    }
+    let resolved = conflicts.len();
+    let transaction = Transaction::change(doc.text(), changes.into_iter());
+    doc.apply(&transaction, view.id);
+    Ok(resolved)
+}
@@ -18,8 +18,10 @@
 use self::document_colors::DocumentColorsHandler;
 
 mod auto_save;
+mod blame;
 pub mod completion;
 pub mod diagnostics;
+mod diff_view;
 mod document_colors;
 mod prompt;
 mod signature_help;
@@ -50,6 +52,8 @@ pub fn setup(config: Arc<ArcSwap<Config>>) -> Handlers {
     completion::register_hooks(&handlers);
     signature_help::register_hooks(&handlers);
     auto_save::register_hooks(&handlers);
+    blame::register_hooks();
+    diff_view::register_hooks();
     diagnostics::register_hooks(&handlers);
     snippet::register_hooks(&handlers);
     document_colors::register_hooks(&handlers);
@@ -0,0 +1,60 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use fugue_event::register_hook;
+use fugue_view::events::{ConfigDidChange, DocumentDidClose, DocumentDidOpen};
+use fugue_view::{DocumentId, Editor};
+
+use crate::commands::{request_blame, sync_blame_views};
+use crate::events::{PostCommand, PostInsertChar};
+
+fn request_inline_blame(editor: &mut Editor, doc_id: DocumentId) {
+    request_blame(editor, doc_id, |_editor, result| {
+        if let Err(err) = result {
+            log::debug!("failed to blame document: {err:#}");
+        }
+    });
+}
+
+pub(super) fn register_hooks() {
+    register_hook!(move |event: &mut DocumentDidOpen<'_>| {
+        if event.editor.config().inline_blame {
+            request_inline_blame(event.editor, event.doc);
+        }
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut ConfigDidChange<'_>| {
+        if event.new.inline_blame && !event.old.inline_blame {
+            let docs: Vec<_> = event
+                .editor
+                .documents()
+                .filter(|doc| doc.path().is_some() && doc.blame().is_none())
+                .map(|doc| doc.id())
+                .collect();
+            for doc_id in docs {
+                request_inline_blame(event.editor, doc_id);
+            }
+        }
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut PostCommand<'_, '_>| {
+        sync_blame_views(event.cx.editor);
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut PostInsertChar<'_, '_>| {
+        sync_blame_views(event.cx.editor);
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut DocumentDidClose<'_>| {
+        let closed = event.doc.id();
+        event
+            .editor
+            .blame_views
+            .retain(|&blame_id, view| blame_id != closed && view.doc != closed);
+        Ok(())
+    });
+}
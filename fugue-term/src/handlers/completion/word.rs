@@ -1,8 +1,9 @@
 // Copyright (c) 2020 Blaž Hrastnik
 // Licensed under MPL-2.0
 
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, collections::HashSet, sync::Arc};
 
+use fugue_core::syntax::config::LanguageServerFeature;
 use fugue_core::{
     self as core, chars::char_is_word, completion::CompletionProvider, movement, Transaction,
 };
@@ -37,6 +38,12 @@ pub(super) fn completion(
     let (view, doc) = current_ref!(editor);
     let rope = doc.text().clone();
     let word_index = editor.handlers.word_index().clone();
+    // words from the rest of the workspace stand in for a language server
+    let workspace_index = editor.workspace_index.clone().filter(|_| {
+        doc.language_servers_with_feature(LanguageServerFeature::Completion)
+            .next()
+            .is_none()
+    });
     let text = doc.text().slice(..);
     let selection = doc.selection(view.id).clone();
     let pos = selection.primary().cursor(text);
@@ -75,8 +82,17 @@ pub(super) fn completion(
     let future = move || {
         let text = rope.slice(..);
         let typed_word: Cow<_> = text.slice(typed_word_range).into();
-        let items = word_index
-            .matches(&typed_word)
+        let mut words = word_index.matches(&typed_word);
+        if let Some(workspace_index) = workspace_index {
+            let known: HashSet<_> = words.iter().cloned().collect();
+            words.extend(
+                workspace_index
+                    .word_matches(&typed_word)
+                    .into_iter()
+                    .filter(|word| !known.contains(word)),
+            );
+        }
+        let items = words
             .into_iter()
             .filter(|word| word.as_str() != typed_word.as_ref())
             .map(|word| {
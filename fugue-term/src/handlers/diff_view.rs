@@ -0,0 +1,29 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use fugue_event::register_hook;
+use fugue_view::events::DocumentDidClose;
+
+use crate::commands::sync_diff_views;
+use crate::events::{PostCommand, PostInsertChar};
+
+pub(super) fn register_hooks() {
+    register_hook!(move |event: &mut PostCommand<'_, '_>| {
+        sync_diff_views(event.cx.editor);
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut PostInsertChar<'_, '_>| {
+        sync_diff_views(event.cx.editor);
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut DocumentDidClose<'_>| {
+        let closed = event.doc.id();
+        event
+            .editor
+            .diff_bases
+            .retain(|&doc_id, &mut base_id| doc_id != closed && base_id != closed);
+        Ok(())
+    });
+}
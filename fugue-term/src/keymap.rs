@@ -580,6 +580,29 @@ fn reverse_map() {
         )
     }
 
+    #[test]
+    fn operator_keymap() {
+        let keys = r#"
+"~" = "operator :reflow 80"
+        "#;
+        let keymap: KeyTrie = toml::from_str(keys).unwrap();
+        let Some(KeyTrie::MappableCommand(MappableCommand::Operator { name, command })) =
+            keymap.search(&[key!('~')])
+        else {
+            panic!("expected an operator");
+        };
+        assert_eq!(name, "operator :reflow 80");
+        assert_eq!(
+            **command,
+            MappableCommand::Typable {
+                name: "reflow".to_string(),
+                args: "80".to_string(),
+                doc: "".to_string(),
+            }
+        );
+        assert!("operator @miw".parse::<MappableCommand>().is_err());
+    }
+
     #[test]
     fn escaped_keymap() {
         use crate::commands::MappableCommand;
@@ -28,6 +28,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
 
         "home" => goto_line_start,
         "end" => goto_line_end,
+        "^" => goto_first_nonwhitespace,
 
         "w" => move_next_word_start,
         "b" => move_prev_word_start,
@@ -47,6 +48,13 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "h" => goto_line_start,
             "l" => goto_line_end,
             "s" => goto_first_nonwhitespace,
+            "~" => switch_case_operator,
+            "u" => lowercase_operator,
+            "U" => uppercase_operator,
+            "C" => comment_operator,
+            ">" => indent_operator,
+            "<" => unindent_operator,
+            "=" => format_operator,
             "d" => goto_definition,
             "D" => goto_declaration,
             "y" => goto_type_definition,
@@ -73,41 +81,9 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "o" => open_below,
         "O" => open_above,
 
-        "d" => { "Delete"
-            "d" => delete_current_line,
-            "i" => delete_textobject_inner,
-            "a" => delete_textobject_around,
-            "w" => delete_next_word_start,
-            "b" => delete_prev_word_start,
-            "e" => delete_next_word_end,
-            "W" => delete_next_long_word_start,
-            "B" => delete_prev_long_word_start,
-            "E" => delete_next_long_word_end,
-            "H" => delete_to_line_start,
-            "L" => delete_to_line_end,
-            "^" => delete_to_first_nonwhitespace,
-            "G" => delete_to_line,
-            "K" => delete_to_file_start,
-            "J" => delete_to_last_line,
-        },
+        "d" => delete_operator,
         "A-d" => delete_selection_noyank,
-        "c" => { "Change"
-            "c" => change_current_line,
-            "i" => change_textobject_inner,
-            "a" => change_textobject_around,
-            "w" => change_next_word_start,
-            "b" => change_prev_word_start,
-            "e" => change_next_word_end,
-            "W" => change_next_long_word_start,
-            "B" => change_prev_long_word_start,
-            "E" => change_next_long_word_end,
-            "H" => change_to_line_start,
-            "L" => change_to_line_end,
-            "^" => change_to_first_nonwhitespace,
-            "G" => change_to_line,
-            "K" => change_to_file_start,
-            "J" => change_to_last_line,
-        },
+        "c" => change_operator,
         "A-c" => change_selection_noyank,
 
         "C" => copy_selection_on_next_line,
@@ -137,6 +113,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "m" => { "Match"
             "m" => match_brackets,
             "s" => surround_add,
+            "S" => surround_operator,
             "r" => surround_replace,
             "d" => surround_delete,
             "a" => select_textobject_around,
@@ -147,11 +124,13 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "D" => goto_first_diag,
             "g" => goto_prev_change,
             "G" => goto_first_change,
+            "m" => goto_prev_conflict,
             "f" => goto_prev_function,
             "t" => goto_prev_class,
             "a" => goto_prev_parameter,
             "c" => goto_prev_comment,
             "e" => goto_prev_entry,
+            "q" => goto_prev_quickfix,
             "T" => goto_prev_test,
             "p" => goto_prev_paragraph,
             "x" => goto_prev_xml_element,
@@ -162,11 +141,13 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "D" => goto_last_diag,
             "g" => goto_next_change,
             "G" => goto_last_change,
+            "m" => goto_next_conflict,
             "f" => goto_next_function,
             "t" => goto_next_class,
             "a" => goto_next_parameter,
             "c" => goto_next_comment,
             "e" => goto_next_entry,
+            "q" => goto_next_quickfix,
             "T" => goto_next_test,
             "p" => goto_next_paragraph,
             "x" => goto_next_xml_element,
@@ -185,23 +166,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "A-u" => earlier,
         "A-U" => later,
 
-        "y" => { "Yank"
-            "y" => yank_current_line,
-            "i" => yank_textobject_inner,
-            "a" => yank_textobject_around,
-            "w" => yank_next_word_start,
-            "b" => yank_prev_word_start,
-            "e" => yank_next_word_end,
-            "W" => yank_next_long_word_start,
-            "B" => yank_prev_long_word_start,
-            "E" => yank_next_long_word_end,
-            "H" => yank_to_line_start,
-            "L" => yank_to_line_end,
-            "^" => yank_to_first_nonwhitespace,
-            "G" => yank_to_line,
-            "K" => yank_to_file_start,
-            "J" => yank_to_last_line,
-        },
+        "y" => yank_operator,
         // yank_all
         "p" => paste_after,
         // paste_all
@@ -274,18 +239,36 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "C-o" => jump_backward,
         "C-s" => save_selection,
 
+        "A-1" => goto_pinned_file_1,
+        "A-2" => goto_pinned_file_2,
+        "A-3" => goto_pinned_file_3,
+        "A-4" => goto_pinned_file_4,
+
         "space" => { "Space"
             "f" => file_picker,
             "F" => file_picker_in_current_directory,
             "e" => file_explorer,
             "E" => file_explorer_in_current_buffer_directory,
+            "t" => file_tree,
+            "T" => file_tree_reveal,
             "b" => buffer_picker,
+            "o" => recent_files_picker,
+            "m" => pinned_files_picker,
+            "M" => pin_buffer,
             "j" => jumplist_picker,
             "s" => lsp_or_syntax_symbol_picker,
             "S" => lsp_or_syntax_workspace_symbol_picker,
             "d" => diagnostics_picker,
             "D" => workspace_diagnostics_picker,
             "g" => changed_file_picker,
+            "l" => commit_picker,
+            "L" => file_history_picker,
+            "x" => { "Merge conflict"
+                "o" => resolve_conflict_ours,
+                "t" => resolve_conflict_theirs,
+                "b" => resolve_conflict_base,
+                "a" => resolve_conflict_both,
+            },
             "a" => code_action,
             "'" => last_picker,
             "G" => { "Debug (experimental)" sticky=true
@@ -298,10 +281,13 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
                 "o" => dap_step_out,
                 "n" => dap_next,
                 "v" => dap_variables,
+                "f" => dap_stack_trace,
+                "d" => dap_disassemble,
                 "t" => dap_terminate,
                 "C-c" => dap_edit_condition,
                 "C-l" => dap_edit_log,
                 "s" => { "Switch"
+                    "s" => dap_switch_session,
                     "t" => dap_switch_thread,
                     "f" => dap_switch_stack_frame,
                     // sl, sb
@@ -337,6 +323,8 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "P" => paste_clipboard_before,
             "R" => replace_selections_with_clipboard,
             "/" => global_search,
+            "i" => line_picker,
+            "I" => buffer_line_picker,
             "k" => hover,
             "r" => rename_symbol,
             "h" => select_references_to_symbol_under_cursor,
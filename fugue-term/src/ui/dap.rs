@@ -0,0 +1,4 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+pub mod stack_trace;
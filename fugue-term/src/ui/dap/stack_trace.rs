@@ -0,0 +1,242 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use fugue_dap::{Client, ThreadId};
+use fugue_lsp::block_on;
+use fugue_view::graphics::{Modifier, Rect};
+use fugue_view::handlers::dap::{refresh_inline_values, select_thread_id};
+use fugue_view::input::{MouseButton, MouseEvent, MouseEventKind};
+use fugue_view::Editor;
+use tui::buffer::Buffer as Surface;
+
+use crate::commands::dap::jump_to_frame;
+use crate::compositor::{Component, Compositor, Context, Event, EventResult};
+use crate::ui::EditorView;
+use crate::{ctrl, key, shift};
+
+pub const ID: &str = "dap-stack-trace";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Thread(ThreadId),
+    /// A thread and the index of the frame in its stack trace.
+    Frame(ThreadId, usize),
+}
+
+/// Side panel listing the threads of the active debugger along with the frames of the stopped
+/// ones. The content is read from the debugger on every render so it follows stops and resumes.
+#[derive(Debug, Default)]
+pub struct StackTrace {
+    /// Index of the selected row.
+    cursor: usize,
+    /// Index of the first visible row.
+    scroll: usize,
+    /// The frame that was active the last time the rows were built, used to move the cursor to
+    /// the active frame when it changes.
+    active: Option<(ThreadId, usize)>,
+    /// Screen row of the first entry, recorded on render to map mouse clicks to rows.
+    row_offset: Option<u16>,
+}
+
+impl StackTrace {
+    fn rows(debugger: &Client) -> Vec<Row> {
+        let mut threads: Vec<_> = debugger.threads.iter().map(|thread| thread.id).collect();
+        // debuggers only report threads on stops, so some may only be known by their frames
+        for thread in debugger.stack_frames.keys() {
+            if !threads.contains(thread) {
+                threads.push(*thread);
+            }
+        }
+
+        let mut rows = Vec::new();
+        for thread in threads {
+            rows.push(Row::Thread(thread));
+            let frames = debugger.stack_frames.get(&thread).map_or(0, Vec::len);
+            rows.extend((0..frames).map(|frame| Row::Frame(thread, frame)));
+        }
+        rows
+    }
+
+    fn active_frame(debugger: &Client) -> Option<(ThreadId, usize)> {
+        Some((debugger.thread_id?, debugger.active_frame?))
+    }
+
+    fn move_cursor(&mut self, editor: &Editor, delta: isize) {
+        let Some(debugger) = editor.debug_adapters.get_active_client() else {
+            return;
+        };
+        let len = Self::rows(debugger).len();
+        self.cursor = self
+            .cursor
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+    }
+
+    /// Makes the selected thread or frame the current one.
+    fn select(&mut self, cx: &mut Context) {
+        let editor = &mut *cx.editor;
+        let Some(debugger) = editor.debug_adapters.get_active_client_mut() else {
+            return;
+        };
+        let Some(&row) = Self::rows(debugger).get(self.cursor) else {
+            return;
+        };
+
+        match row {
+            Row::Thread(thread) => {
+                block_on(select_thread_id(editor, thread, true));
+            }
+            Row::Frame(thread, frame) => {
+                debugger.thread_id = Some(thread);
+                debugger.active_frame = Some(frame);
+                let frame = debugger.stack_frames[&thread][frame].clone();
+                jump_to_frame(editor, cx.jobs, frame);
+            }
+        }
+        block_on(refresh_inline_values(editor));
+    }
+}
+
+impl Component for StackTrace {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        match event {
+            Event::Key(key) => match *key {
+                key!('j') | key!(Down) | ctrl!('n') => self.move_cursor(cx.editor, 1),
+                key!('k') | key!(Up) | ctrl!('p') => self.move_cursor(cx.editor, -1),
+                key!('g') | key!(Home) => self.cursor = 0,
+                shift!('G') | key!('G') | key!(End) => self.move_cursor(cx.editor, isize::MAX),
+                key!(Enter) => self.select(cx),
+                key!('q') => {
+                    let close: crate::compositor::Callback =
+                        Box::new(|compositor: &mut Compositor, _| {
+                            if let Some(editor_view) = compositor.find::<EditorView>() {
+                                editor_view.dock.close(ID);
+                            }
+                        });
+                    return EventResult::Consumed(Some(close));
+                }
+                _ => return EventResult::Ignored(None),
+            },
+            Event::Mouse(MouseEvent { kind, row, .. }) => match kind {
+                MouseEventKind::ScrollDown => self.move_cursor(cx.editor, 1),
+                MouseEventKind::ScrollUp => self.move_cursor(cx.editor, -1),
+                MouseEventKind::Down(MouseButton::Left) => {
+                    let Some(offset) = self.row_offset.and_then(|top| row.checked_sub(top)) else {
+                        return EventResult::Consumed(None);
+                    };
+                    self.cursor = self.scroll + offset as usize;
+                    self.move_cursor(cx.editor, 0);
+                    self.select(cx);
+                }
+                _ => return EventResult::Ignored(None),
+            },
+            _ => return EventResult::Ignored(None),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        let text_style = theme.get("ui.text");
+        let title_style = theme.get("ui.text.focus").add_modifier(Modifier::BOLD);
+        let location_style = theme.get("ui.linenr");
+        let selected_style = theme.get("ui.selection");
+        let width = area.width as usize;
+
+        surface.set_stringn(area.x, area.y, "Call Stack", width, title_style);
+        let area = area.clip_top(1);
+        self.row_offset = Some(area.y);
+
+        let Some(debugger) = cx.editor.debug_adapters.get_active_client() else {
+            surface.set_stringn(area.x, area.y, "No active debugger", width, text_style);
+            return;
+        };
+
+        let rows = Self::rows(debugger);
+        let active = Self::active_frame(debugger);
+        if active != self.active {
+            self.active = active;
+            if let Some(idx) = rows
+                .iter()
+                .position(|row| active.is_some_and(|(t, f)| *row == Row::Frame(t, f)))
+            {
+                self.cursor = idx;
+            }
+        }
+        self.cursor = self.cursor.min(rows.len().saturating_sub(1));
+
+        let height = area.height as usize;
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if height > 0 && self.cursor >= self.scroll + height {
+            self.scroll = self.cursor + 1 - height;
+        }
+
+        for (i, row) in rows.iter().enumerate().skip(self.scroll).take(height) {
+            let y = area.y + (i - self.scroll) as u16;
+            let (label, location) = match *row {
+                Row::Thread(thread) => {
+                    let name = debugger
+                        .threads
+                        .iter()
+                        .find(|t| t.id == thread)
+                        .map_or_else(|| format!("Thread {thread}"), |t| t.name.clone());
+                    let state = debugger
+                        .thread_states
+                        .get(&thread)
+                        .map_or("unknown", String::as_str);
+                    let marker = if debugger.thread_id == Some(thread) {
+                        "▾"
+                    } else {
+                        "▸"
+                    };
+                    (format!("{marker} {name} ({state})"), None)
+                }
+                Row::Frame(thread, idx) => {
+                    let frame = &debugger.stack_frames[&thread][idx];
+                    let marker = if active == Some((thread, idx)) {
+                        "▶"
+                    } else {
+                        " "
+                    };
+                    let source = frame.source.as_ref().and_then(|source| {
+                        source
+                            .path
+                            .as_ref()
+                            .and_then(|path| path.file_name())
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .or_else(|| source.name.clone())
+                    });
+                    let location = match source {
+                        Some(source) => format!(" {source}:{}", frame.line),
+                        None => " <unknown source>".to_owned(),
+                    };
+                    (format!("  {marker} {}", frame.name), Some(location))
+                }
+            };
+
+            let style = if i == self.cursor {
+                text_style.patch(selected_style)
+            } else {
+                text_style
+            };
+            if i == self.cursor {
+                surface.set_style(Rect::new(area.x, y, area.width, 1), selected_style);
+            }
+            let (x, _) = surface.set_stringn(area.x, y, &label, width, style);
+            if let Some(location) = location {
+                let remaining = width.saturating_sub((x - area.x) as usize);
+                let style = if i == self.cursor {
+                    location_style.patch(selected_style)
+                } else {
+                    location_style
+                };
+                surface.set_stringn(x, y, location, remaining, style);
+            }
+        }
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(ID)
+    }
+}
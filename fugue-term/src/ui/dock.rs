@@ -0,0 +1,267 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use fugue_core::Position;
+use fugue_view::graphics::{CursorKind, Rect};
+use fugue_view::input::{MouseEvent, MouseEventKind};
+use fugue_view::keyboard::KeyCode;
+use fugue_view::Editor;
+use tui::buffer::Buffer as Surface;
+use tui::widgets::{Block, Borders, Widget};
+
+use crate::compositor::{Component, Context, Event, EventResult};
+
+/// The edge of the editor area a panel is docked to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DockSide {
+    Left,
+    Right,
+    Bottom,
+}
+
+struct Panel {
+    component: Box<dyn Component>,
+    side: DockSide,
+    /// Width of side panels or height of bottom panels, including the border.
+    size: u16,
+    /// Area the panel was last rendered to, used to route mouse events.
+    area: Rect,
+}
+
+/// Persistent components docked around the split tree.
+///
+/// Unlike compositor layers, docked panels take space away from the views instead of being
+/// drawn over them, and stay open until they are closed explicitly. A focused panel receives
+/// key events first; keys it ignores fall through to the editor.
+#[derive(Default)]
+pub struct Dock {
+    panels: Vec<Panel>,
+    /// Index of the panel receiving key events.
+    focused: Option<usize>,
+}
+
+impl Dock {
+    /// Docks `component` to `side` and focuses it. A panel with the same id is replaced.
+    pub fn open<T: Component>(&mut self, side: DockSide, size: u16, component: T) {
+        let panel = Panel {
+            component: Box::new(component),
+            side,
+            size,
+            area: Rect::default(),
+        };
+        let idx = match self.position(panel.component.id()) {
+            Some(idx) => {
+                self.panels[idx] = panel;
+                idx
+            }
+            None => {
+                self.panels.push(panel);
+                self.panels.len() - 1
+            }
+        };
+        self.focused = Some(idx);
+    }
+
+    /// Removes the panel with the given id. Returns whether a panel was removed.
+    pub fn close(&mut self, id: &'static str) -> bool {
+        let Some(idx) = self.position(Some(id)) else {
+            return false;
+        };
+        self.panels.remove(idx);
+        self.focused = match self.focused {
+            Some(focused) if focused == idx => None,
+            Some(focused) if focused > idx => Some(focused - 1),
+            focused => focused,
+        };
+        true
+    }
+
+    /// Closes the panel with the id of `component` if it has focus, focuses it if it is open and
+    /// docks `component` otherwise.
+    pub fn toggle<T: Component>(&mut self, side: DockSide, size: u16, component: T) {
+        let id = component.id();
+        match self.position(id) {
+            Some(idx) if self.focused == Some(idx) => {
+                self.close(id.unwrap());
+            }
+            Some(idx) => self.focused = Some(idx),
+            None => self.open(side, size, component),
+        }
+    }
+
+    /// Returns whether the panel with the given id has key focus.
+    pub fn has_focus(&self, id: &'static str) -> bool {
+        self.focused.is_some() && self.focused == self.position(Some(id))
+    }
+
+    pub fn contains(&self, id: &'static str) -> bool {
+        self.position(Some(id)).is_some()
+    }
+
+    /// Gives key focus to the panel with the given id. Returns whether the panel is open.
+    pub fn focus(&mut self, id: &'static str) -> bool {
+        let idx = self.position(Some(id));
+        if idx.is_some() {
+            self.focused = idx;
+        }
+        idx.is_some()
+    }
+
+    /// Returns key focus to the editor.
+    pub fn unfocus(&mut self) {
+        self.focused = None;
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused.is_some()
+    }
+
+    pub fn find_id<T: 'static>(&mut self, id: &'static str) -> Option<&mut T> {
+        let idx = self.position(Some(id))?;
+        self.panels[idx].component.as_any_mut().downcast_mut()
+    }
+
+    fn position(&self, id: Option<&'static str>) -> Option<usize> {
+        id?;
+        self.panels
+            .iter()
+            .position(|panel| panel.component.id() == id)
+    }
+
+    /// Assigns an area to every panel and returns what is left for the split tree.
+    pub fn layout(&mut self, mut area: Rect) -> Rect {
+        for panel in &mut self.panels {
+            // always leave some room for the views
+            panel.area = match panel.side {
+                DockSide::Left => {
+                    let width = panel.size.min(area.width.saturating_sub(10));
+                    let panel_area = area.with_width(width);
+                    area = area.clip_left(width);
+                    panel_area
+                }
+                DockSide::Right => {
+                    let width = panel.size.min(area.width.saturating_sub(10));
+                    let panel_area = area.clip_left(area.width - width);
+                    area = area.clip_right(width);
+                    panel_area
+                }
+                DockSide::Bottom => {
+                    let height = panel.size.min(area.height.saturating_sub(3));
+                    let panel_area = area.clip_top(area.height - height);
+                    area = area.clip_bottom(height);
+                    panel_area
+                }
+            };
+        }
+        area
+    }
+
+    pub fn render(&mut self, surface: &mut Surface, cx: &mut Context) {
+        let border_style = cx.editor.theme.get("ui.window");
+        for panel in &mut self.panels {
+            if panel.area.width == 0 || panel.area.height == 0 {
+                continue;
+            }
+            let borders = match panel.side {
+                DockSide::Left => Borders::RIGHT,
+                DockSide::Right => Borders::LEFT,
+                DockSide::Bottom => Borders::TOP,
+            };
+            let block = Block::new().borders(borders).border_style(border_style);
+            let inner = block.inner(panel.area);
+            block.render(panel.area, surface);
+            panel.component.render(inner, surface, cx);
+        }
+    }
+
+    /// Routes key events to the focused panel and mouse events to the panel under the cursor.
+    /// Returns `None` if the event should be handled by the editor instead.
+    pub fn handle_event(&mut self, event: &Event, cx: &mut Context) -> Option<EventResult> {
+        match event {
+            Event::Key(key) => {
+                let idx = self.focused?;
+                match self.panels[idx].component.handle_event(event, cx) {
+                    EventResult::Consumed(callback) => Some(EventResult::Consumed(callback)),
+                    EventResult::Ignored(_) if key.code == KeyCode::Esc => {
+                        self.focused = None;
+                        Some(EventResult::Consumed(None))
+                    }
+                    EventResult::Ignored(_) => None,
+                }
+            }
+            Event::Mouse(MouseEvent {
+                kind, column, row, ..
+            }) => {
+                let idx = self.panels.iter().position(|panel| {
+                    (panel.area.left()..panel.area.right()).contains(column)
+                        && (panel.area.top()..panel.area.bottom()).contains(row)
+                });
+                if matches!(kind, MouseEventKind::Down(_)) {
+                    self.focused = idx;
+                }
+                Some(self.panels[idx?].component.handle_event(event, cx))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn cursor(&self, editor: &Editor) -> Option<(Option<Position>, CursorKind)> {
+        let panel = &self.panels[self.focused?];
+        Some(panel.component.cursor(panel.area, editor))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Dummy(&'static str);
+
+    impl Component for Dummy {
+        fn render(&mut self, _area: Rect, _frame: &mut Surface, _ctx: &mut Context) {}
+
+        fn id(&self) -> Option<&'static str> {
+            Some(self.0)
+        }
+    }
+
+    #[test]
+    fn layout_shrinks_editor_area() {
+        let mut dock = Dock::default();
+        let area = Rect::new(0, 0, 100, 40);
+        assert_eq!(dock.layout(area), area);
+
+        dock.open(DockSide::Right, 30, Dummy("right"));
+        dock.open(DockSide::Bottom, 10, Dummy("bottom"));
+        assert_eq!(dock.layout(area), Rect::new(0, 0, 70, 30));
+        assert_eq!(dock.panels[0].area, Rect::new(70, 0, 30, 40));
+        assert_eq!(dock.panels[1].area, Rect::new(0, 30, 70, 10));
+
+        // the views keep some room on small screens
+        assert_eq!(dock.layout(Rect::new(0, 0, 20, 5)), Rect::new(0, 0, 10, 3));
+    }
+
+    #[test]
+    fn toggle_and_focus() {
+        let mut dock = Dock::default();
+        dock.toggle(DockSide::Left, 20, Dummy("a"));
+        dock.toggle(DockSide::Left, 20, Dummy("b"));
+        assert!(dock.contains("a") && dock.contains("b"));
+        assert_eq!(dock.focused, Some(1));
+
+        // an open panel without focus is focused instead of closed
+        dock.toggle(DockSide::Left, 20, Dummy("a"));
+        assert!(dock.has_focus("a") && !dock.has_focus("b"));
+        dock.toggle(DockSide::Left, 20, Dummy("a"));
+        assert!(!dock.contains("a"));
+        assert_eq!(dock.focused, None);
+        assert!(dock.focus("b"));
+
+        dock.unfocus();
+        assert!(!dock.is_focused());
+        assert!(dock.focus("b"));
+        assert!(!dock.focus("a"));
+        assert!(dock.close("b"));
+        assert!(!dock.is_focused());
+    }
+}
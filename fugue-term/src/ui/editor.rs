@@ -9,32 +9,42 @@
     key,
     keymap::{KeymapResult, Keymaps},
     ui::{
+        dock::Dock,
         document::{render_document, LinePos, TextRenderer},
         statusline,
-        text_decorations::{self, Decoration, DecorationManager, InlineDiagnostics},
+        text_decorations::{
+            self, Decoration, DecorationManager, InlineBlame, InlineDiagnostics, InlineValues,
+        },
         Completion, ProgressSpinners,
     },
 };
 
 use fugue_core::{
+    char_idx_at_visual_offset,
     diagnostic::NumberOrString,
-    graphemes::{next_grapheme_boundary, prev_grapheme_boundary},
+    graphemes::{grapheme_width, next_grapheme_boundary, prev_grapheme_boundary},
+    line_ending::line_end_char_index,
     movement::Direction,
     syntax::{self, OverlayHighlights},
     text_annotations::TextAnnotations,
+    textobject::TextObject,
     unicode::width::UnicodeWidthStr,
-    visual_offset_from_block, Change, Position, Range, Selection, Transaction,
+    visual_offset_from_block, Change, Position, Range, RopeSlice, Selection, Transaction,
 };
+use fugue_stdx::rope::RopeSliceExt;
+use fugue_vcs::Conflict;
 use fugue_view::{
     annotations::diagnostics::DiagnosticFilter,
+    diff_view::{DiffSide, LineChange},
     document::{Mode, SCRATCH_BUFFER_NAME},
     editor::{CompleteAction, CursorShapeConfig},
     graphics::{Color, CursorKind, Modifier, Rect, Style},
     input::{KeyEvent, MouseButton, MouseEvent, MouseEventKind},
     keyboard::{KeyCode, KeyModifiers},
-    Document, Editor, Theme, View,
+    view::ViewPosition,
+    Document, DocumentId, Editor, Theme, View,
 };
-use std::{mem::take, num::NonZeroUsize, ops, path::PathBuf, rc::Rc};
+use std::{borrow::Cow, mem::take, num::NonZeroUsize, ops, path::PathBuf, rc::Rc};
 
 use tui::{buffer::Buffer as Surface, text::Span};
 
@@ -42,11 +52,20 @@ pub struct EditorView {
     pub keymaps: Keymaps,
     on_next_key: Option<(OnKeyCallback, OnKeyCallbackKind)>,
     pseudo_pending: Vec<KeyEvent>,
+    /// The operator waiting for a motion, with the last key of its binding that applies it to
+    /// whole lines when repeated, like `dd`.
+    pending_operator: Option<(commands::PendingOperator, KeyEvent)>,
+    operator_session: Option<OperatorSession>,
+    /// The keys of the last operator that changed the document, which `.` repeats instead of
+    /// the command of `last_insert`.
+    last_operator: Option<Vec<KeyEvent>>,
     pub(crate) last_insert: (commands::MappableCommand, Vec<InsertEvent>),
     pub(crate) completion: Option<Completion>,
     spinners: ProgressSpinners,
     /// Tracks if the terminal window is focused by reaction to terminal focus events
     terminal_focused: bool,
+    /// Persistent panels docked around the split tree.
+    pub dock: Dock,
 }
 
 #[derive(Debug, Clone)]
@@ -60,16 +79,29 @@ pub enum InsertEvent {
     RequestCompletion,
 }
 
+/// An operator being applied, from its binding until its motion is complete.
+struct OperatorSession {
+    /// The keys given so far, to repeat the operator with `.`.
+    keys: Vec<KeyEvent>,
+    command: commands::MappableCommand,
+    doc: DocumentId,
+    version: i32,
+}
+
 impl EditorView {
     pub fn new(keymaps: Keymaps) -> Self {
         Self {
             keymaps,
             on_next_key: None,
             pseudo_pending: Vec::new(),
+            pending_operator: None,
+            operator_session: None,
+            last_operator: None,
             last_insert: (commands::MappableCommand::normal_mode, Vec::new()),
             completion: None,
             spinners: ProgressSpinners::default(),
             terminal_focused: true,
+            dock: Dock::default(),
         }
     }
 
@@ -144,6 +176,30 @@ pub fn render_view(
 
         Self::doc_diagnostics_highlights_into(doc, theme, &mut overlays);
 
+        if let Some(side) = doc.diff_side() {
+            if let Some(highlight) = theme.find_highlight_exact("diff.delta.word") {
+                overlays.push(OverlayHighlights::Homogeneous {
+                    highlight,
+                    ranges: side.words.clone(),
+                });
+            }
+            decorations.add_decoration(Self::diff_lines(side, theme, inner));
+        }
+
+        if !doc.conflicts().is_empty() {
+            decorations.add_decoration(Self::conflict_lines(doc.conflicts(), theme, inner));
+        }
+
+        if doc.is_commit_message() {
+            overlays.push(Self::commit_message_highlights(
+                doc,
+                view_offset,
+                inner,
+                &text_annotations,
+                theme,
+            ));
+        }
+
         if is_focused {
             if let Some(tabstops) = Self::tabstop_highlights(doc, theme) {
                 overlays.push(tabstops);
@@ -199,6 +255,14 @@ pub fn render_view(
             inline_diagnostic_config,
             config.end_of_line_diagnostics,
         ));
+        if !doc.inline_values().is_empty() {
+            decorations.add_decoration(InlineValues::new(doc, theme));
+        }
+        if is_focused && editor.config().inline_blame {
+            if let Some(blame) = InlineBlame::new(doc, theme, primary_cursor) {
+                decorations.add_decoration(blame);
+            }
+        }
         render_document(
             surface,
             inner,
@@ -584,6 +648,92 @@ pub fn highlight_focused_view_elements(
         Some(OverlayHighlights::single(highlight, pos..pos + 1))
     }
 
+    /// Highlights the comments of a commit message, the parts of its lines that run past the
+    /// rulers and the diff below its scissors line.
+    pub fn commit_message_highlights(
+        doc: &Document,
+        offset: ViewPosition,
+        viewport: Rect,
+        text_annotations: &TextAnnotations,
+        theme: &Theme,
+    ) -> OverlayHighlights {
+        let text = doc.text().slice(..);
+        let diff_start = fugue_vcs::verbose_diff_start(text).unwrap_or(usize::MAX);
+        let summary_width = doc
+            .language_config()
+            .and_then(|config| config.rulers.as_ref()?.first())
+            .map_or(50, |ruler| (*ruler as usize).saturating_sub(1));
+        let body_width = doc.text_width();
+        let tab_width = doc.tab_width();
+
+        // soft-wrapped lines take up several rows so the last visible line is found visually
+        let text_fmt = doc.text_format(viewport.width, Some(theme));
+        let (last_char, _) = char_idx_at_visual_offset(
+            text,
+            offset.anchor,
+            (offset.vertical_offset + viewport.height.saturating_sub(1) as usize) as isize,
+            0,
+            &text_fmt,
+            text_annotations,
+        );
+        let first_line = text.char_to_line(offset.anchor.min(text.len_chars()));
+        let last_line = text.char_to_line(last_char.min(text.len_chars()));
+
+        let mut highlights = Vec::new();
+        for line_idx in first_line..=last_line {
+            let line = text.line(line_idx);
+            let start = text.line_to_char(line_idx);
+            let end = line_end_char_index(&text, line_idx);
+            let (scope, range) = if line_idx >= diff_start {
+                let scope = match line.get_char(0) {
+                    _ if line.starts_with("+++") || line.starts_with("---") => "markup.heading",
+                    Some('+') => "diff.plus",
+                    Some('-') => "diff.minus",
+                    Some('@') => "diff.delta",
+                    _ => continue,
+                };
+                (scope, start..end)
+            } else if line.get_char(0) == Some('#') {
+                ("comment", start..end)
+            } else {
+                // the summary is kept shorter than the body so it fits in one line logs
+                let width = if line_idx == 0 {
+                    summary_width
+                } else {
+                    body_width
+                };
+                let Some(overflow) =
+                    Self::char_past_width(text.slice(start..end), width, tab_width)
+                else {
+                    continue;
+                };
+                ("warning", start + overflow..end)
+            };
+            if let Some(highlight) = theme.find_highlight_exact(scope) {
+                highlights.push((highlight, range));
+            }
+        }
+        OverlayHighlights::Heterogenous { highlights }
+    }
+
+    /// Returns the offset of the first char of `line` that is displayed past `width` columns.
+    fn char_past_width(line: RopeSlice, width: usize, tab_width: usize) -> Option<usize> {
+        let mut col = 0;
+        let mut char_idx = 0;
+        for grapheme in line.graphemes() {
+            col += if grapheme == "\t" {
+                tab_width - (col % tab_width)
+            } else {
+                grapheme_width(&Cow::from(grapheme))
+            };
+            if col > width {
+                return Some(char_idx);
+            }
+            char_idx += grapheme.len_chars();
+        }
+        None
+    }
+
     pub fn tabstop_highlights(doc: &Document, theme: &Theme) -> Option<OverlayHighlights> {
         let snippet = doc.active_snippet.as_ref()?;
         let highlight = theme.find_highlight_exact("tabstop")?;
@@ -775,6 +925,82 @@ pub fn render_diagnostics(
         );
     }
 
+    /// Highlights the changed lines of one side of a diff view.
+    pub fn diff_lines<'d>(side: &'d DiffSide, theme: &Theme, inner: Rect) -> impl Decoration + 'd {
+        let added = theme.get("diff.plus.line");
+        let removed = theme.get("diff.minus.line");
+        let modified = theme.get("diff.delta.line");
+        let mut idx = 0;
+        move |renderer: &mut TextRenderer, pos: LinePos| {
+            if idx > 0 && side.lines[idx - 1].0.start > pos.doc_line {
+                idx = 0;
+            }
+            while side
+                .lines
+                .get(idx)
+                .is_some_and(|(lines, _)| lines.end <= pos.doc_line)
+            {
+                idx += 1;
+            }
+            let Some((lines, change)) = side.lines.get(idx) else {
+                return;
+            };
+            if !lines.contains(&pos.doc_line) {
+                return;
+            }
+            let style = match change {
+                LineChange::Added => added,
+                LineChange::Removed => removed,
+                LineChange::Modified => modified,
+            };
+            renderer.set_style(Rect::new(inner.x, pos.visual_line, inner.width, 1), style);
+        }
+    }
+
+    /// Highlights the sides and markers of merge conflicts.
+    pub fn conflict_lines<'d>(
+        conflicts: &'d [Conflict],
+        theme: &Theme,
+        inner: Rect,
+    ) -> impl Decoration + 'd {
+        let marker = theme.get("diff.conflict.marker");
+        let ours = theme.get("diff.conflict.ours");
+        let base = theme.get("diff.conflict.base");
+        let theirs = theme.get("diff.conflict.theirs");
+        let mut idx = 0;
+        move |renderer: &mut TextRenderer, pos: LinePos| {
+            if idx > 0 && conflicts[idx - 1].start > pos.doc_line {
+                idx = 0;
+            }
+            while conflicts
+                .get(idx)
+                .is_some_and(|conflict| conflict.end < pos.doc_line)
+            {
+                idx += 1;
+            }
+            let Some(conflict) = conflicts.get(idx) else {
+                return;
+            };
+            let line = pos.doc_line;
+            let style = if conflict.ours.contains(&line) {
+                ours
+            } else if conflict.theirs.contains(&line) {
+                theirs
+            } else if conflict
+                .base
+                .as_ref()
+                .is_some_and(|lines| lines.contains(&line))
+            {
+                base
+            } else if conflict.lines().contains(&line) {
+                marker
+            } else {
+                return;
+            };
+            renderer.set_style(Rect::new(inner.x, pos.visual_line, inner.width, 1), style);
+        }
+    }
+
     /// Apply the highlighting on the lines where a cursor is active
     pub fn cursorline(doc: &Document, view: &View, theme: &Theme) -> impl Decoration {
         let text = doc.text().slice(..);
@@ -895,6 +1121,7 @@ fn handle_keymap_event(
                     // we can repeat the side effect.
                     self.last_insert.0 = command.clone();
                     self.last_insert.1.clear();
+                    self.last_operator = None;
                 }
             }
 
@@ -962,10 +1189,15 @@ fn command_mode(&mut self, mode: Mode, cxt: &mut commands::Context, event: KeyEv
                 cxt.editor.count = NonZeroUsize::new(i);
             }
             // special handling for repeat operator
-            (key!('.'), _) if self.keymaps.pending().is_empty() => {
+            (key!('.'), _)
+                if self.keymaps.pending().is_empty() && self.pending_operator.is_none() =>
+            {
                 for _ in 0..cxt.editor.count.map_or(1, NonZeroUsize::into) {
                     // first execute whatever put us into insert mode
-                    self.last_insert.0.execute(cxt);
+                    match self.last_operator.clone() {
+                        Some(keys) => self.replay_operator(cxt, &keys),
+                        None => self.last_insert.0.execute(cxt),
+                    }
                     let mut last_savepoint = None;
                     let mut last_request_savepoint = None;
                     // then replay the inputs
@@ -1019,10 +1251,19 @@ fn command_mode(&mut self, mode: Mode, cxt: &mut commands::Context, event: KeyEv
                 // set the register
                 cxt.register = cxt.editor.selected_register.take();
 
-                let res = self.handle_keymap_event(mode, cxt, event);
+                let res = match self.pending_operator.take() {
+                    Some((operator, key)) => {
+                        self.operator_pending_mode(operator, key, cxt, event);
+                        None
+                    }
+                    None => self.handle_keymap_event(mode, cxt, event),
+                };
                 if matches!(&res, Some(KeymapResult::NotFound)) {
                     self.on_next_key(OnKeyCallbackKind::Fallback, cxt, event);
                 }
+                if let Some(operator) = cxt.pending_operator.take() {
+                    self.start_operator(operator, cxt, event);
+                }
                 if self.keymaps.pending().is_empty() {
                     cxt.editor.count = None
                 } else {
@@ -1032,6 +1273,117 @@ fn command_mode(&mut self, mode: Mode, cxt: &mut commands::Context, event: KeyEv
         }
     }
 
+    /// Waits for the motion of an operator started by `event`.
+    fn start_operator(
+        &mut self,
+        operator: commands::PendingOperator,
+        cxt: &mut commands::Context,
+        event: KeyEvent,
+    ) {
+        let count = operator.count.map(|count| count.to_string());
+        let keys = count
+            .iter()
+            .flat_map(|count| count.chars())
+            .map(|ch| KeyEvent {
+                code: KeyCode::Char(ch),
+                modifiers: KeyModifiers::NONE,
+            })
+            .chain(self.pseudo_pending.iter().copied())
+            .collect();
+        let doc = doc!(cxt.editor);
+        self.operator_session = Some(OperatorSession {
+            keys,
+            command: operator.command.clone(),
+            doc: doc.id(),
+            version: doc.version(),
+        });
+        self.pending_operator = Some((operator, event));
+    }
+
+    /// Handles a key while an operator waits for its motion. Motions are bound in the normal
+    /// keymap, `i` and `a` choose text objects and the last key of the operator's binding
+    /// applies it to whole lines.
+    fn operator_pending_mode(
+        &mut self,
+        operator: commands::PendingOperator,
+        operator_key: KeyEvent,
+        cxt: &mut commands::Context,
+        event: KeyEvent,
+    ) {
+        if self.keymaps.pending().is_empty() {
+            match event {
+                _ if event.code == KeyCode::Esc => return,
+                _ if event == operator_key => return commands::apply_operator_lines(cxt, operator),
+                key!('i') => {
+                    return commands::apply_operator_textobject(cxt, operator, TextObject::Inside)
+                }
+                key!('a') => {
+                    return commands::apply_operator_textobject(cxt, operator, TextObject::Around)
+                }
+                _ => (),
+            }
+        }
+        match self.keymaps.get(Mode::Normal, event) {
+            KeymapResult::Matched(command) => {
+                commands::apply_operator_motion(cxt, operator, &[command])
+            }
+            KeymapResult::MatchedSequence(commands) => {
+                commands::apply_operator_motion(cxt, operator, &commands)
+            }
+            KeymapResult::Pending(node) => {
+                cxt.editor.autoinfo = Some(node.infobox());
+                self.pending_operator = Some((operator, operator_key));
+            }
+            KeymapResult::NotFound | KeymapResult::Cancelled(_) => (),
+        }
+    }
+
+    /// Records a key given to an operator, and finishes the operator once its motion is
+    /// complete.
+    fn record_operator_key(&mut self, cxt: &mut commands::Context, key: KeyEvent) {
+        let Some(session) = &mut self.operator_session else {
+            return;
+        };
+        session.keys.push(key);
+        if self.pending_operator.is_some() || self.on_next_key.is_some() {
+            return;
+        }
+        let session = self.operator_session.take().unwrap();
+        let doc = doc!(cxt.editor);
+        let changed = doc.id() != session.doc || doc.version() != session.version;
+        let mode = cxt.editor.mode();
+        if changed || mode == Mode::Insert {
+            self.last_operator = Some(session.keys);
+            self.last_insert.1.clear();
+        }
+        fugue_event::dispatch(PostCommand {
+            command: &session.command,
+            cx: cxt,
+        });
+        if mode != Mode::Normal {
+            fugue_event::dispatch(OnModeSwitch {
+                old_mode: Mode::Normal,
+                new_mode: mode,
+                cx: cxt,
+            });
+        }
+    }
+
+    /// Replays the keys of the last operator for `.`.
+    fn replay_operator(&mut self, cxt: &mut commands::Context, keys: &[KeyEvent]) {
+        cxt.editor.count = None;
+        for &key in keys {
+            if !self.on_next_key(OnKeyCallbackKind::PseudoPending, cxt, key) {
+                self.command_mode(Mode::Normal, cxt, key);
+            }
+            if let Some(callback) = cxt.on_next_key_callback.take() {
+                self.on_next_key = Some(callback);
+            }
+        }
+        self.pending_operator = None;
+        self.operator_session = None;
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn set_completion(
         &mut self,
@@ -1118,6 +1470,8 @@ fn handle_non_key_input(&mut self, cxt: &mut commands::Context) {
         }
         self.handle_keymap_event(cxt.editor.mode, cxt, null_key_event);
         self.pseudo_pending.clear();
+        self.pending_operator = None;
+        self.operator_session = None;
     }
 
     fn handle_mouse_event(
@@ -1247,6 +1601,8 @@ fn handle_mouse_event(
 
                 let offset = config.scroll_lines.unsigned_abs();
                 commands::scroll(cxt, offset, direction, false);
+                commands::sync_blame_views(cxt.editor);
+                commands::sync_diff_views(cxt.editor);
 
                 cxt.editor.tree.focus = current_view;
                 cxt.editor.ensure_cursor_in_view(current_view);
@@ -1363,12 +1719,17 @@ fn handle_event(
         event: &Event,
         context: &mut crate::compositor::Context,
     ) -> EventResult {
+        if let Some(result) = self.dock.handle_event(event, context) {
+            return result;
+        }
+
         let mut cx = commands::Context {
             editor: context.editor,
             count: None,
             register: None,
             callback: Vec::new(),
             on_next_key_callback: None,
+            pending_operator: None,
             jobs: context.jobs,
         };
 
@@ -1463,6 +1824,7 @@ fn handle_event(
                 }
 
                 self.on_next_key = cx.on_next_key_callback.take();
+                self.record_operator_key(&mut cx, key);
                 match self.on_next_key {
                     Some((_, OnKeyCallbackKind::PseudoPending)) => self.pseudo_pending.push(key),
                     _ => self.pseudo_pending.clear(),
@@ -1544,6 +1906,8 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
             editor_area = editor_area.clip_top(1);
         }
 
+        let editor_area = self.dock.layout(editor_area);
+
         // if the terminal size suddenly changed, we need to trigger a resize
         cx.editor.resize(editor_area);
 
@@ -1551,6 +1915,8 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
             Self::render_bufferline(cx.editor, area.with_height(1), surface);
         }
 
+        self.dock.render(surface, cx);
+
         for (view, is_focused) in cx.editor.tree.views() {
             let doc = cx.editor.document(view.doc).unwrap();
             self.render_view(cx.editor, doc, view, area, surface, is_focused);
@@ -1586,6 +1952,11 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
 
         if area.width.saturating_sub(status_msg_width as u16) > key_width {
             let mut disp = String::new();
+            if let Some(session) = &self.operator_session {
+                for key in &session.keys {
+                    disp.push_str(&key.key_sequence_format());
+                }
+            }
             if let Some(count) = cx.editor.count {
                 disp.push_str(&count.to_string())
             }
@@ -1628,6 +1999,9 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
     }
 
     fn cursor(&self, _area: Rect, editor: &Editor) -> (Option<Position>, CursorKind) {
+        if let Some(cursor) = self.dock.cursor(editor) {
+            return cursor;
+        }
         match editor.cursor() {
             // all block cursors are drawn manually
             (pos, CursorKind::Block) => {
@@ -0,0 +1,769 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use fugue_stdx::path::{canonicalize, expand_tilde, get_relative_path};
+use fugue_view::editor::Action;
+use fugue_view::graphics::{Modifier, Rect};
+use fugue_view::input::{MouseButton, MouseEvent, MouseEventKind};
+use fugue_view::Editor;
+use tui::buffer::Buffer as Surface;
+
+use crate::compositor::{self, Callback, Component, Compositor, Context, Event, EventResult};
+use crate::job;
+use crate::ui::{self, directory_entries, EditorView, FileStatusMarkers, Prompt, PromptEvent};
+use crate::{ctrl, key, shift};
+
+pub const ID: &str = "file-tree";
+
+#[derive(Debug)]
+struct Entry {
+    path: PathBuf,
+    is_dir: bool,
+    /// Number of expanded ancestors below the root.
+    depth: usize,
+    expanded: bool,
+}
+
+/// Side panel showing the workspace as a tree. Directories are read when they are expanded,
+/// honoring the ignore settings of the file explorer.
+pub struct FileTree {
+    root: PathBuf,
+    /// The visible entries in display order.
+    entries: Vec<Entry>,
+    /// Index of the selected entry.
+    cursor: usize,
+    /// Index of the first visible entry.
+    scroll: usize,
+    /// Screen row of the first entry, recorded on render to map mouse clicks to entries.
+    row_offset: Option<u16>,
+    markers: FileStatusMarkers,
+}
+
+impl FileTree {
+    pub fn new(root: PathBuf, editor: &Editor) -> Self {
+        let mut tree = Self {
+            markers: FileStatusMarkers::new(editor, root.clone()),
+            root,
+            entries: Vec::new(),
+            cursor: 0,
+            scroll: 0,
+            row_offset: None,
+        };
+        tree.refresh(editor);
+        tree
+    }
+
+    fn read_dir(
+        dir: &Path,
+        depth: usize,
+        expanded: &HashSet<PathBuf>,
+        editor: &Editor,
+        entries: &mut Vec<Entry>,
+    ) {
+        let content = match directory_entries(dir, editor) {
+            Ok(content) => content,
+            Err(err) => {
+                log::debug!("failed to read {}: {err}", dir.display());
+                return;
+            }
+        };
+        for (path, is_dir) in content {
+            let expand = is_dir && expanded.contains(&path);
+            entries.push(Entry {
+                path,
+                is_dir,
+                depth,
+                expanded: expand,
+            });
+            if expand {
+                let path = entries.last().unwrap().path.clone();
+                Self::read_dir(&path, depth + 1, expanded, editor, entries);
+            }
+        }
+    }
+
+    /// Reads the expanded directories again, keeping the cursor on the selected path if it
+    /// still exists.
+    pub fn refresh(&mut self, editor: &Editor) {
+        let expanded: HashSet<_> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.expanded)
+            .map(|entry| entry.path.clone())
+            .collect();
+        let selected = self.selected().map(|entry| entry.path.clone());
+
+        let mut entries = Vec::new();
+        Self::read_dir(&self.root, 0, &expanded, editor, &mut entries);
+        self.entries = entries;
+        self.cursor = selected
+            .and_then(|path| self.entries.iter().position(|entry| entry.path == path))
+            .unwrap_or(self.cursor)
+            .min(self.entries.len().saturating_sub(1));
+    }
+
+    fn selected(&self) -> Option<&Entry> {
+        self.entries.get(self.cursor)
+    }
+
+    fn expand(&mut self, idx: usize, editor: &Editor) {
+        let entry = &self.entries[idx];
+        if !entry.is_dir || entry.expanded {
+            return;
+        }
+        let mut children = Vec::new();
+        Self::read_dir(
+            &entry.path,
+            entry.depth + 1,
+            &HashSet::new(),
+            editor,
+            &mut children,
+        );
+        self.entries[idx].expanded = true;
+        self.entries.splice(idx + 1..idx + 1, children);
+    }
+
+    fn collapse(&mut self, idx: usize) {
+        let depth = self.entries[idx].depth;
+        let end = idx
+            + 1
+            + self.entries[idx + 1..]
+                .iter()
+                .take_while(|entry| entry.depth > depth)
+                .count();
+        self.entries.drain(idx + 1..end);
+        self.entries[idx].expanded = false;
+        if self.cursor > idx {
+            self.cursor = self.cursor.saturating_sub(end - idx - 1).max(idx);
+        }
+    }
+
+    /// Expands the ancestors of `path` and moves the cursor to it. Returns whether `path` is
+    /// shown in the tree.
+    pub fn reveal(&mut self, path: &Path, editor: &Editor) -> bool {
+        loop {
+            if let Some(idx) = self.entries.iter().position(|entry| entry.path == path) {
+                self.cursor = idx;
+                return true;
+            }
+            let Some(idx) = self
+                .entries
+                .iter()
+                .position(|entry| entry.is_dir && !entry.expanded && path.starts_with(&entry.path))
+            else {
+                return false;
+            };
+            self.expand(idx, editor);
+        }
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        self.cursor = self
+            .cursor
+            .saturating_add_signed(delta)
+            .min(self.entries.len().saturating_sub(1));
+    }
+
+    /// Opens the selected file or toggles the selected directory.
+    fn select(&mut self, cx: &mut Context, action: Action) -> Option<Callback> {
+        let entry = self.selected()?;
+        if entry.is_dir {
+            if entry.expanded {
+                self.collapse(self.cursor);
+            } else {
+                self.expand(self.cursor, cx.editor);
+            }
+            return None;
+        }
+        if let Err(err) = cx.editor.open(&entry.path, action) {
+            cx.editor.set_error(err.to_string());
+            return None;
+        }
+        // continue editing the opened file
+        Some(Box::new(|compositor: &mut Compositor, _| {
+            if let Some(editor_view) = compositor.find::<EditorView>() {
+                editor_view.dock.unfocus();
+            }
+        }))
+    }
+
+    /// Collapses the selected directory, or moves the cursor to the parent directory.
+    fn collapse_or_parent(&mut self) {
+        let Some(entry) = self.selected() else {
+            return;
+        };
+        if entry.expanded {
+            self.collapse(self.cursor);
+            return;
+        }
+        let depth = entry.depth;
+        if let Some(parent) = self.entries[..self.cursor]
+            .iter()
+            .rposition(|entry| entry.depth < depth)
+        {
+            self.cursor = parent;
+        }
+    }
+
+    /// The directory new files are created in: the selected directory, or the directory of the
+    /// selected file.
+    fn target_dir(&self) -> PathBuf {
+        match self.selected() {
+            Some(entry) if entry.is_dir => entry.path.clone(),
+            Some(entry) => entry
+                .path
+                .parent()
+                .map_or_else(|| self.root.clone(), Path::to_path_buf),
+            None => self.root.clone(),
+        }
+    }
+
+    fn handle_key(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let Event::Key(key) = event else {
+            return EventResult::Ignored(None);
+        };
+        let callback = match *key {
+            key!('j') | key!(Down) | ctrl!('n') => {
+                self.move_cursor(1);
+                None
+            }
+            key!('k') | key!(Up) | ctrl!('p') => {
+                self.move_cursor(-1);
+                None
+            }
+            key!('g') | key!(Home) => {
+                self.cursor = 0;
+                None
+            }
+            shift!('G') | key!('G') | key!(End) => {
+                self.move_cursor(isize::MAX);
+                None
+            }
+            key!(Enter) | key!('o') => self.select(cx, Action::Replace),
+            ctrl!('s') => self.select(cx, Action::HorizontalSplit),
+            ctrl!('v') => self.select(cx, Action::VerticalSplit),
+            key!('l') | key!(Right) => {
+                match self.selected() {
+                    Some(entry) if entry.is_dir => self.expand(self.cursor, cx.editor),
+                    Some(_) => return EventResult::Consumed(self.select(cx, Action::Replace)),
+                    None => (),
+                }
+                None
+            }
+            key!('h') | key!(Left) => {
+                self.collapse_or_parent();
+                None
+            }
+            key!('a') => {
+                let dir = self.target_dir();
+                Some(file_operation_prompt(
+                    cx.editor,
+                    "create (end with / for a directory): ",
+                    format!("{}/", get_relative_path(&dir).display()),
+                    move |_, _, target| create_path(target),
+                ))
+            }
+            key!('r') => self.selected().map(|entry| {
+                file_operation_prompt(
+                    cx.editor,
+                    "move to: ",
+                    get_relative_path(&entry.path).display().to_string(),
+                    |editor, source, target| {
+                        if let Some(parent) = target.parent() {
+                            fs::create_dir_all(parent)?;
+                        }
+                        editor.move_path(source, target)
+                    },
+                )
+            }),
+            key!('c') => self.selected().map(|entry| {
+                file_operation_prompt(
+                    cx.editor,
+                    "copy to: ",
+                    get_relative_path(&entry.path).display().to_string(),
+                    |_, source, target| copy_path(source, target),
+                )
+            }),
+            key!('d') => self
+                .selected()
+                .map(|entry| trash_prompt(vec![entry.path.clone()])),
+            shift!('R') | key!('R') => {
+                self.refresh(cx.editor);
+                None
+            }
+            key!('q') => Some(Box::new(|compositor: &mut Compositor, _: &mut Context| {
+                if let Some(editor_view) = compositor.find::<EditorView>() {
+                    editor_view.dock.close(ID);
+                }
+            }) as Callback),
+            _ => return EventResult::Ignored(None),
+        };
+        EventResult::Consumed(callback)
+    }
+}
+
+impl Component for FileTree {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let Event::Mouse(MouseEvent { kind, row, .. }) = event else {
+            return self.handle_key(event, cx);
+        };
+        let callback = match kind {
+            MouseEventKind::ScrollDown => {
+                self.move_cursor(3);
+                None
+            }
+            MouseEventKind::ScrollUp => {
+                self.move_cursor(-3);
+                None
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                let Some(offset) = self.row_offset.and_then(|top| row.checked_sub(top)) else {
+                    return EventResult::Consumed(None);
+                };
+                let idx = self.scroll + offset as usize;
+                if idx >= self.entries.len() {
+                    return EventResult::Consumed(None);
+                }
+                self.cursor = idx;
+                self.select(cx, Action::Replace)
+            }
+            _ => return EventResult::Ignored(None),
+        };
+        EventResult::Consumed(callback)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        let text_style = theme.get("ui.text");
+        let title_style = theme.get("ui.text.focus").add_modifier(Modifier::BOLD);
+        let directory_style = theme.get("ui.text.directory");
+        let selected_style = theme.get("ui.selection");
+        let width = area.width as usize;
+
+        let title = self.root.file_name().map_or_else(
+            || self.root.to_string_lossy(),
+            |name| name.to_string_lossy(),
+        );
+        surface.set_stringn(area.x, area.y, title, width, title_style);
+        let area = area.clip_top(1);
+        self.row_offset = Some(area.y);
+
+        let height = area.height as usize;
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if height > 0 && self.cursor >= self.scroll + height {
+            self.scroll = self.cursor + 1 - height;
+        }
+
+        for (i, entry) in self
+            .entries
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(height)
+        {
+            let y = area.y + (i - self.scroll) as u16;
+            let patch = |style: fugue_view::theme::Style| {
+                if i == self.cursor {
+                    style.patch(selected_style)
+                } else {
+                    style
+                }
+            };
+            if i == self.cursor {
+                surface.set_style(Rect::new(area.x, y, area.width, 1), selected_style);
+            }
+
+            // flattened directories show the path below their parent
+            let parent = entry.path.parent().unwrap_or(&self.root);
+            let name = entry.path.strip_prefix(parent).unwrap_or(&entry.path);
+            let (label, style) = match (entry.is_dir, entry.expanded) {
+                (true, true) => (format!("▾ {}/", name.display()), directory_style),
+                (true, false) => (format!("▸ {}/", name.display()), directory_style),
+                (false, _) => (format!("  {}", name.display()), text_style),
+            };
+            let indent = entry.depth * 2;
+            surface.set_stringn(
+                area.x + indent.min(width) as u16,
+                y,
+                label,
+                width.saturating_sub(indent + 2),
+                patch(style),
+            );
+            if let Some((marker, style)) = self.markers.status(&entry.path) {
+                let x = area.right().saturating_sub(1);
+                surface.set_stringn(x, y, marker, 1, patch(style));
+            }
+        }
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(ID)
+    }
+}
+
+/// Builds a callback pushing a prompt for the target path of a file operation on the selected
+/// entry, relative to the working directory. The tree is refreshed and reveals the target once
+/// `operation` succeeds.
+fn file_operation_prompt(
+    editor: &Editor,
+    prompt: &'static str,
+    input: String,
+    operation: impl Fn(&mut Editor, &Path, &Path) -> io::Result<()> + 'static,
+) -> Callback {
+    let source = canonicalize(expand_tilde(Path::new(&input)));
+    let prompt = Prompt::new(
+        prompt.into(),
+        None,
+        ui::completers::filename,
+        move |cx: &mut Context, input: &str, event: PromptEvent| {
+            if event != PromptEvent::Validate || input.is_empty() {
+                return;
+            }
+            let mut target = canonicalize(expand_tilde(Path::new(input)));
+            if input.ends_with(std::path::is_separator) {
+                // keep the separator marking directories
+                target.push("");
+            }
+            match operation(cx.editor, &source, &target) {
+                Ok(()) => refresh(cx, Some(target)),
+                Err(err) => cx.editor.set_error(err.to_string()),
+            }
+        },
+    )
+    .with_line(input, editor);
+    Box::new(move |compositor: &mut Compositor, _: &mut Context| {
+        compositor.push(Box::new(prompt));
+    })
+}
+
+/// Builds a callback asking for confirmation before moving `paths` to the trash.
+pub(crate) fn trash_prompt(paths: Vec<PathBuf>) -> Callback {
+    let message = match paths.as_slice() {
+        [path] => format!(
+            "move {} to the trash? (y/n): ",
+            get_relative_path(path).display()
+        ),
+        paths => format!("move {} files to the trash? (y/n): ", paths.len()),
+    };
+    let prompt = Prompt::new(
+        message.into(),
+        None,
+        ui::completers::none,
+        move |cx: &mut Context, input: &str, event: PromptEvent| {
+            if event != PromptEvent::Validate || input != "y" {
+                return;
+            }
+            let mut kept = 0;
+            for path in &paths {
+                if let Err(err) = move_to_trash(path) {
+                    cx.editor.set_error(format!(
+                        "failed to move {} to the trash: {err}",
+                        get_relative_path(path).display()
+                    ));
+                    break;
+                }
+                kept += close_trashed(cx.editor, path);
+            }
+            if kept > 0 {
+                cx.editor.set_status(format!(
+                    "kept {kept} modified buffers of trashed files open"
+                ));
+            }
+            refresh(cx, None);
+        },
+    );
+    Box::new(move |compositor: &mut Compositor, _: &mut Context| {
+        compositor.push(Box::new(prompt));
+    })
+}
+
+/// Closes the buffers of `path` and the files below it once it was moved to the trash, and
+/// tells the language servers about them. Returns the number of buffers kept open because they
+/// have unsaved changes.
+fn close_trashed(editor: &mut Editor, path: &Path) -> usize {
+    let trashed: Vec<_> = editor
+        .documents()
+        .filter_map(|doc| {
+            let doc_path = doc.path().filter(|doc_path| doc_path.starts_with(path))?;
+            Some((doc.id(), doc_path.clone()))
+        })
+        .collect();
+    let mut kept = 0;
+    for (doc_id, doc_path) in trashed {
+        if editor.close_document(doc_id, false).is_err() {
+            kept += 1;
+        }
+        editor
+            .language_servers
+            .file_event_handler
+            .file_changed(doc_path);
+    }
+    editor
+        .language_servers
+        .file_event_handler
+        .file_changed(path.to_path_buf());
+    kept
+}
+
+/// Builds a callback pushing a prompt for a directory to move `paths` into, relative to the
+/// working directory. Open buffers follow their files.
+pub(crate) fn move_prompt(editor: &Editor, paths: Vec<PathBuf>) -> Callback {
+    let dir = paths
+        .first()
+        .and_then(|path| path.parent())
+        .map(|dir| format!("{}/", get_relative_path(dir).display()))
+        .unwrap_or_default();
+    let prompt = Prompt::new(
+        format!("move {} files to directory: ", paths.len()).into(),
+        None,
+        ui::completers::directory,
+        move |cx: &mut Context, input: &str, event: PromptEvent| {
+            if event != PromptEvent::Validate || input.is_empty() {
+                return;
+            }
+            let dir = canonicalize(expand_tilde(Path::new(input)));
+            let result = fs::create_dir_all(&dir).and_then(|()| {
+                paths.iter().try_for_each(|source| {
+                    let name = source
+                        .file_name()
+                        .ok_or_else(|| io::Error::other("can't move the root"))?;
+                    cx.editor.move_path(source, &dir.join(name))
+                })
+            });
+            match result {
+                Ok(()) => refresh(cx, Some(dir)),
+                Err(err) => cx.editor.set_error(err.to_string()),
+            }
+        },
+    )
+    .with_line(dir, editor);
+    Box::new(move |compositor: &mut Compositor, _: &mut Context| {
+        compositor.push(Box::new(prompt));
+    })
+}
+
+/// Refreshes the open file tree after a file operation, moving its cursor to `reveal`.
+fn refresh(cx: &mut compositor::Context, reveal: Option<PathBuf>) {
+    cx.jobs.callback(async move {
+        let call = job::Callback::EditorCompositor(Box::new(move |editor, compositor| {
+            let Some(tree) = compositor
+                .find::<EditorView>()
+                .and_then(|editor_view| editor_view.dock.find_id::<FileTree>(ID))
+            else {
+                return;
+            };
+            tree.refresh(editor);
+            if let Some(path) = reveal {
+                tree.reveal(&path, editor);
+            }
+        }));
+        Ok(call)
+    });
+}
+
+/// Creates an empty file at `path`, or a directory if `path` ends with a separator. Missing
+/// parent directories are created as well.
+fn create_path(path: &Path) -> io::Result<()> {
+    if path
+        .as_os_str()
+        .to_string_lossy()
+        .ends_with(std::path::MAIN_SEPARATOR)
+    {
+        return fs::create_dir_all(path);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map(|_| ())
+}
+
+/// Copies a file, or a directory with everything below it.
+fn copy_path(source: &Path, target: &Path) -> io::Result<()> {
+    if !source.is_dir() {
+        return fs::copy(source, target).map(|_| ());
+    }
+    if target.starts_with(source) {
+        return Err(io::Error::other("can't copy a directory into itself"));
+    }
+    fs::create_dir(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        copy_path(&entry.path(), &target.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+/// Moves `path` to the trash of the user, following the freedesktop.org trash specification.
+/// Files on other file systems than the home directory go to the trash at the top of their
+/// file system, as they can't be renamed into the home trash.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    use std::io::Write;
+
+    let trash = trash_dir(path)?;
+    fs::create_dir_all(trash.join("files"))?;
+    fs::create_dir_all(trash.join("info"))?;
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::other("can't move the root to the trash"))?
+        .to_string_lossy()
+        .into_owned();
+    // the info file reserves a name in the trash
+    let mut trash_name = name.clone();
+    let mut n = 1;
+    let info = loop {
+        let info = trash.join("info").join(format!("{trash_name}.trashinfo"));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info)
+        {
+            Ok(mut file) => {
+                write!(
+                    file,
+                    "[Trash Info]\nPath={}\nDeletionDate={}\n",
+                    percent_encode(path),
+                    chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+                )?;
+                break info;
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                n += 1;
+                trash_name = format!("{name}.{n}");
+            }
+            Err(err) => return Err(err),
+        }
+    };
+    fs::rename(path, trash.join("files").join(&trash_name)).inspect_err(|_| {
+        let _ = fs::remove_file(info);
+    })
+}
+
+/// Finds the trash directory for `path`: the home trash if both are on the same file system,
+/// otherwise `$topdir/.Trash/$uid` or `$topdir/.Trash-$uid` where `$topdir` is the mount point
+/// of the file system of `path`.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn trash_dir(path: &Path) -> io::Result<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let device = fs::symlink_metadata(path)?.dev();
+    let data_dir = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .ok_or_else(|| io::Error::other("can't find the trash directory"))?;
+    // the data directory may not exist yet, its closest existing ancestor will hold it
+    let home_device = data_dir
+        .ancestors()
+        .find_map(|dir| fs::metadata(dir).ok())
+        .map(|metadata| metadata.dev());
+    if home_device == Some(device) {
+        return Ok(data_dir.join("Trash"));
+    }
+
+    let topdir = path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| fs::metadata(dir).is_ok_and(|metadata| metadata.dev() == device))
+        .last()
+        .ok_or_else(|| io::Error::other("can't find the trash directory"))?;
+    // SAFETY: getuid can't fail
+    let uid = unsafe { libc::getuid() };
+    // the shared trash must be a real directory with the sticky bit set to be trusted
+    let shared = topdir.join(".Trash");
+    if fs::symlink_metadata(&shared)
+        .is_ok_and(|metadata| metadata.is_dir() && metadata.mode() & 0o1000 != 0)
+    {
+        return Ok(shared.join(uid.to_string()));
+    }
+    Ok(topdir.join(format!(".Trash-{uid}")))
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn move_to_trash(_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the trash isn't supported on this platform",
+    ))
+}
+
+/// Encodes `path` the way URIs are, as the trash specification requires.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn percent_encode(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut encoded = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(path: &str, is_dir: bool, depth: usize, expanded: bool) -> Entry {
+        Entry {
+            path: PathBuf::from(path),
+            is_dir,
+            depth,
+            expanded,
+        }
+    }
+
+    #[test]
+    fn collapse_removes_descendants() {
+        let mut tree = FileTree {
+            root: PathBuf::from("/r"),
+            entries: vec![
+                entry("/r/a", true, 0, true),
+                entry("/r/a/b", true, 1, true),
+                entry("/r/a/b/c", false, 2, false),
+                entry("/r/a/d", false, 1, false),
+                entry("/r/e", false, 0, false),
+            ],
+            cursor: 2,
+            scroll: 0,
+            row_offset: None,
+            markers: FileStatusMarkers::default(),
+        };
+        tree.collapse_or_parent();
+        assert_eq!(tree.cursor, 1);
+        tree.collapse_or_parent();
+        assert_eq!(tree.entries.len(), 4);
+        assert!(!tree.entries[1].expanded);
+
+        tree.cursor = 3;
+        tree.collapse(0);
+        assert_eq!(tree.entries.len(), 2);
+        assert_eq!(tree.cursor, 1);
+        assert_eq!(tree.target_dir(), PathBuf::from("/r"));
+        tree.cursor = 0;
+        assert_eq!(tree.target_dir(), PathBuf::from("/r/a"));
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn trash_path_encoding() {
+        assert_eq!(
+            percent_encode(Path::new("/a b/ä%.rs")),
+            "/a%20b/%C3%A4%25.rs"
+        );
+    }
+}
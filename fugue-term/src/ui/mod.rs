@@ -2,8 +2,11 @@
 // Licensed under MPL-2.0
 
 mod completion;
+pub mod dap;
+pub mod dock;
 mod document;
 pub(crate) mod editor;
+pub mod file_tree;
 mod info;
 pub mod lsp;
 mod markdown;
@@ -12,6 +15,8 @@
 pub mod picker;
 pub mod popup;
 pub mod prompt;
+pub mod quickfix;
+pub mod search_replace;
 mod spinner;
 mod statusline;
 mod text;
@@ -32,9 +37,13 @@
 pub use spinner::{ProgressSpinners, Spinner};
 pub use text::Text;
 
+use fugue_vcs::{FileStatus, FileStatusCache};
+use fugue_view::editor::FilePickerConfig;
 use fugue_view::Editor;
 use tui::text::{Span, Spans};
+use tui::widgets::Cell;
 
+use std::collections::HashSet;
 use std::path::Path;
 use std::{error::Error, path::PathBuf};
 
@@ -204,6 +213,35 @@ fn get_excluded_types() -> ignore::types::Types {
         .expect("failed to build excluded_types")
 }
 
+/// Walks the files below `root` the file picker lists, skipping the entries `filter` rejects
+/// as well.
+fn file_picker_walk(
+    config: &FilePickerConfig,
+    root: &Path,
+    filter: impl Fn(&ignore::DirEntry) -> bool + Send + Sync + 'static,
+) -> ignore::Walk {
+    let dedup_symlinks = config.deduplicate_links;
+    let absolute_root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+
+    ignore::WalkBuilder::new(root)
+        .hidden(config.hidden)
+        .parents(config.parents)
+        .ignore(config.ignore)
+        .follow_links(config.follow_symlinks)
+        .git_ignore(config.git_ignore)
+        .git_global(config.git_global)
+        .git_exclude(config.git_exclude)
+        .sort_by_file_name(|name1, name2| name1.cmp(name2))
+        .max_depth(config.max_depth)
+        .filter_entry(move |entry| {
+            filter_picker_entry(entry, &absolute_root, dedup_symlinks) && filter(entry)
+        })
+        .add_custom_ignore_filename(fugue_loader::config_dir().join("ignore"))
+        .add_custom_ignore_filename(".fugue/ignore")
+        .types(get_excluded_types())
+        .build()
+}
+
 #[derive(Debug)]
 pub struct FilePickerData {
     root: PathBuf,
@@ -212,7 +250,6 @@ pub struct FilePickerData {
 type FilePicker = Picker<PathBuf, FilePickerData>;
 
 pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
-    use ignore::WalkBuilder;
     use std::time::Instant;
 
     let config = editor.config();
@@ -223,33 +260,13 @@ pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
 
     let now = Instant::now();
 
-    let dedup_symlinks = config.file_picker.deduplicate_links;
-    let absolute_root = root.canonicalize().unwrap_or_else(|_| root.clone());
-
-    let mut walk_builder = WalkBuilder::new(&root);
-
-    let mut files = walk_builder
-        .hidden(config.file_picker.hidden)
-        .parents(config.file_picker.parents)
-        .ignore(config.file_picker.ignore)
-        .follow_links(config.file_picker.follow_symlinks)
-        .git_ignore(config.file_picker.git_ignore)
-        .git_global(config.file_picker.git_global)
-        .git_exclude(config.file_picker.git_exclude)
-        .sort_by_file_name(|name1, name2| name1.cmp(name2))
-        .max_depth(config.file_picker.max_depth)
-        .filter_entry(move |entry| filter_picker_entry(entry, &absolute_root, dedup_symlinks))
-        .add_custom_ignore_filename(fugue_loader::config_dir().join("ignore"))
-        .add_custom_ignore_filename(".fugue/ignore")
-        .types(get_excluded_types())
-        .build()
-        .filter_map(|entry| {
-            let entry = entry.ok()?;
-            if !entry.file_type()?.is_file() {
-                return None;
-            }
-            Some(entry.into_path())
-        });
+    let files = file_picker_walk(&config.file_picker, &root, |_| true).filter_map(|entry| {
+        let entry = entry.ok()?;
+        if !entry.file_type()?.is_file() {
+            return None;
+        }
+        Some(entry.into_path())
+    });
     log::debug!("file_picker init {:?}", Instant::now().duration_since(now));
 
     let columns = [PickerColumn::new(
@@ -283,6 +300,37 @@ pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
     })
     .with_preview(|_editor, path| Some((path.as_path().into(), None)));
     let injector = picker.injector();
+
+    // recent files come first, the walk skips them
+    let mut recent = HashSet::new();
+    if config.file_picker.recent_first {
+        let absolute_root = fugue_stdx::path::canonicalize(&root);
+        let ranked: Vec<_> = editor
+            .recent_files
+            .ranked()
+            .into_iter()
+            .filter_map(|file| Some(root.join(file.path.strip_prefix(&absolute_root).ok()?)))
+            .collect();
+        // a walk pruned to the paths of the recent files skips those the full walk would skip
+        let candidates = ranked.clone();
+        let walk = file_picker_walk(&config.file_picker, &root, move |entry| {
+            candidates.iter().any(|path| path.starts_with(entry.path()))
+        });
+        let found: HashSet<_> = walk
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                entry.file_type()?.is_file().then(|| entry.into_path())
+            })
+            .collect();
+        for path in ranked {
+            if found.contains(&path) && recent.insert(path.clone()) && injector.push(path).is_err()
+            {
+                break;
+            }
+        }
+    }
+    let mut files = files.filter(move |path| !recent.contains(path));
+
     let timeout = std::time::Instant::now() + std::time::Duration::from_millis(30);
 
     let mut hit_timeout = false;
@@ -307,28 +355,86 @@ pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
     picker
 }
 
-type FileExplorer = Picker<(PathBuf, bool), (PathBuf, Style)>;
+/// Renders the VCS status of files as colored markers: `M` for modified, `S` for staged, `?` for
+/// untracked, `U` for conflicted and `!` for ignored files. The statuses are read from the cache
+/// of the editor on every render, so refreshes show up while a picker is open.
+#[derive(Clone, Default)]
+pub struct FileStatusMarkers {
+    statuses: FileStatusCache,
+    styles: [Style; 5],
+}
+
+impl FileStatusMarkers {
+    /// Creates the markers and refreshes the statuses of the repository containing `cwd`.
+    pub fn new(editor: &Editor, cwd: PathBuf) -> Self {
+        crate::commands::refresh_file_statuses(editor, cwd);
+        let theme = &editor.theme;
+        Self {
+            statuses: editor.file_statuses.clone(),
+            styles: [
+                theme.get("ui.vcs.ignored"),
+                theme.get("ui.vcs.staged"),
+                theme.get("ui.vcs.untracked"),
+                theme.get("ui.vcs.modified"),
+                theme.get("ui.vcs.conflict"),
+            ],
+        }
+    }
+
+    pub fn marker(&self, path: &Path) -> Cell<'static> {
+        match self.status(path) {
+            Some((marker, style)) => Span::styled(marker, style).into(),
+            None => Cell::default(),
+        }
+    }
+
+    /// Returns the marker of `path` along with its style.
+    pub fn status(&self, path: &Path) -> Option<(&'static str, Style)> {
+        let status = self.statuses.load().get(path)?;
+        let marker = match status {
+            FileStatus::Ignored => "!",
+            FileStatus::Staged => "S",
+            FileStatus::Untracked => "?",
+            FileStatus::Modified => "M",
+            FileStatus::Conflict => "U",
+        };
+        Some((marker, self.styles[status as usize]))
+    }
+}
+
+type FileExplorer = Picker<(PathBuf, bool), (PathBuf, Style, FileStatusMarkers)>;
 
 pub fn file_explorer(root: PathBuf, editor: &Editor) -> Result<FileExplorer, std::io::Error> {
     let directory_style = editor.theme.get("ui.text.directory");
     let directory_content = directory_content(&root, editor)?;
-
-    let columns = [PickerColumn::new(
-        "path",
-        |(path, is_dir): &(PathBuf, bool), (root, directory_style): &(PathBuf, Style)| {
-            let name = path.strip_prefix(root).unwrap_or(path).to_string_lossy();
-            if *is_dir {
-                Span::styled(format!("{}/", name), *directory_style).into()
-            } else {
-                name.into()
-            }
-        },
-    )];
+    let markers = FileStatusMarkers::new(editor, root.clone());
+
+    let columns = [
+        PickerColumn::new(
+            "status",
+            |(path, _): &(PathBuf, bool), (_, _, markers): &(PathBuf, Style, FileStatusMarkers)| {
+                markers.marker(path)
+            },
+        )
+        .without_filtering(),
+        PickerColumn::new(
+            "path",
+            |(path, is_dir): &(PathBuf, bool),
+             (root, directory_style, _): &(PathBuf, Style, FileStatusMarkers)| {
+                let name = path.strip_prefix(root).unwrap_or(path).to_string_lossy();
+                if *is_dir {
+                    Span::styled(format!("{}/", name), *directory_style).into()
+                } else {
+                    name.into()
+                }
+            },
+        ),
+    ];
     let picker = Picker::new(
         columns,
-        0,
+        1,
         directory_content,
-        (root, directory_style),
+        (root, directory_style, markers),
         move |cx, (path, is_dir): &(PathBuf, bool), action| {
             if *is_dir {
                 let new_root = fugue_stdx::path::normalize(path);
@@ -352,12 +458,42 @@ pub fn file_explorer(root: PathBuf, editor: &Editor) -> Result<FileExplorer, std
             }
         },
     )
-    .with_preview(|_editor, (path, _is_dir)| Some((path.as_path().into(), None)));
+    .with_preview(|_editor, (path, _is_dir)| Some((path.as_path().into(), None)))
+    .with_batch_action(crate::alt!('x'), |_cx, items| {
+        let paths = entry_paths(items);
+        (!paths.is_empty()).then(|| file_tree::trash_prompt(paths))
+    })
+    .with_batch_action(crate::alt!('r'), |cx, items| {
+        let paths = entry_paths(items);
+        (!paths.is_empty()).then(|| file_tree::move_prompt(cx.editor, paths))
+    });
 
     Ok(picker)
 }
 
+/// The paths of file explorer entries, leaving out the parent directory entry.
+fn entry_paths(items: Vec<&(PathBuf, bool)>) -> Vec<PathBuf> {
+    items
+        .into_iter()
+        .filter(|(path, _)| !path.ends_with(".."))
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
 fn directory_content(root: &Path, editor: &Editor) -> Result<Vec<(PathBuf, bool)>, std::io::Error> {
+    let mut content = directory_entries(root, editor)?;
+    if root.parent().is_some() {
+        content.insert(0, (root.join(".."), true));
+    }
+    Ok(content)
+}
+
+/// Lists the files and directories in `root` that aren't ignored by the file explorer config,
+/// directories first. Single-child directories are flattened if configured.
+pub(crate) fn directory_entries(
+    root: &Path,
+    editor: &Editor,
+) -> Result<Vec<(PathBuf, bool)>, std::io::Error> {
     use ignore::WalkBuilder;
 
     let config = editor.config();
@@ -398,10 +534,6 @@ fn directory_content(root: &Path, editor: &Editor) -> Result<Vec<(PathBuf, bool)
 
     content.sort_by(|(path1, is_dir1), (path2, is_dir2)| (!is_dir1, path1).cmp(&(!is_dir2, path2)));
 
-    if root.parent().is_some() {
-        content.insert(0, (root.join(".."), true));
-    }
-
     Ok(content)
 }
 
@@ -710,6 +842,20 @@ pub fn register(editor: &Editor, input: &str) -> Vec<Completion> {
             .collect()
     }
 
+    pub fn quickfix_list(editor: &Editor, input: &str) -> Vec<Completion> {
+        fuzzy_match(input, editor.quickfix.names(), false)
+            .into_iter()
+            .map(|(name, _)| ((0..), name.to_owned().into()))
+            .collect()
+    }
+
+    pub fn resolution(_editor: &Editor, input: &str) -> Vec<Completion> {
+        fuzzy_match(input, ["ours", "theirs", "base", "both"], false)
+            .into_iter()
+            .map(|(name, _)| ((0..), name.into()))
+            .collect()
+    }
+
     pub fn program(_editor: &Editor, input: &str) -> Vec<Completion> {
         static PROGRAMS_IN_PATH: Lazy<BTreeSet<String>> = Lazy::new(|| {
             // Go through the entire PATH and read all files into a set.
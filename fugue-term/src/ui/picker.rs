@@ -33,8 +33,9 @@
 
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io::Read,
+    ops,
     path::Path,
     sync::{
         atomic::{self, AtomicUsize},
@@ -44,12 +45,14 @@
 
 use crate::ui::{Prompt, PromptEvent};
 use fugue_core::{
-    char_idx_at_visual_offset, fuzzy::MATCHER, movement::Direction,
-    text_annotations::TextAnnotations, unicode::segmentation::UnicodeSegmentation, Position,
+    char_idx_at_visual_offset, fuzzy::MATCHER, movement::Direction, syntax::OverlayHighlights,
+    text_annotations::TextAnnotations, unicode::segmentation::UnicodeSegmentation, Position, Rope,
 };
 use fugue_view::{
     editor::Action,
     graphics::{CursorKind, Margin, Modifier, Rect},
+    input::KeyEvent,
+    quickfix::{QuickfixEntry, QuickfixList},
     theme::Style,
     view::ViewPosition,
     Document, DocumentId, Editor,
@@ -67,6 +70,8 @@
 pub enum PathOrId<'a> {
     Id(DocumentId),
     Path(&'a Path),
+    /// Text generated by the picker's text preview function, cached under this key.
+    Text(&'a str),
 }
 
 impl<'a> From<&'a Path> for PathOrId<'a> {
@@ -83,6 +88,22 @@ fn from(v: DocumentId) -> Self {
 
 type FileCallback<T> = Box<dyn for<'a> Fn(&'a Editor, &'a T) -> Option<FileLocation<'a>>>;
 
+/// Returns the character offset of an item in the line of its location.
+type ColumnCallback<T> = Box<dyn Fn(&Editor, &T) -> usize>;
+
+/// Returns the character index the text of the primary column of an item starts at in its
+/// preview.
+type PreviewMatchCallback<T> = Box<dyn Fn(&Editor, &T) -> Option<usize>>;
+
+/// Generates the text to preview for an item along with the id of its language.
+type TextCallback<T> = Box<dyn Fn(&Editor, &T) -> Option<(String, Option<&'static str>)>>;
+
+type KeyActionCallback<T> = Box<dyn Fn(&mut Context, &T)>;
+
+/// Runs on the marked items, or the selected one if none are marked. The returned callback runs
+/// once the picker is closed, for example to ask for confirmation.
+type BatchActionCallback<T> = Box<dyn Fn(&mut Context, Vec<&T>) -> Option<compositor::Callback>>;
+
 /// File path and range of lines (used to align and highlight lines)
 pub type FileLocation<'a> = (PathOrId<'a>, Option<(usize, usize)>);
 
@@ -269,6 +290,23 @@ pub struct Picker<T: 'static + Send + Sync, D: 'static> {
     read_buffer: Vec<u8>,
     /// Given an item in the picker, return the file path and line number to display.
     file_fn: Option<FileCallback<T>>,
+    /// Generates the previews of [`PathOrId::Text`] locations.
+    text_fn: Option<TextCallback<T>>,
+    /// Locates the primary column of the items in their preview to highlight the match there.
+    preview_match_fn: Option<PreviewMatchCallback<T>>,
+    /// Caches generated previews by their key.
+    text_preview_cache: HashMap<String, CachedPreview>,
+    /// Additional actions run on the selected item when their key is pressed.
+    key_actions: Vec<(KeyEvent, KeyActionCallback<T>)>,
+    /// Additional actions run on the marked items when their key is pressed.
+    batch_actions: Vec<(KeyEvent, BatchActionCallback<T>)>,
+    /// Name of the quickfix list the matched items are exported to.
+    quickfix_name: &'static str,
+    /// Given an item in the picker, return the column exported to the quickfix list.
+    quickfix_column_fn: Option<ColumnCallback<T>>,
+    /// The addresses of the marked items. Items never move once injected, so marks survive
+    /// query changes until the items are cleared.
+    marked: HashSet<usize>,
     /// An event handler for syntax highlighting the currently previewed file.
     preview_highlight_handler: Sender<Arc<Path>>,
     dynamic_query_handler: Option<Sender<DynamicQueryChange>>,
@@ -395,6 +433,14 @@ fn with(
             preview_cache: HashMap::new(),
             read_buffer: Vec::with_capacity(1024),
             file_fn: None,
+            text_fn: None,
+            preview_match_fn: None,
+            text_preview_cache: HashMap::new(),
+            key_actions: Vec::new(),
+            batch_actions: Vec::new(),
+            quickfix_name: "picker",
+            quickfix_column_fn: None,
+            marked: HashSet::new(),
             preview_highlight_handler: PreviewHighlightHandler::<T, D>::default().spawn(),
             dynamic_query_handler: None,
         }
@@ -427,6 +473,67 @@ pub fn with_preview(
         self
     }
 
+    /// Previews items with generated text, like the diff of a commit. `key_fn` identifies the
+    /// text of an item so that it is only generated once.
+    pub fn with_text_preview(
+        mut self,
+        key_fn: impl for<'a> Fn(&'a T) -> &'a str + 'static,
+        text_fn: impl Fn(&Editor, &T) -> Option<(String, Option<&'static str>)> + 'static,
+    ) -> Self {
+        self.file_fn = Some(Box::new(move |_editor, item| {
+            Some((PathOrId::Text(key_fn(item)), None))
+        }));
+        self.text_fn = Some(Box::new(text_fn));
+        self
+    }
+
+    /// Runs `action` with the selected item and closes the picker when `key` is pressed.
+    pub fn with_key_action(
+        mut self,
+        key: KeyEvent,
+        action: impl Fn(&mut Context, &T) + 'static,
+    ) -> Self {
+        self.key_actions.push((key, Box::new(action)));
+        self
+    }
+
+    /// Runs `action` with the marked items, or the selected one if none are marked, and closes
+    /// the picker when `key` is pressed.
+    pub fn with_batch_action(
+        mut self,
+        key: KeyEvent,
+        action: impl Fn(&mut Context, Vec<&T>) -> Option<compositor::Callback> + 'static,
+    ) -> Self {
+        self.batch_actions.push((key, Box::new(action)));
+        self
+    }
+
+    /// Names the quickfix list the matched items are exported to with `C-q`.
+    pub fn with_quickfix_name(mut self, name: &'static str) -> Self {
+        self.quickfix_name = name;
+        self
+    }
+
+    /// Highlights the characters matched in the primary column of the items in their preview,
+    /// which shows that text starting at the character index returned by `match_fn`.
+    pub fn with_preview_match(
+        mut self,
+        match_fn: impl Fn(&Editor, &T) -> Option<usize> + 'static,
+    ) -> Self {
+        self.preview_match_fn = Some(Box::new(match_fn));
+        self
+    }
+
+    /// Sets the character offset in their line the items are exported to the quickfix list
+    /// with. Items are exported at the start of their line otherwise.
+    pub fn with_quickfix_column(
+        mut self,
+        column_fn: impl Fn(&Editor, &T) -> usize + 'static,
+    ) -> Self {
+        self.quickfix_column_fn = Some(Box::new(column_fn));
+        self
+    }
+
     pub fn with_history_register(mut self, history_register: Option<char>) -> Self {
         self.prompt.with_history_register(history_register);
         self
@@ -508,6 +615,124 @@ pub fn selection(&self) -> Option<&T> {
             .map(|item| item.data)
     }
 
+    /// Toggles the mark of the selected item.
+    pub fn toggle_mark(&mut self) {
+        if let Some(item) = self.selection() {
+            let key = item_key(item);
+            if !self.marked.remove(&key) {
+                self.marked.insert(key);
+            }
+        }
+    }
+
+    /// Marks all matched items.
+    pub fn mark_matched(&mut self) {
+        let snapshot = self.matcher.snapshot();
+        self.marked
+            .extend(snapshot.matched_items(..).map(|item| item_key(item.data)));
+    }
+
+    /// Inverts the marks of the matched items.
+    pub fn invert_marks(&mut self) {
+        let snapshot = self.matcher.snapshot();
+        for item in snapshot.matched_items(..) {
+            let key = item_key(item.data);
+            if !self.marked.remove(&key) {
+                self.marked.insert(key);
+            }
+        }
+    }
+
+    /// Returns the marked items in the order they were added to the picker, including the
+    /// ones the query doesn't match.
+    pub fn marked_items(&self) -> Vec<&T> {
+        if self.marked.is_empty() {
+            return Vec::new();
+        }
+        let snapshot = self.matcher.snapshot();
+        (0..snapshot.item_count())
+            .filter_map(|idx| snapshot.get_item(idx))
+            .map(|item| item.data)
+            .filter(|data| self.marked.contains(&item_key(data)))
+            .collect()
+    }
+
+    /// The items actions run on: the marked items or else the selected one.
+    fn targets(&self) -> Vec<&T> {
+        if self.marked.is_empty() {
+            self.selection().into_iter().collect()
+        } else {
+            self.marked_items()
+        }
+    }
+
+    /// Runs the picker callback with `action` on the marked items or the selected one.
+    fn run_callback(&self, cx: &mut Context, action: Action) {
+        for item in self.targets() {
+            (self.callback_fn)(cx, item, action);
+        }
+    }
+
+    /// Exports the locations of the marked items, or all matched items if none are marked, into
+    /// a quickfix list and makes it the current one. Returns whether any item has a location.
+    fn export_quickfix(&self, editor: &mut Editor) -> bool {
+        let Some(file_fn) = &self.file_fn else {
+            return false;
+        };
+        let snapshot = self.matcher.snapshot();
+        let items: Vec<_> = if self.marked.is_empty() {
+            snapshot.matched_items(..).map(|item| item.data).collect()
+        } else {
+            self.marked_items()
+        };
+        let entries: Vec<_> = items
+            .into_iter()
+            .filter_map(|item| {
+                let (location, lines) = file_fn(editor, item)?;
+                let path = match location {
+                    PathOrId::Path(path) => fugue_stdx::path::canonicalize(path),
+                    PathOrId::Id(id) => editor.document(id)?.path()?.clone(),
+                    PathOrId::Text(_) => return None,
+                };
+                if path.is_dir() {
+                    return None;
+                }
+                // the location is shown separately
+                let text = self
+                    .columns
+                    .iter()
+                    .filter(|column| !column.hidden && &*column.name != "path")
+                    .map(|column| column.format_text(item, &self.editor_data))
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                let column = self
+                    .quickfix_column_fn
+                    .as_ref()
+                    .map_or(0, |column_fn| column_fn(editor, item));
+                Some(QuickfixEntry::new(
+                    path,
+                    lines.map_or(0, |(start, _)| start),
+                    column,
+                    text,
+                ))
+            })
+            .collect();
+        if entries.is_empty() {
+            return false;
+        }
+
+        editor.set_status(format!(
+            "Exported {} entries to the quickfix list '{}'",
+            entries.len(),
+            self.quickfix_name
+        ));
+        editor
+            .quickfix
+            .set(QuickfixList::new(self.quickfix_name.to_owned(), entries));
+        true
+    }
+
     fn primary_query(&self) -> Arc<str> {
         self.query
             .get(&self.columns[self.primary_column].name)
@@ -583,6 +808,57 @@ fn handle_prompt_change(&mut self, is_paste: bool) {
         }
     }
 
+    /// The character ranges of the preview matched by the query in the primary column of the
+    /// selected item.
+    fn preview_match_ranges(&self, editor: &Editor) -> Vec<ops::Range<usize>> {
+        let Some(match_fn) = &self.preview_match_fn else {
+            return Vec::new();
+        };
+        let snapshot = self.matcher.snapshot();
+        let Some(item) = snapshot.get_matched_item(self.cursor) else {
+            return Vec::new();
+        };
+        let Some(start) = match_fn(editor, item.data) else {
+            return Vec::new();
+        };
+        let column = &self.columns[self.primary_column];
+        if !column.filter {
+            return Vec::new();
+        }
+        let matcher_index = self.columns[..self.primary_column]
+            .iter()
+            .filter(|column| column.filter)
+            .count();
+
+        let mut indices = Vec::new();
+        let mut matcher = MATCHER.lock();
+        matcher.config = Config::DEFAULT;
+        if self.file_fn.is_some() {
+            matcher.config.set_match_paths()
+        }
+        snapshot.pattern().column_pattern(matcher_index).indices(
+            item.matcher_columns[matcher_index].slice(..),
+            &mut matcher,
+            &mut indices,
+        );
+        indices.sort_unstable();
+        indices.dedup();
+
+        // nucleo only considers the first char of a grapheme, its indices are grapheme indices
+        let text = column.format_text(item.data, &self.editor_data);
+        let mut indices = indices.into_iter().peekable();
+        let mut ranges = Vec::new();
+        let mut char_idx = start;
+        for (grapheme_idx, grapheme) in text.graphemes(true).enumerate() {
+            let len = grapheme.chars().count();
+            if indices.next_if_eq(&(grapheme_idx as u32)).is_some() {
+                ranges.push(char_idx..char_idx + len);
+            }
+            char_idx += len;
+        }
+        ranges
+    }
+
     /// Get (cached) preview for the currently selected item. If a document corresponding
     /// to the path is already open in the editor, it is used instead.
     fn get_preview<'picker, 'editor>(
@@ -681,6 +957,32 @@ fn get_preview<'picker, 'editor>(
                 let doc = editor.documents.get(&id).unwrap();
                 Some((Preview::EditorDocument(doc), range))
             }
+            PathOrId::Text(key) => {
+                let key = key.to_owned();
+                if !self.text_preview_cache.contains_key(&key) {
+                    let preview = match (self.text_fn.as_ref()?)(editor, current) {
+                        Some((text, language)) => {
+                            let mut doc = Document::from(
+                                Rope::from(text),
+                                None,
+                                editor.config.clone(),
+                                editor.syn_loader.clone(),
+                            );
+                            if let Some(language) = language {
+                                let loader = editor.syn_loader.load();
+                                if let Err(err) = doc.set_language_by_language_id(language, &loader)
+                                {
+                                    log::debug!("{err}");
+                                }
+                            }
+                            CachedPreview::Document(Box::new(doc))
+                        }
+                        None => CachedPreview::NotFound,
+                    };
+                    self.text_preview_cache.insert(key.clone(), preview);
+                }
+                Some((Preview::Cached(&self.text_preview_cache[&key]), range))
+            }
         }
     }
 
@@ -695,6 +997,11 @@ fn render_picker(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context)
 
         let text_style = cx.editor.theme.get("ui.text");
         let selected = cx.editor.theme.get("ui.text.focus");
+        let marked_style = cx
+            .editor
+            .theme
+            .try_get("ui.picker.marked")
+            .unwrap_or_else(|| cx.editor.theme.get("ui.selection"));
         let highlight_style = cx.editor.theme.get("special").add_modifier(Modifier::BOLD);
 
         // -- Render the frame:
@@ -712,12 +1019,17 @@ fn render_picker(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context)
         // -- Render the input bar:
 
         let count = format!(
-            "{}{}/{}",
+            "{}{}{}/{}",
             if status.running || self.matcher.active_injectors() > 0 {
                 "(running) "
             } else {
                 ""
             },
+            if self.marked.is_empty() {
+                String::new()
+            } else {
+                format!("{} marked ", self.marked.len())
+            },
             snapshot.matched_item_count(),
             snapshot.item_count(),
         );
@@ -764,8 +1076,9 @@ fn render_picker(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context)
         let options = snapshot.matched_items(offset..end).map(|item| {
             let mut widths = self.widths.iter_mut();
             let mut matcher_index = 0;
+            let is_marked = self.marked.contains(&item_key(item.data));
 
-            Row::new(self.columns.iter().map(|column| {
+            let row = Row::new(self.columns.iter().map(|column| {
                 if column.hidden {
                     return Cell::default();
                 }
@@ -835,7 +1148,12 @@ fn render_picker(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context)
                 }
 
                 cell
-            }))
+            }));
+            if is_marked {
+                row.style(marked_style)
+            } else {
+                row
+            }
         });
 
         let mut table = Table::new(options)
@@ -900,6 +1218,7 @@ fn render_preview(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context
         let inner = inner.inner(margin);
         BLOCK.render(area, surface);
 
+        let match_ranges = self.preview_match_ranges(cx.editor);
         if let Some((preview, range)) = self.get_preview(cx.editor) {
             let doc = match preview.document() {
                 Some(doc)
@@ -972,6 +1291,14 @@ fn render_preview(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context
                 &cx.editor.theme,
                 &mut overlay_highlights,
             );
+            if let Some(highlight) = cx.editor.theme.find_highlight_exact("special") {
+                if !match_ranges.is_empty() {
+                    overlay_highlights.push(OverlayHighlights::Homogeneous {
+                        highlight,
+                        ranges: match_ranges,
+                    });
+                }
+            }
 
             let mut decorations = DecorationManager::default();
 
@@ -1048,28 +1375,45 @@ fn handle_event(&mut self, event: &Event, ctx: &mut Context) -> EventResult {
             _ => return EventResult::Ignored(None),
         };
 
-        let close_fn = |picker: &mut Self| {
+        let close_callback = |picker: &mut Self| -> compositor::Callback {
             // if the picker is very large don't store it as last_picker to avoid
             // excessive memory consumption
-            let callback: compositor::Callback =
-                if picker.matcher.snapshot().item_count() > 1_000_000 {
-                    Box::new(|compositor: &mut Compositor, _ctx| {
-                        // remove the layer
-                        compositor.pop();
-                    })
-                } else {
-                    // stop streaming in new items in the background, really we should
-                    // be restarting the stream somehow once the picker gets
-                    // reopened instead (like for an FS crawl) that would also remove the
-                    // need for the special case above but that is pretty tricky
-                    picker.version.fetch_add(1, atomic::Ordering::Relaxed);
-                    Box::new(|compositor: &mut Compositor, _ctx| {
-                        // remove the layer
-                        compositor.last_picker = compositor.pop();
-                    })
-                };
-            EventResult::Consumed(Some(callback))
+            if picker.matcher.snapshot().item_count() > 1_000_000 {
+                Box::new(|compositor: &mut Compositor, _ctx| {
+                    // remove the layer
+                    compositor.pop();
+                })
+            } else {
+                // stop streaming in new items in the background, really we should
+                // be restarting the stream somehow once the picker gets
+                // reopened instead (like for an FS crawl) that would also remove the
+                // need for the special case above but that is pretty tricky
+                picker.version.fetch_add(1, atomic::Ordering::Relaxed);
+                Box::new(|compositor: &mut Compositor, _ctx| {
+                    // remove the layer
+                    compositor.last_picker = compositor.pop();
+                })
+            }
         };
+        let close_fn = |picker: &mut Self| EventResult::Consumed(Some(close_callback(picker)));
+
+        if let Some((_, action)) = self.key_actions.iter().find(|(key, _)| *key == key_event) {
+            if let Some(option) = self.selection() {
+                action(ctx, option);
+            }
+            return close_fn(self);
+        }
+        if let Some((_, action)) = self.batch_actions.iter().find(|(key, _)| *key == key_event) {
+            let after = action(ctx, self.targets());
+            let close = close_callback(self);
+            let callback: compositor::Callback = Box::new(|compositor, cx| {
+                close(compositor, cx);
+                if let Some(after) = after {
+                    after(compositor, cx);
+                }
+            });
+            return EventResult::Consumed(Some(callback));
+        }
 
         match key_event {
             shift!(Tab) | key!(Up) | ctrl!('p') => {
@@ -1091,11 +1435,13 @@ fn handle_event(&mut self, event: &Event, ctx: &mut Context) -> EventResult {
                 self.to_end();
             }
             key!(Esc) | ctrl!('c') => return close_fn(self),
-            alt!(Enter) => {
-                if let Some(option) = self.selection() {
-                    (self.callback_fn)(ctx, option, self.default_action);
-                }
+            alt!(Enter) => self.run_callback(ctx, self.default_action),
+            alt!('m') => {
+                self.toggle_mark();
+                self.move_by(1, Direction::Forward);
             }
+            alt!('a') => self.mark_matched(),
+            alt!('i') => self.invert_marks(),
             key!(Enter) => {
                 // If the prompt has a history completion and is empty, use enter to accept
                 // that completion
@@ -1116,9 +1462,7 @@ fn handle_event(&mut self, event: &Event, ctx: &mut Context) -> EventResult {
                     // Inserting from the history register is a paste.
                     self.handle_prompt_change(true);
                 } else {
-                    if let Some(option) = self.selection() {
-                        (self.callback_fn)(ctx, option, self.default_action);
-                    }
+                    self.run_callback(ctx, self.default_action);
                     if let Some(history_register) = self.prompt.history_register() {
                         if let Err(err) = ctx
                             .editor
@@ -1132,20 +1476,29 @@ fn handle_event(&mut self, event: &Event, ctx: &mut Context) -> EventResult {
                 }
             }
             ctrl!('s') => {
-                if let Some(option) = self.selection() {
-                    (self.callback_fn)(ctx, option, Action::HorizontalSplit);
-                }
+                self.run_callback(ctx, Action::HorizontalSplit);
                 return close_fn(self);
             }
             ctrl!('v') => {
-                if let Some(option) = self.selection() {
-                    (self.callback_fn)(ctx, option, Action::VerticalSplit);
-                }
+                self.run_callback(ctx, Action::VerticalSplit);
                 return close_fn(self);
             }
             ctrl!('t') => {
                 self.toggle_preview();
             }
+            ctrl!('q') => {
+                if !self.export_quickfix(ctx.editor) {
+                    ctx.editor
+                        .set_error("No locations to export to a quickfix list");
+                    return EventResult::Consumed(None);
+                }
+                let close = close_callback(self);
+                let callback: compositor::Callback = Box::new(|compositor, cx| {
+                    close(compositor, cx);
+                    ui::quickfix::open(compositor, false);
+                });
+                return EventResult::Consumed(Some(callback));
+            }
             _ => {
                 self.prompt_handle_event(event, ctx);
             }
@@ -1190,3 +1543,8 @@ fn drop(&mut self) {
 }
 
 type PickerCallback<T> = Box<dyn Fn(&mut Context, &T, Action)>;
+
+/// Identifies an item by its address, which is stable while the item is in the picker.
+fn item_key<T>(item: &T) -> usize {
+    std::ptr::from_ref(item) as usize
+}
@@ -0,0 +1,210 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use fugue_stdx::path::get_relative_path;
+use fugue_view::graphics::{Modifier, Rect};
+use fugue_view::input::{MouseButton, MouseEvent, MouseEventKind};
+use fugue_view::Editor;
+use tui::buffer::Buffer as Surface;
+
+use crate::commands::goto_quickfix_entry;
+use crate::compositor::{Callback, Component, Compositor, Context, Event, EventResult};
+use crate::ui::dock::DockSide;
+use crate::ui::EditorView;
+use crate::{ctrl, key, shift};
+
+pub const ID: &str = "quickfix";
+
+/// Bottom panel listing the entries of the current quickfix list.
+#[derive(Debug, Default)]
+pub struct QuickfixView {
+    /// Index of the selected entry.
+    cursor: usize,
+    /// Index of the first visible entry.
+    scroll: usize,
+    /// The current entry of the list the last time it was rendered, used to follow navigation
+    /// commands.
+    current: Option<(String, usize)>,
+    /// Screen row of the first entry, recorded on render to map mouse clicks to entries.
+    row_offset: Option<u16>,
+}
+
+/// Docks the quickfix panel to the bottom of the editor, giving it key focus if `focus` is set.
+pub fn open(compositor: &mut Compositor, focus: bool) {
+    let Some(editor_view) = compositor.find::<EditorView>() else {
+        return;
+    };
+    if !editor_view.dock.focus(ID) {
+        editor_view
+            .dock
+            .open(DockSide::Bottom, 10, QuickfixView::default());
+    }
+    if !focus {
+        editor_view.dock.unfocus();
+    }
+}
+
+pub fn close(compositor: &mut Compositor) {
+    if let Some(editor_view) = compositor.find::<EditorView>() {
+        editor_view.dock.close(ID);
+    }
+}
+
+impl QuickfixView {
+    fn move_cursor(&mut self, editor: &Editor, delta: isize) {
+        let len = editor
+            .quickfix
+            .current()
+            .map_or(0, |list| list.entries.len());
+        self.cursor = self
+            .cursor
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+    }
+
+    fn select(&mut self, editor: &mut Editor) -> Option<Callback> {
+        if let Err(err) = goto_quickfix_entry(editor, self.cursor) {
+            editor.set_error(err.to_string());
+            return None;
+        }
+        // continue editing at the entry
+        Some(Box::new(|compositor: &mut Compositor, _| {
+            if let Some(editor_view) = compositor.find::<EditorView>() {
+                editor_view.dock.unfocus();
+            }
+        }))
+    }
+}
+
+impl Component for QuickfixView {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let callback = match event {
+            Event::Key(key) => {
+                match *key {
+                    key!('j') | key!(Down) | ctrl!('n') => {
+                        self.move_cursor(cx.editor, 1);
+                        None
+                    }
+                    key!('k') | key!(Up) | ctrl!('p') => {
+                        self.move_cursor(cx.editor, -1);
+                        None
+                    }
+                    key!('g') | key!(Home) => {
+                        self.cursor = 0;
+                        None
+                    }
+                    shift!('G') | key!('G') | key!(End) => {
+                        self.move_cursor(cx.editor, isize::MAX);
+                        None
+                    }
+                    key!(Enter) => self.select(cx.editor),
+                    key!('q') => Some(Box::new(|compositor: &mut Compositor, _: &mut Context| {
+                        close(compositor)
+                    }) as Callback),
+                    _ => return EventResult::Ignored(None),
+                }
+            }
+            Event::Mouse(MouseEvent { kind, row, .. }) => match kind {
+                MouseEventKind::ScrollDown => {
+                    self.move_cursor(cx.editor, 3);
+                    None
+                }
+                MouseEventKind::ScrollUp => {
+                    self.move_cursor(cx.editor, -3);
+                    None
+                }
+                MouseEventKind::Down(MouseButton::Left) => {
+                    let Some(offset) = self.row_offset.and_then(|top| row.checked_sub(top)) else {
+                        return EventResult::Consumed(None);
+                    };
+                    self.cursor = self.scroll + offset as usize;
+                    self.move_cursor(cx.editor, 0);
+                    self.select(cx.editor)
+                }
+                _ => return EventResult::Ignored(None),
+            },
+            _ => return EventResult::Ignored(None),
+        };
+        EventResult::Consumed(callback)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        cx.editor.sync_quickfix();
+        let theme = &cx.editor.theme;
+        let text_style = theme.get("ui.text");
+        let title_style = theme.get("ui.text.focus").add_modifier(Modifier::BOLD);
+        let location_style = theme.get("ui.linenr");
+        let selected_style = theme.get("ui.selection");
+        let width = area.width as usize;
+
+        let Some(list) = cx.editor.quickfix.current() else {
+            surface.set_stringn(area.x, area.y, "Quickfix", width, title_style);
+            surface.set_stringn(
+                area.x,
+                area.y + 1,
+                "No quickfix list, export one from a picker with C-q",
+                width,
+                text_style,
+            );
+            return;
+        };
+
+        let title = format!(
+            "Quickfix: {} ({}/{})",
+            list.name,
+            (list.index + 1).min(list.entries.len()),
+            list.entries.len()
+        );
+        surface.set_stringn(area.x, area.y, title, width, title_style);
+        let area = area.clip_top(1);
+        self.row_offset = Some(area.y);
+
+        let current = Some((list.name.clone(), list.index));
+        if current != self.current {
+            self.current = current;
+            self.cursor = list.index;
+        }
+        self.cursor = self.cursor.min(list.entries.len().saturating_sub(1));
+
+        let height = area.height as usize;
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if height > 0 && self.cursor >= self.scroll + height {
+            self.scroll = self.cursor + 1 - height;
+        }
+
+        for (i, entry) in list
+            .entries
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(height)
+        {
+            let y = area.y + (i - self.scroll) as u16;
+            let (style, location_style) = if i == self.cursor {
+                surface.set_style(Rect::new(area.x, y, area.width, 1), selected_style);
+                (
+                    text_style.patch(selected_style),
+                    location_style.patch(selected_style),
+                )
+            } else {
+                (text_style, location_style)
+            };
+            let marker = if i == list.index { "▶ " } else { "  " };
+            let location = format!(
+                "{marker}{}:{} ",
+                get_relative_path(&entry.path).display(),
+                entry.line() + 1
+            );
+            let (x, _) = surface.set_stringn(area.x, y, location, width, location_style);
+            let remaining = width.saturating_sub((x - area.x) as usize);
+            // only the first line of multi-line messages fits
+            let text = entry.text.lines().next().unwrap_or_default();
+            surface.set_stringn(x, y, text, remaining, style);
+        }
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(ID)
+    }
+}
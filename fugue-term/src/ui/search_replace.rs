@@ -0,0 +1,631 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use std::fs::File;
+use std::io::Read;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use anyhow::bail;
+use fugue_core::regex::Regex;
+use fugue_core::{Rope, RopeSlice, Transaction};
+use fugue_stdx::path::get_relative_path;
+use fugue_vcs::unified_diff;
+use fugue_view::document::from_reader;
+use fugue_view::editor::{Action, FilePickerConfig};
+use fugue_view::graphics::{Modifier, Rect};
+use fugue_view::theme::Style;
+use fugue_view::Editor;
+use ignore::WalkBuilder;
+use tui::buffer::Buffer as Surface;
+use tui::widgets::{Block, Widget};
+
+use crate::compositor::{Callback, Component, Compositor, Context, Event, EventResult};
+use crate::{ctrl, filter_picker_entry, key};
+
+/// Searches stop once this many matches were found.
+const MAX_MATCHES: usize = 10_000;
+
+/// What to replace the matches of a search with.
+#[derive(Debug, Clone)]
+pub struct Replacement {
+    pub regex: Regex,
+    /// The replacement, expanding capture groups like `$1` or `${name}` unless `literal` is set.
+    pub replacement: String,
+    pub literal: bool,
+}
+
+impl Replacement {
+    /// Finds the byte ranges of the matches in `text` along with their replacements.
+    fn find(&self, text: &str) -> Vec<(Range<usize>, String)> {
+        let mut matches = Vec::new();
+        for captures in self.regex.captures_iter(text) {
+            let found = captures.get(0).unwrap();
+            // replacing empty matches, like `^`, is rarely intended in a whole project
+            if found.is_empty() {
+                continue;
+            }
+            let mut replacement = String::new();
+            if self.literal {
+                replacement.push_str(&self.replacement);
+            } else {
+                captures.expand(&self.replacement, &mut replacement);
+            }
+            matches.push((found.range(), replacement));
+        }
+        matches
+    }
+}
+
+#[derive(Debug)]
+struct Match {
+    /// The characters to replace.
+    range: Range<usize>,
+    line: usize,
+    replacement: String,
+    included: bool,
+}
+
+#[derive(Debug)]
+pub struct FileMatches {
+    path: PathBuf,
+    /// The text the matches were found in: the text of the open document or the file on disk.
+    text: Rope,
+    matches: Vec<Match>,
+}
+
+impl FileMatches {
+    /// Creates the matches of a file from the byte ranges of the matches in `text` and their
+    /// replacements. The ranges must be sorted and must not overlap.
+    pub fn new(
+        path: PathBuf,
+        text: Rope,
+        matches: impl IntoIterator<Item = (Range<usize>, String)>,
+    ) -> Self {
+        let matches = matches
+            .into_iter()
+            .map(|(range, replacement)| {
+                let start = text.byte_to_char(range.start);
+                Match {
+                    range: start..text.byte_to_char(range.end),
+                    line: text.char_to_line(start),
+                    replacement,
+                    included: true,
+                }
+            })
+            .collect();
+        Self {
+            path,
+            text,
+            matches,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn text(&self) -> &Rope {
+        &self.text
+    }
+
+    /// Returns the character ranges of the matches along with their lines.
+    pub fn locations(&self) -> impl Iterator<Item = (Range<usize>, usize)> + '_ {
+        self.matches.iter().map(|m| (m.range.clone(), m.line))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+
+    /// The text with the included matches replaced.
+    fn replaced(&self) -> Rope {
+        let transaction = Transaction::change(&self.text, self.changes());
+        let mut text = self.text.clone();
+        transaction.apply(&mut text);
+        text
+    }
+
+    fn changes(&self) -> impl Iterator<Item = (usize, usize, Option<fugue_core::Tendril>)> + '_ {
+        self.matches.iter().filter(|m| m.included).map(|m| {
+            (
+                m.range.start,
+                m.range.end,
+                Some(m.replacement.as_str().into()),
+            )
+        })
+    }
+}
+
+/// Searches the files below `root` that the file picker would show for matches of
+/// `replacement`. Open documents are searched instead of their files, so unsaved edits are
+/// replaced as well.
+pub fn search_workspace(
+    root: &Path,
+    config: &FilePickerConfig,
+    documents: &[(PathBuf, Rope)],
+    replacement: &Replacement,
+) -> Vec<FileMatches> {
+    search_workspace_with(
+        root,
+        config,
+        documents,
+        |_| true,
+        |_, text| {
+            let text = text.to_string();
+            if !replacement.regex.is_match(&text) {
+                return Vec::new();
+            }
+            replacement.find(&text)
+        },
+    )
+}
+
+/// Searches the files below `root` that the file picker would show and `filter` accepts with
+/// `find`, which returns the byte ranges of the matches in a file and their replacements.
+pub fn search_workspace_with(
+    root: &Path,
+    config: &FilePickerConfig,
+    documents: &[(PathBuf, Rope)],
+    filter: impl Fn(&Path) -> bool,
+    mut find: impl FnMut(&Path, &Rope) -> Vec<(Range<usize>, String)>,
+) -> Vec<FileMatches> {
+    let absolute_root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let dedup_symlinks = config.deduplicate_links;
+    let walk = WalkBuilder::new(root)
+        .hidden(config.hidden)
+        .parents(config.parents)
+        .ignore(config.ignore)
+        .follow_links(config.follow_symlinks)
+        .git_ignore(config.git_ignore)
+        .git_global(config.git_global)
+        .git_exclude(config.git_exclude)
+        .max_depth(config.max_depth)
+        .filter_entry(move |entry| filter_picker_entry(entry, &absolute_root, dedup_symlinks))
+        .add_custom_ignore_filename(fugue_loader::config_dir().join("ignore"))
+        .add_custom_ignore_filename(".fugue/ignore")
+        .sort_by_file_path(Ord::cmp)
+        .build();
+
+    let mut files = Vec::new();
+    let mut count = 0;
+    for entry in walk.flatten() {
+        if !entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+        {
+            continue;
+        }
+        let path = fugue_stdx::path::canonicalize(entry.path());
+        if !filter(&path) {
+            continue;
+        }
+        let text = match documents.iter().find(|(doc_path, _)| *doc_path == path) {
+            Some((_, text)) => text.clone(),
+            None => match read_text(&path) {
+                Some(text) => text,
+                None => continue,
+            },
+        };
+        let matches = find(&path, &text);
+        if matches.is_empty() {
+            continue;
+        }
+        count += matches.len();
+        files.push(FileMatches::new(path, text, matches));
+        if count >= MAX_MATCHES {
+            log::warn!("search and replace stopped after {MAX_MATCHES} matches");
+            break;
+        }
+    }
+    files
+}
+
+/// Reads a file that isn't binary, decoding it like a document opened from it would.
+fn read_text(path: &Path) -> Option<Rope> {
+    let mut content = Vec::new();
+    File::open(path).ok()?.read_to_end(&mut content).ok()?;
+    let peek = &content[..content.len().min(1024)];
+    if content_inspector::inspect(peek).is_binary() {
+        return None;
+    }
+    let (text, ..) = from_reader(&mut content.as_slice(), None).ok()?;
+    Some(text)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    File(usize),
+    Match(usize, usize),
+}
+
+/// Previews the replacements of a project wide search as a diff of each file. Matches can be
+/// excluded before the replacements are applied.
+pub struct SearchReplace {
+    files: Vec<FileMatches>,
+    /// A description of the search and its replacement.
+    title: String,
+    rows: Vec<Row>,
+    /// Index of the selected row.
+    cursor: usize,
+    /// Index of the first visible row.
+    scroll: usize,
+    /// The diff of the selected file, computed when it is first shown.
+    diff: Option<(usize, Vec<String>)>,
+}
+
+impl SearchReplace {
+    pub fn new(files: Vec<FileMatches>, title: String) -> Self {
+        let rows = files
+            .iter()
+            .enumerate()
+            .flat_map(|(i, file)| {
+                std::iter::once(Row::File(i))
+                    .chain((0..file.matches.len()).map(move |j| Row::Match(i, j)))
+            })
+            .collect();
+        Self {
+            files,
+            title,
+            rows,
+            cursor: 0,
+            scroll: 0,
+            diff: None,
+        }
+    }
+
+    fn file_idx(&self) -> Option<usize> {
+        match self.rows.get(self.cursor)? {
+            Row::File(i) | Row::Match(i, _) => Some(*i),
+        }
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        self.cursor = self
+            .cursor
+            .saturating_add_signed(delta)
+            .min(self.rows.len().saturating_sub(1));
+    }
+
+    /// Toggles the selected match, or all matches of the selected file.
+    fn toggle(&mut self) {
+        match self.rows.get(self.cursor) {
+            Some(&Row::File(i)) => {
+                let matches = &mut self.files[i].matches;
+                let included = !matches.iter().all(|m| m.included);
+                matches.iter_mut().for_each(|m| m.included = included);
+                self.diff = None;
+            }
+            Some(&Row::Match(i, j)) => {
+                let m = &mut self.files[i].matches[j];
+                m.included = !m.included;
+                self.diff = None;
+                self.move_cursor(1);
+            }
+            None => (),
+        }
+    }
+
+    fn toggle_all(&mut self) {
+        let included = !self
+            .files
+            .iter()
+            .flat_map(|file| &file.matches)
+            .all(|m| m.included);
+        for m in self.files.iter_mut().flat_map(|file| &mut file.matches) {
+            m.included = included;
+        }
+        self.diff = None;
+    }
+
+    fn counts(&self) -> (usize, usize) {
+        let matches = self.files.iter().flat_map(|file| &file.matches);
+        let included = matches.clone().filter(|m| m.included).count();
+        (included, matches.count())
+    }
+
+    fn diff(&mut self, file: usize) -> &[String] {
+        if !matches!(self.diff, Some((cached, _)) if cached == file) {
+            let before = self.files[file].text.to_string();
+            let after = self.files[file].replaced().to_string();
+            let lines = unified_diff(&before, &after)
+                .lines()
+                .map(str::to_owned)
+                .collect();
+            self.diff = Some((file, lines));
+        }
+        &self.diff.as_ref().unwrap().1
+    }
+
+    fn render_rows(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        let text_style = theme.get("ui.text");
+        let directory_style = theme.get("ui.text.directory");
+        let location_style = theme.get("ui.linenr");
+        let minus_style = theme.get("diff.minus");
+        let plus_style = theme.get("diff.plus");
+        let selected_style = theme.get("ui.selection");
+        let width = area.width as usize;
+
+        let height = area.height as usize;
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if height > 0 && self.cursor >= self.scroll + height {
+            self.scroll = self.cursor + 1 - height;
+        }
+
+        for (i, row) in self.rows.iter().enumerate().skip(self.scroll).take(height) {
+            let y = area.y + (i - self.scroll) as u16;
+            let patch = |style: Style| {
+                if i == self.cursor {
+                    style.patch(selected_style)
+                } else {
+                    style
+                }
+            };
+            if i == self.cursor {
+                surface.set_style(Rect::new(area.x, y, area.width, 1), selected_style);
+            }
+            match *row {
+                Row::File(file) => {
+                    let file = &self.files[file];
+                    let included = file.matches.iter().filter(|m| m.included).count();
+                    let label = format!(
+                        "{} ({included}/{})",
+                        get_relative_path(&file.path).display(),
+                        file.matches.len()
+                    );
+                    surface.set_stringn(area.x, y, label, width, patch(directory_style));
+                }
+                Row::Match(file, m) => {
+                    let file = &self.files[file];
+                    let m = &file.matches[m];
+                    let check = if m.included { "[x]" } else { "[ ]" };
+                    let location = format!("  {check} {}: ", m.line + 1);
+                    let (x, _) =
+                        surface.set_stringn(area.x, y, location, width, patch(location_style));
+                    let found = display_line(file.text.slice(m.range.clone()));
+                    let remaining = width.saturating_sub((x - area.x) as usize);
+                    let (x, _) = surface.set_stringn(x, y, found, remaining, patch(minus_style));
+                    let remaining = width.saturating_sub((x - area.x) as usize);
+                    let (x, _) = surface.set_stringn(x, y, " → ", remaining, patch(text_style));
+                    let remaining = width.saturating_sub((x - area.x) as usize);
+                    let replacement = display_line(m.replacement.as_str().into());
+                    surface.set_stringn(x, y, replacement, remaining, patch(plus_style));
+                }
+            }
+        }
+    }
+
+    fn render_diff(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let Some(file) = self.file_idx() else {
+            return;
+        };
+        // scroll to the hunk of the selected match
+        let line = match self.rows[self.cursor] {
+            Row::Match(i, j) => Some(self.files[i].matches[j].line),
+            Row::File(_) => None,
+        };
+        let theme = &cx.editor.theme;
+        let styles = [
+            theme.get("ui.text"),
+            theme.get("diff.plus"),
+            theme.get("diff.minus"),
+            theme.get("diff.delta"),
+        ];
+        let diff = self.diff(file);
+        let start = line.and_then(|line| hunk_of_line(diff, line)).unwrap_or(0);
+        for (i, line) in diff
+            .iter()
+            .skip(start)
+            .take(area.height as usize)
+            .enumerate()
+        {
+            let style = match line.as_bytes().first() {
+                Some(b'+') => styles[1],
+                Some(b'-') => styles[2],
+                Some(b'@') => styles[3],
+                _ => styles[0],
+            };
+            surface.set_stringn(area.x, area.y + i as u16, line, area.width as usize, style);
+        }
+    }
+
+    /// Applies the included replacements, with one transaction per file that can be undone in
+    /// its buffer. Files that aren't open are opened in the background.
+    fn apply(&self, editor: &mut Editor, write: bool) -> anyhow::Result<()> {
+        let files: Vec<_> = self
+            .files
+            .iter()
+            .filter(|file| file.matches.iter().any(|m| m.included))
+            .collect();
+        // nothing is replaced, nor any file opened, if any file changed since the search
+        for file in &files {
+            let unchanged = match editor.document_by_path(&file.path) {
+                Some(doc) => *doc.text() == file.text,
+                None => read_text(&file.path).is_some_and(|text| text == file.text),
+            };
+            if !unchanged {
+                bail!(
+                    "{} changed since the search, search again",
+                    get_relative_path(&file.path).display()
+                );
+            }
+        }
+        let doc_ids = files
+            .iter()
+            .map(|file| match editor.document_by_path(&file.path) {
+                Some(doc) => Ok(doc.id()),
+                None => editor.open(&file.path, Action::Load),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let focus = editor.tree.focus;
+        for (file, &doc_id) in files.iter().zip(&doc_ids) {
+            let doc = doc_mut!(editor, &doc_id);
+            let view_id = doc.selections().keys().next().copied().unwrap_or(focus);
+            doc.ensure_view_init(view_id);
+            let view = view_mut!(editor, view_id);
+            view.sync_changes(doc);
+            let transaction = Transaction::change(doc.text(), file.changes());
+            doc.apply(&transaction, view_id);
+            doc.append_changes_to_history(view);
+        }
+        if write {
+            for &doc_id in &doc_ids {
+                editor.save::<PathBuf>(doc_id, None, false)?;
+            }
+        }
+
+        let (included, _) = self.counts();
+        editor.set_status(format!(
+            "Replaced {included} matches in {} files",
+            files.len()
+        ));
+        Ok(())
+    }
+
+    fn close(&self, editor: &mut Editor, apply: Option<bool>) -> EventResult {
+        if let Some(write) = apply {
+            if let Err(err) = self.apply(editor, write) {
+                editor.set_error(err.to_string());
+                return EventResult::Consumed(None);
+            }
+        }
+        let close: Callback = Box::new(|compositor: &mut Compositor, _| {
+            compositor.pop();
+        });
+        EventResult::Consumed(Some(close))
+    }
+}
+
+/// Shows the first line of `text`, marking that more lines follow.
+fn display_line(text: RopeSlice) -> String {
+    let mut lines = text.lines();
+    let first = lines.next().map(String::from).unwrap_or_default();
+    let first = first.trim_end_matches(['\n', '\r']);
+    if text.len_lines() > 1 {
+        format!("{first}…")
+    } else {
+        first.to_owned()
+    }
+}
+
+/// Returns the index of the header of the hunk of `diff` containing the zero-indexed `line` of
+/// the old file.
+fn hunk_of_line(diff: &[String], line: usize) -> Option<usize> {
+    diff.iter().rposition(|header| {
+        // headers look like `@@ -start,len +start,len @@`
+        header
+            .strip_prefix("@@ -")
+            .and_then(|header| header.split([',', ' ']).next())
+            .and_then(|start| start.parse::<usize>().ok())
+            .is_some_and(|start| start <= line + 1)
+    })
+}
+
+impl Component for SearchReplace {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let Event::Key(key) = event else {
+            return EventResult::Ignored(None);
+        };
+        match *key {
+            key!('j') | key!(Down) | ctrl!('n') => self.move_cursor(1),
+            key!('k') | key!(Up) | ctrl!('p') => self.move_cursor(-1),
+            key!(PageDown) | ctrl!('d') => self.move_cursor(10),
+            key!(PageUp) | ctrl!('u') => self.move_cursor(-10),
+            key!(Home) | key!('g') => self.cursor = 0,
+            key!(End) | key!('G') => self.move_cursor(isize::MAX),
+            key!(' ') | key!(Tab) => self.toggle(),
+            key!('a') => self.toggle_all(),
+            key!(Enter) => return self.close(cx.editor, Some(false)),
+            key!('w') => return self.close(cx.editor, Some(true)),
+            key!(Esc) | key!('q') | ctrl!('c') => return self.close(cx.editor, None),
+            _ => (),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let background = cx.editor.theme.get("ui.background");
+        let title_style = cx
+            .editor
+            .theme
+            .get("ui.text.focus")
+            .add_modifier(Modifier::BOLD);
+        let help_style = cx.editor.theme.get("ui.text.inactive");
+        surface.clear_with(area, background);
+
+        let rows_area = area.with_width(area.width / 2);
+        let diff_area = area.clip_left(rows_area.width);
+        for area in [rows_area, diff_area] {
+            Block::bordered().render(area, surface);
+        }
+
+        let inner = Block::bordered().inner(rows_area).clip_left(1);
+        let (included, total) = self.counts();
+        let title = format!("{} ({included}/{total})", self.title);
+        surface.set_stringn(inner.x, inner.y, title, inner.width as usize, title_style);
+        surface.set_stringn(
+            inner.x,
+            inner.y + 1,
+            "space: toggle  a: toggle all  enter: replace  w: replace and write",
+            inner.width as usize,
+            help_style,
+        );
+        self.render_rows(inner.clip_top(3), surface, cx);
+        self.render_diff(Block::bordered().inner(diff_area).clip_left(1), surface, cx);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fugue_core::regex::RegexBuilder;
+
+    use super::*;
+
+    fn file(text: &str, regex: &str, replacement: &str, literal: bool) -> FileMatches {
+        let replacement = Replacement {
+            regex: RegexBuilder::new(regex).build().unwrap(),
+            replacement: replacement.to_owned(),
+            literal,
+        };
+        let matches = replacement.find(text);
+        FileMatches::new(PathBuf::from("/a"), Rope::from(text), matches)
+    }
+
+    #[test]
+    fn replace_with_captures() {
+        let mut file = file(
+            "fn föö(x) {}\nfn bar(y) {}\n",
+            r"fn (\w+)\((\w)\)",
+            "fn ${1}_new(${2}2)",
+            false,
+        );
+        assert_eq!(file.matches.len(), 2);
+        assert_eq!(file.matches[1].line, 1);
+        assert_eq!(file.replaced(), "fn föö_new(x2) {}\nfn bar_new(y2) {}\n");
+
+        file.matches[0].included = false;
+        assert_eq!(file.replaced(), "fn föö(x) {}\nfn bar_new(y2) {}\n");
+
+        let file = self::file("a.b $1", r"\$1", "$0", true);
+        assert_eq!(file.replaced(), "a.b $0");
+    }
+
+    #[test]
+    fn find_hunk() {
+        let diff: Vec<String> = ["@@ -1,2 +1,2 @@", "-a", "+b", "@@ -10 +10 @@", "-c", "+d"]
+            .map(String::from)
+            .into();
+        assert_eq!(hunk_of_line(&diff, 0), Some(0));
+        assert_eq!(hunk_of_line(&diff, 9), Some(3));
+    }
+
+    #[test]
+    fn read_text_decodes_like_documents() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"\xEF\xBB\xBFfn main() {}\n").unwrap();
+        assert_eq!(read_text(file.path()).unwrap(), "fn main() {}\n");
+    }
+}
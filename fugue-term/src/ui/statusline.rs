@@ -4,6 +4,7 @@
 use fugue_core::indent::IndentStyle;
 use fugue_core::{coords_at_pos, encoding, unicode::width::UnicodeWidthStr, Position};
 use fugue_lsp::lsp::DiagnosticSeverity;
+use fugue_vcs::DiffBase;
 use fugue_view::document::DEFAULT_LANGUAGE_NAME;
 use fugue_view::{
     document::{Mode, SCRATCH_BUFFER_NAME},
@@ -158,8 +159,10 @@ fn get_render_function<'a, F>(element_id: StatusLineElementID) -> impl Fn(&mut R
         fugue_view::editor::StatusLineElement::Separator => render_separator,
         fugue_view::editor::StatusLineElement::Spacer => render_spacer,
         fugue_view::editor::StatusLineElement::VersionControl => render_version_control,
+        fugue_view::editor::StatusLineElement::DiffBase => render_diff_base,
         fugue_view::editor::StatusLineElement::Register => render_register,
         fugue_view::editor::StatusLineElement::CurrentWorkingDirectory => render_cwd,
+        fugue_view::editor::StatusLineElement::DebugSession => render_debug_session,
     }
 }
 
@@ -548,6 +551,16 @@ fn render_version_control<'a, F>(context: &mut RenderContext<'a>, write: F)
     write(context, head.into());
 }
 
+fn render_diff_base<'a, F>(context: &mut RenderContext<'a>, write: F)
+where
+    F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
+{
+    let base = context.doc.active_diff_base();
+    if *base != DiffBase::Index {
+        write(context, format!(" diff:{base} ").into());
+    }
+}
+
 fn render_register<'a, F>(context: &mut RenderContext<'a>, write: F)
 where
     F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
@@ -586,3 +599,18 @@ fn render_cwd<'a, F>(context: &mut RenderContext<'a>, write: F)
         .to_string();
     write(context, cwd.into())
 }
+
+fn render_debug_session<'a, F>(context: &mut RenderContext<'a>, write: F)
+where
+    F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
+{
+    let sessions = &context.editor.debug_adapters;
+    let Some(debugger) = sessions.get_active_client() else {
+        return;
+    };
+    let text = match sessions.len() {
+        1 => format!(" dbg:{} ", debugger.name),
+        len => format!(" dbg:{} (+{}) ", debugger.name, len - 1),
+    };
+    write(context, text.into());
+}
@@ -9,9 +9,13 @@
 
 use crate::ui::document::{LinePos, TextRenderer};
 
+pub use blame::InlineBlame;
 pub use diagnostics::InlineDiagnostics;
+pub use inline_values::InlineValues;
 
+mod blame;
 mod diagnostics;
+mod inline_values;
 
 /// Decorations are the primary mechanism for extending the text rendering.
 ///
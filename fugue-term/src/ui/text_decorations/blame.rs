@@ -0,0 +1,89 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use fugue_core::doc_formatter::FormattedGrapheme;
+use fugue_core::line_ending::line_end_char_index;
+use fugue_core::Position;
+use fugue_view::theme::Style;
+use fugue_view::{Document, Theme};
+
+use crate::ui::document::{LinePos, TextRenderer};
+use crate::ui::text_decorations::Decoration;
+
+/// Renders the commit that last changed the cursor line after the end of the line.
+pub struct InlineBlame {
+    /// The char index of the cursor line's end.
+    anchor: usize,
+    text: String,
+    /// Whether the anchor is on the visual line currently being rendered.
+    pending: bool,
+    style: Style,
+}
+
+impl InlineBlame {
+    pub fn new(doc: &Document, theme: &Theme, cursor: usize) -> Option<Self> {
+        let text = doc.text().slice(..);
+        let line = text.char_to_line(cursor);
+        let commit = doc.line_blame(line)?;
+        Some(InlineBlame {
+            anchor: line_end_char_index(&text, line),
+            text: format!(
+                "{}, {} • {}",
+                commit.author,
+                commit.relative_time(),
+                commit.summary
+            ),
+            pending: false,
+            style: theme.get("ui.virtual.inline-blame"),
+        })
+    }
+}
+
+impl Decoration for InlineBlame {
+    fn reset_pos(&mut self, pos: usize) -> usize {
+        self.pending = false;
+        if pos <= self.anchor {
+            self.anchor
+        } else {
+            usize::MAX
+        }
+    }
+
+    fn decorate_grapheme(
+        &mut self,
+        _renderer: &mut TextRenderer,
+        grapheme: &FormattedGrapheme,
+    ) -> usize {
+        self.pending = grapheme.char_idx == self.anchor;
+        usize::MAX
+    }
+
+    fn render_virt_lines(
+        &mut self,
+        renderer: &mut TextRenderer,
+        pos: LinePos,
+        virt_off: Position,
+    ) -> Position {
+        if !std::mem::take(&mut self.pending) {
+            return Position::new(0, 0);
+        }
+        // leave some room between the line and the blame
+        let col = virt_off.col + 4;
+        if !renderer.column_in_bounds(col, 1) {
+            return Position::new(0, 0);
+        }
+
+        let draw_col = (col - renderer.offset.col) as u16;
+        let (end_col, _) = renderer.set_string_truncated(
+            renderer.viewport.x + draw_col,
+            pos.visual_line,
+            &self.text,
+            renderer.viewport.width.saturating_sub(draw_col) as usize,
+            |_| self.style,
+            true,
+            false,
+        );
+        let width = end_col.saturating_sub(renderer.viewport.x + draw_col) as usize;
+        Position::new(0, width + 4)
+    }
+}
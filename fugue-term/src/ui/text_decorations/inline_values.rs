@@ -0,0 +1,174 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use std::ops::Range;
+
+use fugue_core::doc_formatter::FormattedGrapheme;
+use fugue_core::line_ending::line_end_char_index;
+use fugue_core::{Position, RopeSlice};
+use fugue_view::document::InlineValue;
+use fugue_view::theme::Style;
+use fugue_view::{Document, Theme};
+
+use crate::ui::document::{LinePos, TextRenderer};
+use crate::ui::text_decorations::Decoration;
+
+/// Renders the debugger values of a line after its end, separated by commas.
+pub struct InlineValues<'a> {
+    text: RopeSlice<'a>,
+    values: &'a [InlineValue],
+    /// Index of the first value that hasn't been rendered yet.
+    next: usize,
+    /// Values on the visual line currently being rendered.
+    pending: Option<Range<usize>>,
+    style: Style,
+}
+
+impl<'a> InlineValues<'a> {
+    pub fn new(doc: &'a Document, theme: &Theme) -> Self {
+        InlineValues {
+            text: doc.text().slice(..),
+            values: doc.inline_values(),
+            next: 0,
+            pending: None,
+            style: theme.get("ui.virtual.inline-value"),
+        }
+    }
+
+    /// The char index of the line end the next value is rendered after.
+    fn next_anchor(&self) -> usize {
+        match self.values.get(self.next) {
+            Some(value) => line_end_char_index(&self.text, self.text.char_to_line(value.char_idx)),
+            None => usize::MAX,
+        }
+    }
+}
+
+impl Decoration for InlineValues<'_> {
+    fn reset_pos(&mut self, pos: usize) -> usize {
+        let line = self.text.char_to_line(pos.min(self.text.len_chars()));
+        let line_start = self.text.line_to_char(line);
+        self.next = self
+            .values
+            .partition_point(|value| value.char_idx < line_start);
+        self.pending = None;
+        self.next_anchor()
+    }
+
+    fn decorate_grapheme(
+        &mut self,
+        _renderer: &mut TextRenderer,
+        grapheme: &FormattedGrapheme,
+    ) -> usize {
+        let start = self.next;
+        while self.next < self.values.len() && self.next_anchor() == grapheme.char_idx {
+            self.next += 1;
+        }
+        self.pending = Some(start..self.next);
+        self.next_anchor()
+    }
+
+    fn render_virt_lines(
+        &mut self,
+        renderer: &mut TextRenderer,
+        pos: LinePos,
+        virt_off: Position,
+    ) -> Position {
+        let Some(values) = self.pending.take() else {
+            return Position::new(0, 0);
+        };
+        let col = virt_off.col + 1;
+        if !renderer.column_in_bounds(col, 1) {
+            return Position::new(0, 0);
+        }
+
+        let text = self.values[values]
+            .iter()
+            .map(|value| value.text.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let draw_col = (col - renderer.offset.col) as u16;
+        let (end_col, _) = renderer.set_string_truncated(
+            renderer.viewport.x + draw_col,
+            pos.visual_line,
+            &text,
+            renderer.viewport.width.saturating_sub(draw_col) as usize,
+            |_| self.style,
+            true,
+            false,
+        );
+        let width = end_col.saturating_sub(renderer.viewport.x + draw_col) as usize;
+        Position::new(0, width + 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arc_swap::ArcSwap;
+    use fugue_core::syntax::Loader;
+    use fugue_core::text_annotations::TextAnnotations;
+    use fugue_core::Rope;
+    use fugue_view::graphics::Rect;
+    use fugue_view::view::ViewPosition;
+    use tui::buffer::Buffer as Surface;
+
+    use super::*;
+    use crate::ui::document::render_document;
+    use crate::ui::text_decorations::DecorationManager;
+
+    #[test]
+    fn values_are_rendered_after_the_line_end() {
+        let mut doc = Document::from(
+            Rope::from("let a = 1;\nlet b = a;\n"),
+            None,
+            Arc::new(ArcSwap::new(
+                Arc::new(fugue_view::editor::Config::default()),
+            )),
+            Arc::new(ArcSwap::from_pointee(Loader::default())),
+        );
+        doc.set_inline_values(vec![
+            InlineValue {
+                char_idx: 15,
+                text: "b = 1".into(),
+            },
+            InlineValue {
+                char_idx: 4,
+                text: "a = 1".into(),
+            },
+            InlineValue {
+                char_idx: 19,
+                text: "a = 1".into(),
+            },
+        ]);
+
+        let area = Rect::new(0, 0, 30, 2);
+        let mut surface = Surface::empty(area);
+        let theme = Theme::default();
+        let mut decorations = DecorationManager::default();
+        decorations.add_decoration(InlineValues::new(&doc, &theme));
+        render_document(
+            &mut surface,
+            area,
+            &doc,
+            ViewPosition::default(),
+            &TextAnnotations::default(),
+            None,
+            Vec::new(),
+            &theme,
+            decorations,
+        );
+
+        let lines: Vec<String> = (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| surface.get(x, y).unwrap().symbol.as_str())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_owned()
+            })
+            .collect();
+        assert_eq!(lines, ["let a = 1;  a = 1", "let b = a;  b = 1, a = 1"]);
+    }
+}
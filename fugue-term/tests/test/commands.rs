@@ -7,6 +7,7 @@
 
 mod insert;
 mod movement;
+mod operator;
 mod reverse_selection_contents;
 mod rotate_selection_contents;
 mod write;
@@ -0,0 +1,100 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use super::*;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn operator_paragraph_motion() -> anyhow::Result<()> {
+    use fugue_core::hashmap;
+    use fugue_term::keymap;
+    use fugue_view::document::Mode;
+
+    let mut config = Config::default();
+    config.keys.insert(
+        Mode::Normal,
+        keymap!({"Normal Mode"
+            "}" => goto_next_paragraph,
+        }),
+    );
+
+    // `}` stops on the blank line, so `d}` deletes the lines of the paragraph
+    test_with_config(
+        AppBuilder::new().with_config(config.clone()),
+        (
+            indoc! {"\
+                #[a|]#aa
+                bbb
+
+                ccc
+                "},
+            "d}",
+            "#[\n|]#ccc\n",
+        ),
+    )
+    .await?;
+
+    test_with_config(
+        AppBuilder::new().with_config(config),
+        (
+            indoc! {"\
+                a#[a|]#a
+                bbb
+
+                ccc
+                "},
+            "d}",
+            indoc! {"\
+                a#[\n|]#
+
+                ccc
+                "},
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn operator_find_motion() -> anyhow::Result<()> {
+    // `f` is inclusive, the comma is yanked too
+    test(("#[f|]#oo, bar\n", "yf,P", "#[foo,|]#foo, bar\n")).await?;
+    test(("#[f|]#oo, bar\n", "df,", "#[ |]#bar\n")).await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn operator_counts() -> anyhow::Result<()> {
+    test(("#[o|]#ne two three four\n", "c3eX<esc>", "X#[ |]#four\n")).await?;
+    // the count of the operator multiplies the count of the motion
+    test(("#[a|]# b c d e f g h\n", "2d3w", "#[g|]# h\n")).await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn operator_repeat() -> anyhow::Result<()> {
+    test(("#[o|]#ne two three\n", "dw.", "#[t|]#hree\n")).await?;
+    test(("#[o|]#ne\ntwo\nthree\n", "dd.", "#[t|]#hree\n")).await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn operator_fixed_motion_commands() -> anyhow::Result<()> {
+    test((
+        "#[o|]#ne two\n",
+        "<space>?delete_next_word_start<ret>",
+        "#[t|]#wo\n",
+    ))
+    .await?;
+    test((
+        "#[o|]#ne\ntwo\n",
+        "<space>?yank_current_line<ret>P",
+        "#[one\n|]#one\ntwo\n",
+    ))
+    .await?;
+
+    Ok(())
+}
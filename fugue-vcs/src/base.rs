@@ -0,0 +1,59 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use std::{convert::Infallible, fmt, str::FromStr};
+
+/// The version of a file that documents are compared against.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum DiffBase {
+    /// The staged file, or the file at `HEAD` if it has no staged changes.
+    #[default]
+    Index,
+    /// A revision like a branch, tag or commit.
+    Rev(String),
+    /// The merge base of `HEAD` and a revision, written as `...<rev>`.
+    MergeBase(String),
+}
+
+impl FromStr for DiffBase {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let base = match s {
+            "" | "index" => Self::Index,
+            _ => match s.strip_prefix("...") {
+                Some(rev) => Self::MergeBase(rev.to_owned()),
+                None => Self::Rev(s.to_owned()),
+            },
+        };
+        Ok(base)
+    }
+}
+
+impl fmt::Display for DiffBase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index => f.write_str("index"),
+            Self::Rev(rev) => f.write_str(rev),
+            Self::MergeBase(rev) => write!(f, "...{rev}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_diff_base() {
+        assert_eq!("index".parse(), Ok(DiffBase::Index));
+        assert_eq!("HEAD~2".parse(), Ok(DiffBase::Rev("HEAD~2".to_owned())));
+        assert_eq!(
+            "...main".parse(),
+            Ok(DiffBase::MergeBase("main".to_owned()))
+        );
+        for base in ["index", "v1.0", "...origin/main"] {
+            assert_eq!(base.parse::<DiffBase>().unwrap().to_string(), base);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::diff::{Diff, Hunk};
+
+/// A commit that last changed some lines of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameCommit {
+    /// The full hex id of the commit.
+    pub id: String,
+    pub author: String,
+    /// Author time in seconds since the unix epoch.
+    pub time: i64,
+    /// The first line of the commit message.
+    pub summary: String,
+    /// Path of the file relative to the repository root in this commit. Differs from the
+    /// current path if the file was renamed since.
+    pub path: String,
+}
+
+impl BlameCommit {
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(8)]
+    }
+
+    /// The author time relative to now, e.g. `3 days ago`.
+    pub fn relative_time(&self) -> String {
+        time_ago(self.time)
+    }
+}
+
+/// The blame of every line of a file at `HEAD`.
+#[derive(Debug, Clone, Default)]
+pub struct FileBlame {
+    commits: Vec<BlameCommit>,
+    /// Index into `commits` for every line of the file at `HEAD`.
+    lines: Vec<usize>,
+}
+
+impl FileBlame {
+    #[cfg(feature = "git")]
+    pub(crate) fn new(commits: Vec<BlameCommit>, lines: Vec<usize>) -> Self {
+        Self { commits, lines }
+    }
+
+    /// The commit that last changed `line` of the file at `HEAD`.
+    pub fn base_line(&self, line: usize) -> Option<&BlameCommit> {
+        self.lines.get(line).map(|&commit| &self.commits[commit])
+    }
+
+    /// The commit that last changed `line` of the document, where `diff` holds the changes of
+    /// the document to the diff base and `base_hunks` the changes of the diff base to `HEAD`.
+    /// Returns `None` for lines that were changed but not committed yet.
+    pub fn line(
+        &self,
+        line: usize,
+        diff: Option<&Diff>,
+        base_hunks: &[Hunk],
+    ) -> Option<&BlameCommit> {
+        let line = match diff {
+            Some(diff) => base_line((0..diff.len()).map(|i| diff.nth_hunk(i)), line as u32)?,
+            None => line as u32,
+        };
+        let line = base_line(base_hunks.iter().cloned(), line)?;
+        self.base_line(line as usize)
+    }
+
+    pub fn commit(&self, id: &str) -> Option<&BlameCommit> {
+        self.commits.iter().find(|commit| commit.id == id)
+    }
+}
+
+/// Maps a line of the document to the line of the diff base it originates from, or `None` if
+/// the line was added or changed by one of the `hunks`.
+fn base_line(hunks: impl Iterator<Item = Hunk>, line: u32) -> Option<u32> {
+    let mut offset: i64 = 0;
+    for hunk in hunks {
+        if hunk.after.start > line {
+            break;
+        }
+        if hunk.after.contains(&line) {
+            return None;
+        }
+        offset = hunk.before.end as i64 - hunk.after.end as i64;
+    }
+    u32::try_from(line as i64 + offset).ok()
+}
+
+/// Describes `time`, in seconds since the unix epoch, relative to now.
+pub(crate) fn time_ago(time: i64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs() as i64);
+    relative_time(now - time)
+}
+
+fn relative_time(seconds: i64) -> String {
+    const UNITS: [(i64, &str); 6] = [
+        (60 * 60 * 24 * 365, "year"),
+        (60 * 60 * 24 * 30, "month"),
+        (60 * 60 * 24 * 7, "week"),
+        (60 * 60 * 24, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+    ];
+    match UNITS.iter().find(|(unit, _)| seconds >= *unit) {
+        Some((unit, name)) => {
+            let count = seconds / unit;
+            let plural = if count == 1 { "" } else { "s" };
+            format!("{count} {name}{plural} ago")
+        }
+        None => "just now".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn map_document_lines_to_base() {
+        let hunks = [
+            // line 1 was changed
+            Hunk {
+                before: 1..2,
+                after: 1..2,
+            },
+            // two lines were inserted before line 4
+            Hunk {
+                before: 4..4,
+                after: 4..6,
+            },
+            // lines 7 and 8 were deleted
+            Hunk {
+                before: 7..9,
+                after: 9..9,
+            },
+        ];
+        let map = |line| base_line(hunks.iter().cloned(), line);
+        assert_eq!(map(0), Some(0));
+        assert_eq!(map(1), None);
+        assert_eq!(map(3), Some(3));
+        assert_eq!(map(4), None);
+        assert_eq!(map(5), None);
+        assert_eq!(map(6), Some(4));
+        assert_eq!(map(9), Some(9));
+
+        assert_eq!(relative_time(30), "just now");
+        assert_eq!(relative_time(60 * 60 * 24 * 3), "3 days ago");
+        assert_eq!(relative_time(60 * 60), "1 hour ago");
+    }
+}
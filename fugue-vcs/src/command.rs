@@ -0,0 +1,56 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+//! Helpers for providers that shell out to the command line interface of a VCS.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+
+/// Runs `program` with `args` in `cwd` and returns its output. Fails if the program exits with
+/// a non-zero status.
+pub(crate) fn run(
+    program: &str,
+    cwd: &Path,
+    args: &[&str],
+    envs: &[(&str, &str)],
+) -> Result<Vec<u8>> {
+    let output = Command::new(program)
+        .args(args)
+        .envs(envs.iter().copied())
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("failed to run {program}"))?;
+    if !output.status.success() {
+        bail!(
+            "`{program} {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Finds the root of the repository containing `path`, which contains a `marker` directory.
+pub(crate) fn find_root(path: &Path, marker: &str) -> Result<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(marker).is_dir())
+        .map(Path::to_path_buf)
+        .with_context(|| format!("{} is not inside a {marker} repository", path.display()))
+}
+
+/// Finds the root of the repository containing `file` and the path of the file relative to it,
+/// using `/` as separator.
+pub(crate) fn find_file(file: &Path, marker: &str) -> Result<(PathBuf, String)> {
+    let file = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+    let root = find_root(&file, marker)?;
+    let rela_path = file
+        .strip_prefix(&root)?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    Ok((root, rela_path))
+}
@@ -0,0 +1,111 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use std::path::Path;
+
+use fugue_core::RopeSlice;
+
+/// Everything below this line of a commit message is ignored, `git commit --verbose` places the
+/// diff of the commit there.
+pub const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// The files git asks an editor to fill in with a commit message.
+const MESSAGE_FILES: &[&str] = &["COMMIT_EDITMSG", "MERGE_MSG", "SQUASH_MSG"];
+
+/// Returns whether `path` is a file git writes a commit message template to.
+pub fn is_commit_message(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| MESSAGE_FILES.contains(&name))
+}
+
+/// Returns the index of the first line below the scissors line of a commit message.
+pub fn verbose_diff_start(text: RopeSlice) -> Option<usize> {
+    text.lines()
+        .position(|line| {
+            line.get_char(0) == Some('#')
+                && line.len_chars() <= SCISSORS.len() + 2
+                && line.to_string().trim_end() == SCISSORS
+        })
+        .map(|line| line + 1)
+}
+
+/// Strips a commit message the way `git commit --cleanup=strip` does: everything below the
+/// scissors line and all comment lines are removed, trailing whitespace is trimmed and
+/// consecutive empty lines are collapsed.
+pub fn cleanup_message(text: &str) -> String {
+    let mut message = String::new();
+    let mut empty_lines = 0;
+    for line in text.lines() {
+        if line.trim_end() == SCISSORS {
+            break;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            empty_lines += 1;
+            continue;
+        }
+        if !message.is_empty() && empty_lines > 0 {
+            message.push('\n');
+        }
+        empty_lines = 0;
+        message.push_str(line);
+        message.push('\n');
+    }
+    message
+}
+
+/// Builds the template of a commit message listing `changes` in comments, followed by `diff`
+/// below the scissors line.
+pub(crate) fn template(branch: Option<&str>, changes: &[String], diff: &str) -> String {
+    let mut template = String::from(
+        "\n\
+         # Please enter the commit message for your changes. Lines starting\n\
+         # with '#' will be ignored, and an empty message aborts the commit.\n\
+         #\n",
+    );
+    if let Some(branch) = branch {
+        template.push_str(&format!("# On branch {branch}\n"));
+    }
+    template.push_str("# Changes to be committed:\n");
+    for change in changes {
+        template.push_str(&format!("#\t{change}\n"));
+    }
+    template.push_str("#\n");
+    template.push_str(SCISSORS);
+    template.push_str(
+        "\n\
+         # Do not modify or remove the line above.\n\
+         # Everything below it will be ignored.\n",
+    );
+    template.push_str(diff.trim_start_matches('\n'));
+    template
+}
+
+#[cfg(test)]
+mod test {
+    use fugue_core::Rope;
+
+    use super::*;
+
+    #[test]
+    fn cleanup() {
+        let text = "summary  \n\n\n# comment\nbody\n\n#\n\n";
+        assert_eq!(cleanup_message(text), "summary\n\nbody\n");
+
+        let text = template(
+            Some("main"),
+            &["modified:   a".to_owned()],
+            "\n--- a/a\n+++ b/a\n@@ -1 +1 @@\n-a\n+b\n",
+        );
+        assert_eq!(cleanup_message(&text), "");
+        assert_eq!(cleanup_message(&format!("fix\n{text}")), "fix\n");
+
+        let text = Rope::from(text);
+        let start = verbose_diff_start(text.slice(..)).unwrap();
+        assert_eq!(text.line(start + 2), "--- a/a\n");
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use fugue_core::{Assoc, ChangeSet, RopeSlice};
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+/// A region of a file left with conflict markers by a merge. All positions are line indices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The line of the `<<<<<<<` marker.
+    pub start: usize,
+    /// The lines of our side.
+    pub ours: Range<usize>,
+    /// The lines of the common ancestor, present for `diff3` style conflicts.
+    pub base: Option<Range<usize>>,
+    /// The lines of their side.
+    pub theirs: Range<usize>,
+    /// The line of the `>>>>>>>` marker.
+    pub end: usize,
+}
+
+/// Which side of a [`Conflict`] to keep when resolving it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    Base,
+    Both,
+}
+
+impl Conflict {
+    /// All lines of the conflict, including its markers.
+    pub fn lines(&self) -> Range<usize> {
+        self.start..self.end + 1
+    }
+
+    /// The lines that replace the conflict when it is resolved with `resolution`.
+    pub fn resolve(&self, resolution: Resolution) -> anyhow::Result<Vec<Range<usize>>> {
+        let lines = match resolution {
+            Resolution::Ours => vec![self.ours.clone()],
+            Resolution::Theirs => vec![self.theirs.clone()],
+            Resolution::Base => match &self.base {
+                Some(base) => vec![base.clone()],
+                None => bail!("conflict has no base section"),
+            },
+            Resolution::Both => vec![self.ours.clone(), self.theirs.clone()],
+        };
+        Ok(lines)
+    }
+}
+
+impl FromStr for Resolution {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ours" => Ok(Resolution::Ours),
+            "theirs" => Ok(Resolution::Theirs),
+            "base" => Ok(Resolution::Base),
+            "both" => Ok(Resolution::Both),
+            _ => Err(anyhow!("expected one of ours, theirs, base or both")),
+        }
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Resolution::Ours => "ours",
+            Resolution::Theirs => "theirs",
+            Resolution::Base => "base",
+            Resolution::Both => "both",
+        })
+    }
+}
+
+fn is_marker(line: RopeSlice, marker: &str) -> bool {
+    let mut chars = line.chars();
+    marker.chars().all(|c| chars.next() == Some(c)) && chars.next().is_none_or(char::is_whitespace)
+}
+
+/// Finds the conflict regions of `text`. Incomplete regions are ignored.
+pub fn parse_conflicts(text: RopeSlice) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    let mut start = None;
+    let mut base = None;
+    let mut separator = None;
+    for (i, line) in text.lines().enumerate() {
+        // every marker starts with one of these characters
+        if !matches!(line.get_char(0), Some('<' | '|' | '=' | '>')) {
+            continue;
+        }
+        if is_marker(line, OURS_MARKER) {
+            start = Some(i);
+            base = None;
+            separator = None;
+        } else if start.is_some() && separator.is_none() && is_marker(line, BASE_MARKER) {
+            base = Some(i);
+        } else if start.is_some() && separator.is_none() && is_marker(line, SEPARATOR) {
+            separator = Some(i);
+        } else if is_marker(line, THEIRS_MARKER) {
+            let (Some(start), Some(separator)) = (start.take(), separator.take()) else {
+                continue;
+            };
+            let base = base.take();
+            conflicts.push(Conflict {
+                start,
+                ours: start + 1..base.unwrap_or(separator),
+                base: base.map(|base| base + 1..separator),
+                theirs: separator + 1..i,
+                end: i,
+            });
+        }
+    }
+    conflicts
+}
+
+fn is_marker_line(line: RopeSlice) -> bool {
+    matches!(line.get_char(0), Some('<' | '|' | '=' | '>'))
+        && [OURS_MARKER, BASE_MARKER, SEPARATOR, THEIRS_MARKER]
+            .iter()
+            .any(|marker| is_marker(line, marker))
+}
+
+/// Updates the `conflicts` of `old_text` after `changes` turned it into `text`. The conflicts
+/// are only parsed again when one of the changed lines holds a conflict marker before or after
+/// the change, otherwise they are moved along with the lines around them.
+pub fn update_conflicts(
+    conflicts: &mut Vec<Conflict>,
+    old_text: RopeSlice,
+    text: RopeSlice,
+    changes: &ChangeSet,
+) {
+    let mut offset = 0isize;
+    for (from, to, insert) in changes.changes_iter() {
+        let new_from = from.saturating_add_signed(offset);
+        let new_to = new_from + insert.map_or(0, |insert| insert.chars().count());
+        offset += (new_to - new_from) as isize - (to - from) as isize;
+        let mut old_lines = old_text.char_to_line(from)..=old_text.char_to_line(to);
+        let mut new_lines = text.char_to_line(new_from)..=text.char_to_line(new_to);
+        if old_lines.any(|line| is_marker_line(old_text.line(line)))
+            || new_lines.any(|line| is_marker_line(text.line(line)))
+        {
+            *conflicts = parse_conflicts(text);
+            return;
+        }
+    }
+
+    let map_line = |line: usize| {
+        text.char_to_line(changes.map_pos(old_text.line_to_char(line), Assoc::Before))
+    };
+    for conflict in conflicts {
+        let start = map_line(conflict.start);
+        let base = conflict.base.as_ref().map(|_| map_line(conflict.ours.end));
+        let separator = map_line(conflict.theirs.start - 1);
+        let end = map_line(conflict.end);
+        *conflict = Conflict {
+            start,
+            ours: start + 1..base.unwrap_or(separator),
+            base: base.map(|base| base + 1..separator),
+            theirs: separator + 1..end,
+            end,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fugue_core::{Rope, Transaction};
+
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let text = Rope::from(
+            "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\nb\n\
+             <<<<<<< HEAD\nx\n||||||| base\ny\n=======\nz\nw\n>>>>>>>\n=======\n",
+        );
+        let conflicts = parse_conflicts(text.slice(..));
+        assert_eq!(
+            conflicts,
+            vec![
+                Conflict {
+                    start: 1,
+                    ours: 2..3,
+                    base: None,
+                    theirs: 4..5,
+                    end: 5,
+                },
+                Conflict {
+                    start: 7,
+                    ours: 8..9,
+                    base: Some(10..11),
+                    theirs: 12..14,
+                    end: 14,
+                },
+            ]
+        );
+        assert!(conflicts[0].resolve(Resolution::Base).is_err());
+        assert_eq!(
+            conflicts[1].resolve(Resolution::Both).unwrap(),
+            vec![8..9, 12..14]
+        );
+    }
+
+    #[test]
+    fn ignore_incomplete() {
+        let text = Rope::from("<<<<<<< HEAD\na\n=======\n<<<<<<<< not a marker\n");
+        assert!(parse_conflicts(text.slice(..)).is_empty());
+    }
+
+    #[test]
+    fn update() {
+        let old_text = Rope::from("a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>>\n");
+        let mut conflicts = parse_conflicts(old_text.slice(..));
+
+        // edits between the markers move the conflict without parsing it again
+        let mut text = old_text.clone();
+        let transaction = Transaction::change(
+            &text,
+            [
+                (0, 0, Some("x\ny\n".into())),
+                (19, 19, Some("\nmore".into())),
+            ]
+            .into_iter(),
+        );
+        assert!(transaction.apply(&mut text));
+        update_conflicts(
+            &mut conflicts,
+            old_text.slice(..),
+            text.slice(..),
+            transaction.changes(),
+        );
+        assert_eq!(conflicts, parse_conflicts(text.slice(..)));
+        assert_eq!(conflicts[0].ours, 4..6);
+
+        // typing the last character of a marker finds a new conflict
+        let old_text = Rope::from("<<<<<<< HEAD\na\n=======\nb\n>>>>>>\n");
+        let mut conflicts = parse_conflicts(old_text.slice(..));
+        assert!(conflicts.is_empty());
+        let mut text = old_text.clone();
+        let transaction = Transaction::change(&text, [(28, 28, Some(">".into()))].into_iter());
+        assert!(transaction.apply(&mut text));
+        update_conflicts(
+            &mut conflicts,
+            old_text.slice(..),
+            text.slice(..),
+            transaction.changes(),
+        );
+        assert_eq!(conflicts.len(), 1);
+    }
+}
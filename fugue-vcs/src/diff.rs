@@ -2,8 +2,10 @@
 // Licensed under MPL-2.0
 
 use std::iter::Peekable;
+use std::ops::Range;
 use std::sync::Arc;
 
+use fugue_core::chars::{categorize_char, CharCategory};
 use fugue_core::Rope;
 use fugue_event::RenderLockGuard;
 use imara_diff::Algorithm;
@@ -126,6 +128,91 @@ fn update_document_impl(
 // cap average line length to 128 for files with MAX_DIFF_LINES
 const MAX_DIFF_BYTES: usize = MAX_DIFF_LINES * 128;
 
+/// Computes the line hunks between two texts synchronously.
+#[cfg(feature = "git")]
+pub(crate) fn compute_hunks(before: &str, after: &str) -> Vec<Hunk> {
+    let input = imara_diff::InternedInput::new(before, after);
+    let mut diff = imara_diff::Diff::compute(ALGORITHM, &input);
+    diff.postprocess_lines(&input);
+    diff.hunks().collect()
+}
+
+/// Renders the changes between two texts as a unified diff without file headers.
+pub fn unified_diff(before: &str, after: &str) -> String {
+    use imara_diff::{BasicLineDiffPrinter, InternedInput, UnifiedDiffConfig};
+
+    let input = InternedInput::new(before, after);
+    let mut diff = imara_diff::Diff::compute(ALGORITHM, &input);
+    diff.postprocess_lines(&input);
+    diff.unified_diff(
+        &BasicLineDiffPrinter(&input.interner),
+        UnifiedDiffConfig::default(),
+        &input,
+    )
+    .to_string()
+}
+
+/// Computes which words differ between two texts, for example the lines of a hunk. Returns the
+/// changed char ranges of `before` and of `after`.
+pub fn word_diff(before: &str, after: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let before_words: Vec<_> = words(before).collect();
+    let after_words: Vec<_> = words(after).collect();
+    let mut input = imara_diff::InternedInput::default();
+    input.update_before(before_words.iter().copied());
+    input.update_after(after_words.iter().copied());
+    let diff = imara_diff::Diff::compute(ALGORITHM, &input);
+
+    let before_offsets = char_offsets(&before_words);
+    let after_offsets = char_offsets(&after_words);
+    let mut before_changes = Vec::new();
+    let mut after_changes = Vec::new();
+    for hunk in diff.hunks() {
+        if !hunk.before.is_empty() {
+            before_changes.push(
+                before_offsets[hunk.before.start as usize]
+                    ..before_offsets[hunk.before.end as usize],
+            );
+        }
+        if !hunk.after.is_empty() {
+            after_changes.push(
+                after_offsets[hunk.after.start as usize]..after_offsets[hunk.after.end as usize],
+            );
+        }
+    }
+    (before_changes, after_changes)
+}
+
+/// Splits text into runs of word characters, runs of whitespace and single other characters.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let mut chars = rest.char_indices();
+        let (_, first) = chars.next()?;
+        let category = categorize_char(first);
+        let end = match category {
+            CharCategory::Word | CharCategory::Whitespace => chars
+                .find(|&(_, ch)| categorize_char(ch) != category)
+                .map_or(rest.len(), |(i, _)| i),
+            _ => first.len_utf8(),
+        };
+        let (word, tail) = rest.split_at(end);
+        rest = tail;
+        Some(word)
+    })
+}
+
+/// The char offset of the start of every word, followed by the end of the last word.
+fn char_offsets(words: &[&str]) -> Vec<usize> {
+    let mut offset = 0;
+    let mut offsets = Vec::with_capacity(words.len() + 1);
+    offsets.push(0);
+    for word in words {
+        offset += word.chars().count();
+        offsets.push(offset);
+    }
+    offsets
+}
+
 /// A list of changes in a file sorted in ascending
 /// non-overlapping order
 #[derive(Debug)]
@@ -317,3 +404,19 @@ fn next(&mut self) -> Option<Self::Item> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn changed_words() {
+        let (before, after) = word_diff("let foo = bar(1);\n", "let foo = baz(1, 2);\n");
+        assert_eq!(before, vec![10..13]);
+        assert_eq!(after, vec![10..13, 15..18]);
+        assert_eq!(
+            words("a_b  c,d").collect::<Vec<_>>(),
+            vec!["a_b", "  ", "c", ",", "d"]
+        );
+    }
+}
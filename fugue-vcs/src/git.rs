@@ -4,13 +4,16 @@
 use anyhow::{bail, Context, Result};
 use arc_swap::ArcSwap;
 use gix::filter::plumbing::driver::apply::Delay;
+use std::collections::HashSet;
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use gix::bstr::ByteSlice;
+use gix::bstr::{BString, ByteSlice};
 use gix::diff::Rewrites;
 use gix::dir::entry::Status;
+use gix::dir::walk::EmissionMode;
+use gix::index::entry::{Flags, Mode, Stage};
 use gix::objs::tree::EntryKind;
 use gix::sec::trust::DefaultForLevel;
 use gix::status::{
@@ -20,7 +23,9 @@
 };
 use gix::{Commit, ObjectId, Repository, ThreadSafeRepository};
 
-use crate::FileChange;
+use crate::{
+    BlameCommit, DiffBase, FileBlame, FileChange, FileStatus, FileStatuses, Hunk, LogCommit,
+};
 
 #[cfg(test)]
 mod test;
@@ -30,7 +35,162 @@ fn get_repo_dir(file: &Path) -> Result<&Path> {
     file.parent().context("file has no parent directory")
 }
 
-pub fn get_diff_base(file: &Path) -> Result<Vec<u8>> {
+/// Reads the content of `file` at `base`.
+pub fn get_diff_base(file: &Path, base: &DiffBase) -> Result<Vec<u8>> {
+    let (repo, file, rela_path) = open_file_repo(file)?;
+    let file_oid = find_file_in_base(&repo, &file, &rela_path, base)?;
+    read_blob(&repo, file_oid, &rela_path)
+}
+
+/// Reads the content of `file` at `HEAD`.
+pub fn get_head_base(file: &Path) -> Result<Vec<u8>> {
+    let (repo, file, rela_path) = open_file_repo(file)?;
+    let file_oid = find_file_in_commit(&repo, &repo.head_commit()?, &file)?;
+    read_blob(&repo, file_oid, &rela_path)
+}
+
+/// The changes of `file` at `base` relative to `HEAD`, where `before` refers to lines at `HEAD`
+/// and `after` to lines at `base`. For the index these are the staged changes.
+pub fn get_base_hunks(file: &Path, base: &DiffBase) -> Result<Vec<Hunk>> {
+    let (repo, file, rela_path) = open_file_repo(file)?;
+    let Ok(base_oid) = find_file_in_base(&repo, &file, &rela_path, base) else {
+        return Ok(Vec::new());
+    };
+    let base = repo.find_object(base_oid)?.detach().data;
+    let head = match repo
+        .head_commit()
+        .ok()
+        .and_then(|head| find_file_in_commit(&repo, &head, &file).ok())
+    {
+        Some(oid) => repo.find_object(oid)?.detach().data,
+        None => Vec::new(),
+    };
+    Ok(crate::diff::compute_hunks(
+        &String::from_utf8_lossy(&head),
+        &String::from_utf8_lossy(&base),
+    ))
+}
+
+/// Finds the object that contains the contents of a file at `base`.
+fn find_file_in_base(
+    repo: &Repository,
+    file: &Path,
+    rela_path: &BString,
+    base: &DiffBase,
+) -> Result<ObjectId> {
+    if *base == DiffBase::Index {
+        let index = repo.index_or_empty()?;
+        if let Some(entry) = index.entry_by_path_and_stage(rela_path.as_ref(), Stage::Unconflicted)
+        {
+            if entry.mode == Mode::FILE || entry.mode == Mode::FILE_EXECUTABLE {
+                return Ok(entry.id);
+            }
+        }
+    }
+    find_file_in_commit(repo, &base_commit(repo, base)?, file)
+}
+
+/// Resolves the commit of `base`. The index resolves to `HEAD`.
+fn base_commit<'repo>(repo: &'repo Repository, base: &DiffBase) -> Result<Commit<'repo>> {
+    let commit = match base {
+        DiffBase::Index => repo.head_commit()?,
+        DiffBase::Rev(rev) => repo
+            .rev_parse_single(rev.as_str())?
+            .object()?
+            .peel_to_commit()?,
+        DiffBase::MergeBase(rev) => {
+            let other = repo
+                .rev_parse_single(rev.as_str())?
+                .object()?
+                .peel_to_commit()?;
+            let merge_base = repo.merge_base(repo.head_id()?, other.id)?;
+            repo.find_commit(merge_base)?
+        }
+    };
+    Ok(commit)
+}
+
+/// Replaces the content of `file` in the index with `content`, adding the file to the index if
+/// it isn't tracked yet.
+pub fn stage(file: &Path, content: &[u8]) -> Result<()> {
+    let (repo, file, rela_path) = open_file_repo(file)?;
+    let mut index = repo.open_index()?;
+
+    let (mut pipeline, _) = repo.filter_pipeline(None)?;
+    let mut converted = Vec::with_capacity(content.len());
+    pipeline
+        .convert_to_git(content, Path::new(rela_path.to_str()?), &index)?
+        .read_to_end(&mut converted)?;
+    let oid = repo.write_blob(&converted)?.detach();
+
+    match index.entry_mut_by_path_and_stage(rela_path.as_ref(), Stage::Unconflicted) {
+        Some(entry) => {
+            entry.id = oid;
+            // the content no longer matches the file on disk, clearing the stat forces git to
+            // compare contents instead of trusting the modification time
+            entry.stat = Default::default();
+        }
+        None => {
+            let mode = if is_executable(&file) {
+                Mode::FILE_EXECUTABLE
+            } else {
+                Mode::FILE
+            };
+            index.dangerously_push_entry(
+                Default::default(),
+                oid,
+                Flags::empty(),
+                mode,
+                rela_path.as_ref(),
+            );
+            index.sort_entries();
+        }
+    }
+    index.write(Default::default())?;
+    Ok(())
+}
+
+/// Resets the content of `file` in the index to `HEAD`, removing it from the index if it was
+/// newly added.
+pub fn unstage(file: &Path) -> Result<()> {
+    let (repo, file, rela_path) = open_file_repo(file)?;
+    let mut index = repo.open_index()?;
+    let head_oid = repo
+        .head_commit()
+        .ok()
+        .and_then(|head| find_file_in_commit(&repo, &head, &file).ok());
+
+    match head_oid {
+        Some(oid) => {
+            let entry = index
+                .entry_mut_by_path_and_stage(rela_path.as_ref(), Stage::Unconflicted)
+                .context("file is not in the index")?;
+            entry.id = oid;
+            entry.stat = Default::default();
+        }
+        None => {
+            index.remove_entries(|_, path, _| path == rela_path);
+        }
+    }
+    index.write(Default::default())?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_executable(file: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    file.metadata()
+        .is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_file: &Path) -> bool {
+    false
+}
+
+/// Opens the repository containing `file` and returns it along with the resolved path of the
+/// file and its path relative to the work tree.
+fn open_file_repo(file: &Path) -> Result<(Repository, PathBuf, BString)> {
     debug_assert!(!file.exists() || file.is_file());
     debug_assert!(file.is_absolute());
     let file = gix::path::realpath(file).context("resolve symlinks")?;
@@ -41,25 +201,24 @@ pub fn get_diff_base(file: &Path) -> Result<Vec<u8>> {
     let repo = open_repo(repo_dir)
         .context("failed to open git repo")?
         .to_thread_local();
-    let head = repo.head_commit()?;
-    let file_oid = find_file_in_commit(&repo, &head, &file)?;
-
-    let file_object = repo.find_object(file_oid)?;
-    let data = file_object.detach().data;
-    // Get the actual data that git would make out of the git object.
-    // This will apply the user's git config or attributes like crlf conversions.
-    if let Some(work_dir) = repo.workdir() {
-        let rela_path = file.strip_prefix(work_dir)?;
-        let rela_path = gix::path::try_into_bstr(rela_path)?;
-        let (mut pipeline, _) = repo.filter_pipeline(None)?;
-        let mut worktree_outcome =
-            pipeline.convert_to_worktree(&data, rela_path.as_ref(), Delay::Forbid)?;
-        let mut buf = Vec::with_capacity(data.len());
-        worktree_outcome.read_to_end(&mut buf)?;
-        Ok(buf)
-    } else {
-        Ok(data)
-    }
+    let work_dir = repo.workdir().context("repo has no worktree")?;
+    let rela_path = gix::path::to_unix_separators_on_windows(gix::path::try_into_bstr(
+        file.strip_prefix(work_dir)?,
+    )?)
+    .into_owned();
+    Ok((repo, file, rela_path))
+}
+
+/// Reads a blob and converts it to what git would check out into the work tree. This applies
+/// the user's git config or attributes like crlf conversions.
+fn read_blob(repo: &Repository, oid: ObjectId, rela_path: &BString) -> Result<Vec<u8>> {
+    let data = repo.find_object(oid)?.detach().data;
+    let (mut pipeline, _) = repo.filter_pipeline(None)?;
+    let mut worktree_outcome =
+        pipeline.convert_to_worktree(&data, rela_path.as_ref(), Delay::Forbid)?;
+    let mut buf = Vec::with_capacity(data.len());
+    worktree_outcome.read_to_end(&mut buf)?;
+    Ok(buf)
 }
 
 pub fn get_current_head_name(file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
@@ -82,8 +241,427 @@ pub fn get_current_head_name(file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
     Ok(Arc::new(ArcSwap::from_pointee(name.into_boxed_str())))
 }
 
-pub fn for_each_changed_file(cwd: &Path, f: impl Fn(Result<FileChange>) -> bool) -> Result<()> {
-    status(&open_repo(cwd)?.to_thread_local(), f)
+pub fn blame(file: &Path) -> Result<FileBlame> {
+    let (repo, _, rela_path) = open_file_repo(file)?;
+    let head = repo.head_commit()?;
+    let outcome = repo.blame_file(rela_path.as_ref(), head.id, Default::default())?;
+
+    let mut commits: Vec<BlameCommit> = Vec::new();
+    let mut lines = Vec::new();
+    for entry in &outcome.entries {
+        let id = entry.commit_id.to_string();
+        let idx = match commits.iter().position(|commit| commit.id == id) {
+            Some(idx) => idx,
+            None => {
+                let commit = repo.find_commit(entry.commit_id)?;
+                let author = commit.author()?;
+                let path = entry
+                    .source_file_name
+                    .as_ref()
+                    .unwrap_or(&rela_path)
+                    .to_string();
+                commits.push(BlameCommit {
+                    id,
+                    author: author.name.to_string(),
+                    time: author.time()?.seconds,
+                    summary: commit.message()?.summary().to_string(),
+                    path,
+                });
+                commits.len() - 1
+            }
+        };
+        let range = entry.range_in_blamed_file();
+        if lines.len() < range.end {
+            lines.resize(range.end, idx);
+        }
+        lines[range].fill(idx);
+    }
+
+    Ok(FileBlame::new(commits, lines))
+}
+
+/// Renders the changes `commit` made as a unified diff, preceded by a header describing the
+/// commit. With `path` (relative to the repository root) only the changes to that file are shown.
+pub fn commit_diff(cwd: &Path, commit: &str, path: Option<&str>) -> Result<String> {
+    use std::fmt::Write;
+
+    let repo = open_repo(cwd)?.to_thread_local();
+    let commit = repo.find_commit(ObjectId::from_hex(commit.as_bytes())?)?;
+    let parent_tree = match commit.parent_ids().next() {
+        Some(parent) => Some(parent.object()?.into_commit().tree()?),
+        None => None,
+    };
+    let tree = commit.tree()?;
+
+    let author = commit.author()?;
+    let mut out = String::new();
+    writeln!(out, "commit {}", commit.id)?;
+    writeln!(out, "Author: {} <{}>", author.name, author.email)?;
+    writeln!(
+        out,
+        "Date:   {}",
+        author.time()?.format(gix::date::time::format::DEFAULT)?
+    )?;
+    writeln!(out)?;
+    for line in commit.message_raw()?.lines() {
+        writeln!(out, "    {}", line.to_str_lossy())?;
+    }
+
+    if let Some(path) = path {
+        let blob = |tree: Option<&gix::Tree>| -> Result<Vec<u8>> {
+            Ok(match tree.map(|tree| tree.lookup_entry_by_path(path)) {
+                Some(Ok(Some(entry))) => repo.find_object(entry.object_id())?.detach().data,
+                Some(Err(err)) => return Err(err.into()),
+                _ => Vec::new(),
+            })
+        };
+        write_file_diff(
+            &mut out,
+            path,
+            path,
+            &blob(parent_tree.as_ref())?,
+            &blob(Some(&tree))?,
+        )?;
+        return Ok(out);
+    }
+
+    write_tree_diff(&mut out, &repo, parent_tree.as_ref(), &tree)?;
+    Ok(out)
+}
+
+/// Writes the unified diffs of all changes between `old` and `new` to `out`, returning a short
+/// description of each change in the style of `git status`.
+fn write_tree_diff(
+    out: &mut String,
+    repo: &Repository,
+    old: Option<&gix::Tree>,
+    new: &gix::Tree,
+) -> Result<Vec<String>> {
+    let mut options = gix::diff::Options::default();
+    options.track_rewrites(Some(Rewrites::default()));
+    let blob = |id: ObjectId, mode: gix::object::tree::EntryMode| -> Result<Vec<u8>> {
+        Ok(if mode.is_blob() {
+            repo.find_object(id)?.detach().data
+        } else {
+            Vec::new()
+        })
+    };
+    let mut changes = Vec::new();
+    for change in repo.diff_tree_to_tree(old, new, options)? {
+        use gix::object::tree::diff::ChangeDetached;
+        if change.entry_mode().is_tree() {
+            continue;
+        }
+        let (kind, before_path, after_path, before, after) = match change {
+            ChangeDetached::Addition {
+                location,
+                entry_mode,
+                id,
+                ..
+            } => (
+                "new file",
+                location.clone(),
+                location,
+                Vec::new(),
+                blob(id, entry_mode)?,
+            ),
+            ChangeDetached::Deletion {
+                location,
+                entry_mode,
+                id,
+                ..
+            } => (
+                "deleted",
+                location.clone(),
+                location,
+                blob(id, entry_mode)?,
+                Vec::new(),
+            ),
+            ChangeDetached::Modification {
+                location,
+                previous_entry_mode,
+                previous_id,
+                entry_mode,
+                id,
+            } => (
+                "modified",
+                location.clone(),
+                location,
+                blob(previous_id, previous_entry_mode)?,
+                blob(id, entry_mode)?,
+            ),
+            ChangeDetached::Rewrite {
+                source_location,
+                source_entry_mode,
+                source_id,
+                entry_mode,
+                id,
+                location,
+                copy,
+                ..
+            } => (
+                if copy { "copied" } else { "renamed" },
+                source_location,
+                location,
+                blob(source_id, source_entry_mode)?,
+                blob(id, entry_mode)?,
+            ),
+        };
+        let (before_path, after_path) = (before_path.to_str_lossy(), after_path.to_str_lossy());
+        changes.push(if before_path == after_path {
+            format!("{kind}:   {after_path}")
+        } else {
+            format!("{kind}:   {before_path} -> {after_path}")
+        });
+        write_file_diff(out, &before_path, &after_path, &before, &after)?;
+    }
+    Ok(changes)
+}
+
+fn write_file_diff(
+    out: &mut String,
+    before_path: &str,
+    after_path: &str,
+    before: &[u8],
+    after: &[u8],
+) -> Result<()> {
+    use std::fmt::Write;
+
+    writeln!(out)?;
+    writeln!(out, "--- a/{before_path}")?;
+    writeln!(out, "+++ b/{after_path}")?;
+    if before.contains(&0) || after.contains(&0) {
+        writeln!(out, "Binary files differ")?;
+        return Ok(());
+    }
+    out.push_str(&crate::diff::unified_diff(
+        &String::from_utf8_lossy(before),
+        &String::from_utf8_lossy(after),
+    ));
+    Ok(())
+}
+
+/// Builds the template of a commit message for the changes staged in the repository at `cwd`.
+pub fn commit_template(cwd: &Path) -> Result<String> {
+    let repo = open_repo(cwd)?.to_thread_local();
+    let tree = repo.find_tree(write_index_tree(&repo)?)?;
+    let head_tree = match repo.head_commit() {
+        Ok(head) => Some(head.tree()?),
+        Err(_) => None,
+    };
+    let mut diff = String::new();
+    let changes = write_tree_diff(&mut diff, &repo, head_tree.as_ref(), &tree)?;
+    if changes.is_empty() {
+        bail!("no changes added to commit");
+    }
+    let branch = repo.head_name()?.map(|name| name.shorten().to_string());
+    Ok(crate::commit::template(branch.as_deref(), &changes, &diff))
+}
+
+/// Commits the changes staged in the repository at `cwd` with `message`, returning the short id
+/// of the new commit. This runs `git commit` so that hooks and commit signing apply.
+pub fn commit(cwd: &Path, message: &str) -> Result<String> {
+    let repo = open_repo(cwd)?.to_thread_local();
+    if message.trim().is_empty() {
+        bail!("aborting commit due to empty commit message");
+    }
+    let workdir = repo.workdir().context("repository has no working tree")?;
+    git(
+        workdir,
+        &["commit", "--quiet", "--cleanup=verbatim", "--file=-"],
+        message.as_bytes(),
+    )?;
+    let id = git(workdir, &["rev-parse", "--short", "HEAD"], &[])?;
+    Ok(String::from_utf8_lossy(&id).trim().to_owned())
+}
+
+/// Runs the git command line interface with `args` in `cwd`, writing `input` to its stdin.
+fn git(cwd: &Path, args: &[&str], input: &[u8]) -> Result<Vec<u8>> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run git")?;
+    let mut stdin = child.stdin.take().context("failed to open stdin of git")?;
+    let input = input.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    writer.join().ok();
+    if !output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = if stderr.trim().is_empty() {
+            stdout
+        } else {
+            stderr
+        };
+        bail!("`git {}` failed: {}", args.join(" "), reason.trim());
+    }
+    Ok(output.stdout)
+}
+
+/// Writes the tree of the files staged in the index of `repo`, like `git write-tree`.
+fn write_index_tree(repo: &Repository) -> Result<ObjectId> {
+    let index = repo.index_or_empty()?;
+    let mut editor = repo.edit_tree(ObjectId::empty_tree(repo.object_hash()))?;
+    for entry in index.entries() {
+        let path = entry.path(&index);
+        if entry.stage() != Stage::Unconflicted {
+            bail!("{path} has unresolved conflicts");
+        }
+        if entry.flags.contains(Flags::INTENT_TO_ADD) {
+            continue;
+        }
+        let Some(mode) = entry.mode.to_tree_entry_mode() else {
+            continue;
+        };
+        editor.upsert(path, mode.kind(), entry.id)?;
+    }
+    Ok(editor.write()?.detach())
+}
+
+/// Walks the history of `HEAD`, newest first, passing at most `limit` commits to `on_commit`
+/// until it returns `false`. With `file`, only the commits that changed it are passed, following
+/// renames.
+pub fn log(
+    cwd: &Path,
+    file: Option<&Path>,
+    limit: usize,
+    on_commit: &mut dyn FnMut(LogCommit) -> bool,
+) -> Result<()> {
+    let (repo, mut path) = match file {
+        Some(file) => {
+            let (repo, _, rela_path) = open_file_repo(file)?;
+            (repo, Some(rela_path))
+        }
+        None => (open_repo(cwd)?.to_thread_local(), None),
+    };
+
+    let walk = repo
+        .head_commit()?
+        .ancestors()
+        .sorting(gix::revision::walk::Sorting::ByCommitTime(
+            Default::default(),
+        ))
+        .all()?;
+    let mut count = 0;
+    for info in walk {
+        if count >= limit {
+            break;
+        }
+        let commit = info?.object()?;
+        let (commit_path, added) = match &mut path {
+            Some(path) => match file_change(&repo, &commit, path)? {
+                FileHistory::Unchanged => continue,
+                FileHistory::Changed => (Some(path.to_string()), false),
+                FileHistory::Renamed(source) => {
+                    let current = std::mem::replace(path, source);
+                    (Some(current.to_string()), false)
+                }
+                FileHistory::Added => (Some(path.to_string()), true),
+            },
+            None => (None, false),
+        };
+        count += 1;
+        if !on_commit(log_commit(&commit, commit_path)?) || added {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn log_commit(commit: &Commit, path: Option<String>) -> Result<LogCommit> {
+    let author = commit.author()?;
+    Ok(LogCommit {
+        id: commit.id.to_string(),
+        author: author.name.to_string(),
+        time: author.time()?.seconds,
+        summary: commit.message()?.summary().to_string(),
+        path,
+    })
+}
+
+/// How a commit changed a file compared to its first parent.
+enum FileHistory {
+    Unchanged,
+    Changed,
+    /// The file was renamed from the given path.
+    Renamed(BString),
+    /// The file was created by the commit.
+    Added,
+}
+
+fn file_change(repo: &Repository, commit: &Commit, path: &BString) -> Result<FileHistory> {
+    let entry_id = |tree: &gix::Tree| -> Result<Option<ObjectId>> {
+        Ok(tree
+            .lookup_entry_by_path(gix::path::from_bstr(path.as_bstr()))?
+            .map(|entry| entry.object_id()))
+    };
+    let tree = commit.tree()?;
+    let id = entry_id(&tree)?;
+    let Some(parent) = commit.parent_ids().next() else {
+        return Ok(if id.is_some() {
+            FileHistory::Added
+        } else {
+            FileHistory::Unchanged
+        });
+    };
+    let parent_tree = parent.object()?.into_commit().tree()?;
+    let parent_id = entry_id(&parent_tree)?;
+    if id == parent_id {
+        return Ok(FileHistory::Unchanged);
+    }
+    if id.is_none() || parent_id.is_some() {
+        return Ok(FileHistory::Changed);
+    }
+
+    let mut options = gix::diff::Options::default();
+    options.track_rewrites(Some(Rewrites::default()));
+    for change in repo.diff_tree_to_tree(&parent_tree, &tree, options)? {
+        if let gix::object::tree::diff::ChangeDetached::Rewrite {
+            source_location,
+            location,
+            copy: false,
+            ..
+        } = change
+        {
+            if location == *path {
+                return Ok(FileHistory::Renamed(source_location));
+            }
+        }
+    }
+    Ok(FileHistory::Added)
+}
+
+/// Reads `file` as it was in `commit`. `path` overrides the path of the file relative to the
+/// repository root, e.g. to read it from before it was renamed.
+pub fn file_at_commit(file: &Path, commit: &str, path: Option<&str>) -> Result<Vec<u8>> {
+    let (repo, _, rela_path) = open_file_repo(file)?;
+    let rela_path = path.map_or(rela_path, BString::from);
+    let commit = repo.find_commit(ObjectId::from_hex(commit.as_bytes())?)?;
+    let entry = commit
+        .tree()?
+        .lookup_entry_by_path(gix::path::from_bstr(rela_path.as_bstr()))?
+        .with_context(|| format!("{rela_path} does not exist in {}", commit.id))?;
+    if !entry.mode().is_blob() {
+        bail!("{rela_path} is not a file in {}", commit.id);
+    }
+    read_blob(&repo, entry.object_id(), &rela_path)
+}
+
+pub fn for_each_changed_file(
+    cwd: &Path,
+    base: &DiffBase,
+    f: impl Fn(Result<FileChange>) -> bool,
+) -> Result<()> {
+    status(&open_repo(cwd)?.to_thread_local(), base, f)
 }
 
 fn open_repo(path: &Path) -> Result<ThreadSafeRepository> {
@@ -128,8 +706,60 @@ fn open_repo(path: &Path) -> Result<ThreadSafeRepository> {
     Ok(res)
 }
 
-/// Emulates the result of running `git status` from the command line.
-fn status(repo: &Repository, f: impl Fn(Result<FileChange>) -> bool) -> Result<()> {
+/// Computes the status of the changed, untracked and ignored files of the repository at `cwd`.
+/// Ignored directories are reported as a whole instead of file by file.
+pub fn file_statuses(cwd: &Path) -> Result<FileStatuses> {
+    let repo = open_repo(cwd)?.to_thread_local();
+    let work_dir = repo
+        .workdir()
+        .ok_or_else(|| anyhow::anyhow!("working tree not found"))?
+        .to_path_buf();
+    let status_platform = repo
+        .status(gix::progress::Discard)?
+        .untracked_files(UntrackedFiles::Files)
+        .dirwalk_options(|options| options.emit_ignored(Some(EmissionMode::CollapseDirectory)));
+
+    let mut statuses = FileStatuses::default();
+    for item in status_platform.into_iter(Vec::new())? {
+        let (rela_path, status) = match item? {
+            gix::status::Item::TreeIndex(change) => {
+                (change.location().to_owned(), FileStatus::Staged)
+            }
+            gix::status::Item::IndexWorktree(item) => match item {
+                Item::Modification {
+                    rela_path,
+                    status: EntryStatus::Conflict { .. },
+                    ..
+                } => (rela_path, FileStatus::Conflict),
+                Item::Modification {
+                    rela_path,
+                    status: EntryStatus::Change(_),
+                    ..
+                } => (rela_path, FileStatus::Modified),
+                Item::DirectoryContents { entry, .. } => match entry.status {
+                    Status::Untracked => (entry.rela_path, FileStatus::Untracked),
+                    Status::Ignored(_) => (entry.rela_path, FileStatus::Ignored),
+                    _ => continue,
+                },
+                Item::Rewrite { dirwalk_entry, .. } => {
+                    (dirwalk_entry.rela_path, FileStatus::Modified)
+                }
+                _ => continue,
+            },
+        };
+        statuses.insert(work_dir.join(rela_path.to_path()?), status);
+    }
+    Ok(statuses)
+}
+
+/// Emulates the result of running `git status` from the command line. Changes staged in the
+/// index are only reported when comparing against a revision, as the index itself is the default
+/// base.
+fn status(
+    repo: &Repository,
+    base: &DiffBase,
+    f: impl Fn(Result<FileChange>) -> bool,
+) -> Result<()> {
     let work_dir = repo
         .workdir()
         .ok_or_else(|| anyhow::anyhow!("working tree not found"))?
@@ -153,41 +783,38 @@ fn status(repo: &Repository, f: impl Fn(Result<FileChange>) -> bool) -> Result<(
     // No filtering based on path
     let empty_patterns = vec![];
 
-    let status_iter = status_platform.into_index_worktree_iter(empty_patterns)?;
+    if *base == DiffBase::Index {
+        for item in status_platform.into_index_worktree_iter(empty_patterns)? {
+            let Ok(item) = item.map_err(|err| f(Err(err.into()))) else {
+                continue;
+            };
+            let Some(change) = index_worktree_change(&work_dir, item)? else {
+                continue;
+            };
+            if !f(Ok(change)) {
+                break;
+            }
+        }
+        return Ok(());
+    }
 
-    for item in status_iter {
+    let tree = base_commit(repo, base)?.tree_id()?;
+    // a file changed in the index and the work tree is reported twice
+    let mut seen = HashSet::new();
+    for item in status_platform.head_tree(tree).into_iter(empty_patterns)? {
         let Ok(item) = item.map_err(|err| f(Err(err.into()))) else {
             continue;
         };
         let change = match item {
-            Item::Modification {
-                rela_path, status, ..
-            } => {
-                let path = work_dir.join(rela_path.to_path()?);
-                match status {
-                    EntryStatus::Conflict { .. } => FileChange::Conflict { path },
-                    EntryStatus::Change(Change::Removed) => FileChange::Deleted { path },
-                    EntryStatus::Change(Change::Modification { .. }) => {
-                        FileChange::Modified { path }
-                    }
-                    _ => continue,
-                }
-            }
-            Item::DirectoryContents { entry, .. } if entry.status == Status::Untracked => {
-                FileChange::Untracked {
-                    path: work_dir.join(entry.rela_path.to_path()?),
-                }
-            }
-            Item::Rewrite {
-                source,
-                dirwalk_entry,
-                ..
-            } => FileChange::Renamed {
-                from_path: work_dir.join(source.rela_path().to_path()?),
-                to_path: work_dir.join(dirwalk_entry.rela_path.to_path()?),
-            },
-            _ => continue,
+            gix::status::Item::IndexWorktree(item) => index_worktree_change(&work_dir, item)?,
+            gix::status::Item::TreeIndex(change) => Some(tree_index_change(&work_dir, change)?),
         };
+        let Some(change) = change else {
+            continue;
+        };
+        if !seen.insert(change.path().to_path_buf()) {
+            continue;
+        }
         if !f(Ok(change)) {
             break;
         }
@@ -196,6 +823,61 @@ fn status(repo: &Repository, f: impl Fn(Result<FileChange>) -> bool) -> Result<(
     Ok(())
 }
 
+fn index_worktree_change(work_dir: &Path, item: Item) -> Result<Option<FileChange>> {
+    let change = match item {
+        Item::Modification {
+            rela_path, status, ..
+        } => {
+            let path = work_dir.join(rela_path.to_path()?);
+            match status {
+                EntryStatus::Conflict { .. } => FileChange::Conflict { path },
+                EntryStatus::Change(Change::Removed) => FileChange::Deleted { path },
+                EntryStatus::Change(Change::Modification { .. }) => FileChange::Modified { path },
+                _ => return Ok(None),
+            }
+        }
+        Item::DirectoryContents { entry, .. } if entry.status == Status::Untracked => {
+            FileChange::Untracked {
+                path: work_dir.join(entry.rela_path.to_path()?),
+            }
+        }
+        Item::Rewrite {
+            source,
+            dirwalk_entry,
+            ..
+        } => FileChange::Renamed {
+            from_path: work_dir.join(source.rela_path().to_path()?),
+            to_path: work_dir.join(dirwalk_entry.rela_path.to_path()?),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(change))
+}
+
+fn tree_index_change(work_dir: &Path, change: gix::diff::index::Change) -> Result<FileChange> {
+    use gix::diff::index::ChangeRef;
+
+    let change = match change {
+        ChangeRef::Addition { location, .. } | ChangeRef::Modification { location, .. } => {
+            FileChange::Modified {
+                path: work_dir.join(location.to_path()?),
+            }
+        }
+        ChangeRef::Deletion { location, .. } => FileChange::Deleted {
+            path: work_dir.join(location.to_path()?),
+        },
+        ChangeRef::Rewrite {
+            source_location,
+            location,
+            ..
+        } => FileChange::Renamed {
+            from_path: work_dir.join(source_location.to_path()?),
+            to_path: work_dir.join(location.to_path()?),
+        },
+    };
+    Ok(change)
+}
+
 /// Finds the object that contains the contents of a file at a specific commit.
 fn find_file_in_commit(repo: &Repository, commit: &Commit, file: &Path) -> Result<ObjectId> {
     let repo_dir = repo.workdir().context("repo has no worktree")?;
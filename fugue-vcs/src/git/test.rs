@@ -5,7 +5,7 @@
 
 use tempfile::TempDir;
 
-use crate::git;
+use crate::{git, DiffBase, FileStatus, LogCommit};
 
 fn exec_git_cmd(args: &str, git_dir: &Path) {
     let res = Command::new("git")
@@ -43,6 +43,16 @@ fn create_commit(repo: &Path, add_modified: bool) {
     exec_git_cmd("commit -m message", repo);
 }
 
+fn log(cwd: &Path, file: Option<&Path>, limit: usize) -> Vec<LogCommit> {
+    let mut commits = Vec::new();
+    git::log(cwd, file, limit, &mut |commit| {
+        commits.push(commit);
+        true
+    })
+    .unwrap();
+    commits
+}
+
 fn empty_git_repo() -> TempDir {
     let tmp = tempfile::tempdir().expect("create temp dir for git testing");
     exec_git_cmd("init", tmp.path());
@@ -57,7 +67,7 @@ fn missing_file() {
     let file = temp_git.path().join("file.txt");
     File::create(&file).unwrap().write_all(b"foo").unwrap();
 
-    assert!(git::get_diff_base(&file).is_err());
+    assert!(git::get_diff_base(&file, &DiffBase::Index).is_err());
 }
 
 #[test]
@@ -67,7 +77,10 @@ fn unmodified_file() {
     let contents = b"foo".as_slice();
     File::create(&file).unwrap().write_all(contents).unwrap();
     create_commit(temp_git.path(), true);
-    assert_eq!(git::get_diff_base(&file).unwrap(), Vec::from(contents));
+    assert_eq!(
+        git::get_diff_base(&file, &DiffBase::Index).unwrap(),
+        Vec::from(contents)
+    );
 }
 
 #[test]
@@ -79,7 +92,10 @@ fn modified_file() {
     create_commit(temp_git.path(), true);
     File::create(&file).unwrap().write_all(b"bar").unwrap();
 
-    assert_eq!(git::get_diff_base(&file).unwrap(), Vec::from(contents));
+    assert_eq!(
+        git::get_diff_base(&file, &DiffBase::Index).unwrap(),
+        Vec::from(contents)
+    );
 }
 
 /// Test that `get_file_head` does not return content for a directory.
@@ -98,7 +114,7 @@ fn directory() {
 
     std::fs::remove_dir_all(&dir).unwrap();
     File::create(&dir).unwrap().write_all(b"bar").unwrap();
-    assert!(git::get_diff_base(&dir).is_err());
+    assert!(git::get_diff_base(&dir, &DiffBase::Index).is_err());
 }
 
 /// Test that `get_diff_base` resolves symlinks so that the same diff base is
@@ -122,8 +138,14 @@ fn symlink() {
     symlink("file.txt", &file_link).unwrap();
     create_commit(temp_git.path(), true);
 
-    assert_eq!(git::get_diff_base(&file_link).unwrap(), contents);
-    assert_eq!(git::get_diff_base(&file).unwrap(), contents);
+    assert_eq!(
+        git::get_diff_base(&file_link, &DiffBase::Index).unwrap(),
+        contents
+    );
+    assert_eq!(
+        git::get_diff_base(&file, &DiffBase::Index).unwrap(),
+        contents
+    );
 }
 
 /// Test that `get_diff_base` returns content when the file is a symlink to
@@ -144,6 +166,267 @@ fn symlink_to_git_repo() {
     let file_link = temp_dir.path().join("file_link.txt");
     symlink(&file, &file_link).unwrap();
 
-    assert_eq!(git::get_diff_base(&file_link).unwrap(), contents);
-    assert_eq!(git::get_diff_base(&file).unwrap(), contents);
+    assert_eq!(
+        git::get_diff_base(&file_link, &DiffBase::Index).unwrap(),
+        contents
+    );
+    assert_eq!(
+        git::get_diff_base(&file, &DiffBase::Index).unwrap(),
+        contents
+    );
+}
+
+#[test]
+fn blame() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"a\nb\n").unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&file).unwrap().write_all(b"a\nc\n").unwrap();
+    create_commit(temp_git.path(), true);
+
+    let blame = git::blame(&file).unwrap();
+    let first = blame.base_line(0).unwrap();
+    let second = blame.base_line(1).unwrap();
+    assert_ne!(first.id, second.id);
+    assert_eq!(first.author, "author");
+    assert_eq!(first.summary, "message");
+    assert_eq!(first.path, "file.txt");
+    assert!(blame.base_line(2).is_none());
+
+    let diff = git::commit_diff(temp_git.path(), &second.id, Some("file.txt")).unwrap();
+    assert!(diff.starts_with(&format!(
+        "commit {}\nAuthor: author <author@example.com>",
+        second.id
+    )));
+    assert!(diff.ends_with("--- a/file.txt\n+++ b/file.txt\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n"));
+}
+
+#[test]
+fn stage_and_unstage() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file)
+        .unwrap()
+        .write_all(b"a\nb\nc\n")
+        .unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&file)
+        .unwrap()
+        .write_all(b"a\nB\nC\n")
+        .unwrap();
+
+    // stage only the change to the second line
+    git::stage(&file, b"a\nB\nc\n").unwrap();
+    assert_eq!(
+        git::get_diff_base(&file, &DiffBase::Index).unwrap(),
+        b"a\nB\nc\n"
+    );
+    assert_eq!(git::get_head_base(&file).unwrap(), b"a\nb\nc\n");
+    let hunks = git::get_base_hunks(&file, &DiffBase::Index).unwrap();
+    assert_eq!(hunks.len(), 1);
+    assert_eq!(hunks[0].before, 1..2);
+    assert_eq!(hunks[0].after, 1..2);
+    // the index written by us must be readable by git
+    exec_git_cmd("diff --cached --stat", temp_git.path());
+
+    git::unstage(&file).unwrap();
+    assert_eq!(
+        git::get_diff_base(&file, &DiffBase::Index).unwrap(),
+        b"a\nb\nc\n"
+    );
+    assert!(git::get_base_hunks(&file, &DiffBase::Index)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn stage_untracked_file() {
+    let temp_git = empty_git_repo();
+    let committed = temp_git.path().join("committed.txt");
+    File::create(&committed).unwrap().write_all(b"foo").unwrap();
+    create_commit(temp_git.path(), true);
+
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"bar\n").unwrap();
+    git::stage(&file, b"bar\n").unwrap();
+    assert_eq!(
+        git::get_diff_base(&file, &DiffBase::Index).unwrap(),
+        b"bar\n"
+    );
+    assert_eq!(
+        git::get_base_hunks(&file, &DiffBase::Index).unwrap().len(),
+        1
+    );
+    assert_eq!(
+        git::get_diff_base(&committed, &DiffBase::Index).unwrap(),
+        b"foo"
+    );
+
+    git::unstage(&file).unwrap();
+    assert!(git::get_diff_base(&file, &DiffBase::Index).is_err());
+}
+
+#[test]
+fn diff_against_revision() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"a\n").unwrap();
+    create_commit(temp_git.path(), true);
+    exec_git_cmd("tag v1", temp_git.path());
+    File::create(&file).unwrap().write_all(b"b\n").unwrap();
+    create_commit(temp_git.path(), true);
+
+    let rev = |rev: &str| rev.parse::<DiffBase>().unwrap();
+    assert_eq!(git::get_diff_base(&file, &rev("v1")).unwrap(), b"a\n");
+    assert_eq!(git::get_diff_base(&file, &rev("HEAD")).unwrap(), b"b\n");
+    assert_eq!(git::get_diff_base(&file, &rev("...v1")).unwrap(), b"a\n");
+    assert!(git::get_diff_base(&file, &rev("missing")).is_err());
+    assert_eq!(git::get_base_hunks(&file, &rev("v1")).unwrap().len(), 1);
+
+    let changes = std::sync::Mutex::new(Vec::new());
+    let collect = |change: anyhow::Result<crate::FileChange>| {
+        changes
+            .lock()
+            .unwrap()
+            .push(change.unwrap().path().to_path_buf());
+        true
+    };
+    git::for_each_changed_file(temp_git.path(), &DiffBase::Index, collect).unwrap();
+    assert!(changes.lock().unwrap().is_empty());
+    git::for_each_changed_file(temp_git.path(), &rev("v1"), collect).unwrap();
+    assert_eq!(changes.lock().unwrap().len(), 1);
+    assert!(changes.lock().unwrap()[0].ends_with("file.txt"));
+}
+
+#[test]
+fn file_history_follows_renames() {
+    let temp_git = empty_git_repo();
+    File::create(temp_git.path().join("old.txt"))
+        .unwrap()
+        .write_all(b"a\nb\nc\n")
+        .unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(temp_git.path().join("other.txt"))
+        .unwrap()
+        .write_all(b"x\n")
+        .unwrap();
+    create_commit(temp_git.path(), true);
+    exec_git_cmd("mv old.txt new.txt", temp_git.path());
+    create_commit(temp_git.path(), false);
+    let file = temp_git.path().join("new.txt");
+    File::create(&file)
+        .unwrap()
+        .write_all(b"a\nb\nd\n")
+        .unwrap();
+    create_commit(temp_git.path(), true);
+
+    let history = log(temp_git.path(), Some(&file), 100);
+    let paths: Vec<_> = history
+        .iter()
+        .map(|commit| commit.path.as_deref().unwrap())
+        .collect();
+    assert_eq!(paths, ["new.txt", "new.txt", "old.txt"]);
+    assert_eq!(log(temp_git.path(), None, 100).len(), 4);
+    assert_eq!(log(temp_git.path(), None, 2).len(), 2);
+
+    let first = &history[2];
+    assert_eq!(
+        git::file_at_commit(&file, &first.id, Some("old.txt")).unwrap(),
+        b"a\nb\nc\n"
+    );
+    assert!(git::file_at_commit(&file, &first.id, None).is_err());
+
+    let diff = git::commit_diff(temp_git.path(), &history[0].id, None).unwrap();
+    assert!(diff.ends_with("--- a/new.txt\n+++ b/new.txt\n@@ -1,3 +1,3 @@\n a\n b\n-c\n+d\n"));
+}
+
+#[test]
+fn commit_staged_changes() {
+    let temp_git = empty_git_repo();
+    std::fs::create_dir(temp_git.path().join("dir")).unwrap();
+    File::create(temp_git.path().join("dir/file.txt"))
+        .unwrap()
+        .write_all(b"a\n")
+        .unwrap();
+    assert!(git::commit_template(temp_git.path()).is_err());
+    exec_git_cmd("add -A", temp_git.path());
+
+    let template = git::commit_template(temp_git.path()).unwrap();
+    assert!(template.contains("#\tnew file:   dir/file.txt\n"));
+    assert!(template.ends_with("--- a/dir/file.txt\n+++ b/dir/file.txt\n@@ -1,0 +1,1 @@\n+a\n"));
+    git::commit(temp_git.path(), "initial\n").unwrap();
+    exec_git_cmd("diff --cached --exit-code", temp_git.path());
+
+    File::create(temp_git.path().join("dir/file.txt"))
+        .unwrap()
+        .write_all(b"b\n")
+        .unwrap();
+    assert!(git::commit(temp_git.path(), "nothing staged\n").is_err());
+    exec_git_cmd("add -A", temp_git.path());
+    assert!(git::commit(temp_git.path(), "\n").is_err());
+    git::commit(temp_git.path(), "second\n").unwrap();
+    exec_git_cmd("diff --cached --exit-code", temp_git.path());
+
+    let history = log(temp_git.path(), None, 10);
+    let summaries: Vec<_> = history.iter().map(|commit| &*commit.summary).collect();
+    assert_eq!(summaries, ["second", "initial"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn commit_runs_hooks() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_git = empty_git_repo();
+    let hook = temp_git.path().join(".git/hooks/pre-commit");
+    std::fs::write(&hook, "#!/bin/sh\necho rejected by hook >&2\nexit 1\n").unwrap();
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+    File::create(temp_git.path().join("file.txt"))
+        .unwrap()
+        .write_all(b"a\n")
+        .unwrap();
+    exec_git_cmd("add -A", temp_git.path());
+    let err = git::commit(temp_git.path(), "initial\n").unwrap_err();
+    assert!(err.to_string().contains("rejected by hook"));
+}
+
+#[test]
+fn file_statuses() {
+    let temp_git = empty_git_repo();
+    let path = temp_git.path();
+    std::fs::create_dir_all(path.join("src")).unwrap();
+    std::fs::create_dir_all(path.join("target/debug")).unwrap();
+    for file in ["src/staged.rs", "src/modified.rs"] {
+        File::create(path.join(file))
+            .unwrap()
+            .write_all(b"a\n")
+            .unwrap();
+    }
+    File::create(path.join(".gitignore"))
+        .unwrap()
+        .write_all(b"target\n")
+        .unwrap();
+    create_commit(path, true);
+
+    File::create(path.join("src/staged.rs"))
+        .unwrap()
+        .write_all(b"b\n")
+        .unwrap();
+    exec_git_cmd("add src/staged.rs", path);
+    File::create(path.join("src/modified.rs"))
+        .unwrap()
+        .write_all(b"b\n")
+        .unwrap();
+    File::create(path.join("untracked.txt")).unwrap();
+    File::create(path.join("target/debug/out")).unwrap();
+
+    let statuses = git::file_statuses(path).unwrap();
+    let status = |file: &str| statuses.get(&path.join(file));
+    assert_eq!(status("src/staged.rs"), Some(FileStatus::Staged));
+    assert_eq!(status("src/modified.rs"), Some(FileStatus::Modified));
+    assert_eq!(status("src"), Some(FileStatus::Modified));
+    assert_eq!(status("untracked.txt"), Some(FileStatus::Untracked));
+    assert_eq!(status("target/debug/out"), Some(FileStatus::Ignored));
+    assert_eq!(status(".gitignore"), None);
 }
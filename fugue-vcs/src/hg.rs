@@ -0,0 +1,179 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+//! A provider for Mercurial repositories implemented with the `hg` command line interface.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use arc_swap::ArcSwap;
+
+use crate::command::{find_file, find_root, run};
+use crate::{DiffBase, FileChange};
+
+const MARKER: &str = ".hg";
+
+fn hg(root: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    // ignore user configuration that changes the output format
+    run("hg", root, args, &[("HGPLAIN", "1")])
+}
+
+/// The revision `base` refers to. Mercurial has no index, so by default the working directory is
+/// compared to its parent.
+fn revision(base: &DiffBase) -> String {
+    match base {
+        DiffBase::Index => ".".to_owned(),
+        DiffBase::Rev(rev) => rev.clone(),
+        DiffBase::MergeBase(rev) => format!("ancestor(., {rev})"),
+    }
+}
+
+pub fn get_diff_base(file: &Path, base: &DiffBase) -> Result<Vec<u8>> {
+    let (root, rela_path) = find_file(file, MARKER)?;
+    hg(
+        &root,
+        &["cat", "-r", &revision(base), &format!("path:{rela_path}")],
+    )
+}
+
+/// The branch of the working directory.
+pub fn get_current_head_name(file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
+    let (root, _) = find_file(file, MARKER)?;
+    let name = hg(&root, &["branch"])?;
+    let name = String::from_utf8_lossy(&name).trim().to_owned();
+    Ok(Arc::new(ArcSwap::from_pointee(name.into_boxed_str())))
+}
+
+pub fn for_each_changed_file(
+    cwd: &Path,
+    base: &DiffBase,
+    f: impl Fn(Result<FileChange>) -> bool,
+) -> Result<()> {
+    let root = find_root(cwd, MARKER)?;
+    let rev = revision(base);
+    let mut args = vec!["status", "--copies"];
+    if *base != DiffBase::Index {
+        args.extend(["--rev", &rev]);
+    }
+    let status = hg(&root, &args)?;
+    let status = String::from_utf8_lossy(&status);
+
+    // `--copies` lists the source of an added file on the following line, indented by two
+    // spaces. Sources that were removed make the addition a rename.
+    let mut lines = status.lines().peekable();
+    let mut changes = Vec::new();
+    let mut copy_sources = HashSet::new();
+    while let Some(line) = lines.next() {
+        let Some((status, path)) = line.split_once(' ') else {
+            continue;
+        };
+        let source = lines
+            .next_if(|line| line.starts_with("  "))
+            .map(|line| &line[2..]);
+        changes.push((status, path, source));
+        copy_sources.extend(source);
+    }
+
+    let removed: HashSet<_> = changes
+        .iter()
+        .filter(|(status, ..)| matches!(*status, "R" | "!"))
+        .map(|(_, path, _)| *path)
+        .collect();
+    for (status, path, source) in changes {
+        let change = match (status, source) {
+            ("A", Some(source)) if removed.contains(source) => FileChange::Renamed {
+                from_path: root.join(source),
+                to_path: root.join(path),
+            },
+            ("A" | "?", _) => FileChange::Untracked {
+                path: root.join(path),
+            },
+            ("M", _) => FileChange::Modified {
+                path: root.join(path),
+            },
+            ("R" | "!", _) if copy_sources.contains(path) => continue,
+            ("R" | "!", _) => FileChange::Deleted {
+                path: root.join(path),
+            },
+            _ => continue,
+        };
+        if !f(Ok(change)) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::process::Command;
+
+    use super::*;
+
+    fn exec_hg_cmd(args: &[&str], dir: &Path) {
+        let output = Command::new("hg")
+            .args(args)
+            .current_dir(dir)
+            .env("HGPLAIN", "1")
+            .env("HGRCPATH", "")
+            .env("HGUSER", "fugue-test <test@fugue.org>")
+            .output()
+            .unwrap_or_else(|_| panic!("`hg {args:?}` failed"));
+        assert!(
+            output.status.success(),
+            "`hg {args:?}` failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    #[ignore = "requires hg"]
+    fn changed_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path();
+        exec_hg_cmd(&["init"], repo);
+        let file = repo.join("file.txt");
+        fs::write(&file, "a\n").unwrap();
+        fs::write(repo.join("old.txt"), "x\n").unwrap();
+        exec_hg_cmd(&["commit", "-A", "-m", "first"], repo);
+        fs::write(&file, "b\n").unwrap();
+        exec_hg_cmd(&["mv", "old.txt", "new.txt"], repo);
+
+        assert_eq!(get_diff_base(&file, &DiffBase::Index).unwrap(), b"a\n");
+        assert!(get_diff_base(&repo.join("missing.txt"), &DiffBase::Index).is_err());
+        assert_eq!(
+            &**get_current_head_name(&file).unwrap().load_full(),
+            "default"
+        );
+
+        let changes = std::sync::Mutex::new(Vec::new());
+        for_each_changed_file(repo, &DiffBase::Index, |change| {
+            let change = match change.unwrap() {
+                FileChange::Modified { path } => format!("M {}", path.display()),
+                FileChange::Renamed { from_path, to_path } => {
+                    format!("R {} {}", from_path.display(), to_path.display())
+                }
+                change => panic!("unexpected change of {}", change.path().display()),
+            };
+            changes.lock().unwrap().push(change);
+            true
+        })
+        .unwrap();
+        let mut changes = changes.into_inner().unwrap();
+        changes.sort();
+        assert_eq!(
+            changes,
+            [
+                format!("M {}", file.display()),
+                format!(
+                    "R {} {}",
+                    repo.join("old.txt").display(),
+                    repo.join("new.txt").display()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,30 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use crate::blame::time_ago;
+
+/// A commit in the history of the repository or of a single file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogCommit {
+    /// The full hex id of the commit.
+    pub id: String,
+    pub author: String,
+    /// Author time in seconds since the unix epoch.
+    pub time: i64,
+    /// The first line of the commit message.
+    pub summary: String,
+    /// For file histories, the path of the file relative to the repository root in this commit.
+    /// Differs from the current path if the file was renamed since.
+    pub path: Option<String>,
+}
+
+impl LogCommit {
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(8)]
+    }
+
+    /// The author time relative to now, e.g. `3 days ago`.
+    pub fn relative_time(&self) -> String {
+        time_ago(self.time)
+    }
+}
@@ -0,0 +1,324 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+//! A provider for [Jujutsu](https://github.com/jj-vcs/jj) repositories, including repositories
+//! colocated with git, implemented with the `jj` command line interface.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{ensure, Context, Result};
+use arc_swap::ArcSwap;
+
+use crate::command::{find_file, find_root, run};
+use crate::{DiffBase, FileChange};
+
+const MARKER: &str = ".jj";
+
+fn jj(root: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let mut jj_args = vec!["--no-pager", "--color", "never"];
+    jj_args.extend_from_slice(args);
+    run("jj", root, &jj_args, &[])
+}
+
+/// The revset `base` refers to. jj has no index, so [`DiffBase::Index`] stands for the parents
+/// of the working-copy commit. `@-` is ambiguous for merges though, so it is only used for
+/// revisions other than the index.
+fn revset(base: &DiffBase) -> String {
+    match base {
+        DiffBase::Index => "@-".to_owned(),
+        DiffBase::Rev(rev) => rev.clone(),
+        DiffBase::MergeBase(rev) => format!("heads(::@ & ::({rev}))"),
+    }
+}
+
+/// A fileset matching exactly the file at `rela_path`.
+fn fileset(rela_path: &str) -> String {
+    let escaped = rela_path.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("root-file:\"{escaped}\"")
+}
+
+pub fn get_diff_base(file: &Path, base: &DiffBase) -> Result<Vec<u8>> {
+    let (root, rela_path) = find_file(file, MARKER)?;
+    let fileset = fileset(&rela_path);
+    if *base != DiffBase::Index {
+        return file_at(&root, &revset(base), &fileset, &rela_path);
+    }
+
+    // `@-` is ambiguous for merges, so the merged parents are recovered by reverting the changes
+    // of the working-copy commit
+    let diff = jj(
+        &root,
+        &[
+            "diff",
+            "--ignore-working-copy",
+            "--git",
+            "-r",
+            "@",
+            &fileset,
+        ],
+    )?;
+    let diff = String::from_utf8_lossy(&diff);
+    let header = |prefix: &str| diff.lines().any(|line| line.starts_with(prefix));
+    ensure!(
+        !header("new file mode"),
+        "{rela_path} does not exist in the parents of the working copy"
+    );
+    ensure!(!header("Binary files"), "{rela_path} is a binary file");
+    let content = if header("deleted file mode") {
+        Vec::new()
+    } else {
+        file_at(&root, "@", &fileset, &rela_path)?
+    };
+    revert_diff(&content, &diff)
+}
+
+/// Reads the file matched by `fileset` in `rev`. `jj file show` succeeds without output when
+/// nothing matches, so the file is looked up first.
+fn file_at(root: &Path, rev: &str, fileset: &str, rela_path: &str) -> Result<Vec<u8>> {
+    let files = jj(
+        root,
+        &["file", "list", "--ignore-working-copy", "-r", rev, fileset],
+    )?;
+    ensure!(!files.is_empty(), "{rela_path} does not exist in {rev}");
+    jj(
+        root,
+        &["file", "show", "--ignore-working-copy", "-r", rev, fileset],
+    )
+}
+
+/// Undoes the changes of the git style `diff` of a single file on `content`, the file after the
+/// changes.
+fn revert_diff(content: &[u8], diff: &str) -> Result<Vec<u8>> {
+    let lines: Vec<&[u8]> = content.split_inclusive(|&b| b == b'\n').collect();
+    let mut before = Vec::with_capacity(content.len());
+    let mut line = 0;
+    let mut last_removed = false;
+    let mut in_hunk = false;
+    for diff_line in diff.lines() {
+        if let Some(header) = diff_line.strip_prefix("@@ ") {
+            let after = header
+                .split(' ')
+                .find_map(|range| range.strip_prefix('+'))
+                .context("invalid hunk header")?;
+            let (start, len) = after.split_once(',').unwrap_or((after, "1"));
+            let (start, len): (usize, usize) = (start.parse()?, len.parse()?);
+            // an empty range starts after the given line
+            let start = if len == 0 { start } else { start - 1 };
+            ensure!(start >= line && start <= lines.len(), "diff does not apply");
+            lines[line..start]
+                .iter()
+                .for_each(|l| before.extend_from_slice(l));
+            line = start;
+            in_hunk = true;
+            continue;
+        }
+        if !in_hunk {
+            continue;
+        }
+        match diff_line.as_bytes().first() {
+            Some(b' ') => {
+                let l = lines.get(line).context("diff does not apply")?;
+                before.extend_from_slice(l);
+                line += 1;
+                last_removed = false;
+            }
+            Some(b'-') => {
+                before.extend_from_slice(&diff_line.as_bytes()[1..]);
+                before.push(b'\n');
+                last_removed = true;
+            }
+            Some(b'+') => {
+                line += 1;
+                last_removed = false;
+            }
+            // "\ No newline at end of file" refers to the line before it
+            Some(b'\\') if last_removed => {
+                before.pop();
+            }
+            _ => (),
+        }
+    }
+    ensure!(line <= lines.len(), "diff does not apply");
+    lines[line..]
+        .iter()
+        .for_each(|l| before.extend_from_slice(l));
+    Ok(before)
+}
+
+/// The change id of the working-copy commit followed by its bookmarks.
+pub fn get_current_head_name(file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
+    let (root, _) = find_file(file, MARKER)?;
+    let name = jj(
+        &root,
+        &[
+            "log",
+            "--ignore-working-copy",
+            "--no-graph",
+            "-r",
+            "@",
+            "-T",
+            r#"separate(" ", change_id.shortest(8), bookmarks)"#,
+        ],
+    )?;
+    let name = String::from_utf8_lossy(&name).trim().to_owned();
+    Ok(Arc::new(ArcSwap::from_pointee(name.into_boxed_str())))
+}
+
+pub fn for_each_changed_file(
+    cwd: &Path,
+    base: &DiffBase,
+    f: impl Fn(Result<FileChange>) -> bool,
+) -> Result<()> {
+    let root = find_root(cwd, MARKER)?;
+    let summary = match base {
+        DiffBase::Index => jj(&root, &["diff", "--summary", "-r", "@"])?,
+        base => jj(
+            &root,
+            &["diff", "--summary", "--from", &revset(base), "--to", "@"],
+        )?,
+    };
+    for line in String::from_utf8_lossy(&summary).lines() {
+        let Some((status, path)) = line.split_once(' ') else {
+            continue;
+        };
+        let change = match status {
+            "M" => FileChange::Modified {
+                path: root.join(path),
+            },
+            "A" => FileChange::Untracked {
+                path: root.join(path),
+            },
+            "D" => FileChange::Deleted {
+                path: root.join(path),
+            },
+            "R" | "C" => {
+                let Some((from, to)) = parse_rename(path) else {
+                    continue;
+                };
+                if status == "R" {
+                    FileChange::Renamed {
+                        from_path: root.join(from),
+                        to_path: root.join(to),
+                    }
+                } else {
+                    FileChange::Untracked {
+                        path: root.join(to),
+                    }
+                }
+            }
+            _ => continue,
+        };
+        if !f(Ok(change)) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Splits a rename like `src/{old => new}.rs` into both paths.
+fn parse_rename(path: &str) -> Option<(String, String)> {
+    let (prefix, rest) = path.split_once('{').unwrap_or(("", path));
+    let (renamed, suffix) = rest.rsplit_once('}').unwrap_or((rest, ""));
+    let (from, to) = renamed.split_once(" => ")?;
+    let join = |part: &str| format!("{prefix}{part}{suffix}").replace("//", "/");
+    Some((join(from), join(to)))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::process::Command;
+
+    use super::*;
+
+    #[test]
+    fn rename() {
+        assert_eq!(
+            parse_rename("src/{old => new}.rs"),
+            Some(("src/old.rs".to_owned(), "src/new.rs".to_owned()))
+        );
+        assert_eq!(
+            parse_rename("{a => b/c}/file"),
+            Some(("a/file".to_owned(), "b/c/file".to_owned()))
+        );
+        assert_eq!(
+            parse_rename("src/{ => nested}/file"),
+            Some(("src/file".to_owned(), "src/nested/file".to_owned()))
+        );
+        assert_eq!(
+            parse_rename("old => new"),
+            Some(("old".to_owned(), "new".to_owned()))
+        );
+        assert_eq!(parse_rename("file"), None);
+    }
+
+    fn exec_jj_cmd(args: &[&str], dir: &Path, config: &Path) {
+        let output = Command::new("jj")
+            .args(args)
+            .current_dir(dir)
+            .env("JJ_CONFIG", config)
+            .env("JJ_USER", "fugue-test")
+            .env("JJ_EMAIL", "test@fugue.org")
+            .output()
+            .unwrap_or_else(|_| panic!("`jj {args:?}` failed"));
+        assert!(
+            output.status.success(),
+            "`jj {args:?}` failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    fn revert() {
+        let diff = "diff --git a/file b/file\nindex 1..2 100644\n--- a/file\n+++ b/file\n\
+                    @@ -1,2 +1,2 @@\n-a\n+A\n b\n@@ -4,0 +5,1 @@\n+e\n@@ -6,1 +7,1 @@\n-f\n\
+                    \\ No newline at end of file\n+F\n";
+        let content = b"A\nb\nc\nd\ne\nx\nF\n";
+        assert_eq!(
+            revert_diff(content, diff).unwrap(),
+            b"a\nb\nc\nd\nx\nf".to_vec()
+        );
+        assert_eq!(revert_diff(b"a\n", "").unwrap(), b"a\n".to_vec());
+        assert!(revert_diff(b"a\n", "@@ -1,1 +5,1 @@\n-b\n+a\n").is_err());
+    }
+
+    #[test]
+    #[ignore = "requires jj"]
+    fn changed_file() {
+        let temp = tempfile::tempdir().unwrap();
+        // keep the config outside of the working copy, which jj tracks automatically
+        let config = temp.path().join("config.toml");
+        fs::write(&config, "").unwrap();
+        let repo = temp.path().join("repo");
+        fs::create_dir(&repo).unwrap();
+        exec_jj_cmd(&["git", "init"], &repo, &config);
+        let file = repo.join("file.txt");
+        fs::write(&file, "a\n").unwrap();
+        exec_jj_cmd(&["commit", "-m", "first"], &repo, &config);
+        fs::write(&file, "b\n").unwrap();
+        exec_jj_cmd(
+            &["bookmark", "create", "-r", "@", "feature"],
+            &repo,
+            &config,
+        );
+
+        assert_eq!(get_diff_base(&file, &DiffBase::Index).unwrap(), b"a\n");
+        assert!(get_diff_base(&repo.join("missing.txt"), &DiffBase::Index).is_err());
+        let rev = DiffBase::Rev("@-".to_owned());
+        assert!(get_diff_base(&repo.join("missing.txt"), &rev).is_err());
+        let head = get_current_head_name(&file).unwrap();
+        assert!(head.load().ends_with("feature"));
+
+        let changes = std::sync::Mutex::new(Vec::new());
+        for_each_changed_file(&repo, &DiffBase::Index, |change| {
+            changes
+                .lock()
+                .unwrap()
+                .push(change.unwrap().path().to_path_buf());
+            true
+        })
+        .unwrap();
+        assert_eq!(*changes.lock().unwrap(), [file]);
+    }
+}
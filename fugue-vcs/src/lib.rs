@@ -2,8 +2,9 @@
 // Licensed under MPL-2.0
 
 //! `fugue_vcs` provides types for working with diffs from a Version Control System (VCS).
-//! Currently `git` is the only supported provider for diffs, but this architecture allows
-//! for other providers to be added in the future.
+//! Providers for `git`, Jujutsu (`jj`) and Mercurial (`hg`) are compiled in via features. The
+//! `jj` and `hg` providers shell out to their command line interfaces and only support diffs,
+//! head names and changed files.
 
 use anyhow::{anyhow, bail, Result};
 use arc_swap::ArcSwap;
@@ -12,16 +13,32 @@
     sync::Arc,
 };
 
+#[cfg(any(feature = "jj", feature = "hg"))]
+mod command;
 #[cfg(feature = "git")]
 mod git;
+#[cfg(feature = "hg")]
+mod hg;
+#[cfg(feature = "jj")]
+mod jj;
 
+mod base;
+mod blame;
+mod commit;
+mod conflict;
 mod diff;
+mod history;
 
-pub use diff::{DiffHandle, Hunk};
+pub use base::DiffBase;
+pub use blame::{BlameCommit, FileBlame};
+pub use commit::{cleanup_message, is_commit_message, verbose_diff_start, SCISSORS};
+pub use conflict::{parse_conflicts, update_conflicts, Conflict, Resolution};
+pub use diff::{unified_diff, word_diff, Diff, DiffHandle, Hunk};
+pub use history::LogCommit;
 
 mod status;
 
-pub use status::FileChange;
+pub use status::{FileChange, FileStatus, FileStatusCache, FileStatuses};
 
 /// Contains all active diff providers. Diff providers are compiled in via features. Currently
 /// only `git` is supported.
@@ -31,12 +48,12 @@ pub struct DiffProviderRegistry {
 }
 
 impl DiffProviderRegistry {
-    /// Get the given file from the VCS. This provides the unedited document as a "base"
-    /// for a diff to be created.
-    pub fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
+    /// Get the given file at `base` from the VCS. This provides the unedited document as a
+    /// "base" for a diff to be created.
+    pub fn get_diff_base(&self, file: &Path, base: &DiffBase) -> Option<Vec<u8>> {
         self.providers
             .iter()
-            .find_map(|provider| match provider.get_diff_base(file) {
+            .find_map(|provider| match provider.get_diff_base(file, base) {
                 Ok(res) => Some(res),
                 Err(err) => {
                     log::debug!("{err:#?}");
@@ -60,18 +77,123 @@ pub fn get_current_head_name(&self, file: &Path) -> Option<Arc<ArcSwap<Box<str>>
             })
     }
 
+    /// Computes which commit last changed each line of `file` at `HEAD`. This walks the history
+    /// of the file, so it runs in a background task.
+    pub async fn blame(&self, file: PathBuf) -> Result<FileBlame> {
+        let registry = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut first_err = None;
+            for provider in &registry.providers {
+                match provider.blame(&file) {
+                    Ok(blame) => return Ok(blame),
+                    Err(err) => _ = first_err.get_or_insert(err),
+                }
+            }
+            Err(first_err.unwrap_or_else(|| anyhow!("no diff provider available")))
+        })
+        .await?
+    }
+
+    /// Renders the changes `commit` made. With `path`, which is relative to the root of the
+    /// repository containing `cwd`, only the changes to that file are included.
+    pub fn commit_diff(&self, cwd: &Path, commit: &str, path: Option<&str>) -> Result<String> {
+        self.first_success(|provider| provider.commit_diff(cwd, commit, path))
+    }
+
+    /// Lists at most `limit` commits of the history of the repository containing `cwd`, newest
+    /// first, passing them to `on_commit` until it returns `false`. With `file` only the commits
+    /// changing it are listed, following renames. This walks the history, so it runs in a
+    /// background task.
+    pub async fn log(
+        &self,
+        cwd: PathBuf,
+        file: Option<PathBuf>,
+        limit: usize,
+        mut on_commit: impl FnMut(LogCommit) -> bool + Send + 'static,
+    ) -> Result<()> {
+        let registry = self.clone();
+        tokio::task::spawn_blocking(move || {
+            registry.first_success(|provider| {
+                provider.log(&cwd, file.as_deref(), limit, &mut on_commit)
+            })
+        })
+        .await?
+    }
+
+    /// Reads `file` as it was in `commit`. `path` overrides the path of the file relative to the
+    /// root of the repository, e.g. to read it from before it was renamed.
+    pub fn file_at_commit(&self, file: &Path, commit: &str, path: Option<&str>) -> Result<Vec<u8>> {
+        self.first_success(|provider| provider.file_at_commit(file, commit, path))
+    }
+
+    /// Get the given file at `HEAD`, ignoring any changes staged in the index.
+    pub fn get_head_base(&self, file: &Path) -> Option<Vec<u8>> {
+        self.first_success(|provider| provider.get_head_base(file))
+            .map_err(|err| log::debug!("failed to open head of {}: {err:#?}", file.display()))
+            .ok()
+    }
+
+    /// The changes to `file` at `base` relative to `HEAD`. `before` refers to lines at `HEAD`
+    /// and `after` to lines at `base`. For the index these are the staged changes.
+    pub fn get_base_hunks(&self, file: &Path, base: &DiffBase) -> Vec<Hunk> {
+        self.first_success(|provider| provider.get_base_hunks(file, base))
+            .unwrap_or_default()
+    }
+
+    /// Replaces the staged content of `file` with `content`.
+    pub fn stage(&self, file: &Path, content: &[u8]) -> Result<()> {
+        self.first_success(|provider| provider.stage(file, content))
+    }
+
+    /// Resets the staged content of `file` to `HEAD`.
+    pub fn unstage(&self, file: &Path) -> Result<()> {
+        self.first_success(|provider| provider.unstage(file))
+    }
+
+    /// Builds the template of a commit message for the changes staged in the repository
+    /// containing `cwd`, listing them in comments followed by their diff.
+    pub fn commit_template(&self, cwd: &Path) -> Result<String> {
+        self.first_success(|provider| provider.commit_template(cwd))
+    }
+
+    /// Commits the changes staged in the repository containing `cwd` with `message`. Returns the
+    /// short id of the new commit.
+    pub fn commit(&self, cwd: &Path, message: &str) -> Result<String> {
+        self.first_success(|provider| provider.commit(cwd, message))
+    }
+
+    /// Computes the status of the changed, untracked and ignored files of the repository
+    /// containing `cwd`.
+    pub fn file_statuses(&self, cwd: &Path) -> Result<FileStatuses> {
+        self.first_success(|provider| provider.file_statuses(cwd))
+    }
+
+    /// Returns the result of the first provider that succeeds, or the error of the first
+    /// provider if none do.
+    fn first_success<T>(&self, mut f: impl FnMut(&DiffProvider) -> Result<T>) -> Result<T> {
+        let mut first_err = None;
+        for provider in &self.providers {
+            match f(provider) {
+                Ok(res) => return Ok(res),
+                Err(err) => _ = first_err.get_or_insert(err),
+            }
+        }
+        Err(first_err.unwrap_or_else(|| anyhow!("no diff provider available")))
+    }
+
     /// Fire-and-forget changed file iteration. Runs everything in a background task. Keeps
-    /// iteration until `on_change` returns `false`.
+    /// iteration until `on_change` returns `false`. Files are compared against `base`.
     pub fn for_each_changed_file(
         self,
         cwd: PathBuf,
+        base: DiffBase,
         f: impl Fn(Result<FileChange>) -> bool + Send + 'static,
     ) {
         tokio::task::spawn_blocking(move || {
             if self
                 .providers
                 .iter()
-                .find_map(|provider| provider.for_each_changed_file(&cwd, &f).ok())
+                .find_map(|provider| provider.for_each_changed_file(&cwd, &base, &f).ok())
                 .is_none()
             {
                 f(Err(anyhow!("no diff provider returns success")));
@@ -82,11 +204,15 @@ pub fn for_each_changed_file(
 
 impl Default for DiffProviderRegistry {
     fn default() -> Self {
-        // currently only git is supported
-        // TODO make this configurable when more providers are added
+        // TODO make this configurable
         let providers = vec![
+            // jj repositories are usually colocated with git, so jj has to be tried first
+            #[cfg(feature = "jj")]
+            DiffProvider::Jj,
             #[cfg(feature = "git")]
             DiffProvider::Git,
+            #[cfg(feature = "hg")]
+            DiffProvider::Hg,
             DiffProvider::None,
         ];
         DiffProviderRegistry { providers }
@@ -101,14 +227,22 @@ fn default() -> Self {
 enum DiffProvider {
     #[cfg(feature = "git")]
     Git,
+    #[cfg(feature = "jj")]
+    Jj,
+    #[cfg(feature = "hg")]
+    Hg,
     None,
 }
 
 impl DiffProvider {
-    fn get_diff_base(&self, file: &Path) -> Result<Vec<u8>> {
+    fn get_diff_base(&self, file: &Path, base: &DiffBase) -> Result<Vec<u8>> {
         match self {
             #[cfg(feature = "git")]
-            Self::Git => git::get_diff_base(file),
+            Self::Git => git::get_diff_base(file, base),
+            #[cfg(feature = "jj")]
+            Self::Jj => jj::get_diff_base(file, base),
+            #[cfg(feature = "hg")]
+            Self::Hg => hg::get_diff_base(file, base),
             Self::None => bail!("No diff support compiled in"),
         }
     }
@@ -117,18 +251,182 @@ fn get_current_head_name(&self, file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
         match self {
             #[cfg(feature = "git")]
             Self::Git => git::get_current_head_name(file),
+            #[cfg(feature = "jj")]
+            Self::Jj => jj::get_current_head_name(file),
+            #[cfg(feature = "hg")]
+            Self::Hg => hg::get_current_head_name(file),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
+    fn blame(&self, file: &Path) -> Result<FileBlame> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::blame(file),
+            #[cfg(feature = "jj")]
+            Self::Jj => bail!("Not supported for jj repositories"),
+            #[cfg(feature = "hg")]
+            Self::Hg => bail!("Not supported for hg repositories"),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
+    fn commit_diff(&self, cwd: &Path, commit: &str, path: Option<&str>) -> Result<String> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::commit_diff(cwd, commit, path),
+            #[cfg(feature = "jj")]
+            Self::Jj => bail!("Not supported for jj repositories"),
+            #[cfg(feature = "hg")]
+            Self::Hg => bail!("Not supported for hg repositories"),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
+    fn log(
+        &self,
+        cwd: &Path,
+        file: Option<&Path>,
+        limit: usize,
+        on_commit: &mut dyn FnMut(LogCommit) -> bool,
+    ) -> Result<()> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::log(cwd, file, limit, on_commit),
+            #[cfg(feature = "jj")]
+            Self::Jj => bail!("Not supported for jj repositories"),
+            #[cfg(feature = "hg")]
+            Self::Hg => bail!("Not supported for hg repositories"),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
+    fn file_at_commit(&self, file: &Path, commit: &str, path: Option<&str>) -> Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::file_at_commit(file, commit, path),
+            #[cfg(feature = "jj")]
+            Self::Jj => bail!("Not supported for jj repositories"),
+            #[cfg(feature = "hg")]
+            Self::Hg => bail!("Not supported for hg repositories"),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
+    fn get_head_base(&self, file: &Path) -> Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::get_head_base(file),
+            #[cfg(feature = "jj")]
+            Self::Jj => bail!("Not supported for jj repositories"),
+            #[cfg(feature = "hg")]
+            Self::Hg => bail!("Not supported for hg repositories"),
             Self::None => bail!("No diff support compiled in"),
         }
     }
 
+    fn get_base_hunks(&self, file: &Path, base: &DiffBase) -> Result<Vec<Hunk>> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::get_base_hunks(file, base),
+            #[cfg(feature = "jj")]
+            Self::Jj => bail!("Not supported for jj repositories"),
+            #[cfg(feature = "hg")]
+            Self::Hg => bail!("Not supported for hg repositories"),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
+    fn stage(&self, file: &Path, content: &[u8]) -> Result<()> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::stage(file, content),
+            #[cfg(feature = "jj")]
+            Self::Jj => bail!("Not supported for jj repositories"),
+            #[cfg(feature = "hg")]
+            Self::Hg => bail!("Not supported for hg repositories"),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
+    fn unstage(&self, file: &Path) -> Result<()> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::unstage(file),
+            #[cfg(feature = "jj")]
+            Self::Jj => bail!("Not supported for jj repositories"),
+            #[cfg(feature = "hg")]
+            Self::Hg => bail!("Not supported for hg repositories"),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
+    fn commit_template(&self, cwd: &Path) -> Result<String> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::commit_template(cwd),
+            #[cfg(feature = "jj")]
+            Self::Jj => bail!("Not supported for jj repositories"),
+            #[cfg(feature = "hg")]
+            Self::Hg => bail!("Not supported for hg repositories"),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
+    fn commit(&self, cwd: &Path, message: &str) -> Result<String> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::commit(cwd, message),
+            #[cfg(feature = "jj")]
+            Self::Jj => bail!("Not supported for jj repositories"),
+            #[cfg(feature = "hg")]
+            Self::Hg => bail!("Not supported for hg repositories"),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
+    fn file_statuses(&self, cwd: &Path) -> Result<FileStatuses> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::file_statuses(cwd),
+            #[cfg(feature = "jj")]
+            Self::Jj => self.changed_file_statuses(cwd),
+            #[cfg(feature = "hg")]
+            Self::Hg => self.changed_file_statuses(cwd),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
+    /// Derives the statuses of files from their changes, for providers that can't tell staged or
+    /// ignored files apart.
+    #[cfg(any(feature = "jj", feature = "hg"))]
+    fn changed_file_statuses(&self, cwd: &Path) -> Result<FileStatuses> {
+        let statuses = std::cell::RefCell::new(FileStatuses::default());
+        self.for_each_changed_file(cwd, &DiffBase::Index, |change| {
+            match change {
+                Ok(change) => statuses
+                    .borrow_mut()
+                    .insert(change.path().to_path_buf(), FileStatus::from(&change)),
+                Err(err) => log::debug!("{err:#}"),
+            }
+            true
+        })?;
+        Ok(statuses.into_inner())
+    }
+
     fn for_each_changed_file(
         &self,
         cwd: &Path,
+        base: &DiffBase,
         f: impl Fn(Result<FileChange>) -> bool,
     ) -> Result<()> {
         match self {
             #[cfg(feature = "git")]
-            Self::Git => git::for_each_changed_file(cwd, f),
+            Self::Git => git::for_each_changed_file(cwd, base, f),
+            #[cfg(feature = "jj")]
+            Self::Jj => jj::for_each_changed_file(cwd, base, f),
+            #[cfg(feature = "hg")]
+            Self::Hg => hg::for_each_changed_file(cwd, base, f),
             Self::None => bail!("No diff support compiled in"),
         }
     }
@@ -1,7 +1,15 @@
 // Copyright (c) 2020 Blaž Hrastnik
 // Licensed under MPL-2.0
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use parking_lot::Mutex;
+
+use crate::DiffProviderRegistry;
 
 /// States for a file having been changed.
 pub enum FileChange {
@@ -31,3 +39,145 @@ pub fn path(&self) -> &Path {
         }
     }
 }
+
+/// The status of a file as shown in file listings. Statuses are ordered by importance: a file
+/// with several statuses, e.g. staged and modified again, shows the most important one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FileStatus {
+    /// Ignored by the VCS.
+    Ignored,
+    /// Has changes staged for the next commit.
+    Staged,
+    /// Not tracked by the VCS.
+    Untracked,
+    /// Has changes that aren't staged.
+    Modified,
+    /// Has unresolved merge conflicts.
+    Conflict,
+}
+
+impl From<&FileChange> for FileStatus {
+    fn from(change: &FileChange) -> Self {
+        match change {
+            FileChange::Untracked { .. } => Self::Untracked,
+            FileChange::Conflict { .. } => Self::Conflict,
+            FileChange::Modified { .. }
+            | FileChange::Deleted { .. }
+            | FileChange::Renamed { .. } => Self::Modified,
+        }
+    }
+}
+
+/// The [`FileStatus`] of the files of a working copy. Directories take the most important status
+/// of the files they contain, except that they aren't ignored because they contain ignored files.
+#[derive(Debug, Default)]
+pub struct FileStatuses {
+    statuses: HashMap<PathBuf, FileStatus>,
+}
+
+impl FileStatuses {
+    /// Records `status` for `path` and the directories containing it.
+    pub fn insert(&mut self, path: PathBuf, status: FileStatus) {
+        if status != FileStatus::Ignored {
+            for dir in path.ancestors().skip(1) {
+                let dir_status = self.statuses.entry(dir.to_path_buf()).or_insert(status);
+                *dir_status = (*dir_status).max(status);
+            }
+        }
+        let file_status = self.statuses.entry(path).or_insert(status);
+        *file_status = (*file_status).max(status);
+    }
+
+    /// The status of `path`. Files inside ignored directories are ignored as well.
+    pub fn get(&self, path: &Path) -> Option<FileStatus> {
+        self.statuses.get(path).copied().or_else(|| {
+            path.ancestors()
+                .skip(1)
+                .any(|dir| self.statuses.get(dir) == Some(&FileStatus::Ignored))
+                .then_some(FileStatus::Ignored)
+        })
+    }
+}
+
+/// The [`FileStatuses`] of the working directory, recomputed in the background on request.
+#[derive(Clone, Default)]
+pub struct FileStatusCache {
+    statuses: Arc<ArcSwap<FileStatuses>>,
+    /// The working directory of the latest refresh that hasn't started yet.
+    pending: Arc<Mutex<Option<PathBuf>>>,
+    /// The working directory of the latest refresh.
+    cwd: Arc<Mutex<Option<PathBuf>>>,
+    refreshing: Arc<AtomicBool>,
+}
+
+impl FileStatusCache {
+    pub fn load(&self) -> Arc<FileStatuses> {
+        self.statuses.load_full()
+    }
+
+    /// Recomputes the statuses of the repository containing `cwd` in a background task. Requests
+    /// made while a refresh is running are coalesced into a single refresh once it finishes.
+    pub fn refresh(&self, registry: DiffProviderRegistry, cwd: PathBuf) {
+        *self.cwd.lock() = Some(cwd.clone());
+        *self.pending.lock() = Some(cwd);
+        if self.refreshing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let cache = self.clone();
+        tokio::task::spawn_blocking(move || loop {
+            loop {
+                let Some(cwd) = cache.pending.lock().take() else {
+                    break;
+                };
+                let statuses = registry.file_statuses(&cwd).unwrap_or_else(|err| {
+                    log::debug!("failed to compute file statuses: {err:#}");
+                    FileStatuses::default()
+                });
+                cache.statuses.store(Arc::new(statuses));
+                fugue_event::request_redraw();
+            }
+            cache.refreshing.store(false, Ordering::Release);
+            // a refresh may have been requested after the last check
+            if cache.pending.lock().is_none() || cache.refreshing.swap(true, Ordering::AcqRel) {
+                break;
+            }
+        });
+    }
+
+    /// Recomputes the statuses after files changed, if they were ever requested.
+    pub fn invalidate(&self, registry: DiffProviderRegistry) {
+        let cwd = self.cwd.lock().clone();
+        if let Some(cwd) = cwd {
+            self.refresh(registry, cwd);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aggregate_directories() {
+        let mut statuses = FileStatuses::default();
+        statuses.insert("/repo/src/a.rs".into(), FileStatus::Staged);
+        statuses.insert("/repo/src/b.rs".into(), FileStatus::Modified);
+        statuses.insert("/repo/src/a.rs".into(), FileStatus::Untracked);
+        statuses.insert("/repo/target".into(), FileStatus::Ignored);
+
+        assert_eq!(
+            statuses.get(Path::new("/repo/src/a.rs")),
+            Some(FileStatus::Untracked)
+        );
+        assert_eq!(
+            statuses.get(Path::new("/repo/src")),
+            Some(FileStatus::Modified)
+        );
+        assert_eq!(statuses.get(Path::new("/repo")), Some(FileStatus::Modified));
+        assert_eq!(
+            statuses.get(Path::new("/repo/target/debug/fugue")),
+            Some(FileStatus::Ignored)
+        );
+        assert_eq!(statuses.get(Path::new("/repo/README.md")), None);
+    }
+}
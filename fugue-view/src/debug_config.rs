@@ -0,0 +1,402 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+//! Per-project debug configurations.
+//!
+//! Besides the templates of a language's debugger in `languages.toml`, debug sessions can be
+//! configured for a workspace in `.fugue/debug.toml`:
+//!
+//! ```toml
+//! [[configurations]]
+//! name = "Debug server"
+//! adapter = "lldb-dap"
+//! request = "launch"
+//! pre-launch-task = "cargo build"
+//! args = { program = "${workspaceFolder}/target/debug/server", args = ["--port", "8080"] }
+//! ```
+//!
+//! The `configurations` of a VS Code `.vscode/launch.json` are read as well, where `type` names
+//! the adapter and every other key apart from `name`, `request` and `preLaunchTask` is passed to
+//! the debugger. A `preLaunchTask` refers to a task label in `.vscode/tasks.json`.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::expansion;
+use crate::Editor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// `.fugue/debug.toml`
+    Fugue,
+    /// `.vscode/launch.json`
+    VsCode,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Fugue => ".fugue/debug.toml",
+            Self::VsCode => ".vscode/launch.json",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectDebugConfig {
+    pub name: String,
+    /// Name of the debug adapter from `languages.toml`. The debugger of the focused document's
+    /// language is used if unset or unknown.
+    pub adapter: Option<String>,
+    /// Either `launch` or `attach`.
+    pub request: String,
+    /// A shell command that must succeed before the debugger is started.
+    pub pre_launch_task: Option<String>,
+    /// Arguments of the launch or attach request.
+    pub args: Map<String, Value>,
+    pub source: ConfigSource,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct FugueConfigs {
+    #[serde(default)]
+    configurations: Vec<FugueConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct FugueConfig {
+    name: String,
+    adapter: Option<String>,
+    request: String,
+    pre_launch_task: Option<String>,
+    #[serde(default)]
+    args: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct LaunchJson {
+    #[serde(default)]
+    configurations: Vec<Map<String, Value>>,
+}
+
+#[derive(Deserialize)]
+struct TasksJson {
+    #[serde(default)]
+    tasks: Vec<Task>,
+}
+
+#[derive(Deserialize)]
+struct Task {
+    label: String,
+    /// `shell` or `process`. The command of a process task is a program rather than a shell
+    /// command line.
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
+    command: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+impl Task {
+    /// The shell command line running the task, with its arguments quoted.
+    fn command_line(&self) -> Option<String> {
+        let command = self.command.as_deref()?;
+        let mut line = match self.kind.as_deref() {
+            Some("process") => shell_words::quote(command).into_owned(),
+            _ => command.to_owned(),
+        };
+        for arg in &self.args {
+            line.push(' ');
+            line.push_str(&shell_words::quote(arg));
+        }
+        Some(line)
+    }
+}
+
+/// Reads the debug configurations of the given workspace. Missing files are skipped.
+pub fn load(workspace: &Path) -> Result<Vec<ProjectDebugConfig>> {
+    let mut configs = Vec::new();
+
+    let path = workspace.join(".fugue").join("debug.toml");
+    if let Some(content) = read_optional(&path)? {
+        let file: FugueConfigs =
+            toml::from_str(&content).with_context(|| format!("invalid {}", path.display()))?;
+        configs.extend(
+            file.configurations
+                .into_iter()
+                .map(|config| ProjectDebugConfig {
+                    name: config.name,
+                    adapter: config.adapter,
+                    request: config.request,
+                    pre_launch_task: config.pre_launch_task,
+                    args: config.args,
+                    source: ConfigSource::Fugue,
+                }),
+        );
+    }
+
+    let vscode = workspace.join(".vscode");
+    let path = vscode.join("launch.json");
+    if let Some(content) = read_optional(&path)? {
+        let file: LaunchJson = serde_json::from_str(&strip_jsonc(&content))
+            .with_context(|| format!("invalid {}", path.display()))?;
+        let tasks = match read_optional(&vscode.join("tasks.json"))? {
+            Some(content) => serde_json::from_str::<TasksJson>(&strip_jsonc(&content))
+                .map(|file| file.tasks)
+                .unwrap_or_else(|err| {
+                    log::warn!("Ignoring invalid .vscode/tasks.json: {err}");
+                    Vec::new()
+                }),
+            None => Vec::new(),
+        };
+        for mut args in file.configurations {
+            let mut take_string = |key| match args.remove(key) {
+                Some(Value::String(value)) => Some(value),
+                _ => None,
+            };
+            let (Some(name), Some(request)) = (take_string("name"), take_string("request")) else {
+                log::warn!(
+                    "Skipping {} configuration without a name or request",
+                    path.display()
+                );
+                continue;
+            };
+            let adapter = take_string("type");
+            let pre_launch_task = match take_string("preLaunchTask") {
+                Some(label) => Some(
+                    tasks
+                        .iter()
+                        .find(|task| task.label == label)
+                        .and_then(Task::command_line)
+                        .with_context(|| {
+                            format!(
+                                "unknown task '{label}' in the preLaunchTask of '{name}' in {}",
+                                path.display()
+                            )
+                        })?,
+                ),
+                None => None,
+            };
+            configs.push(ProjectDebugConfig {
+                name,
+                adapter,
+                request,
+                pre_launch_task,
+                args,
+                source: ConfigSource::VsCode,
+            });
+        }
+    }
+
+    Ok(configs)
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+impl ProjectDebugConfig {
+    /// The request arguments with `${...}` variables expanded in every string. The workspace is
+    /// used as `cwd` unless the configuration sets one.
+    pub fn expanded_args(&self, editor: &Editor, workspace: &Path) -> Result<Value> {
+        let mut args = Value::Object(self.args.clone());
+        expand_value(editor, &mut args)?;
+        if let Value::Object(args) = &mut args {
+            args.entry("cwd")
+                .or_insert_with(|| Value::String(workspace.to_string_lossy().into_owned()));
+        }
+        Ok(args)
+    }
+
+    /// The pre-launch shell command with `${...}` variables expanded.
+    pub fn expanded_pre_launch_task(&self, editor: &Editor) -> Result<Option<String>> {
+        self.pre_launch_task
+            .as_deref()
+            .map(|task| expansion::expand_launch_variables(editor, task).map(Cow::into_owned))
+            .transpose()
+    }
+}
+
+fn expand_value(editor: &Editor, value: &mut Value) -> Result<()> {
+    match value {
+        Value::String(string) => {
+            if let Cow::Owned(expanded) = expansion::expand_launch_variables(editor, string)? {
+                *string = expanded;
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                expand_value(editor, value)?;
+            }
+        }
+        Value::Object(map) => {
+            for value in map.values_mut() {
+                expand_value(editor, value)?;
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) => (),
+    }
+    Ok(())
+}
+
+/// Removes the comments and trailing commas VS Code allows in its JSON files.
+fn strip_jsonc(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => (),
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push(c);
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = None;
+                for c in chars.by_ref() {
+                    if prev == Some('*') && c == '/' {
+                        break;
+                    }
+                    prev = Some(c);
+                }
+            }
+            ('}' | ']', _) => {
+                // drop a trailing comma before the closing bracket
+                let trimmed = out.trim_end().len();
+                if out[..trimmed].ends_with(',') {
+                    out.truncate(trimmed - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn jsonc() {
+        let input = r#"{
+            // comment
+            "url": "http://example.com", /* block
+            comment */
+            "list": [1, 2,],
+            "escaped": "\" // not a comment",
+        }"#;
+        let value: Value = serde_json::from_str(&strip_jsonc(input)).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "url": "http://example.com",
+                "list": [1, 2],
+                "escaped": "\" // not a comment",
+            })
+        );
+    }
+
+    #[test]
+    fn load_configs() {
+        let workspace = tempfile::tempdir().unwrap();
+        std::fs::create_dir(workspace.path().join(".fugue")).unwrap();
+        std::fs::create_dir(workspace.path().join(".vscode")).unwrap();
+        std::fs::write(
+            workspace.path().join(".fugue/debug.toml"),
+            r#"
+            [[configurations]]
+            name = "server"
+            adapter = "lldb-dap"
+            request = "launch"
+            pre-launch-task = "make"
+            args = { program = "${workspaceFolder}/server" }
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            workspace.path().join(".vscode/launch.json"),
+            r#"{
+                "version": "0.2.0",
+                "configurations": [
+                    {
+                        "type": "debugpy",
+                        "request": "launch",
+                        "name": "current file",
+                        "program": "${file}",
+                        "preLaunchTask": "build", // defined in tasks.json
+                    },
+                    { "type": "debugpy", "request": "attach", "name": "attach", "preLaunchTask": "run" },
+                ],
+            }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            workspace.path().join(".vscode/tasks.json"),
+            r#"{ "tasks": [
+                { "label": "build", "type": "shell", "command": "make", "args": ["all", "out dir"] },
+                { "label": "run", "type": "process", "command": "/my bin/run" },
+            ] }"#,
+        )
+        .unwrap();
+
+        let configs = load(workspace.path()).unwrap();
+        assert_eq!(configs.len(), 3);
+        assert_eq!(configs[0].name, "server");
+        assert_eq!(configs[0].source, ConfigSource::Fugue);
+        assert_eq!(configs[0].pre_launch_task.as_deref(), Some("make"));
+        assert_eq!(
+            configs[0].args["program"],
+            Value::from("${workspaceFolder}/server")
+        );
+        assert_eq!(configs[1].adapter.as_deref(), Some("debugpy"));
+        assert_eq!(
+            configs[1].pre_launch_task.as_deref(),
+            Some("make all 'out dir'")
+        );
+        assert_eq!(
+            configs[1].args.keys().collect::<Vec<_>>(),
+            ["program"].iter().collect::<Vec<_>>()
+        );
+        assert_eq!(configs[2].request, "attach");
+        assert_eq!(configs[2].pre_launch_task.as_deref(), Some("'/my bin/run'"));
+
+        // labels of tasks that don't exist aren't run as commands
+        std::fs::write(
+            workspace.path().join(".vscode/tasks.json"),
+            r#"{ "tasks": [] }"#,
+        )
+        .unwrap();
+        let err = load(workspace.path()).unwrap_err();
+        assert!(err.to_string().contains("unknown task 'build'"), "{err}");
+    }
+}
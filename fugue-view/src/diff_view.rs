@@ -0,0 +1,265 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+use std::ops::Range;
+
+use fugue_core::text_annotations::LineAnnotation;
+use fugue_core::{Position, RopeSlice};
+use fugue_vcs::{word_diff, DiffHandle, Hunk};
+
+use crate::{Document, DocumentId};
+
+/// Hunks with more lines than this are not diffed word by word.
+const MAX_WORD_DIFF_LINES: usize = 200;
+
+/// Two documents shown side by side, with the lines of both sides aligned and their
+/// differences highlighted.
+pub struct DiffView {
+    /// The original text, shown on the left.
+    pub base: DocumentId,
+    /// The changed text, shown on the right.
+    pub doc: DocumentId,
+    handle: DiffHandle,
+    /// The versions of both documents last sent to the differ.
+    versions: (i32, i32),
+    /// The hunks the sides were last computed from.
+    hunks: Option<Vec<Hunk>>,
+}
+
+/// How one document of a [`DiffView`] is aligned and highlighted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffSide {
+    /// The number of filler lines inserted above a line, sorted by line.
+    pub fillers: Vec<(usize, usize)>,
+    /// The changed lines, sorted by line.
+    pub lines: Vec<(Range<usize>, LineChange)>,
+    /// The char ranges of changed words within modified lines.
+    pub words: Vec<Range<usize>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineChange {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl DiffView {
+    pub fn new(base: &Document, doc: &Document) -> Self {
+        DiffView {
+            base: base.id(),
+            doc: doc.id(),
+            handle: DiffHandle::new(base.text().clone(), doc.text().clone()),
+            versions: (base.version(), doc.version()),
+            hunks: None,
+        }
+    }
+
+    pub fn contains(&self, doc: DocumentId) -> bool {
+        self.base == doc || self.doc == doc
+    }
+
+    /// Sends changes of either document to the differ. Returns the sides of the base and the
+    /// changed document when the diff changed since the last call.
+    pub fn update(&mut self, base: &Document, doc: &Document) -> Option<(DiffSide, DiffSide)> {
+        if base.version() != self.versions.0 {
+            self.handle.update_diff_base(base.text().clone());
+        }
+        if doc.version() != self.versions.1 {
+            self.handle.update_document(doc.text().clone(), false);
+        }
+        let versions = (base.version(), doc.version());
+
+        let diff = self.handle.load();
+        let hunks: Vec<_> = (0..diff.len()).map(|i| diff.nth_hunk(i)).collect();
+        if self.hunks.as_ref() == Some(&hunks) && versions == self.versions {
+            return None;
+        }
+        self.versions = versions;
+        let sides = compute_sides(diff.diff_base().slice(..), diff.doc().slice(..), &hunks);
+        drop(diff);
+        self.hunks = Some(hunks);
+        Some(sides)
+    }
+
+    /// Maps a line of one side to the corresponding line of the other side.
+    pub fn map_line(&self, line: usize, from_base: bool) -> usize {
+        let line = line as u32;
+        let mut offset: i64 = 0;
+        for hunk in self.hunks.iter().flatten() {
+            let (from, to) = if from_base {
+                (&hunk.before, &hunk.after)
+            } else {
+                (&hunk.after, &hunk.before)
+            };
+            if from.start > line {
+                break;
+            }
+            if from.contains(&line) {
+                return (to.start + (line - from.start).min(to.len() as u32)) as usize;
+            }
+            offset = to.end as i64 - from.end as i64;
+        }
+        (line as i64 + offset).max(0) as usize
+    }
+}
+
+fn compute_sides(base: RopeSlice, doc: RopeSlice, hunks: &[Hunk]) -> (DiffSide, DiffSide) {
+    let mut base_side = DiffSide::default();
+    let mut doc_side = DiffSide::default();
+    for hunk in hunks {
+        let before = hunk.before.start as usize..hunk.before.end as usize;
+        let after = hunk.after.start as usize..hunk.after.end as usize;
+        if after.len() > before.len() {
+            base_side
+                .fillers
+                .push((before.end, after.len() - before.len()));
+        } else if before.len() > after.len() {
+            doc_side
+                .fillers
+                .push((after.end, before.len() - after.len()));
+        }
+
+        if hunk.is_pure_insertion() {
+            doc_side.lines.push((after, LineChange::Added));
+            continue;
+        }
+        if hunk.is_pure_removal() {
+            base_side.lines.push((before, LineChange::Removed));
+            continue;
+        }
+        if before.len() <= MAX_WORD_DIFF_LINES && after.len() <= MAX_WORD_DIFF_LINES {
+            let base_start = base.line_to_char(before.start);
+            let doc_start = doc.line_to_char(after.start);
+            let (base_words, doc_words) = word_diff(
+                &base
+                    .slice(base_start..base.line_to_char(before.end))
+                    .to_string(),
+                &doc.slice(doc_start..doc.line_to_char(after.end))
+                    .to_string(),
+            );
+            base_side.words.extend(
+                base_words
+                    .into_iter()
+                    .map(|range| range.start + base_start..range.end + base_start),
+            );
+            doc_side.words.extend(
+                doc_words
+                    .into_iter()
+                    .map(|range| range.start + doc_start..range.end + doc_start),
+            );
+        }
+        base_side.lines.push((before, LineChange::Modified));
+        doc_side.lines.push((after, LineChange::Modified));
+    }
+    (base_side, doc_side)
+}
+
+/// Inserts empty lines into one side of a diff view so that unchanged lines of both sides are
+/// displayed next to each other.
+pub(crate) struct DiffFillers<'a> {
+    text: RopeSlice<'a>,
+    fillers: &'a [(usize, usize)],
+    idx: usize,
+}
+
+impl<'a> DiffFillers<'a> {
+    pub(crate) fn new(doc: &'a Document, side: &'a DiffSide) -> Box<Self> {
+        Box::new(DiffFillers {
+            text: doc.text().slice(..),
+            fillers: &side.fillers,
+            idx: 0,
+        })
+    }
+}
+
+impl LineAnnotation for DiffFillers<'_> {
+    fn reset_pos(&mut self, char_idx: usize) -> usize {
+        let line = self.text.char_to_line(char_idx.min(self.text.len_chars()));
+        self.idx = self
+            .fillers
+            .partition_point(|&(filler_line, _)| filler_line <= line);
+        usize::MAX
+    }
+
+    fn virtual_lines_above_first_line(&mut self) -> usize {
+        match self.fillers.first() {
+            Some(&(0, count)) => count,
+            _ => 0,
+        }
+    }
+
+    fn insert_virtual_lines(
+        &mut self,
+        line_end_char_idx: usize,
+        _line_end_visual_pos: Position,
+        doc_line: usize,
+    ) -> Position {
+        // fillers go after the last visual line of a document line, not at soft wraps
+        if doc_line + 1 >= self.text.len_lines()
+            || line_end_char_idx != self.text.line_to_char(doc_line + 1)
+        {
+            return Position::new(0, 0);
+        }
+        while self
+            .fillers
+            .get(self.idx)
+            .is_some_and(|&(line, _)| line <= doc_line)
+        {
+            self.idx += 1;
+        }
+        match self.fillers.get(self.idx) {
+            Some(&(line, count)) if line == doc_line + 1 => {
+                self.idx += 1;
+                Position::new(count, 0)
+            }
+            _ => Position::new(0, 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fugue_core::Rope;
+
+    use super::*;
+
+    #[test]
+    fn align_sides() {
+        let base = Rope::from("a\nb\nc\nd\n");
+        let doc = Rope::from("a\nB\nx\ny\nc\n");
+        let hunks = [
+            Hunk {
+                before: 1..2,
+                after: 1..4,
+            },
+            Hunk {
+                before: 3..4,
+                after: 5..5,
+            },
+        ];
+        let (base_side, doc_side) = compute_sides(base.slice(..), doc.slice(..), &hunks);
+        assert_eq!(base_side.fillers, vec![(2, 2)]);
+        assert_eq!(doc_side.fillers, vec![(5, 1)]);
+        assert_eq!(
+            base_side.lines,
+            vec![(1..2, LineChange::Modified), (3..4, LineChange::Removed)]
+        );
+        assert_eq!(doc_side.lines, vec![(1..4, LineChange::Modified)]);
+        assert_eq!(base_side.words, vec![2..3]);
+        assert_eq!(doc_side.words, vec![2..7]);
+    }
+
+    #[test]
+    fn fillers_for_insertion_at_start() {
+        let base = Rope::from("c\n");
+        let doc = Rope::from("a\nb\nc\n");
+        let hunks = [Hunk {
+            before: 0..0,
+            after: 0..2,
+        }];
+        let (base_side, doc_side) = compute_sides(base.slice(..), doc.slice(..), &hunks);
+        assert_eq!(base_side.fillers, vec![(0, 2)]);
+        assert!(doc_side.fillers.is_empty());
+    }
+}
@@ -16,7 +16,9 @@
 use fugue_event::TaskController;
 use fugue_lsp::util::lsp_pos_to_pos;
 use fugue_stdx::faccess::{copy_metadata, readonly};
-use fugue_vcs::{DiffHandle, DiffProviderRegistry};
+use fugue_vcs::{
+    BlameCommit, Conflict, DiffBase, DiffHandle, DiffProviderRegistry, FileBlame, Hunk,
+};
 use futures_util::future::BoxFuture;
 use futures_util::FutureExt;
 use once_cell::sync::OnceCell;
@@ -47,6 +49,7 @@
 };
 
 use crate::{
+    diff_view::DiffSide,
     editor::Config,
     events::{DocumentDidChange, SelectionDidChange},
     expansion,
@@ -200,6 +203,18 @@ pub struct Document {
     pub(crate) language_servers: HashMap<LanguageServerName, Arc<Client>>,
 
     diff_handle: Option<DiffHandle>,
+    /// Commits that last changed each line at `HEAD`, computed on demand.
+    blame: Option<Arc<FileBlame>>,
+    /// Overrides the editor wide diff base for this document.
+    diff_base_override: Option<DiffBase>,
+    /// The base the diff was last loaded from.
+    active_diff_base: DiffBase,
+    /// The changes of the diff base relative to `HEAD`.
+    base_hunks: Vec<Hunk>,
+    /// Alignment and highlights when this document is shown in a diff view.
+    diff_side: Option<DiffSide>,
+    /// The merge conflict regions of the text.
+    conflicts: Vec<Conflict>,
     version_control_head: Option<Arc<ArcSwap<Box<str>>>>,
 
     // when document was used for most-recent-used buffer picker
@@ -211,6 +226,9 @@ pub struct Document {
 
     /// Annotations for LSP document color swatches
     pub color_swatches: Option<DocumentColorSwatches>,
+    /// Debugger values rendered at the end of lines while a debug session is stopped, sorted by
+    /// position.
+    pub(crate) inline_values: Vec<InlineValue>,
     // NOTE: ideally this would live on the handler for color swatches. This is blocked on a
     // large refactor that would make `&mut Editor` available on the `DocumentDidChange` event.
     pub color_swatch_controller: TaskController,
@@ -229,6 +247,17 @@ pub struct DocumentColorSwatches {
     pub color_swatches_padding: Vec<InlineAnnotation>,
 }
 
+/// A value reported by the debugger for an expression in the document.
+///
+/// All values anchored on the same line are joined and rendered after the end of that line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineValue {
+    /// Char index of the expression the value belongs to.
+    pub char_idx: usize,
+    /// Rendered text, for example `count = 3`.
+    pub text: String,
+}
+
 /// Inlay hints for a single `(Document, View)` combo.
 ///
 /// There are `*_inlay_hints` field for each kind of hints an LSP can send since we offer the
@@ -696,6 +725,7 @@ pub fn from(
         let line_ending = config.load().default_line_ending.into();
         let changes = ChangeSet::new(text.slice(..));
         let old_state = None;
+        let conflicts = fugue_vcs::parse_conflicts(text.slice(..));
 
         Self {
             id: DocumentId::default(),
@@ -726,12 +756,19 @@ pub fn from(
             modified_since_accessed: false,
             language_servers: HashMap::new(),
             diff_handle: None,
+            blame: None,
+            diff_base_override: None,
+            active_diff_base: DiffBase::Index,
+            base_hunks: Vec::new(),
+            diff_side: None,
+            conflicts,
             config,
             version_control_head: None,
             focused_at: std::time::Instant::now(),
             readonly: false,
             jump_labels: HashMap::new(),
             color_swatches: None,
+            inline_values: Vec::new(),
             color_swatch_controller: TaskController::new(),
             syn_loader,
             previous_diagnostic_id: None,
@@ -1222,6 +1259,7 @@ pub fn reload(
         &mut self,
         view: &mut View,
         provider_registry: &DiffProviderRegistry,
+        diff_base: &DiffBase,
     ) -> Result<(), Error> {
         let encoding = self.encoding;
         let path = match self.path() {
@@ -1248,16 +1286,19 @@ pub fn reload(
         self.pickup_last_saved_time();
         self.detect_indent_and_line_ending();
 
-        match provider_registry.get_diff_base(&path) {
-            Some(diff_base) => self.set_diff_base(diff_base),
-            None => self.diff_handle = None,
-        }
-
-        self.version_control_head = provider_registry.get_current_head_name(&path);
+        self.refresh_version_control(provider_registry, diff_base);
 
         Ok(())
     }
 
+    /// Reads the file of the document from disk, decoded with the encoding of the document.
+    pub fn read_file(&self) -> Result<Rope, Error> {
+        let path = self.path().ok_or_else(|| anyhow!("document has no path"))?;
+        let mut file = std::fs::File::open(path)?;
+        let (rope, ..) = from_reader(&mut file, Some(self.encoding))?;
+        Ok(rope)
+    }
+
     /// Sets the [`Document`]'s encoding with the encoding correspondent to `label`.
     pub fn set_encoding(&mut self, label: &str) -> Result<(), Error> {
         let encoding =
@@ -1455,6 +1496,13 @@ fn apply_impl(
             }
         }
 
+        fugue_vcs::update_conflicts(
+            &mut self.conflicts,
+            old_doc.slice(..),
+            self.text.slice(..),
+            changes,
+        );
+
         // TODO: all of that should likely just be hooks
         // start computing the diff in parallel
         if let Some(diff_handle) = &self.diff_handle {
@@ -1528,6 +1576,12 @@ fn apply_impl(
             apply_inlay_hint_changes(padding_after_inlay_hints);
         }
 
+        changes.update_positions(
+            self.inline_values
+                .iter_mut()
+                .map(|value| (&mut value.char_idx, Assoc::After)),
+        );
+
         fugue_event::dispatch(DocumentDidChange {
             doc: self,
             view: view_id,
@@ -1733,6 +1787,21 @@ pub fn append_changes_to_history(&mut self, view: &mut View) {
         view.apply(&transaction, self);
     }
 
+    /// Replaces the whole text without adding an entry to the undo history, for documents whose
+    /// content is generated like debugger views. The history is cleared as it no longer applies.
+    pub fn replace_generated_text(&mut self, text: String, view: &mut View) {
+        let transaction = Transaction::change(
+            self.text(),
+            std::iter::once((0, self.text.len_chars(), Some(text.into()))),
+        );
+        self.apply(&transaction, view.id);
+        self.changes = ChangeSet::new(self.text().slice(..));
+        self.old_state = None;
+        self.history.set(History::default());
+        self.last_saved_revision = 0;
+        view.apply(&transaction, self);
+    }
+
     pub fn id(&self) -> DocumentId {
         self.id
     }
@@ -1888,6 +1957,92 @@ pub fn set_diff_base(&mut self, diff_base: Vec<u8>) {
         }
     }
 
+    /// Reloads the diff base, the staged changes and the current head from version control.
+    /// `diff_base` is used unless this document overrides it.
+    pub fn refresh_version_control(
+        &mut self,
+        provider_registry: &DiffProviderRegistry,
+        diff_base: &DiffBase,
+    ) {
+        let Some(path) = self.path().cloned() else {
+            return;
+        };
+        self.active_diff_base = self
+            .diff_base_override
+            .clone()
+            .unwrap_or_else(|| diff_base.clone());
+        match provider_registry.get_diff_base(&path, &self.active_diff_base) {
+            Some(diff_base) => self.set_diff_base(diff_base),
+            None => self.diff_handle = None,
+        }
+        self.base_hunks = provider_registry.get_base_hunks(&path, &self.active_diff_base);
+        self.version_control_head = provider_registry.get_current_head_name(&path);
+    }
+
+    /// The base the diff of this document compares against.
+    pub fn active_diff_base(&self) -> &DiffBase {
+        &self.active_diff_base
+    }
+
+    pub fn diff_base_override(&self) -> Option<&DiffBase> {
+        self.diff_base_override.as_ref()
+    }
+
+    /// Compares this document against `diff_base` instead of the editor wide diff base. Takes
+    /// effect on the next [`Document::refresh_version_control`].
+    pub fn set_diff_base_override(&mut self, diff_base: Option<DiffBase>) {
+        self.diff_base_override = diff_base;
+    }
+
+    /// The changes staged in the index relative to `HEAD`, where `after` refers to lines of
+    /// the diff base. Empty unless the diff base is the index.
+    pub fn staged_hunks(&self) -> &[Hunk] {
+        if self.active_diff_base == DiffBase::Index {
+            &self.base_hunks
+        } else {
+            &[]
+        }
+    }
+
+    /// The merge conflict regions of the document, sorted by line.
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    /// Whether the document is a commit message, either written by git for an editor or
+    /// opened by `:commit`.
+    pub fn is_commit_message(&self) -> bool {
+        self.language_name() == Some("git-commit")
+            || self
+                .path()
+                .is_some_and(|path| fugue_vcs::is_commit_message(path))
+    }
+
+    pub fn diff_side(&self) -> Option<&DiffSide> {
+        self.diff_side.as_ref()
+    }
+
+    pub fn set_diff_side(&mut self, diff_side: Option<DiffSide>) {
+        self.diff_side = diff_side;
+    }
+
+    pub fn blame(&self) -> Option<&Arc<FileBlame>> {
+        self.blame.as_ref()
+    }
+
+    pub fn set_blame(&mut self, blame: Option<Arc<FileBlame>>) {
+        self.blame = blame;
+    }
+
+    /// The commit that last changed `line`, or `None` if the line has uncommitted changes or
+    /// the blame wasn't computed yet.
+    pub fn line_blame(&self, line: usize) -> Option<&BlameCommit> {
+        let diff = self.diff_handle.as_ref().map(DiffHandle::load);
+        self.blame
+            .as_deref()?
+            .line(line, diff.as_ref(), &self.base_hunks)
+    }
+
     pub fn version_control_head(&self) -> Option<Arc<Box<str>>> {
         self.version_control_head.as_ref().map(|a| a.load_full())
     }
@@ -2305,6 +2460,21 @@ pub fn reset_all_inlay_hints(&mut self) {
         self.inlay_hints = Default::default();
     }
 
+    /// Set the debugger inline values for this document.
+    pub fn set_inline_values(&mut self, mut inline_values: Vec<InlineValue>) {
+        inline_values.sort_by_key(|value| value.char_idx);
+        self.inline_values = inline_values;
+    }
+
+    /// Get the debugger inline values for this document, sorted by position.
+    pub fn inline_values(&self) -> &[InlineValue] {
+        &self.inline_values
+    }
+
+    pub fn clear_inline_values(&mut self) {
+        self.inline_values.clear();
+    }
+
     pub fn has_language_server_with_feature(&self, feature: LanguageServerFeature) -> bool {
         self.language_servers_with_feature(feature).next().is_some()
     }
@@ -2391,6 +2561,54 @@ fn changeset_to_changes_ignore_line_endings() {
         );
     }
 
+    #[test]
+    fn inline_values_are_mapped_through_changes() {
+        let mut doc = Document::from(
+            Rope::from("let a = 1;\nlet b = a;\n"),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        let view = ViewId::default();
+        doc.set_selection(view, Selection::single(0, 0));
+        doc.set_inline_values(vec![
+            InlineValue {
+                char_idx: 15,
+                text: "b = 1".into(),
+            },
+            InlineValue {
+                char_idx: 4,
+                text: "a = 1".into(),
+            },
+        ]);
+        assert_eq!(doc.inline_values()[0].char_idx, 4);
+
+        let transaction = Transaction::insert(doc.text(), doc.selection(view), "\n".into());
+        doc.apply(&transaction, view);
+
+        let positions: Vec<_> = doc.inline_values().iter().map(|v| v.char_idx).collect();
+        assert_eq!(positions, [5, 16]);
+    }
+
+    #[test]
+    fn generated_text_has_no_history() {
+        let mut doc = Document::from(
+            Rope::from("first"),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        let mut view = View::new(doc.id(), Default::default());
+        doc.set_selection(view.id, Selection::single(0, 0));
+        doc.replace_generated_text("second".into(), &mut view);
+        doc.replace_generated_text("third".into(), &mut view);
+
+        assert_eq!(doc.text(), "third");
+        assert!(!doc.is_modified());
+        assert!(!doc.undo(&mut view));
+        assert_eq!(doc.text(), "third");
+    }
+
     #[test]
     fn changeset_to_changes() {
         use fugue_lsp::{lsp, Client, OffsetEncoding};
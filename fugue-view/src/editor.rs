@@ -4,21 +4,27 @@
 use crate::{
     annotations::diagnostics::{DiagnosticFilter, InlineDiagnosticsConfig},
     clipboard::ClipboardProvider,
+    diff_view::DiffView,
     document::{
         DocumentOpenError, DocumentSavedEventFuture, DocumentSavedEventResult, Mode, SavePoint,
     },
     events::{DocumentDidClose, DocumentDidOpen, DocumentFocusLost},
+    file_watcher::FileWatcher,
     graphics::{CursorKind, Rect},
     handlers::Handlers,
     info::Info,
     input::KeyEvent,
+    pinned_files::PinnedFiles,
+    quickfix::QuickfixLists,
+    recent_files::RecentFiles,
     register::Registers,
     theme::{self, Theme},
     tree::{self, Tree},
+    workspace_index::WorkspaceIndex,
     Document, DocumentId, View, ViewId,
 };
 use fugue_event::dispatch;
-use fugue_vcs::DiffProviderRegistry;
+use fugue_vcs::{DiffBase, DiffProviderRegistry, FileStatusCache};
 
 use fugue_lsp::{Call, LanguageServerId};
 use futures_util::stream::select_all::SelectAll;
@@ -47,6 +53,7 @@
 pub use fugue_core::diagnostic::Severity;
 use fugue_core::{
     auto_pairs::AutoPairs,
+    coords_at_pos,
     diagnostic::DiagnosticProvider,
     syntax::{
         self,
@@ -206,6 +213,9 @@ pub struct FilePickerConfig {
     /// WalkBuilder options
     /// Maximum Depth to recurse directories in file picker and global search. Defaults to `None`.
     pub max_depth: Option<usize>,
+    /// Lists recently opened files first, ranked by frecency, while the query is empty.
+    /// Defaults to true.
+    pub recent_first: bool,
 }
 
 impl Default for FilePickerConfig {
@@ -220,6 +230,7 @@ fn default() -> Self {
             git_global: true,
             git_exclude: true,
             max_depth: None,
+            recent_first: true,
         }
     }
 }
@@ -421,6 +432,9 @@ pub struct Config {
     /// Display diagnostic below the line they occur.
     pub inline_diagnostics: InlineDiagnosticsConfig,
     pub end_of_line_diagnostics: DiagnosticFilter,
+    /// Show the author, date and summary of the commit that last changed the cursor line at
+    /// the end of the line. Defaults to `false`.
+    pub inline_blame: bool,
     // Set to override the default clipboard provider
     pub clipboard_provider: ClipboardProvider,
     /// Whether to read settings from [EditorConfig](https://editorconfig.org) files. Defaults to
@@ -428,6 +442,16 @@ pub struct Config {
     pub editor_config: bool,
     /// Whether to render rainbow colors for matching brackets. Defaults to `false`.
     pub rainbow_brackets: bool,
+    /// Whether to watch the workspace for files changed by other programs. Buffers without
+    /// unsaved changes are reloaded, others ask whether to reload them. Defaults to `true`.
+    pub watch_files: bool,
+    /// Whether to index the definitions and words in the files of the workspace in the
+    /// background. The index serves goto definition, workspace symbols and word completion when
+    /// no language server does. Defaults to `true`.
+    pub workspace_index: bool,
+    /// Whether a debug session that stops while another one is focused takes the focus.
+    /// Otherwise only a status message is shown. Defaults to `false`.
+    pub focus_stopped_debug_session: bool,
     /// Whether to enable Kitty Keyboard Protocol
     pub kitty_keyboard_protocol: KittyKeyboardProtocolConfig,
     pub buffer_picker: BufferPickerConfig,
@@ -592,6 +616,8 @@ fn default() -> Self {
             ],
             center: vec![],
             right: vec![
+                E::DiffBase,
+                E::DebugSession,
                 E::Diagnostics,
                 E::Selections,
                 E::Register,
@@ -690,11 +716,17 @@ pub enum StatusLineElement {
     /// Current version control information
     VersionControl,
 
+    /// The revision the diff gutter compares against, unless it is the index
+    DiffBase,
+
     /// Indicator for selected register
     Register,
 
     /// The base of current working directory
     CurrentWorkingDirectory,
+
+    /// The focused debug session and the number of running sessions
+    DebugSession,
 }
 
 // Cursor shape is read and used on every rendered frame and so needs
@@ -1115,9 +1147,13 @@ fn default() -> Self {
             jump_label_alphabet: ('a'..='z').collect(),
             inline_diagnostics: InlineDiagnosticsConfig::default(),
             end_of_line_diagnostics: DiagnosticFilter::Enable(Severity::Hint),
+            inline_blame: false,
             clipboard_provider: ClipboardProvider::default(),
             editor_config: true,
             rainbow_brackets: false,
+            watch_files: true,
+            workspace_index: true,
+            focus_stopped_debug_session: false,
             kitty_keyboard_protocol: Default::default(),
             buffer_picker: BufferPickerConfig::default(),
         }
@@ -1146,6 +1182,15 @@ pub struct Breakpoint {
     pub log_message: Option<String>,
 }
 
+/// A scratch document opened by `:blame`, see [`Editor::blame_views`].
+#[derive(Debug, Clone, Copy)]
+pub struct BlameView {
+    /// The blamed document.
+    pub doc: DocumentId,
+    /// The version of `doc` the text of the view was generated from.
+    pub version: i32,
+}
+
 use futures_util::stream::{Flatten, Once};
 
 type Diagnostics = BTreeMap<Uri, Vec<(lsp::Diagnostic, DiagnosticProvider)>>;
@@ -1171,9 +1216,37 @@ pub struct Editor {
     pub language_servers: fugue_lsp::Registry,
     pub diagnostics: Diagnostics,
     pub diff_providers: DiffProviderRegistry,
+    /// The base documents are compared against unless they override it.
+    pub diff_base: DiffBase,
+    /// Pairs of documents shown side by side with their differences aligned.
+    pub diff_views: Vec<DiffView>,
+    /// Read-only scratch documents opened by `:diff` with the diff base of a document, keyed by
+    /// that document.
+    pub diff_bases: HashMap<DocumentId, DocumentId>,
+    /// The VCS status of the files in the working directory, shown in file listings.
+    pub file_statuses: FileStatusCache,
+    /// Reports files changed by other programs, see [`Config::watch_files`].
+    pub file_watcher: Option<FileWatcher>,
+    /// The definitions and words in the workspace, see [`Config::workspace_index`].
+    pub workspace_index: Option<WorkspaceIndex>,
+    /// Locations exported from pickers to be worked through one by one.
+    pub quickfix: QuickfixLists,
+    /// The files opened in the workspace, also in previous sessions.
+    pub recent_files: RecentFiles,
+    /// The files pinned to slots in the workspace.
+    pub pinned_files: PinnedFiles,
 
     pub debug_adapters: dap::registry::Registry,
     pub breakpoints: HashMap<PathBuf, Vec<Breakpoint>>,
+    /// Scratch documents holding the content of debugger sources that have no path, keyed by
+    /// their `sourceReference`.
+    pub debug_sources: HashMap<(DebugAdapterId, usize), DocumentId>,
+    /// The scratch document showing the instructions around the instruction pointer.
+    pub debug_disassembly: Option<DocumentId>,
+    /// The scratch document showing a hex dump of the debuggee's memory.
+    pub debug_memory: Option<crate::handlers::dap::MemoryView>,
+    /// Scratch documents opened by `:blame`, mapped to the document they blame.
+    pub blame_views: HashMap<DocumentId, BlameView>,
 
     pub syn_loader: Arc<ArcSwap<syntax::Loader>>,
     /// The editor theme (hardcoded to base16_transparent)
@@ -1228,6 +1301,8 @@ pub enum EditorEvent {
     DebuggerEvent((DebugAdapterId, dap::Payload)),
     IdleTimer,
     Redraw,
+    /// Files were changed by other programs.
+    FilesChanged(Vec<PathBuf>),
 }
 
 #[derive(Debug, Clone)]
@@ -1310,8 +1385,21 @@ pub fn new(
             language_servers,
             diagnostics: Diagnostics::new(),
             diff_providers: DiffProviderRegistry::default(),
+            diff_base: DiffBase::default(),
+            diff_views: Vec::new(),
+            diff_bases: HashMap::new(),
+            file_statuses: FileStatusCache::default(),
+            file_watcher: None,
+            workspace_index: None,
+            quickfix: QuickfixLists::default(),
+            recent_files: RecentFiles::default(),
+            pinned_files: PinnedFiles::default(),
             debug_adapters: dap::registry::Registry::new(),
             breakpoints: HashMap::new(),
+            debug_sources: HashMap::new(),
+            debug_disassembly: None,
+            debug_memory: None,
+            blame_views: HashMap::new(),
             syn_loader,
             last_selection: None,
             registers: Registers::new(Box::new(arc_swap::access::Map::new(
@@ -1498,9 +1586,24 @@ pub fn move_path(&mut self, old_path: &Path, new_path: &Path) -> io::Result<()>
             fs::rename(old_path, &new_path)?;
         }
 
-        if let Some(doc) = self.document_by_path(old_path) {
-            self.set_doc_path(doc.id(), &new_path);
+        // a moved directory takes the documents of the files below it along
+        let old_path = canonicalize(old_path);
+        let moved: Vec<_> = self
+            .documents()
+            .filter_map(|doc| {
+                let rel = doc.path()?.strip_prefix(&old_path).ok()?;
+                let path = if rel.as_os_str().is_empty() {
+                    new_path.clone()
+                } else {
+                    new_path.join(rel)
+                };
+                Some((doc.id(), path))
+            })
+            .collect();
+        for (doc_id, path) in moved {
+            self.set_doc_path(doc_id, &path);
         }
+        let old_path = old_path.as_path();
         let is_dir = new_path.is_dir();
         for ls in self.language_servers.iter_clients() {
             // A new language server might have been started in `set_doc_path` and won't
@@ -1519,6 +1622,15 @@ pub fn move_path(&mut self, old_path: &Path, new_path: &Path) -> io::Result<()>
         Ok(())
     }
 
+    /// Maps the entries of the quickfix lists through the edits to the open documents.
+    pub fn sync_quickfix(&mut self) {
+        for list in self.quickfix.lists_mut() {
+            for doc in self.documents.values_mut() {
+                list.sync(doc);
+            }
+        }
+    }
+
     pub fn set_doc_path(&mut self, doc_id: DocumentId, path: &Path) {
         let doc = doc_mut!(self, &doc_id);
         let old_path = doc.path();
@@ -1670,7 +1782,54 @@ fn replace_document_in_view(&mut self, current_view: ViewId, doc_id: DocumentId)
         view.sync_changes(doc);
         doc.mark_as_focused();
 
-        view.ensure_cursor_in_view(doc, scrolloff)
+        view.ensure_cursor_in_view(doc, scrolloff);
+    }
+
+    /// Records the path of a document opened or switched to in the recent files.
+    fn visit_recent_file(&mut self, doc_id: DocumentId) {
+        if let Some(path) = self.documents.get(&doc_id).and_then(|doc| doc.path()) {
+            self.recent_files.visit(path);
+        }
+    }
+
+    /// Remembers the cursor positions in the pinned files that are open, taking the cursor of
+    /// the most recently focused view.
+    pub fn remember_pinned_cursors(&mut self) {
+        let paths: Vec<_> = self
+            .pinned_files
+            .files()
+            .iter()
+            .map(|file| file.path.clone())
+            .collect();
+        for path in paths {
+            if let Some(doc_id) = self.document_by_path(&path).map(Document::id) {
+                self.remember_pinned_cursor(doc_id);
+            }
+        }
+    }
+
+    fn remember_pinned_cursor(&mut self, doc_id: DocumentId) {
+        let Some(doc) = self.documents.get(&doc_id) else {
+            return;
+        };
+        let Some(path) = doc.path() else {
+            return;
+        };
+        // the focused view is the most recently focused one
+        let Some(selection) = doc
+            .selections()
+            .iter()
+            .filter_map(|(view_id, selection)| {
+                Some((self.tree.try_get(*view_id)?.focused_at, selection))
+            })
+            .max_by_key(|(focused_at, _)| *focused_at)
+            .map(|(_, selection)| selection)
+        else {
+            return;
+        };
+        let text = doc.text().slice(..);
+        let coords = coords_at_pos(text, selection.primary().cursor(text));
+        self.pinned_files.set_cursor(path, coords.row, coords.col);
     }
 
     pub fn switch(&mut self, id: DocumentId, action: Action) {
@@ -1734,6 +1893,7 @@ pub fn switch(&mut self, id: DocumentId, action: Action) {
                 }
 
                 self.replace_document_in_view(view_id, id);
+                self.visit_recent_file(id);
 
                 dispatch(DocumentFocusLost {
                     editor: self,
@@ -1769,6 +1929,7 @@ pub fn switch(&mut self, id: DocumentId, action: Action) {
                 let doc = doc_mut!(self, &id);
                 doc.ensure_view_init(view_id);
                 doc.mark_as_focused();
+                self.visit_recent_file(id);
                 focus_lost
             }
         };
@@ -1783,7 +1944,7 @@ pub fn switch(&mut self, id: DocumentId, action: Action) {
     }
 
     /// Generate an id for a new document and register it.
-    fn new_document(&mut self, mut doc: Document) -> DocumentId {
+    pub(crate) fn new_document(&mut self, mut doc: Document) -> DocumentId {
         let id = self.next_document_id;
         // Safety: adding 1 from 1 is fine, practically impossible to reach usize max
         self.next_document_id =
@@ -1800,7 +1961,7 @@ fn new_document(&mut self, mut doc: Document) -> DocumentId {
         id
     }
 
-    fn new_file_from_document(&mut self, action: Action, doc: Document) -> DocumentId {
+    pub fn new_file_from_document(&mut self, action: Action, doc: Document) -> DocumentId {
         let id = self.new_document(doc);
         self.switch(id, action);
         id
@@ -1857,10 +2018,7 @@ pub fn open(&mut self, path: &Path, action: Action) -> Result<DocumentId, Docume
                 Editor::doc_diagnostics(&self.language_servers, &self.diagnostics, &doc);
             doc.replace_diagnostics(diagnostics, &[], None);
 
-            if let Some(diff_base) = self.diff_providers.get_diff_base(&path) {
-                doc.set_diff_base(diff_base);
-            }
-            doc.set_version_control_head(self.diff_providers.get_current_head_name(&path));
+            doc.refresh_version_control(&self.diff_providers, &self.diff_base);
 
             let id = self.new_document(doc);
             self.launch_language_servers(id);
@@ -1898,6 +2056,7 @@ pub fn close_document(&mut self, doc_id: DocumentId, force: bool) -> Result<(),
 
         // This will also disallow any follow-up writes
         self.saves.remove(&doc_id);
+        self.remember_pinned_cursor(doc_id);
 
         enum Action {
             Close(ViewId),
@@ -2026,6 +2185,7 @@ pub fn focus(&mut self, view_id: ViewId) {
         }
 
         let prev_id = std::mem::replace(&mut self.tree.focus, view_id);
+        view_mut!(self).focused_at = std::time::Instant::now();
         doc_mut!(self).mark_as_focused();
 
         let focus_lost = self.tree.get(prev_id).doc;
@@ -2214,6 +2374,9 @@ pub async fn wait_event(&mut self) -> EditorEvent {
                 Some(event) = self.debug_adapters.incoming.next() => {
                     return EditorEvent::DebuggerEvent(event)
                 }
+                Some(paths) = Self::file_changes(&mut self.file_watcher) => {
+                    return EditorEvent::FilesChanged(paths)
+                }
 
                 _ = fugue_event::redraw_requested() => {
                     if  !self.needs_redraw{
@@ -2236,6 +2399,13 @@ pub async fn wait_event(&mut self) -> EditorEvent {
         }
     }
 
+    async fn file_changes(file_watcher: &mut Option<FileWatcher>) -> Option<Vec<PathBuf>> {
+        match file_watcher {
+            Some(file_watcher) => file_watcher.changes().await,
+            None => std::future::pending().await,
+        }
+    }
+
     pub async fn flush_writes(&mut self) -> anyhow::Result<()> {
         while self.write_count > 0 {
             if let Some(save_event) = self.save_queue.next().await {
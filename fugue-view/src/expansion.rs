@@ -224,6 +224,57 @@ fn expand_inner<'a>(editor: &Editor, content: Cow<'a, str>) -> Result<Cow<'a, st
     }
 }
 
+/// Expands the `${...}` variables of VS Code's `launch.json` that have an equivalent
+/// [`Variable`], as well as `${env:NAME}` environment variables. Unknown variables, like
+/// `${command:...}`, are left as they are.
+pub fn expand_launch_variables<'a>(editor: &Editor, input: &'a str) -> Result<Cow<'a, str>> {
+    let mut expanded = String::new();
+    let mut start = 0;
+
+    while let Some(offset) = input[start..].find("${") {
+        let idx = start + offset;
+        let Some(len) = input[idx..].find('}') else {
+            break;
+        };
+        let name = &input[idx + 2..idx + len];
+        let value = match name {
+            "workspaceFolder" | "workspaceRoot" => {
+                Some(expand_variable(editor, Variable::WorkspaceDirectory)?)
+            }
+            "workspaceFolderBasename" => Some(Cow::Owned(
+                fugue_loader::find_workspace()
+                    .0
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            )),
+            "file" => Some(expand_variable(editor, Variable::FilePathAbsolute)?),
+            "relativeFile" => Some(expand_variable(editor, Variable::BufferName)?),
+            "cwd" => Some(expand_variable(editor, Variable::CurrentWorkingDirectory)?),
+            "lineNumber" => Some(expand_variable(editor, Variable::CursorLine)?),
+            "selectedText" => Some(expand_variable(editor, Variable::Selection)?),
+            "pathSeparator" => Some(Cow::Borrowed(std::path::MAIN_SEPARATOR_STR)),
+            name => name
+                .strip_prefix("env:")
+                .map(|var| Cow::Owned(std::env::var(var).unwrap_or_default())),
+        };
+
+        expanded.push_str(&input[start..idx]);
+        match value {
+            Some(value) => expanded.push_str(&value),
+            None => expanded.push_str(&input[idx..=idx + len]),
+        }
+        start = idx + len + 1;
+    }
+
+    if expanded.is_empty() && start == 0 {
+        Ok(Cow::Borrowed(input))
+    } else {
+        expanded.push_str(&input[start..]);
+        Ok(Cow::Owned(expanded))
+    }
+}
+
 // Note: the lifetime of the expanded variable (the `Cow`) must not be tied to the lifetime of
 // the borrow of `Editor`. That would prevent commands from mutating the `Editor` until the
 // command consumed or cloned all arguments - this is poor ergonomics. A sensible thing for this
@@ -291,3 +342,60 @@ fn expand_variable(editor: &Editor, variable: Variable) -> Result<Cow<'static, s
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arc_swap::{access::Map, ArcSwap};
+    use fugue_core::syntax::Loader;
+    use tokio::sync::mpsc::channel;
+
+    use super::*;
+    use crate::editor::{Action, Config};
+    use crate::graphics::Rect;
+    use crate::handlers::{completion::CompletionHandler, word_index, Handlers};
+
+    fn editor() -> Editor {
+        let handlers = Handlers {
+            completions: CompletionHandler::new(channel(1).0),
+            signature_hints: channel(1).0,
+            auto_save: channel(1).0,
+            document_colors: channel(1).0,
+            word_index: word_index::Handler::spawn(),
+            pull_diagnostics: channel(1).0,
+            pull_all_documents_diagnostics: channel(1).0,
+        };
+        let config = Arc::new(ArcSwap::from_pointee(Config::default()));
+        let mut editor = Editor::new(
+            Rect::new(0, 0, 80, 24),
+            Arc::new(ArcSwap::from_pointee(Loader::default())),
+            Arc::new(Map::new(config, |config: &Config| config)),
+            handlers,
+        );
+        editor.new_file(Action::VerticalSplit);
+        editor
+    }
+
+    #[tokio::test]
+    async fn launch_variables() {
+        let editor = editor();
+        let workspace = fugue_loader::find_workspace().0;
+        std::env::set_var("FUGUE_LAUNCH_TEST", "value");
+
+        assert_eq!(
+            expand_launch_variables(&editor, "${workspaceFolder}/bin").unwrap(),
+            format!("{}/bin", workspace.display())
+        );
+        assert_eq!(
+            expand_launch_variables(&editor, "line ${lineNumber}, ${env:FUGUE_LAUNCH_TEST}")
+                .unwrap(),
+            "line 1, value"
+        );
+        // unknown and unterminated variables are left alone
+        assert_eq!(
+            expand_launch_variables(&editor, "${command:pickProcess} ${file").unwrap(),
+            "${command:pickProcess} ${file"
+        );
+    }
+}
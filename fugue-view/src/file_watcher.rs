@@ -0,0 +1,205 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+//! Watches the workspace for files changed by other programs, like formatters, code generators
+//! or `git checkout`. Only Linux is supported for now, using inotify.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time::Instant;
+
+/// How long to wait for further events before reporting a batch of changes. Programs often
+/// write several files at once, e.g. when switching branches.
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// The longest a batch is held back while events keep coming in, so that a program writing
+/// continuously doesn't delay the changes forever.
+const MAX_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Directories beyond this number aren't watched, to stay clear of the limits of the system.
+#[cfg(target_os = "linux")]
+const MAX_WATCHED_DIRS: usize = 10_000;
+
+pub struct FileWatcher {
+    changes: UnboundedReceiver<Vec<PathBuf>>,
+}
+
+impl FileWatcher {
+    /// Starts watching `root` and the directories below it that aren't ignored. Returns `None`
+    /// if file watching isn't supported on this platform or couldn't be set up.
+    pub fn new(root: PathBuf) -> Option<Self> {
+        let (raw_tx, raw_rx) = mpsc::unbounded_channel();
+        if let Err(err) = watch(root, raw_tx) {
+            log::error!("failed to start the file watcher: {err}");
+            return None;
+        }
+        let (tx, changes) = mpsc::unbounded_channel();
+        tokio::spawn(debounce(raw_rx, tx));
+        Some(Self { changes })
+    }
+
+    /// Waits for the next batch of changed files. Changes include modified, created, deleted and
+    /// renamed files.
+    pub async fn changes(&mut self) -> Option<Vec<PathBuf>> {
+        self.changes.recv().await
+    }
+}
+
+async fn debounce(mut rx: UnboundedReceiver<PathBuf>, tx: UnboundedSender<Vec<PathBuf>>) {
+    while let Some(path) = rx.recv().await {
+        let mut paths = vec![path.clone()];
+        let mut seen = HashSet::from([path]);
+        let deadline = Instant::now() + MAX_DEBOUNCE;
+        while let Ok(Some(path)) =
+            tokio::time::timeout_at(deadline.min(Instant::now() + DEBOUNCE), rx.recv()).await
+        {
+            if seen.insert(path.clone()) {
+                paths.push(path);
+            }
+        }
+        if tx.send(paths).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn watch(root: PathBuf, tx: UnboundedSender<PathBuf>) -> std::io::Result<()> {
+    inotify::spawn(fugue_stdx::path::canonicalize(root), tx)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn watch(_root: PathBuf, _tx: UnboundedSender<PathBuf>) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "file watching is only supported on Linux",
+    ))
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use std::collections::HashMap;
+    use std::ffi::OsStr;
+    use std::io;
+    use std::mem::MaybeUninit;
+    use std::os::fd::OwnedFd;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+
+    use rustix::fs::inotify::{self, CreateFlags, ReadFlags, WatchFlags};
+    use rustix::io::Errno;
+    use tokio::sync::mpsc::UnboundedSender;
+
+    use super::MAX_WATCHED_DIRS;
+
+    const WATCH_FLAGS: WatchFlags = WatchFlags::CLOSE_WRITE
+        .union(WatchFlags::CREATE)
+        .union(WatchFlags::DELETE)
+        .union(WatchFlags::MOVED_FROM)
+        .union(WatchFlags::MOVED_TO)
+        .union(WatchFlags::ONLYDIR);
+
+    /// Watches the directories below `root` in a background thread, sending the paths of changed
+    /// files to `tx`. Directories created later are watched as well.
+    pub fn spawn(root: PathBuf, tx: UnboundedSender<PathBuf>) -> io::Result<()> {
+        let fd = inotify::init(CreateFlags::CLOEXEC)?;
+        std::thread::Builder::new()
+            .name("file-watcher".to_owned())
+            .spawn(move || {
+                let mut dirs = HashMap::new();
+                watch_tree(&fd, &root, &mut dirs);
+                if let Err(err) = read_events(&fd, &mut dirs, &tx) {
+                    log::error!("file watcher stopped: {err}");
+                }
+            })?;
+        Ok(())
+    }
+
+    fn read_events(
+        fd: &OwnedFd,
+        dirs: &mut HashMap<i32, PathBuf>,
+        tx: &UnboundedSender<PathBuf>,
+    ) -> io::Result<()> {
+        let mut buf = [MaybeUninit::uninit(); 4096];
+        let mut reader = inotify::Reader::new(fd, &mut buf);
+        loop {
+            let event = match reader.next() {
+                Ok(event) => event,
+                Err(Errno::INTR) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let flags = event.events();
+            if flags.contains(ReadFlags::IGNORED) {
+                dirs.remove(&event.wd());
+                continue;
+            }
+            let (Some(dir), Some(name)) = (dirs.get(&event.wd()), event.file_name()) else {
+                continue;
+            };
+            let path = dir.join(OsStr::from_bytes(name.to_bytes()));
+            if flags.contains(ReadFlags::ISDIR) {
+                if flags.intersects(ReadFlags::CREATE | ReadFlags::MOVED_TO) {
+                    watch_tree(fd, &path, dirs);
+                }
+                continue;
+            }
+            if tx.send(path).is_err() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Watches `root` and the directories below it that aren't ignored by `.gitignore` and
+    /// `.ignore` files.
+    fn watch_tree(fd: &OwnedFd, root: &Path, dirs: &mut HashMap<i32, PathBuf>) {
+        let walk = ignore::WalkBuilder::new(root)
+            .hidden(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build();
+        for entry in walk.flatten() {
+            if !entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_dir())
+            {
+                continue;
+            }
+            if dirs.len() >= MAX_WATCHED_DIRS {
+                log::warn!("not watching more than {MAX_WATCHED_DIRS} directories");
+                return;
+            }
+            match inotify::add_watch(fd, entry.path(), WATCH_FLAGS) {
+                Ok(wd) => _ = dirs.insert(wd, entry.into_path()),
+                Err(Errno::NOSPC) => {
+                    log::warn!("reached the inotify watch limit of the system");
+                    return;
+                }
+                Err(err) => log::debug!("failed to watch {}: {err}", entry.path().display()),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn report_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fugue_stdx::path::canonicalize(dir.path());
+        std::fs::create_dir(root.join("src")).unwrap();
+        let mut watcher = FileWatcher::new(root.clone()).unwrap();
+        // the directories are watched in the background
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        std::fs::write(root.join("src/a.rs"), "a").unwrap();
+        std::fs::write(root.join("src/a.rs"), "b").unwrap();
+        std::fs::write(root.join("b.rs"), "b").unwrap();
+        let mut changes = watcher.changes().await.unwrap();
+        changes.sort();
+        assert_eq!(changes, vec![root.join("b.rs"), root.join("src/a.rs")]);
+    }
+}
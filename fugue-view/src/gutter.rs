@@ -100,10 +100,15 @@ pub fn diff<'doc>(
     let added = theme.get("diff.plus.gutter");
     let deleted = theme.get("diff.minus.gutter");
     let modified = theme.get("diff.delta.gutter");
+    let staged_added = theme.get("diff.plus.gutter.staged");
+    let staged_deleted = theme.get("diff.minus.gutter.staged");
+    let staged_modified = theme.get("diff.delta.gutter.staged");
     if let Some(diff_handle) = doc.diff_handle() {
         let hunks = diff_handle.load();
         let mut hunk_i = 0;
         let mut hunk = hunks.nth_hunk(hunk_i);
+        let staged = doc.staged_hunks();
+        let mut staged_i = 0;
         Box::new(
             move |line: usize, _selected: bool, first_visual_line: bool, out: &mut String| {
                 // truncating the line is fine here because we don't compute diffs
@@ -119,7 +124,41 @@ pub fn diff<'doc>(
                 }
 
                 if hunk.after.start > line as u32 {
-                    return None;
+                    // the line is unchanged compared to the index, it may still have changes
+                    // staged compared to `HEAD`
+                    let offset = match hunk_i.checked_sub(1) {
+                        Some(prev) => {
+                            let prev = hunks.nth_hunk(prev);
+                            prev.before.end as i64 - prev.after.end as i64
+                        }
+                        None => 0,
+                    };
+                    let line = (line as i64 + offset) as u32;
+                    let staged_hunk = loop {
+                        let hunk = staged.get(staged_i)?;
+                        if hunk.after.end < line
+                            || !hunk.is_pure_removal() && line == hunk.after.end
+                        {
+                            staged_i += 1;
+                        } else {
+                            break hunk;
+                        }
+                    };
+                    if staged_hunk.after.start > line {
+                        return None;
+                    }
+                    let (icon, style) = if staged_hunk.is_pure_insertion() {
+                        ("▐", staged_added)
+                    } else if staged_hunk.is_pure_removal() {
+                        if !first_visual_line {
+                            return None;
+                        }
+                        ("▁", staged_deleted)
+                    } else {
+                        ("▐", staged_modified)
+                    };
+                    write!(out, "{}", icon).unwrap();
+                    return Some(style);
                 }
 
                 let (icon, style) = if hunk.is_pure_insertion() {
@@ -1,18 +1,27 @@
 // Copyright (c) 2020 Blaž Hrastnik
 // Licensed under MPL-2.0
 
+use crate::document::InlineValue;
 use crate::editor::{Action, Breakpoint};
-use crate::{align_view, Align, Editor};
+use crate::{align_view, Align, Document, DocumentId, Editor};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
 use dap::requests::DisconnectArguments;
-use fugue_core::Selection;
+use fugue_core::line_ending::line_end_char_index;
+use fugue_core::syntax::config::LanguageServerFeature;
+use fugue_core::syntax::{Loader, QueryIterEvent};
+use fugue_core::{Range, Rope, Selection};
 use fugue_dap::{
     self as dap, registry::DebugAdapterId, Client, ConnectionType, Payload, Request, ThreadId,
+    Variable,
 };
-use fugue_lsp::block_on;
-use log::{error, warn};
+use fugue_lsp::util::{lsp_range_to_range, range_to_lsp_range};
+use fugue_lsp::{block_on, lsp};
+use log::warn;
 use serde_json::{json, Value};
+use std::collections::HashSet;
 use std::fmt::Write;
-use std::path::PathBuf;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 
 #[macro_export]
 macro_rules! debugger {
@@ -45,7 +54,7 @@ pub async fn select_thread_id(editor: &mut Editor, thread_id: ThreadId, force: b
 
     let frame = debugger.stack_frames[&thread_id].first().cloned();
     if let Some(frame) = &frame {
-        jump_to_stack_frame(editor, frame);
+        jump_to_stack_frame(editor, frame).await;
     }
 }
 
@@ -58,18 +67,37 @@ pub async fn fetch_stack_trace(debugger: &mut Client, thread_id: ThreadId) {
     debugger.active_frame = Some(0);
 }
 
-pub fn jump_to_stack_frame(editor: &mut Editor, frame: &fugue_dap::StackFrame) {
-    let path = if let Some(fugue_dap::Source {
-        path: Some(ref path),
-        ..
-    }) = frame.source
-    {
-        path.clone()
-    } else {
+/// Jumps to `frame`. Sources that only exist inside the debugger are fetched first.
+pub async fn jump_to_stack_frame(editor: &mut Editor, frame: &fugue_dap::StackFrame) {
+    if let Some((debugger, request)) = source_request(editor, frame) {
+        match request.await {
+            Ok(response) => add_debug_source(editor, debugger, frame, response.content),
+            Err(e) => {
+                editor.set_error(format!("Unable to jump to stack frame: {}", e));
+                return;
+            }
+        }
+    }
+    show_stack_frame(editor, frame);
+}
+
+/// Opens the source of `frame` and selects the frame's location. Sources that only exist inside
+/// the debugger must have been fetched with [`source_request`] before.
+pub fn show_stack_frame(editor: &mut Editor, frame: &fugue_dap::StackFrame) {
+    let Some(source) = &frame.source else {
         return;
     };
 
-    if let Err(e) = editor.open(&path, Action::Replace) {
+    let result = match (&source.path, source.source_reference) {
+        (Some(path), _) => editor
+            .open(path, Action::Replace)
+            .map_err(anyhow::Error::from),
+        (None, Some(source_reference)) if source_reference > 0 => {
+            open_source_reference(editor, source_reference)
+        }
+        _ => return,
+    };
+    if let Err(e) = result {
         editor.set_error(format!("Unable to jump to stack frame: {}", e));
         return;
     }
@@ -88,6 +116,640 @@ pub fn jump_to_stack_frame(editor: &mut Editor, frame: &fugue_dap::StackFrame) {
     align_view(doc, view, Align::Center);
 }
 
+/// Returns the `source` request fetching the content of the source of `frame` if the source only
+/// exists inside the debugger and hasn't been fetched yet, along with the debugger it is sent to.
+/// The content is added with [`add_debug_source`].
+pub fn source_request(
+    editor: &Editor,
+    frame: &fugue_dap::StackFrame,
+) -> Option<(
+    DebugAdapterId,
+    impl Future<Output = fugue_dap::Result<dap::requests::SourceResponse>> + 'static,
+)> {
+    let source = frame.source.as_ref()?;
+    let source_reference = source.source_reference.filter(|&reference| reference > 0)?;
+    if source.path.is_some() {
+        return None;
+    }
+    let debugger = editor.debug_adapters.get_active_client()?;
+    let fetched = editor
+        .debug_sources
+        .get(&(debugger.id(), source_reference))
+        .is_some_and(|doc_id| editor.documents.contains_key(doc_id));
+    if fetched {
+        return None;
+    }
+    Some((debugger.id(), debugger.source(source.clone())))
+}
+
+/// Adds the content of a source that only exists inside the debugger as a read-only scratch
+/// document, which is reused by later jumps into the same source.
+pub fn add_debug_source(
+    editor: &mut Editor,
+    debugger: DebugAdapterId,
+    frame: &fugue_dap::StackFrame,
+    content: String,
+) {
+    let Some(source) = &frame.source else {
+        return;
+    };
+    let Some(source_reference) = source.source_reference else {
+        return;
+    };
+    let mut doc = Document::from(
+        Rope::from(content),
+        None,
+        editor.config.clone(),
+        editor.syn_loader.clone(),
+    );
+    doc.readonly = true;
+    if let Some(name) = &source.name {
+        let loader = editor.syn_loader.load();
+        if let Some(language) = loader.language_for_filename(Path::new(name)) {
+            doc.set_language(Some(loader.language(language).config().clone()), &loader);
+        }
+    }
+
+    let doc_id = editor.new_document(doc);
+    editor
+        .debug_sources
+        .insert((debugger, source_reference), doc_id);
+}
+
+/// Switches to the scratch document holding a source fetched from the active debugger.
+fn open_source_reference(
+    editor: &mut Editor,
+    source_reference: usize,
+) -> anyhow::Result<DocumentId> {
+    let debugger = editor
+        .debug_adapters
+        .get_active_client()
+        .ok_or_else(|| anyhow::anyhow!("no active debugger"))?;
+    let doc_id = editor
+        .debug_sources
+        .get(&(debugger.id(), source_reference))
+        .copied()
+        .filter(|doc_id| editor.documents.contains_key(doc_id))
+        .ok_or_else(|| anyhow::anyhow!("the source has not been fetched"))?;
+    editor.switch(doc_id, Action::Replace);
+    Ok(doc_id)
+}
+
+/// Removes the debugger inline values from every document.
+pub fn clear_inline_values(editor: &mut Editor) {
+    for doc in editor.documents_mut() {
+        doc.clear_inline_values();
+    }
+}
+
+/// Computes the values shown at the end of lines for the current stack frame.
+///
+/// Values come from `textDocument/inlineValue` when a language server supports it. Otherwise
+/// the local variables found by the language's locals query are matched against the variables
+/// in the frame's scopes. Only lines
+/// between roughly one screen above the view and the stopped location are considered.
+pub async fn refresh_inline_values(editor: &mut Editor) {
+    clear_inline_values(editor);
+
+    let Some(frame) = editor.debug_adapters.current_stack_frame().cloned() else {
+        return;
+    };
+    let Some(path) = frame
+        .source
+        .as_ref()
+        .and_then(|source| source.path.as_ref())
+    else {
+        return;
+    };
+    let Some(doc) = editor.document_by_path(path) else {
+        return;
+    };
+    let focused_view = editor.tree.get(editor.tree.focus);
+    let view = if focused_view.doc == doc.id() {
+        Some(focused_view)
+    } else {
+        editor
+            .tree
+            .views()
+            .map(|(view, _)| view)
+            .find(|view| view.doc == doc.id())
+    };
+    let Some(view) = view else {
+        return;
+    };
+
+    let doc_id = doc.id();
+    let text = doc.text().clone();
+    let Some(stopped) = dap_pos_to_pos(&text, frame.line, frame.column) else {
+        return;
+    };
+    let stopped = stopped.min(text.len_chars());
+    let stopped_line = text.char_to_line(stopped);
+    let first_visible_line =
+        text.char_to_line(doc.view_offset(view.id).anchor.min(text.len_chars()));
+    let first_line = first_visible_line
+        .saturating_sub(view.inner_height())
+        .min(stopped_line);
+    let range = Range::new(
+        text.line_to_char(first_line),
+        line_end_char_index(&text.slice(..), stopped_line),
+    );
+
+    let request = doc
+        .language_servers_with_feature(LanguageServerFeature::InlineValues)
+        .find_map(|language_server| {
+            let offset_encoding = language_server.offset_encoding();
+            let context = lsp::InlineValueContext {
+                frame_id: frame.id as i32,
+                stopped_location: range_to_lsp_range(&text, Range::point(stopped), offset_encoding),
+            };
+            let future = language_server.text_document_inline_value(
+                doc.identifier(),
+                range_to_lsp_range(&text, range, offset_encoding),
+                context,
+            )?;
+            Some((future, offset_encoding))
+        });
+    let identifiers = match request {
+        Some(_) => Vec::new(),
+        None => syntax_identifiers(doc, &editor.syn_loader.load(), range),
+    };
+
+    let Some(debugger) = editor.debug_adapters.get_active_client() else {
+        return;
+    };
+    let mut values = Vec::new();
+    match request {
+        Some((future, offset_encoding)) => {
+            let response = match future.await {
+                Ok(response) => response.unwrap_or_default(),
+                Err(err) => {
+                    log::error!("Failed to request inline values: {err}");
+                    return;
+                }
+            };
+            let variables = if response
+                .iter()
+                .any(|value| matches!(value, lsp::InlineValue::VariableLookup(_)))
+            {
+                frame_variables(debugger, frame.id).await
+            } else {
+                Vec::new()
+            };
+
+            for value in response {
+                let value = match value {
+                    lsp::InlineValue::Text(value) => {
+                        lsp_range_to_range(&text, value.range, offset_encoding)
+                            .map(|range| (range.from(), value.text))
+                    }
+                    lsp::InlineValue::VariableLookup(lookup) => {
+                        let Some(range) = lsp_range_to_range(&text, lookup.range, offset_encoding)
+                        else {
+                            continue;
+                        };
+                        let name = lookup
+                            .variable_name
+                            .unwrap_or_else(|| text.slice(range.from()..range.to()).to_string());
+                        variables
+                            .iter()
+                            .find(|variable| {
+                                if lookup.case_sensitive_lookup {
+                                    variable.name == name
+                                } else {
+                                    variable.name.eq_ignore_ascii_case(&name)
+                                }
+                            })
+                            .map(|variable| (range.from(), format!("{name} = {}", variable.value)))
+                    }
+                    lsp::InlineValue::EvaluatableExpression(expression) => {
+                        let Some(range) =
+                            lsp_range_to_range(&text, expression.range, offset_encoding)
+                        else {
+                            continue;
+                        };
+                        let expression = expression
+                            .expression
+                            .unwrap_or_else(|| text.slice(range.from()..range.to()).to_string());
+                        debugger
+                            .eval(expression.clone(), Some(frame.id))
+                            .await
+                            .ok()
+                            .map(|response| {
+                                (range.from(), format!("{expression} = {}", response.result))
+                            })
+                    }
+                };
+                if let Some((char_idx, text)) = value {
+                    values.push(InlineValue { char_idx, text });
+                }
+            }
+        }
+        None if identifiers.is_empty() => return,
+        None => {
+            let variables = frame_variables(debugger, frame.id).await;
+            for (char_idx, name) in identifiers {
+                if let Some(variable) = variables.iter().find(|variable| variable.name == name) {
+                    values.push(InlineValue {
+                        char_idx,
+                        text: format!("{name} = {}", variable.value),
+                    });
+                }
+            }
+        }
+    }
+
+    // Show every value once per line, multi-line values are collapsed onto a single line.
+    values.sort_by_key(|value| value.char_idx);
+    let mut seen = HashSet::new();
+    values.retain_mut(|value| {
+        value.text = value.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        seen.insert((text.char_to_line(value.char_idx), value.text.clone()))
+    });
+
+    if let Some(doc) = editor.documents.get_mut(&doc_id) {
+        doc.set_inline_values(values);
+    }
+}
+
+/// Collects the variables of all non-expensive scopes of a stack frame.
+async fn frame_variables(debugger: &Client, frame_id: usize) -> Vec<Variable> {
+    let Ok(scopes) = debugger.scopes(frame_id).await else {
+        return Vec::new();
+    };
+    let mut variables = Vec::new();
+    for scope in scopes.iter().filter(|scope| !scope.expensive) {
+        if let Ok(scope_variables) = debugger.variables(scope.variables_reference).await {
+            variables.extend(scope_variables);
+        }
+    }
+    variables
+}
+
+/// Returns the position and name of each local variable within `range`: the references and
+/// definitions captured by the locals queries of the document's languages.
+fn syntax_identifiers(doc: &Document, loader: &Loader, range: Range) -> Vec<(usize, String)> {
+    let Some(syntax) = doc.syntax() else {
+        return Vec::new();
+    };
+    let text = doc.text().slice(..);
+    let start = text.char_to_byte(range.from()) as u32;
+    let end = text.char_to_byte(range.to()) as u32;
+
+    let mut identifiers = Vec::new();
+    let mut iter = syntax.locals(text, loader, start..end);
+    while let Some(event) = iter.next() {
+        let QueryIterEvent::Match(mat) = event else {
+            continue;
+        };
+        let Some(query) = loader.locals_query(iter.current_language()) else {
+            continue;
+        };
+        let capture = query.query.capture_name(mat.capture);
+        if capture != "local.reference" && !capture.starts_with("local.definition") {
+            continue;
+        }
+        let from = text.byte_to_char(mat.node.start_byte() as usize);
+        let to = text.byte_to_char(mat.node.end_byte() as usize);
+        identifiers.push((from, text.slice(from..to).to_string()));
+    }
+    identifiers
+}
+
+/// Number of instructions disassembled on each side of the instruction pointer.
+const DISASSEMBLY_CONTEXT: usize = 32;
+/// Number of bytes shown by the memory view unless another length is given.
+pub const MEMORY_VIEW_SIZE: usize = 256;
+const MEMORY_VIEW_COLUMNS: usize = 16;
+
+/// The memory shown by the memory view.
+#[derive(Debug, Clone)]
+pub struct MemoryView {
+    pub doc: DocumentId,
+    pub memory_reference: String,
+    /// Number of bytes shown.
+    pub length: usize,
+}
+
+/// The step granularity to use for the focused document: stepping in the disassembly moves by
+/// instruction when the debugger supports it.
+pub fn stepping_granularity(editor: &Editor) -> Option<String> {
+    let debugger = editor.debug_adapters.get_active_client()?;
+    let doc = editor.tree.get(editor.tree.focus).doc;
+    (editor.debug_disassembly == Some(doc)
+        && debugger
+            .capabilities()
+            .supports_stepping_granularity
+            .unwrap_or_default())
+    .then(|| "instruction".to_owned())
+}
+
+fn is_visible(editor: &Editor, doc_id: DocumentId) -> bool {
+    editor.tree.views().any(|(view, _)| view.doc == doc_id)
+}
+
+/// Shows `text` in a read-only scratch document, updating `doc_id` in place if it is still open
+/// and opening a new document in a split otherwise. The cursor of every view of the document is
+/// moved to `line`.
+fn show_debug_view(
+    editor: &mut Editor,
+    doc_id: Option<DocumentId>,
+    text: String,
+    line: usize,
+) -> DocumentId {
+    let doc_id = match doc_id.filter(|doc_id| editor.documents.contains_key(doc_id)) {
+        Some(doc_id) => {
+            if !is_visible(editor, doc_id) {
+                editor.switch(doc_id, Action::VerticalSplit);
+            }
+            let view_id = editor
+                .tree
+                .views()
+                .find(|(view, _)| view.doc == doc_id)
+                .map(|(view, _)| view.id)
+                .unwrap();
+            let view = view_mut!(editor, view_id);
+            let doc = doc_mut!(editor, &doc_id);
+            doc.replace_generated_text(text, view);
+            doc_id
+        }
+        None => {
+            let mut doc = Document::from(
+                Rope::from(text),
+                None,
+                editor.config.clone(),
+                editor.syn_loader.clone(),
+            );
+            doc.readonly = true;
+            editor.new_file_from_document(Action::VerticalSplit, doc)
+        }
+    };
+
+    let views: Vec<_> = editor
+        .tree
+        .views()
+        .filter(|(view, _)| view.doc == doc_id)
+        .map(|(view, _)| view.id)
+        .collect();
+    for view_id in views {
+        let view = view_mut!(editor, view_id);
+        let doc = doc_mut!(editor, &doc_id);
+        let text = doc.text();
+        let pos = text.line_to_char(line.min(text.len_lines().saturating_sub(1)));
+        doc.set_selection(view_id, Selection::point(pos));
+        align_view(doc, view, Align::Center);
+    }
+    doc_id
+}
+
+/// Shows the instructions around the instruction pointer of the current stack frame.
+pub async fn disassemble(editor: &mut Editor) -> anyhow::Result<()> {
+    let debugger = editor
+        .debug_adapters
+        .get_active_client()
+        .ok_or_else(|| anyhow::anyhow!("no active debugger"))?;
+    if !debugger
+        .capabilities()
+        .supports_disassemble_request
+        .unwrap_or_default()
+    {
+        anyhow::bail!("the debugger does not support disassembly");
+    }
+    let pointer = debugger
+        .current_stack_frame()
+        .ok_or_else(|| anyhow::anyhow!("no current stack frame"))?
+        .instruction_pointer_reference
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("the current stack frame has no instruction pointer"))?;
+
+    let instructions = debugger
+        .disassemble(
+            pointer.clone(),
+            -(DISASSEMBLY_CONTEXT as isize),
+            DISASSEMBLY_CONTEXT * 2,
+        )
+        .await?;
+
+    let mut text = String::new();
+    let mut lines = 0;
+    let mut current_line = 0;
+    let mut symbol = None;
+    for (i, instruction) in instructions.iter().enumerate() {
+        // label the start of every symbol like objdump does
+        if instruction.symbol.is_some() && instruction.symbol != symbol {
+            symbol = instruction.symbol.clone();
+            writeln!(text, "<{}>:", symbol.as_deref().unwrap_or_default()).unwrap();
+            lines += 1;
+        }
+        let is_current = instruction.address == pointer
+            || (i == DISASSEMBLY_CONTEXT
+                && !instructions
+                    .iter()
+                    .any(|instruction| instruction.address == pointer));
+        if is_current {
+            current_line = lines;
+        }
+        writeln!(
+            text,
+            "{} {}  {:<24} {}",
+            if is_current { "=>" } else { "  " },
+            instruction.address,
+            instruction.instruction_bytes.as_deref().unwrap_or_default(),
+            instruction.instruction,
+        )
+        .unwrap();
+        lines += 1;
+    }
+
+    let doc = show_debug_view(editor, editor.debug_disassembly, text, current_line);
+    editor.debug_disassembly = Some(doc);
+    Ok(())
+}
+
+/// Shows a hex dump of `length` bytes of memory at `address`. The address is either a memory
+/// reference such as `0x1000` or an expression evaluated in the current stack frame whose value
+/// refers to memory, like a pointer.
+pub async fn show_memory(editor: &mut Editor, address: &str, length: usize) -> anyhow::Result<()> {
+    let debugger = editor
+        .debug_adapters
+        .get_active_client()
+        .ok_or_else(|| anyhow::anyhow!("no active debugger"))?;
+    if !debugger
+        .capabilities()
+        .supports_read_memory_request
+        .unwrap_or_default()
+    {
+        anyhow::bail!("the debugger does not support reading memory");
+    }
+
+    let memory_reference = if parse_address(address).is_some() {
+        address.to_owned()
+    } else {
+        let frame_id = debugger.current_stack_frame().map(|frame| frame.id);
+        debugger
+            .eval(address.to_owned(), frame_id)
+            .await?
+            .memory_reference
+            .ok_or_else(|| anyhow::anyhow!("'{address}' does not refer to memory"))?
+    };
+
+    let doc = editor
+        .debug_memory
+        .as_ref()
+        .map(|memory| memory.doc)
+        .filter(|doc| editor.documents.contains_key(doc));
+    let text = read_memory_dump(editor, &memory_reference, length).await?;
+    let doc = show_debug_view(editor, doc, text, 0);
+    editor.debug_memory = Some(MemoryView {
+        doc,
+        memory_reference,
+        length,
+    });
+    Ok(())
+}
+
+/// Writes `data` to the memory at `address`, a memory reference or an expression like for
+/// [`show_memory`].
+pub async fn write_memory(editor: &mut Editor, address: &str, data: &[u8]) -> anyhow::Result<()> {
+    let debugger = editor
+        .debug_adapters
+        .get_active_client()
+        .ok_or_else(|| anyhow::anyhow!("no active debugger"))?;
+    if !debugger
+        .capabilities()
+        .supports_write_memory_request
+        .unwrap_or_default()
+    {
+        anyhow::bail!("the debugger does not support writing memory");
+    }
+
+    let memory_reference = if parse_address(address).is_some() {
+        address.to_owned()
+    } else {
+        let frame_id = debugger.current_stack_frame().map(|frame| frame.id);
+        debugger
+            .eval(address.to_owned(), frame_id)
+            .await?
+            .memory_reference
+            .ok_or_else(|| anyhow::anyhow!("'{address}' does not refer to memory"))?
+    };
+    debugger.write_memory(memory_reference, 0, data).await?;
+    refresh_memory(editor).await
+}
+
+async fn refresh_memory(editor: &mut Editor) -> anyhow::Result<()> {
+    let Some(memory) = editor.debug_memory.clone() else {
+        return Ok(());
+    };
+    if !is_visible(editor, memory.doc) {
+        return Ok(());
+    }
+
+    let line = editor
+        .tree
+        .views()
+        .find(|(view, _)| view.doc == memory.doc)
+        .map_or(0, |(view, _)| {
+            let doc = &editor.documents[&memory.doc];
+            doc.selection(view.id)
+                .primary()
+                .cursor_line(doc.text().slice(..))
+        });
+    let text = read_memory_dump(editor, &memory.memory_reference, memory.length).await?;
+    show_debug_view(editor, Some(memory.doc), text, line);
+    Ok(())
+}
+
+/// Refreshes the disassembly and memory views after the debuggee stopped. The disassembly is
+/// also opened when the current frame has no source to show.
+async fn refresh_debug_views(editor: &mut Editor) {
+    let Some(debugger) = editor.debug_adapters.get_active_client() else {
+        return;
+    };
+    let frame_without_source = debugger.current_stack_frame().is_some_and(|frame| {
+        frame
+            .source
+            .as_ref()
+            .is_none_or(|source| source.path.is_none() && source.source_reference.is_none())
+    });
+    let show_disassembly = editor
+        .debug_disassembly
+        .is_some_and(|doc| is_visible(editor, doc))
+        || (frame_without_source
+            && debugger
+                .capabilities()
+                .supports_disassemble_request
+                .unwrap_or_default());
+    if show_disassembly {
+        if let Err(err) = disassemble(editor).await {
+            log::warn!("Failed to disassemble: {err}");
+        }
+    }
+    if let Err(err) = refresh_memory(editor).await {
+        log::warn!("Failed to read memory: {err}");
+    }
+}
+
+async fn read_memory_dump(
+    editor: &Editor,
+    memory_reference: &str,
+    length: usize,
+) -> anyhow::Result<String> {
+    let debugger = editor
+        .debug_adapters
+        .get_active_client()
+        .ok_or_else(|| anyhow::anyhow!("no active debugger"))?;
+    let response = debugger
+        .read_memory(memory_reference.to_owned(), 0, length)
+        .await?;
+    let data = match response.data {
+        Some(data) => BASE64_STANDARD
+            .decode(data)
+            .map_err(|err| anyhow::anyhow!("the debugger sent invalid memory data: {err}"))?,
+        None => Vec::new(),
+    };
+    let address = parse_address(&response.address).unwrap_or_default();
+    let mut bytes = data.into_iter().map(Some).collect::<Vec<_>>();
+    // bytes past the end of the data couldn't be read
+    bytes.resize(length, None);
+    Ok(hex_dump(address, &bytes))
+}
+
+fn parse_address(address: &str) -> Option<u64> {
+    match address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => address.parse().ok(),
+    }
+}
+
+/// Formats memory like `xxd`, with `??` for unreadable bytes.
+fn hex_dump(address: u64, bytes: &[Option<u8>]) -> String {
+    let mut text = String::new();
+    for (i, row) in bytes.chunks(MEMORY_VIEW_COLUMNS).enumerate() {
+        let row_address = address.wrapping_add((i * MEMORY_VIEW_COLUMNS) as u64);
+        write!(text, "{row_address:#018x} ").unwrap();
+        for (j, byte) in row.iter().enumerate() {
+            if j % 8 == 0 {
+                text.push(' ');
+            }
+            match byte {
+                Some(byte) => write!(text, "{byte:02x} ").unwrap(),
+                None => text.push_str("?? "),
+            }
+        }
+        text.push(' ');
+        text.extend(row.iter().map(|byte| match byte {
+            Some(byte) if byte.is_ascii_graphic() || *byte == b' ' => *byte as char,
+            _ => '.',
+        }));
+        text.push('\n');
+    }
+    text
+}
+
 pub fn breakpoints_changed(
     debugger: &mut dap::Client,
     path: PathBuf,
@@ -145,6 +807,34 @@ pub fn breakpoints_changed(
     Ok(())
 }
 
+/// Sends the breakpoints of `path` to every debug session. Only the focused session updates the
+/// state shown in the gutter since breakpoint ids and verification differ between sessions.
+pub fn sync_breakpoints(editor: &mut Editor, path: PathBuf) -> Result<(), anyhow::Error> {
+    let active = editor.debug_adapters.active_client_id();
+    let breakpoints = editor.breakpoints.entry(path.clone()).or_default();
+    for debugger in editor.debug_adapters.clients_mut() {
+        if Some(debugger.id()) == active {
+            breakpoints_changed(debugger, path.clone(), breakpoints)?;
+        } else {
+            breakpoints_changed(debugger, path.clone(), &mut breakpoints.clone())?;
+        }
+    }
+    Ok(())
+}
+
+/// Makes `id` the focused debug session, refreshing inline values from it.
+pub async fn focus_session(editor: &mut Editor, id: DebugAdapterId) {
+    editor.debug_adapters.set_active_client(id);
+    clear_inline_values(editor);
+    let Some(debugger) = editor.debug_adapters.get_client_mut(id) else {
+        return;
+    };
+    if let Some(frame) = debugger.current_stack_frame().cloned() {
+        jump_to_stack_frame(editor, &frame).await;
+        refresh_inline_values(editor).await;
+    }
+}
+
 impl Editor {
     pub async fn handle_debugger_message(
         &mut self,
@@ -175,6 +865,12 @@ pub async fn handle_debugger_message(
                         all_threads_stopped,
                         ..
                     }) => {
+                        if self.debug_adapters.active_client_id() != Some(id)
+                            && self.config().focus_stopped_debug_session
+                        {
+                            focus_session(self, id).await;
+                        }
+                        let focused = self.debug_adapters.active_client_id() == Some(id);
                         let debugger = match self.debug_adapters.get_client_mut(id) {
                             Some(debugger) => debugger,
                             None => return false,
@@ -182,21 +878,34 @@ pub async fn handle_debugger_message(
 
                         let all_threads_stopped = all_threads_stopped.unwrap_or_default();
 
+                        match debugger.request::<dap::requests::Threads>(()).await {
+                            Ok(response) => debugger.threads = response.threads,
+                            Err(err) => log::warn!("Failed to fetch threads: {err}"),
+                        }
+
                         if all_threads_stopped {
-                            if let Ok(response) =
-                                debugger.request::<dap::requests::Threads>(()).await
-                            {
-                                for thread in response.threads {
-                                    fetch_stack_trace(debugger, thread.id).await;
-                                }
-                                select_thread_id(self, thread_id.unwrap_or_default(), false).await;
+                            let threads: Vec<_> =
+                                debugger.threads.iter().map(|thread| thread.id).collect();
+                            for thread in threads {
+                                debugger.thread_states.insert(thread, reason.clone());
+                                fetch_stack_trace(debugger, thread).await;
+                            }
+                            let thread_id = thread_id.unwrap_or_default();
+                            if focused {
+                                select_thread_id(self, thread_id, false).await;
+                            } else {
+                                debugger.thread_id.get_or_insert(thread_id);
                             }
                         } else if let Some(thread_id) = thread_id {
                             debugger.thread_states.insert(thread_id, reason.clone()); // TODO: dap uses "type" || "reason" here
 
                             fetch_stack_trace(debugger, thread_id).await;
                             // whichever thread stops is made "current" (if no previously selected thread).
-                            select_thread_id(self, thread_id, false).await;
+                            if focused {
+                                select_thread_id(self, thread_id, false).await;
+                            } else {
+                                debugger.thread_id.get_or_insert(thread_id);
+                            }
                         }
 
                         let scope = match thread_id {
@@ -215,20 +924,46 @@ pub async fn handle_debugger_message(
                             status.push_str(" (all threads stopped)");
                         }
 
+                        if !focused {
+                            // Leave the focused session alone, the user switches explicitly.
+                            if let Some(debugger) = self.debug_adapters.get_client(id) {
+                                status = format!("{}: {status}", debugger.name);
+                            }
+                            self.set_status(status);
+                            return true;
+                        }
+
                         self.set_status(status);
+                        refresh_inline_values(self).await;
+                        refresh_debug_views(self).await;
                     }
-                    Event::Continued(events::ContinuedBody { thread_id, .. }) => {
+                    Event::Continued(events::ContinuedBody {
+                        thread_id,
+                        all_threads_continued,
+                    }) => {
                         let debugger = match self.debug_adapters.get_client_mut(id) {
                             Some(debugger) => debugger,
                             None => return false,
                         };
 
+                        if all_threads_continued.unwrap_or_default() {
+                            for thread in &debugger.threads {
+                                debugger
+                                    .thread_states
+                                    .insert(thread.id, "running".to_owned());
+                            }
+                            debugger.stack_frames.clear();
+                        }
                         debugger
                             .thread_states
                             .insert(thread_id, "running".to_owned());
+                        debugger.stack_frames.remove(&thread_id);
                         if debugger.thread_id == Some(thread_id) {
                             debugger.resume_application();
                         }
+                        if self.debug_adapters.active_client_id() == Some(id) {
+                            clear_inline_values(self);
+                        }
                     }
                     Event::Thread(thread) => {
                         self.set_status(format!("Thread {}: {}", thread.thread_id, thread.reason));
@@ -237,10 +972,26 @@ pub async fn handle_debugger_message(
                             None => return false,
                         };
 
+                        if thread.reason == "exited" {
+                            debugger.threads.retain(|t| t.id != thread.thread_id);
+                            debugger.thread_states.remove(&thread.thread_id);
+                            debugger.stack_frames.remove(&thread.thread_id);
+                        } else if !debugger.threads.iter().any(|t| t.id == thread.thread_id) {
+                            // the name is filled in by the `threads` request on the next stop
+                            debugger.threads.push(dap::Thread {
+                                id: thread.thread_id,
+                                name: format!("Thread {}", thread.thread_id),
+                            });
+                        }
+
                         debugger.thread_id = Some(thread.thread_id);
                         // set the stack frame for the thread
                     }
                     Event::Breakpoint(events::BreakpointBody { reason, breakpoint }) => {
+                        // breakpoint ids are only meaningful to the session that reported them
+                        if self.debug_adapters.active_client_id() != Some(id) {
+                            return false;
+                        }
                         match &reason[..] {
                             "new" => {
                                 if let Some(source) = breakpoint.source {
@@ -308,6 +1059,8 @@ pub async fn handle_debugger_message(
                     }
                     Event::Initialized(_) => {
                         self.set_status("Debugger initialized...");
+                        // new sessions are focused, so their breakpoint state is shown
+                        self.debug_adapters.set_active_client(id);
                         let debugger = match self.debug_adapters.get_client_mut(id) {
                             Some(debugger) => debugger,
                             None => return false,
@@ -323,8 +1076,6 @@ pub async fn handle_debugger_message(
                         if debugger.configuration_done().await.is_ok() {
                             self.set_status("Debugged application started");
                         }; // TODO: do we need to handle error?
-
-                        self.debug_adapters.set_active_client(id);
                     }
                     Event::Terminated(terminated) => {
                         let debugger = match self.debug_adapters.get_client_mut(id) {
@@ -358,17 +1109,26 @@ pub async fn handle_debugger_message(
 
                         match restart_arg {
                             Some(Value::Bool(false)) | None => {
+                                let name = std::mem::take(&mut debugger.name);
+                                let was_active = self.debug_adapters.active_client_id() == Some(id)
+                                    || self.debug_adapters.active_client_id().is_none();
                                 self.debug_adapters.remove_client(id);
-                                self.debug_adapters.unset_active_client();
-                                self.set_status(
-                                    "Terminated debugging session and disconnected debugger.",
-                                );
+                                self.set_status(format!(
+                                    "Terminated debugging session '{name}' and disconnected debugger."
+                                ));
 
-                                // Go through all breakpoints and set verfified to false
-                                // this should update the UI to show the breakpoints are no longer connected
-                                for breakpoints in self.breakpoints.values_mut() {
-                                    for breakpoint in breakpoints.iter_mut() {
-                                        breakpoint.verified = false;
+                                match self.debug_adapters.active_client_id() {
+                                    Some(next) if was_active => focus_session(self, next).await,
+                                    Some(_) => (),
+                                    None => {
+                                        clear_inline_values(self);
+                                        // Go through all breakpoints and set verfified to false
+                                        // this should update the UI to show the breakpoints are no longer connected
+                                        for breakpoints in self.breakpoints.values_mut() {
+                                            for breakpoint in breakpoints.iter_mut() {
+                                                breakpoint.verified = false;
+                                            }
+                                        }
                                     }
                                 }
                             }
@@ -406,6 +1166,20 @@ pub async fn handle_debugger_message(
                             ));
                         }
                     }
+                    Event::Memory(events::MemoryBody {
+                        memory_reference, ..
+                    }) => {
+                        if !self
+                            .debug_memory
+                            .as_ref()
+                            .is_some_and(|memory| memory.memory_reference == memory_reference)
+                        {
+                            return false;
+                        }
+                        if let Err(err) = refresh_memory(self).await {
+                            log::warn!("Failed to read memory: {err}");
+                        }
+                    }
                     ev => {
                         log::warn!("Unhandled event {:?}", ev);
                         return false; // return early to skip render
@@ -443,33 +1217,12 @@ pub async fn handle_debugger_message(
                         }))
                     }
                     Ok(Request::StartDebugging(arguments)) => {
-                        let debugger = match self.debug_adapters.get_client_mut(id) {
-                            Some(debugger) => debugger,
-                            None => {
-                                self.set_error("No active debugger found.");
-                                return true;
-                            }
-                        };
-                        // Currently we only support starting a child debugger if the parent is using the TCP transport
-                        let socket = match debugger.socket {
-                            Some(socket) => socket,
-                            None => {
-                                self.set_error("Child debugger can only be started if the parent debugger is using TCP transport.");
-                                return true;
-                            }
-                        };
-
-                        let config = match debugger.config.clone() {
-                            Some(config) => config,
-                            None => {
-                                error!("No configuration found for the debugger.");
-                                return true;
-                            }
-                        };
-
-                        let result = self.debug_adapters.start_client(Some(socket), &config);
-
-                        let client_id = match result {
+                        let name = arguments
+                            .configuration
+                            .get("name")
+                            .and_then(Value::as_str)
+                            .map(str::to_owned);
+                        let client_id = match self.debug_adapters.start_child_client(id, name) {
                             Ok(child) => child,
                             Err(err) => {
                                 self.set_error(format!(
@@ -516,3 +1269,26 @@ pub async fn handle_debugger_message(
         true
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn memory_hex_dump() {
+        let mut bytes: Vec<_> = b"hello, world!\n\0\x7f".iter().copied().map(Some).collect();
+        bytes.extend([Some(0xff), None]);
+        assert_eq!(
+            hex_dump(0x1000, &bytes),
+            "0x0000000000001000  68 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 00 7f  hello, world!...\n\
+             0x0000000000001010  ff ??  ..\n"
+        );
+    }
+
+    #[test]
+    fn memory_addresses() {
+        assert_eq!(parse_address("0x1000"), Some(0x1000));
+        assert_eq!(parse_address("4096"), Some(4096));
+        assert_eq!(parse_address("ptr"), None);
+    }
+}
@@ -260,7 +260,7 @@ async fn run(self, mut events: mpsc::UnboundedReceiver<Event>) {
     }
 }
 
-fn words(text: RopeSlice) -> impl Iterator<Item = RopeSlice> {
+pub(crate) fn words(text: RopeSlice) -> impl Iterator<Item = RopeSlice> {
     let mut cursor = Range::point(0);
     if text
         .get_char(cursor.anchor)
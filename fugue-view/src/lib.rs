@@ -6,20 +6,27 @@
 
 pub mod annotations;
 pub mod clipboard;
+pub mod debug_config;
+pub mod diff_view;
 pub mod document;
 pub mod editor;
 pub mod events;
 pub mod expansion;
+pub mod file_watcher;
 pub mod graphics;
 pub mod gutter;
 pub mod handlers;
 pub mod info;
 pub mod input;
 pub mod keyboard;
+pub mod pinned_files;
+pub mod quickfix;
+pub mod recent_files;
 pub mod register;
 pub mod theme;
 pub mod tree;
 pub mod view;
+pub mod workspace_index;
 
 use std::num::NonZeroUsize;
 
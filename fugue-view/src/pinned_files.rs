@@ -0,0 +1,180 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+//! The files pinned to numbered slots in a workspace, to move between the handful of files a
+//! task needs. Slots are persisted across sessions along with the cursor position each file was
+//! left at.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedFile {
+    pub path: PathBuf,
+    /// The 0 indexed line of the cursor when the file was last left.
+    pub line: usize,
+    /// The 0 indexed column of the cursor, in characters.
+    pub column: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct PinnedFiles {
+    /// The file the slots of all workspaces are stored in. Nothing is persisted without it.
+    store: Option<PathBuf>,
+    workspace: PathBuf,
+    files: Vec<PinnedFile>,
+}
+
+/// The stored slots by workspace.
+type Store = HashMap<PathBuf, Vec<PinnedFile>>;
+
+impl PinnedFiles {
+    /// Loads the pinned files of `workspace` from `store`.
+    pub fn load(store: PathBuf, workspace: PathBuf) -> Self {
+        let files = read_store(&store).remove(&workspace).unwrap_or_default();
+        Self {
+            store: Some(store),
+            workspace,
+            files,
+        }
+    }
+
+    /// Writes the pinned files to the store, keeping the slots of other workspaces.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        let mut stored = read_store(store);
+        if self.files.is_empty() {
+            stored.remove(&self.workspace);
+        } else {
+            stored.insert(self.workspace.clone(), self.files.clone());
+        }
+        if let Some(dir) = store.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(store, serde_json::to_vec(&stored)?)?;
+        Ok(())
+    }
+
+    /// The pinned files, by slot. Slots are 0 indexed.
+    pub fn files(&self) -> &[PinnedFile] {
+        &self.files
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&PinnedFile> {
+        self.files.get(slot)
+    }
+
+    /// Returns the slot `path` is pinned to.
+    pub fn slot(&self, path: &Path) -> Option<usize> {
+        self.files.iter().position(|file| file.path == path)
+    }
+
+    /// Pins `path` to `slot`, or to a new slot at the end. Pinned files are moved instead.
+    /// Returns the slot the file ended up in.
+    pub fn pin(&mut self, path: &Path, slot: Option<usize>, line: usize, column: usize) -> usize {
+        let file = match self.slot(path) {
+            Some(current) => self.files.remove(current),
+            None => PinnedFile {
+                path: path.to_path_buf(),
+                line,
+                column,
+            },
+        };
+        let slot = slot.unwrap_or(self.files.len()).min(self.files.len());
+        self.files.insert(slot, file);
+        slot
+    }
+
+    pub fn unpin(&mut self, slot: usize) -> Option<PinnedFile> {
+        (slot < self.files.len()).then(|| self.files.remove(slot))
+    }
+
+    /// Moves the file in slot `from` to slot `to`, shifting the files in between. Returns
+    /// whether both slots exist.
+    pub fn move_slot(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.files.len() || to >= self.files.len() {
+            return false;
+        }
+        let file = self.files.remove(from);
+        self.files.insert(to, file);
+        true
+    }
+
+    /// Remembers the cursor position in `path`, if it is pinned.
+    pub fn set_cursor(&mut self, path: &Path, line: usize, column: usize) {
+        if let Some(file) = self.files.iter_mut().find(|file| file.path == path) {
+            file.line = line;
+            file.column = column;
+        }
+    }
+}
+
+fn read_store(store: &Path) -> Store {
+    fs::read(store)
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn paths(pinned: &PinnedFiles) -> Vec<&Path> {
+        pinned
+            .files()
+            .iter()
+            .map(|file| file.path.as_path())
+            .collect()
+    }
+
+    #[test]
+    fn pin_and_reorder() {
+        let mut pinned = PinnedFiles::default();
+        assert_eq!(pinned.pin(Path::new("a"), None, 0, 0), 0);
+        assert_eq!(pinned.pin(Path::new("b"), None, 0, 0), 1);
+        assert_eq!(pinned.pin(Path::new("c"), Some(0), 0, 0), 0);
+        assert_eq!(paths(&pinned), ["c", "a", "b"].map(Path::new));
+
+        // pinning again moves the file and keeps its cursor
+        pinned.set_cursor(Path::new("b"), 3, 4);
+        assert_eq!(pinned.pin(Path::new("b"), Some(9), 0, 0), 2);
+        assert_eq!(pinned.pin(Path::new("b"), Some(1), 0, 0), 1);
+        assert_eq!(paths(&pinned), ["c", "b", "a"].map(Path::new));
+        assert_eq!((pinned.files()[1].line, pinned.files()[1].column), (3, 4));
+
+        assert!(pinned.move_slot(0, 2));
+        assert!(!pinned.move_slot(0, 3));
+        assert_eq!(paths(&pinned), ["b", "a", "c"].map(Path::new));
+        assert_eq!(pinned.unpin(1).unwrap().path, Path::new("a"));
+        assert_eq!(pinned.unpin(5), None);
+        assert_eq!(paths(&pinned), ["b", "c"].map(Path::new));
+    }
+
+    #[test]
+    fn persist_per_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("pinned_files.json");
+        let mut a = PinnedFiles::load(store.clone(), PathBuf::from("/a"));
+        a.pin(Path::new("/a/file"), None, 1, 2);
+        a.save().unwrap();
+        let mut b = PinnedFiles::load(store.clone(), PathBuf::from("/b"));
+        b.pin(Path::new("/b/file"), None, 0, 0);
+        b.save().unwrap();
+
+        let a = PinnedFiles::load(store, PathBuf::from("/a"));
+        assert_eq!(
+            a.files(),
+            [PinnedFile {
+                path: PathBuf::from("/a/file"),
+                line: 1,
+                column: 2,
+            }]
+        );
+    }
+}
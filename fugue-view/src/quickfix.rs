@@ -0,0 +1,200 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+//! Quickfix lists collect locations from pickers, like search results or diagnostics, so they
+//! can be worked through one by one after the picker closed.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use fugue_core::{line_ending::line_end_char_index, Assoc, RopeSlice};
+
+use crate::{Document, DocumentId};
+
+#[derive(Debug, Clone)]
+pub struct QuickfixEntry {
+    pub path: PathBuf,
+    /// Describes the entry, like the matched line or the diagnostic message.
+    pub text: String,
+    /// The zero-indexed line of the entry, as of the last sync.
+    line: usize,
+    /// The character offset of the entry in its line, as of the last sync.
+    column: usize,
+    /// The character index of the entry in its open document, at the revision recorded by the
+    /// list.
+    char_idx: Option<usize>,
+}
+
+impl QuickfixEntry {
+    /// Creates an entry for the zero-indexed `line` and character `column` of the file at
+    /// `path`.
+    pub fn new(path: PathBuf, line: usize, column: usize, text: String) -> Self {
+        Self {
+            path,
+            text,
+            line,
+            column,
+            char_idx: None,
+        }
+    }
+
+    /// The character index of the entry in `text`, the text of its document.
+    pub fn char_idx(&self, text: RopeSlice) -> usize {
+        match self.char_idx {
+            Some(pos) => pos.min(text.len_chars()),
+            None => self.last_known_char_idx(text),
+        }
+    }
+
+    /// The character index of the last known line and column in `text`, clamped to the line.
+    fn last_known_char_idx(&self, text: RopeSlice) -> usize {
+        let line = self.line.min(text.len_lines() - 1);
+        let start = text.line_to_char(line);
+        start + self.column.min(line_end_char_index(&text, line) - start)
+    }
+
+    /// The zero-indexed line of the entry.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QuickfixList {
+    pub name: String,
+    pub entries: Vec<QuickfixEntry>,
+    /// Index of the current entry.
+    pub index: usize,
+    /// The revisions of the documents the character positions of the entries refer to.
+    revisions: HashMap<DocumentId, usize>,
+}
+
+impl QuickfixList {
+    pub fn new(name: String, entries: Vec<QuickfixEntry>) -> Self {
+        Self {
+            name,
+            entries,
+            index: 0,
+            revisions: HashMap::new(),
+        }
+    }
+
+    /// Maps the positions of the entries in `doc` through the edits made since the last sync.
+    /// Entries are placed at their line and column when their document is first synced.
+    pub fn sync(&mut self, doc: &mut Document) {
+        let Some(path) = doc.path().cloned() else {
+            return;
+        };
+        let revision = doc.get_current_revision();
+        let changes = match self.revisions.insert(doc.id(), revision) {
+            Some(old) if old == revision => return,
+            Some(old) => doc.history.get_mut().changes_since(old),
+            None => None,
+        };
+
+        let text = doc.text().slice(..);
+        for entry in self.entries.iter_mut().filter(|entry| entry.path == path) {
+            let pos = match (entry.char_idx, &changes) {
+                (Some(pos), Some(transaction)) => transaction.changes().map_pos(pos, Assoc::Before),
+                // a document opened since the last sync starts from the last known position
+                _ => entry.last_known_char_idx(text),
+            };
+            entry.char_idx = Some(pos);
+            entry.line = text.char_to_line(pos);
+            entry.column = pos - text.line_to_char(entry.line);
+        }
+    }
+}
+
+/// The quickfix lists of the editor by name. Navigation uses the current list.
+#[derive(Debug, Default)]
+pub struct QuickfixLists {
+    lists: Vec<QuickfixList>,
+    current: usize,
+}
+
+impl QuickfixLists {
+    /// Adds `list` and makes it the current one. A list with the same name is replaced.
+    pub fn set(&mut self, list: QuickfixList) {
+        match self.lists.iter().position(|other| other.name == list.name) {
+            Some(idx) => {
+                self.lists[idx] = list;
+                self.current = idx;
+            }
+            None => {
+                self.lists.push(list);
+                self.current = self.lists.len() - 1;
+            }
+        }
+    }
+
+    /// Makes the list called `name` the current one. Returns whether it exists.
+    pub fn select(&mut self, name: &str) -> bool {
+        let idx = self.lists.iter().position(|list| list.name == name);
+        if let Some(idx) = idx {
+            self.current = idx;
+        }
+        idx.is_some()
+    }
+
+    pub fn current(&self) -> Option<&QuickfixList> {
+        self.lists.get(self.current)
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut QuickfixList> {
+        self.lists.get_mut(self.current)
+    }
+
+    pub fn lists_mut(&mut self) -> impl Iterator<Item = &mut QuickfixList> {
+        self.lists.iter_mut()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.lists.iter().map(|list| list.name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arc_swap::ArcSwap;
+    use fugue_core::{syntax, Rope, Transaction};
+
+    use super::*;
+    use crate::editor::Config;
+    use crate::View;
+
+    #[test]
+    fn map_entries_through_edits() {
+        let mut doc = Document::from(
+            Rope::from("a\nb\nc\n"),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        doc.set_path(Some(std::path::Path::new("/tmp/quickfix")));
+        let path = doc.path().unwrap().clone();
+        let mut list = QuickfixList::new(
+            "test".to_owned(),
+            vec![
+                QuickfixEntry::new(path.clone(), 1, 0, "b".to_owned()),
+                QuickfixEntry::new(path.clone(), 2, 5, "c".to_owned()),
+            ],
+        );
+        list.sync(&mut doc);
+        // the column is clamped to the end of the line
+        assert_eq!(list.entries[1].char_idx(doc.text().slice(..)), 5);
+
+        let mut view = View::new(doc.id(), Default::default());
+        doc.ensure_view_init(view.id);
+        let transaction = Transaction::insert(doc.text(), doc.selection(view.id), "x\n".into());
+        doc.apply(&transaction, view.id);
+        doc.append_changes_to_history(&mut view);
+        list.sync(&mut doc);
+
+        let text = doc.text().slice(..);
+        assert_eq!(list.entries[0].line(), 2);
+        assert_eq!(list.entries[1].char_idx(text), 7);
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+//! The files recently opened in a workspace, persisted across sessions and ranked by
+//! frecency: how often and how recently they were opened.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Files beyond this many are forgotten, starting with the lowest ranked.
+const MAX_ENTRIES: usize = 500;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    /// How often the file was opened.
+    pub count: u32,
+    /// When the file was last opened, in seconds since the Unix epoch.
+    pub accessed: u64,
+}
+
+impl RecentFile {
+    /// Ranks the file by its access count, weighted by how long ago it was last opened.
+    pub fn frecency(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.accessed);
+        let weight = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.count as f64 * weight
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RecentFiles {
+    /// The file the lists of all workspaces are stored in. Nothing is persisted without it.
+    store: Option<PathBuf>,
+    /// Only files below the workspace are recorded.
+    workspace: PathBuf,
+    files: Vec<RecentFile>,
+}
+
+/// The stored lists by workspace.
+type Store = HashMap<PathBuf, Vec<RecentFile>>;
+
+impl RecentFiles {
+    /// Loads the recent files of `workspace` from `store`.
+    pub fn load(store: PathBuf, workspace: PathBuf) -> Self {
+        let files = read_store(&store).remove(&workspace).unwrap_or_default();
+        Self {
+            store: Some(store),
+            workspace,
+            files,
+        }
+    }
+
+    /// Writes the recent files to the store, keeping the lists of other workspaces.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        // merge with the lists other instances may have written since loading
+        let mut stored = read_store(store);
+        stored.insert(self.workspace.clone(), self.files.clone());
+        if let Some(dir) = store.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(store, serde_json::to_vec(&stored)?)?;
+        Ok(())
+    }
+
+    /// Records that `path` was opened.
+    pub fn visit(&mut self, path: &Path) {
+        self.visit_at(path, now());
+    }
+
+    fn visit_at(&mut self, path: &Path, now: u64) {
+        if !path.starts_with(&self.workspace) {
+            return;
+        }
+        match self.files.iter_mut().find(|file| file.path == path) {
+            Some(file) => {
+                file.count = file.count.saturating_add(1);
+                file.accessed = now;
+            }
+            None => self.files.push(RecentFile {
+                path: path.to_path_buf(),
+                count: 1,
+                accessed: now,
+            }),
+        }
+        if self.files.len() > MAX_ENTRIES {
+            self.files.sort_by(|a, b| rank(a, b, now));
+            self.files.truncate(MAX_ENTRIES);
+        }
+    }
+
+    /// Returns the recent files, highest ranked first.
+    pub fn ranked(&self) -> Vec<&RecentFile> {
+        self.ranked_at(now())
+    }
+
+    fn ranked_at(&self, now: u64) -> Vec<&RecentFile> {
+        let mut files: Vec<_> = self.files.iter().collect();
+        files.sort_by(|a, b| rank(a, b, now));
+        files
+    }
+}
+
+/// Orders higher ranked files first, breaking ties by the last access.
+fn rank(a: &RecentFile, b: &RecentFile, now: u64) -> std::cmp::Ordering {
+    b.frecency(now)
+        .total_cmp(&a.frecency(now))
+        .then(b.accessed.cmp(&a.accessed))
+}
+
+fn read_store(store: &Path) -> Store {
+    fs::read(store)
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rank_by_frecency() {
+        let mut recent = RecentFiles {
+            workspace: PathBuf::from("/w"),
+            ..Default::default()
+        };
+        let now = 10 * WEEK;
+        // opened often but long ago
+        for _ in 0..6 {
+            recent.visit_at(Path::new("/w/old"), now - 2 * WEEK);
+        }
+        recent.visit_at(Path::new("/w/new"), now - 60);
+        recent.visit_at(Path::new("/w/new"), now - 30);
+        recent.visit_at(Path::new("/w/today"), now - 2 * HOUR);
+        recent.visit_at(Path::new("/elsewhere"), now);
+
+        let ranked = recent.ranked_at(now);
+        let paths: Vec<_> = ranked.iter().map(|file| file.path.as_path()).collect();
+        assert_eq!(paths, ["/w/new", "/w/today", "/w/old"].map(Path::new));
+        assert_eq!(ranked[0].count, 2);
+    }
+
+    #[test]
+    fn persist_per_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("recent_files.json");
+        let mut a = RecentFiles::load(store.clone(), PathBuf::from("/a"));
+        a.visit(Path::new("/a/file"));
+        a.save().unwrap();
+        let mut b = RecentFiles::load(store.clone(), PathBuf::from("/b"));
+        b.visit(Path::new("/b/file"));
+        b.save().unwrap();
+
+        let a = RecentFiles::load(store, PathBuf::from("/a"));
+        assert_eq!(a.ranked().len(), 1);
+        assert_eq!(a.ranked()[0].path, Path::new("/a/file"));
+    }
+}
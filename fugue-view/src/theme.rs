@@ -58,6 +58,11 @@ macro_rules! add_style {
     add_style!("ui.background.separator", Style::default().fg(Color::Gray));
     add_style!("ui.text", Style::default().fg(Color::LightGray));
     add_style!("ui.text.focus", Style::default().fg(Color::White));
+    add_style!("ui.vcs.modified", Style::default().fg(Color::Yellow));
+    add_style!("ui.vcs.staged", Style::default().fg(Color::Green));
+    add_style!("ui.vcs.untracked", Style::default().fg(Color::LightGreen));
+    add_style!("ui.vcs.conflict", Style::default().fg(Color::LightRed));
+    add_style!("ui.vcs.ignored", Style::default().fg(Color::Gray));
     add_style!("ui.menu", Style::default().fg(Color::White));
     add_style!(
         "ui.menu.selected",
@@ -140,6 +145,13 @@ macro_rules! add_style {
         "ui.virtual.inlay-hint.type",
         Style::default().fg(Color::White).bg(Color::Gray)
     );
+    add_style!(
+        "ui.virtual.inline-value",
+        Style::default()
+            .fg(Color::Gray)
+            .add_modifier(Modifier::ITALIC)
+    );
+    add_style!("ui.virtual.inline-blame", Style::default().fg(Color::Gray));
     add_style!("ui.virtual.wrap", Style::default().fg(Color::Gray));
     add_style!(
         "ui.virtual.jump-label",
@@ -208,6 +220,34 @@ macro_rules! add_style {
     add_style!("diff.delta", Style::default().fg(Color::LightBlue));
     add_style!("diff.delta.moved", Style::default().fg(Color::Blue));
     add_style!("diff.minus", Style::default().fg(Color::LightRed));
+    add_style!("diff.plus.gutter.staged", Style::default().fg(Color::Green));
+    add_style!("diff.delta.gutter.staged", Style::default().fg(Color::Blue));
+    add_style!("diff.minus.gutter.staged", Style::default().fg(Color::Red));
+    add_style!("diff.plus.line", Style::default().bg(Color::Indexed(22)));
+    add_style!("diff.delta.line", Style::default().bg(Color::Indexed(17)));
+    add_style!("diff.minus.line", Style::default().bg(Color::Indexed(52)));
+    add_style!(
+        "diff.conflict.marker",
+        Style::default().bg(Color::Indexed(237))
+    );
+    add_style!(
+        "diff.conflict.ours",
+        Style::default().bg(Color::Indexed(22))
+    );
+    add_style!(
+        "diff.conflict.base",
+        Style::default().bg(Color::Indexed(236))
+    );
+    add_style!(
+        "diff.conflict.theirs",
+        Style::default().bg(Color::Indexed(17))
+    );
+    add_style!(
+        "diff.delta.word",
+        Style::default()
+            .bg(Color::Indexed(25))
+            .add_modifier(Modifier::BOLD)
+    );
 
     // Diagnostics
     add_style!("info", Style::default().fg(Color::LightBlue));
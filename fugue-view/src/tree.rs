@@ -137,6 +137,7 @@ pub fn insert(&mut self, view: View) -> ViewId {
         container.children.insert(pos, node);
         // focus the new node
         self.focus = node;
+        self.get_mut(node).focused_at = std::time::Instant::now();
 
         // recalculate all the sizes
         self.recalculate();
@@ -210,6 +211,7 @@ pub fn split(&mut self, view: View, layout: Layout) -> ViewId {
 
         // focus the new node
         self.focus = node;
+        self.get_mut(node).focused_at = std::time::Instant::now();
 
         // recalculate all the sizes
         self.recalculate();
@@ -254,6 +256,7 @@ pub fn remove(&mut self, index: ViewId) {
         if self.focus == index {
             // focus on something else
             self.focus = self.prev();
+            self.get_mut(self.focus).focused_at = std::time::Instant::now();
         }
 
         let parent = self.nodes[index].parent;
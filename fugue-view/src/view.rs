@@ -4,6 +4,7 @@
 use crate::{
     align_view,
     annotations::diagnostics::InlineDiagnostics,
+    diff_view::DiffFillers,
     document::{DocumentColorSwatches, DocumentInlayHints},
     editor::{GutterConfig, GutterType},
     graphics::Rect,
@@ -157,6 +158,8 @@ pub struct View {
     // left to future work. For now we treat all views as focused and give them
     // each their own handler.
     pub diagnostics_handler: DiagnosticsHandler,
+    /// When the view was last focused, to find the most recently used view.
+    pub focused_at: std::time::Instant,
 }
 
 impl fmt::Debug for View {
@@ -182,6 +185,7 @@ pub fn new(doc: DocumentId, gutters: GutterConfig) -> Self {
             gutters,
             doc_revisions: HashMap::new(),
             diagnostics_handler: DiagnosticsHandler::new(),
+            focused_at: std::time::Instant::now(),
         }
     }
 
@@ -513,6 +517,11 @@ pub fn text_annotations<'a>(
             ));
         }
 
+        // added last so that the sides of a diff view are aligned after all other virtual lines
+        if let Some(side) = doc.diff_side() {
+            text_annotations.add_line_annotation(DiffFillers::new(doc, side));
+        }
+
         text_annotations
     }
 
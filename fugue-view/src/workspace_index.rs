@@ -0,0 +1,444 @@
+// Copyright (c) 2020 Blaž Hrastnik
+// Licensed under MPL-2.0
+
+//! An index of the definitions and words in the files of the workspace, used for navigation and
+//! completion without a language server.
+//!
+//! The workspace is walked in the background when the editor starts, extracting the tags of
+//! every file whose language has a tags query. Files are indexed again when they are saved or
+//! changed by other programs. The index is cached on disk, so that later sessions only parse
+//! the files modified in between.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use arc_swap::ArcSwap;
+use fugue_core::fuzzy::fuzzy_match;
+use fugue_core::syntax::{Loader, QueryIterEvent};
+use fugue_core::{RopeSlice, Syntax};
+use ignore::{WalkBuilder, WalkState};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::document::from_reader;
+use crate::handlers::word_index::words;
+
+/// Files larger than this are left out, they are most likely generated.
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Bumped whenever the format of the cache changes, discarding older caches.
+const CACHE_VERSION: u32 = 1;
+
+/// Completion only needs the best matching words, the menu filters them further as more is
+/// typed.
+const MAX_WORD_MATCHES: usize = 1000;
+
+/// A definition found by the tags query of a language.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedTag {
+    pub name: String,
+    /// The kind of definition, like `function` or `struct`, from its `definition.*` capture.
+    pub kind: String,
+    /// The character range of the name.
+    pub start: usize,
+    pub end: usize,
+    /// 0 indexed lines
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    /// The modification time of the file when it was indexed, in nanoseconds since the Unix
+    /// epoch. Files are only indexed again when it changes.
+    modified: u64,
+    tags: Vec<IndexedTag>,
+    /// The distinct words of the file.
+    words: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cache {
+    version: u32,
+    files: HashMap<PathBuf, IndexedFile>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    files: HashMap<PathBuf, IndexedFile>,
+    /// Reference counts of the words in all files.
+    words: HashMap<String, u32>,
+    /// Counts the changes to the index, to tell whether it changed since it was cached.
+    changes: u64,
+    /// The value of `changes` when the index was last written to the cache.
+    saved_changes: u64,
+}
+
+impl Inner {
+    fn insert(&mut self, path: PathBuf, file: IndexedFile) {
+        self.remove(&path);
+        for word in &file.words {
+            *self.words.entry(word.clone()).or_default() += 1;
+        }
+        self.files.insert(path, file);
+        self.changes += 1;
+    }
+
+    fn remove(&mut self, path: &Path) {
+        let Some(file) = self.files.remove(path) else {
+            return;
+        };
+        for word in file.words {
+            if let Some(count) = self.words.get_mut(&word) {
+                *count -= 1;
+                if *count == 0 {
+                    self.words.remove(&word);
+                }
+            }
+        }
+        self.changes += 1;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkspaceIndex {
+    root: PathBuf,
+    inner: Arc<RwLock<Inner>>,
+    /// The file the index is cached in. Nothing is cached without it.
+    cache: Option<PathBuf>,
+    /// Whether the index covers the whole workspace, either from the cache or a completed walk.
+    ready: Arc<AtomicBool>,
+    /// Sends the paths of changed files to the indexer.
+    updates: mpsc::UnboundedSender<Vec<PathBuf>>,
+}
+
+impl WorkspaceIndex {
+    /// Starts indexing the files found by `walker` below `root` in a background thread. The
+    /// index is cached in a file below `cache_dir` named after the workspace.
+    pub fn spawn(
+        root: PathBuf,
+        walker: WalkBuilder,
+        loader: Arc<ArcSwap<Loader>>,
+        cache_dir: Option<&Path>,
+    ) -> Self {
+        let cache = cache_dir.map(|dir| dir.join(cache_name(&root)));
+        let (updates, rx) = mpsc::unbounded_channel();
+        let index = Self {
+            root,
+            inner: Arc::default(),
+            cache,
+            ready: Arc::default(),
+            updates,
+        };
+        let indexer = index.clone();
+        let spawned = std::thread::Builder::new()
+            .name("workspace-index".to_owned())
+            .spawn(move || indexer.run(walker, loader, rx));
+        if let Err(err) = spawned {
+            log::error!("failed to start the workspace indexer: {err}");
+        }
+        index
+    }
+
+    fn run(
+        self,
+        walker: WalkBuilder,
+        loader: Arc<ArcSwap<Loader>>,
+        mut updates: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+    ) {
+        if self.load_cache() {
+            self.ready.store(true, Ordering::Relaxed);
+        }
+        self.index_workspace(walker, &loader);
+        self.ready.store(true, Ordering::Relaxed);
+        if let Err(err) = self.save() {
+            log::error!("failed to cache the workspace index: {err}");
+        }
+
+        while let Some(paths) = updates.blocking_recv() {
+            let loader = loader.load();
+            for path in paths {
+                self.index_path(path, &loader);
+            }
+        }
+    }
+
+    fn load_cache(&self) -> bool {
+        let Some(cache) = self
+            .cache
+            .as_deref()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|content| serde_json::from_slice::<Cache>(&content).ok())
+            .filter(|cache| cache.version == CACHE_VERSION)
+        else {
+            return false;
+        };
+        let mut inner = self.inner.write();
+        for (path, file) in cache.files {
+            inner.insert(path, file);
+        }
+        inner.saved_changes = inner.changes;
+        true
+    }
+
+    /// Indexes the files that changed since they were cached and forgets the deleted ones.
+    fn index_workspace(&self, walker: WalkBuilder, loader: &ArcSwap<Loader>) {
+        let seen = Mutex::new(HashSet::new());
+        walker.build_parallel().run(|| {
+            let seen = &seen;
+            let loader = loader.load_full();
+            Box::new(move |entry| {
+                let Ok(entry) = entry else {
+                    return WalkState::Continue;
+                };
+                if entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_file())
+                {
+                    let path = entry.into_path();
+                    seen.lock().insert(path.clone());
+                    self.index_path(path, &loader);
+                }
+                WalkState::Continue
+            })
+        });
+
+        let seen = seen.into_inner();
+        let mut inner = self.inner.write();
+        let deleted: Vec<_> = inner
+            .files
+            .keys()
+            .filter(|path| !seen.contains(*path))
+            .cloned()
+            .collect();
+        for path in deleted {
+            inner.remove(&path);
+        }
+    }
+
+    /// Indexes `path` if it changed since it was last indexed, or forgets it if it can't be
+    /// indexed anymore.
+    fn index_path(&self, path: PathBuf, loader: &Loader) {
+        let Some(modified) = fs::metadata(&path)
+            .ok()
+            .filter(|metadata| metadata.is_file() && metadata.len() <= MAX_FILE_SIZE)
+            .and_then(|metadata| metadata.modified().ok())
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|modified| modified.as_nanos() as u64)
+        else {
+            self.inner.write().remove(&path);
+            return;
+        };
+        let indexed = self.inner.read().files.get(&path).map(|file| file.modified);
+        if indexed == Some(modified) {
+            return;
+        }
+        match index_file(&path, modified, loader) {
+            Some(file) => self.inner.write().insert(path, file),
+            None => self.inner.write().remove(&path),
+        }
+    }
+
+    /// Indexes the files at `paths` again, in the background. Paths outside of the workspace are
+    /// ignored.
+    pub fn update(&self, paths: impl IntoIterator<Item = PathBuf>) {
+        let paths: Vec<_> = paths
+            .into_iter()
+            .map(fugue_stdx::path::normalize)
+            .filter(|path| path.starts_with(&self.root))
+            .collect();
+        if !paths.is_empty() {
+            let _ = self.updates.send(paths);
+        }
+    }
+
+    /// Writes the index to the cache if it changed. The index is only locked while it's
+    /// copied, and the cache is replaced at once so that it's never left half written.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(cache) = &self.cache else {
+            return Ok(());
+        };
+        let (files, changes) = {
+            let inner = self.inner.read();
+            if inner.changes == inner.saved_changes {
+                return Ok(());
+            }
+            (inner.files.clone(), inner.changes)
+        };
+        let dir = cache
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("invalid cache path"))?;
+        fs::create_dir_all(dir)?;
+        let content = serde_json::to_vec(&Cache {
+            version: CACHE_VERSION,
+            files,
+        })?;
+        let mut file = tempfile::NamedTempFile::new_in(dir)?;
+        file.write_all(&content)?;
+        file.persist(cache)?;
+        self.inner.write().saved_changes = changes;
+        Ok(())
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Whether the index covers the workspace. Until then, files may be missing.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Relaxed)
+    }
+
+    /// Returns the definitions in the workspace, by file.
+    pub fn tags(&self) -> Vec<(PathBuf, IndexedTag)> {
+        let inner = self.inner.read();
+        inner
+            .files
+            .iter()
+            .flat_map(|(path, file)| file.tags.iter().map(|tag| (path.clone(), tag.clone())))
+            .collect()
+    }
+
+    /// Returns the definitions of `name` in the workspace.
+    pub fn definitions(&self, name: &str) -> Vec<(PathBuf, IndexedTag)> {
+        let inner = self.inner.read();
+        let mut definitions: Vec<_> = inner
+            .files
+            .iter()
+            .flat_map(|(path, file)| {
+                file.tags
+                    .iter()
+                    .filter(|tag| tag.name == name)
+                    .map(|tag| (path.clone(), tag.clone()))
+            })
+            .collect();
+        definitions.sort_by(|(a, a_tag), (b, b_tag)| a.cmp(b).then(a_tag.start.cmp(&b_tag.start)));
+        definitions
+    }
+
+    /// Returns the best matching words in the workspace for `pattern`, at most
+    /// [`MAX_WORD_MATCHES`] of them.
+    pub fn word_matches(&self, pattern: &str) -> Vec<String> {
+        let inner = self.inner.read();
+        let mut matches = fuzzy_match(pattern, inner.words.keys(), false);
+        matches.sort_unstable_by_key(|(_, score)| std::cmp::Reverse(*score));
+        matches
+            .into_iter()
+            .take(MAX_WORD_MATCHES)
+            .map(|(word, _)| word.to_owned())
+            .collect()
+    }
+}
+
+/// Names the cache file of the workspace at `root` after a hash of its path. FNV-1a is used
+/// because the hashers of the standard library may change between Rust releases.
+fn cache_name(root: &Path) -> String {
+    let hash = root
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{hash:016x}.json")
+}
+
+/// Reads the file at `path` and extracts its tags and words. Returns `None` for files in
+/// languages that aren't known.
+fn index_file(path: &Path, modified: u64, loader: &Loader) -> Option<IndexedFile> {
+    let language = loader.language_for_filename(path)?;
+    let mut file = fs::File::open(path).ok()?;
+    let (rope, _encoding, _has_bom) = from_reader(&mut file, None).ok()?;
+    let text = rope.slice(..);
+
+    let tags = if loader.tag_query(language).is_some() {
+        Syntax::new(text, language, loader)
+            .map(|syntax| tags(&syntax, text, loader))
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let words: HashSet<String> = words(text).map(String::from).collect();
+
+    Some(IndexedFile {
+        modified,
+        tags,
+        words: words.into_iter().collect(),
+    })
+}
+
+/// Extracts the definitions from the tags queries of the languages in `syntax`.
+fn tags(syntax: &Syntax, text: RopeSlice, loader: &Loader) -> Vec<IndexedTag> {
+    let mut tags = Vec::new();
+    let mut iter = syntax.tags(text, loader, ..);
+    while let Some(event) = iter.next() {
+        let QueryIterEvent::Match(mat) = event else {
+            continue;
+        };
+        let Some(query) = loader.tag_query(iter.current_language()) else {
+            continue;
+        };
+        let Some(kind) = query
+            .query
+            .capture_name(mat.capture)
+            .strip_prefix("definition.")
+        else {
+            continue;
+        };
+        let range = mat.node.byte_range();
+        let start = text.byte_to_char(range.start as usize);
+        let end = text.byte_to_char(range.end as usize);
+        tags.push(IndexedTag {
+            name: text.slice(start..end).to_string(),
+            kind: kind.to_owned(),
+            start,
+            end,
+            start_line: text.char_to_line(start),
+            end_line: text.char_to_line(end),
+        });
+    }
+    tags
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn file(words: &[&str]) -> IndexedFile {
+        IndexedFile {
+            modified: 0,
+            tags: Vec::new(),
+            words: words.iter().map(|word| word.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn count_words() {
+        let mut inner = Inner::default();
+        inner.insert("a".into(), file(&["foo", "bar"]));
+        inner.insert("b".into(), file(&["foo"]));
+        assert_eq!(inner.words.get("foo"), Some(&2));
+
+        inner.insert("a".into(), file(&["baz"]));
+        assert_eq!(inner.words.get("foo"), Some(&1));
+        assert_eq!(inner.words.get("bar"), None);
+
+        inner.remove(Path::new("b"));
+        let words: HashSet<_> = inner.words.keys().map(String::as_str).collect();
+        assert_eq!(words, HashSet::from(["baz"]));
+    }
+
+    #[test]
+    fn stable_cache_name() {
+        assert_eq!(
+            cache_name(Path::new("/home/user/project")),
+            "f8a71a04e8340307.json"
+        );
+    }
+}
@@ -40,6 +40,7 @@ pub fn querycheck(languages: impl Iterator<Item = String>) -> Result<(), DynErro
             LanguageData::compile_indent_query(grammar, config)?;
             LanguageData::compile_textobject_query(grammar, config)?;
             LanguageData::compile_tag_query(grammar, config)?;
+            LanguageData::compile_locals_query(grammar, config)?;
             LanguageData::compile_rainbow_query(grammar, config)?;
         }
 